@@ -0,0 +1,165 @@
+use std::fmt::Write;
+
+use glam::*;
+use itertools::Itertools;
+use wgpu_3dgs_viewer as gs;
+
+use crate::app;
+
+/// The annotations to export as geometry for other 3D tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnnotationExport {
+    /// Whether to include the measurement lines.
+    pub measurements: bool,
+
+    /// Whether to include the mask shape wireframes.
+    pub mask_shapes: bool,
+}
+
+impl AnnotationExport {
+    /// The file extension.
+    pub const EXTENSION: &str = "obj";
+
+    /// The number of segments of each ring of an ellipsoid wireframe.
+    const ELLIPSOID_SEGMENTS: usize = 32;
+
+    /// Create a new annotation export including everything.
+    pub const fn new() -> Self {
+        Self {
+            measurements: true,
+            mask_shapes: true,
+        }
+    }
+
+    /// Whether anything is included.
+    pub fn any(self) -> bool {
+        self.measurements || self.mask_shapes
+    }
+
+    /// Write the included annotations as line elements of a Wavefront OBJ file.
+    ///
+    /// All positions are in world space, each annotation is a separate object.
+    pub fn to_obj(self, gs: &app::GaussianSplatting) -> String {
+        let mut obj = Obj::new();
+
+        if self.measurements {
+            for (i, hit_pair) in gs.measurement.hit_pairs.iter().enumerate() {
+                obj.object(&format!("measurement_{i}_{}", hit_pair.label));
+                for segment in hit_pair.segments() {
+                    obj.polyline(&segment, false);
+                }
+            }
+        }
+
+        if self.mask_shapes {
+            for (key, model) in gs.models.iter().sorted_by_key(|(key, _)| *key) {
+                for (i, shape) in model.mask.shapes.iter().enumerate() {
+                    obj.object(&format!("mask_{key}_{i}"));
+
+                    let transform = Mat4::from_scale_rotation_translation(
+                        shape.shape.scale,
+                        shape.shape.rotation,
+                        shape.shape.pos,
+                    );
+
+                    match shape.shape.kind {
+                        gs::MaskShapeKind::Box => Self::box_wireframe(&mut obj, transform),
+                        gs::MaskShapeKind::Ellipsoid => {
+                            Self::ellipsoid_wireframe(&mut obj, transform)
+                        }
+                    }
+                }
+            }
+        }
+
+        obj.out
+    }
+
+    /// Write the edges of a unit box transformed by `transform`.
+    fn box_wireframe(obj: &mut Obj, transform: Mat4) {
+        let corner = |x: f32, y: f32, z: f32| transform.transform_point3(Vec3::new(x, y, z) * 0.5);
+
+        for z in [-1.0, 1.0] {
+            obj.polyline(
+                &[
+                    corner(-1.0, -1.0, z),
+                    corner(1.0, -1.0, z),
+                    corner(1.0, 1.0, z),
+                    corner(-1.0, 1.0, z),
+                ],
+                true,
+            );
+        }
+
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            obj.polyline(&[corner(x, y, -1.0), corner(x, y, 1.0)], false);
+        }
+    }
+
+    /// Write the three axis rings of a unit sphere transformed by `transform`.
+    fn ellipsoid_wireframe(obj: &mut Obj, transform: Mat4) {
+        for axes in [[Vec3::X, Vec3::Y], [Vec3::Y, Vec3::Z], [Vec3::Z, Vec3::X]] {
+            let ring = (0..Self::ELLIPSOID_SEGMENTS)
+                .map(|i| {
+                    let angle = i as f32 / Self::ELLIPSOID_SEGMENTS as f32 * std::f32::consts::TAU;
+                    transform.transform_point3(axes[0] * angle.cos() + axes[1] * angle.sin())
+                })
+                .collect::<Vec<_>>();
+
+            obj.polyline(&ring, true);
+        }
+    }
+}
+
+impl Default for AnnotationExport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A minimal Wavefront OBJ writer with line elements only.
+#[derive(Debug)]
+struct Obj {
+    /// The output.
+    out: String,
+
+    /// The number of written vertices.
+    vertex_count: usize,
+}
+
+impl Obj {
+    /// Create a new OBJ writer.
+    fn new() -> Self {
+        Self {
+            out: format!("# {} annotations\n", app::App::NAME),
+            vertex_count: 0,
+        }
+    }
+
+    /// Start a new object.
+    ///
+    /// Whitespace in the name is replaced since it is not allowed.
+    fn object(&mut self, name: &str) {
+        let name = name
+            .chars()
+            .map(|c| if c.is_whitespace() { '_' } else { c })
+            .collect::<String>();
+
+        writeln!(self.out, "o {name}").expect("write object");
+    }
+
+    /// Write the points and a line element connecting them.
+    fn polyline(&mut self, points: &[Vec3], closed: bool) {
+        for point in points {
+            writeln!(self.out, "v {} {} {}", point.x, point.y, point.z).expect("write vertex");
+        }
+
+        // OBJ indices are 1-based
+        let indices = (self.vertex_count + 1..=self.vertex_count + points.len())
+            .chain(closed.then_some(self.vertex_count + 1))
+            .join(" ");
+        writeln!(self.out, "l {indices}").expect("write line");
+
+        self.vertex_count += points.len();
+    }
+}
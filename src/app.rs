@@ -1,16 +1,21 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io::{BufRead, Cursor},
     ops::Range,
+    path::PathBuf,
     sync::mpsc,
 };
 
 use glam::*;
 use itertools::Itertools;
+use num_format::ToFormattedString;
 use strum::{Display, EnumCount, EnumIter, IntoEnumIterator};
 use wgpu_3dgs_viewer as gs;
 
-use crate::{tab, util};
+use crate::{
+    annotation, capture, config, diagnostics, project, registration, sample, script, tab, util,
+    viewer,
+};
 
 /// The main application.
 #[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
@@ -19,21 +24,410 @@ pub struct App {
     /// The tab manager.
     tab_manager: tab::Manager,
 
-    /// The state of the application.
-    state: State,
+    /// The viewer shown in the scene tab, which owns the state of the application.
+    #[serde(rename = "state")]
+    viewer: viewer::ViewerWidget,
+
+    /// The deployment config, which overrides the built-in defaults.
+    #[serde(skip)]
+    config: Option<config::Config>,
+
+    /// The time of the last pointer activity for auto-hiding the interface.
+    #[serde(skip)]
+    activity_time: f64,
+
+    /// Whether the interface is auto-hidden.
+    #[serde(skip)]
+    interface_hidden: bool,
+
+    /// Whether the browser has entered fullscreen for the presentation mode.
+    ///
+    /// This is only available on the web.
+    #[cfg(target_arch = "wasm32")]
+    #[serde(skip)]
+    web_fullscreen: bool,
+
+    /// The live statistics in the window title, [`None`] if not shown.
+    #[serde(skip)]
+    title_stats: Option<TitleStats>,
+
+    /// Whether to also close the models in the reset to defaults modal, [`None`] if not shown.
+    #[serde(skip)]
+    reset_confirm: Option<bool>,
+
+    /// The end time of the previous frame for limiting the frame rate.
+    ///
+    /// This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    frame_end: Option<std::time::Instant>,
+
+    /// The window geometry to save, [`None`] before the first frame.
+    ///
+    /// This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    window: Option<WindowGeometry>,
 }
 
 impl App {
+    /// The name of the application.
+    pub const NAME: &str = "3D Gaussian Splatting Viewer";
+
     /// Create a main application.
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         #[cfg(target_arch = "wasm32")]
         Self::apple_silicon_crash_warning();
 
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        let config = config::Config::load();
+
+        // The config only overrides the defaults, the saved state is restored as is
+        let mut app = match cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Self>(storage, eframe::APP_KEY))
+        {
+            Some(app) => app,
+            None => {
+                let mut app = Self::default();
+                if let Some(config) = &config {
+                    config.apply(&mut app.viewer.state, &mut app.tab_manager);
+                }
+                app
+            }
+        };
+        app.config = config;
+
+        app.viewer.load_recovery();
+
+        // The window is not fullscreen on start, so start with the normal layout
+        app.tab_manager.set_presenting(false);
+
+        if app.viewer.state.settings.load_sample && !app.viewer.has_recovery() {
+            app.viewer.load_sample();
+        }
+
+        app
+    }
+
+    /// Get the path of the vsync setting.
+    ///
+    /// The setting is needed to create the window before the app state is loaded, so it is
+    /// saved separately. This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn vsync_path() -> Option<PathBuf> {
+        eframe::storage_dir(Self::NAME).map(|dir| dir.join("vsync"))
+    }
+
+    /// Load the vsync setting, which is on by default.
+    ///
+    /// This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_vsync() -> bool {
+        Self::vsync_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .is_none_or(|vsync| vsync.trim() != "false")
+    }
+
+    /// Save the vsync setting.
+    ///
+    /// This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_vsync(vsync: bool) {
+        let Some(path) = Self::vsync_path() else {
+            return;
+        };
+
+        if let Err(e) = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, vsync.to_string()))
+        {
+            log::error!("Error saving vsync setting: {e}");
+        }
+    }
+
+    /// Get the path of the window geometry.
+    ///
+    /// The geometry is needed to create the window before the app state is loaded, so it is
+    /// saved separately. This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn window_path() -> Option<PathBuf> {
+        eframe::storage_dir(Self::NAME).map(|dir| dir.join("window.json"))
+    }
+
+    /// Apply the saved window geometry to the viewport builder.
+    ///
+    /// The size is clamped to the largest monitor by eframe, and to the current monitor on the
+    /// first frame. This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn restore_window(builder: egui::ViewportBuilder) -> egui::ViewportBuilder {
+        let Some(window) = Self::window_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str::<WindowGeometry>(&json).ok())
+        else {
+            return builder;
+        };
+
+        let builder = builder
+            .with_inner_size(window.inner_size)
+            .with_maximized(window.maximized)
+            .with_clamp_size_to_monitor_size(true);
+
+        match window.position {
+            Some(position) => builder.with_position(position),
+            None => builder,
+        }
+    }
+
+    /// Save the window geometry.
+    ///
+    /// This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_window(window: &WindowGeometry) {
+        let Some(path) = Self::window_path() else {
+            return;
+        };
+
+        if let Err(e) = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                serde_json::to_string(window)
+                    .map_err(std::io::Error::other)
+                    .and_then(|json| std::fs::write(&path, json))
+            })
+        {
+            log::error!("Error saving window geometry: {e}");
+        }
+    }
+
+    /// Update the window geometry to save.
+    ///
+    /// The size and position are only taken from the normal window, so leaving the maximized
+    /// window or the presentation mode on the next start restores the normal window. On the
+    /// first frame, the window is clamped to the monitor it is shown on.
+    ///
+    /// This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_window(&mut self, ctx: &egui::Context) {
+        let (maximized, fullscreen, minimized, outer_rect, monitor_size) = ctx.input(|input| {
+            let viewport = input.viewport();
+            (
+                viewport.maximized.unwrap_or(false),
+                viewport.fullscreen.unwrap_or(false),
+                viewport.minimized.unwrap_or(false),
+                viewport.outer_rect,
+                viewport.monitor_size,
+            )
+        });
+
+        if minimized {
+            return;
+        }
+
+        // Sizes are in points, which is also what the viewport builder takes
+        let inner_size = ctx.screen_rect().size();
+
+        let window = match self.window.take() {
+            Some(mut window) => {
+                window.maximized = maximized;
+                if !maximized && !fullscreen {
+                    window.inner_size = inner_size;
+                    window.position = outer_rect.map(|rect| rect.min);
+                }
+                window
+            }
+            None => {
+                // The saved size may be larger than the monitor the window is shown on
+                if let Some(monitor_size) = monitor_size {
+                    let clamped = inner_size.min(monitor_size);
+                    if !maximized && !fullscreen && clamped != inner_size {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(clamped));
+                    }
+                }
+
+                WindowGeometry {
+                    inner_size,
+                    position: outer_rect.map(|rect| rect.min),
+                    maximized,
+                }
+            }
+        };
+
+        self.window = Some(window);
+    }
+
+    /// Limit the frame rate to the maximum FPS by sleeping until the frame interval has passed.
+    ///
+    /// This is only available on native, the web relies on the browser to schedule frames.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn limit_frame_rate(&mut self) {
+        if let (Some(max_fps), Some(frame_end)) =
+            (self.viewer.state.performance.max_fps, self.frame_end)
+        {
+            let interval = std::time::Duration::from_secs_f64(1.0 / max_fps.max(1) as f64);
+            if let Some(remaining) = interval.checked_sub(frame_end.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
+
+        self.frame_end = Some(std::time::Instant::now());
+    }
+
+    /// Enter or leave the presentation mode.
+    ///
+    /// The presentation mode shows only the scene in fullscreen, without the menu bar.
+    fn set_presenting(&mut self, ctx: &egui::Context, presenting: bool) {
+        self.tab_manager.set_presenting(presenting);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(presenting));
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = ctx;
+            self.web_fullscreen = false;
+
+            match presenting {
+                true => {
+                    if let Err(e) = Self::get_canvas().request_fullscreen() {
+                        log::error!("Request fullscreen: {e:?}");
+                    }
+                }
+                false => {
+                    let document = Self::get_document();
+                    if document.fullscreen_element().is_some() {
+                        document.exit_fullscreen();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Leave the presentation mode when the browser leaves fullscreen, e.g. by Escape.
+    ///
+    /// This is only available on the web.
+    #[cfg(target_arch = "wasm32")]
+    fn sync_web_fullscreen(&mut self, ctx: &egui::Context) {
+        if !self.tab_manager.is_presenting() {
+            return;
+        }
+
+        // Entering fullscreen is asynchronous, so only leave after it has been entered
+        match Self::get_document().fullscreen_element().is_some() {
+            true => self.web_fullscreen = true,
+            false if self.web_fullscreen => self.set_presenting(ctx, false),
+            false => {}
+        }
+    }
+
+    /// Update the live statistics in the window title.
+    ///
+    /// The title is updated once per second, and restored when the statistics are turned off.
+    fn update_title_stats(&mut self, ctx: &egui::Context) {
+        /// The interval between title updates in seconds.
+        const INTERVAL: f64 = 1.0;
+
+        let time = ctx.input(|input| input.time);
+
+        match (
+            self.viewer.state.performance.title_stats,
+            &mut self.title_stats,
+        ) {
+            (true, Some(stats)) => {
+                stats.frames += 1;
+
+                let elapsed = time - stats.time;
+                if elapsed >= INTERVAL {
+                    let count = match &self.viewer.state.gs {
+                        Loadable::Loaded(gs) => self.viewer.state.performance.rendered_count(gs),
+                        Loadable::Unloaded { .. } => 0,
+                    };
+
+                    Self::set_title(
+                        ctx,
+                        format!(
+                            "{} - {:.1} FPS - {} Gaussians",
+                            stats.base,
+                            stats.frames as f64 / elapsed,
+                            count.to_formatted_string(&num_format::Locale::en)
+                        ),
+                    );
+
+                    stats.time = time;
+                    stats.frames = 0;
+                }
+            }
+            (true, None) => {
+                #[cfg(not(target_arch = "wasm32"))]
+                let base = Self::NAME.to_string();
+                #[cfg(target_arch = "wasm32")]
+                let base = Self::get_document().title();
+
+                self.title_stats = Some(TitleStats {
+                    base,
+                    time,
+                    frames: 0,
+                });
+            }
+            (false, Some(stats)) => {
+                Self::set_title(ctx, std::mem::take(&mut stats.base));
+                self.title_stats = None;
+            }
+            (false, None) => {}
+        }
+    }
+
+    /// Set the title of the native window or the web page.
+    fn set_title(ctx: &egui::Context, title: String) {
+        #[cfg(not(target_arch = "wasm32"))]
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = ctx;
+            Self::get_document().set_title(&title);
+        }
+    }
+
+    /// Update whether the interface is auto-hidden.
+    ///
+    /// The interface is hidden after the pointer is inactive for the delay in settings, and is
+    /// revealed when the pointer moves near the edges of the window.
+    fn update_auto_hide(&mut self, ctx: &egui::Context) {
+        /// The distance from the edges to reveal the interface.
+        const EDGE: f32 = 32.0;
+
+        if !self.viewer.state.settings.auto_hide {
+            self.interface_hidden = false;
+            return;
+        }
+
+        let (time, active, near_edge) = ctx.input(|input| {
+            (
+                input.time,
+                input.pointer.is_moving() || input.pointer.any_down(),
+                input
+                    .pointer
+                    .hover_pos()
+                    .is_some_and(|pos| !input.screen_rect().shrink(EDGE).contains(pos)),
+            )
+        });
+
+        let popup_open = ctx.memory(|memory| memory.any_popup_open());
+
+        if (active && (!self.interface_hidden || near_edge)) || popup_open {
+            self.activity_time = time;
+            self.interface_hidden = false;
         }
 
-        Self::default()
+        let delay = self.viewer.state.settings.auto_hide_delay as f64;
+        if !self.interface_hidden {
+            match time - self.activity_time >= delay {
+                true => self.interface_hidden = true,
+                false => ctx.request_repaint_after_secs((self.activity_time + delay - time) as f32),
+            }
+        }
     }
 
     /// Get the document.
@@ -96,57 +490,189 @@ impl App {
         egui::menu::bar(ui, |ui| {
             ui.menu_button("File", |ui| {
                 if ui.button("Open model").clicked() {
-                    self.state.gs = Loadable::unloaded();
-                    let Loadable::Unloaded(unloaded) = &mut self.state.gs else {
+                    self.discard(ctx, DiscardAction::OpenModel);
+                    ui.close_menu();
+                }
+
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui
+                    .button("Open folder")
+                    .on_hover_text("Open all the PLY models in a folder as one scene")
+                    .clicked()
+                {
+                    self.discard(ctx, DiscardAction::OpenFolder);
+                    ui.close_menu();
+                }
+
+                if ui
+                    .add_enabled(
+                        self.viewer.state.gs.is_loaded(),
+                        egui::Button::new("Close models"),
+                    )
+                    .clicked()
+                {
+                    self.discard(ctx, DiscardAction::CloseModels);
+                    ui.close_menu();
+                }
+
+                if ui
+                    .add_enabled(
+                        self.viewer.state.gs.is_loaded(),
+                        egui::Button::new("Export model"),
+                    )
+                    .clicked()
+                {
+                    let Loadable::Loaded(gs) = &mut self.viewer.state.gs else {
                         unreachable!()
                     };
 
-                    let tx = unloaded.tx.clone();
-                    let ctx = ui.ctx().clone();
-                    let task = rfd::AsyncFileDialog::new()
-                        .set_title("Open a PLY file")
-                        .pick_file();
-                    let compressions = self.state.compressions.clone();
+                    gs.export_modal = Some(ExportModal::new(
+                        gs,
+                        self.viewer.state.settings.coordinate_system,
+                    ));
 
-                    util::exec_task(async move {
-                        if let Some(file) = task.await {
-                            let filename = match file.file_name().trim().is_empty() {
-                                true => "Unnamed".to_string(),
-                                false => file.file_name().trim().to_string(),
-                            };
-                            let reader = Cursor::new(file.read().await);
-                            let gs = GaussianSplatting::new(filename, reader, compressions)
-                                .map_err(|e| e.to_string());
+                    ui.close_menu();
+                }
 
-                            tx.send(gs).expect("send gs");
-                            ctx.request_repaint();
+                ui.add_enabled_ui(self.viewer.state.gs.is_loaded(), |ui| {
+                    ui.menu_button("Export annotations", |ui| {
+                        let Loadable::Loaded(gs) = &mut self.viewer.state.gs else {
+                            unreachable!()
+                        };
+
+                        ui.checkbox(
+                            &mut gs.annotation_export.measurements,
+                            format!("Measurements ({})", gs.measurement.hit_pairs.len()),
+                        );
+                        ui.checkbox(
+                            &mut gs.annotation_export.mask_shapes,
+                            format!(
+                                "Mask Shapes ({})",
+                                gs.models
+                                    .values()
+                                    .map(|model| model.mask.shapes.len())
+                                    .sum::<usize>()
+                            ),
+                        );
+
+                        if ui
+                            .add_enabled(
+                                gs.annotation_export.any(),
+                                egui::Button::new("Export OBJ"),
+                            )
+                            .on_hover_text(
+                                "Export as lines in world space for other 3D tools, e.g. Blender",
+                            )
+                            .clicked()
+                        {
+                            let obj = gs.annotation_export.to_obj(gs);
+                            let scene_tx = gs.scene_tx.clone();
+                            let task = rfd::AsyncFileDialog::new()
+                                .set_title("Export the annotations")
+                                .add_filter("OBJ", &[annotation::AnnotationExport::EXTENSION])
+                                .set_file_name(format!(
+                                    "annotations.{}",
+                                    annotation::AnnotationExport::EXTENSION
+                                ))
+                                .save_file();
+
+                            util::exec_task(async move {
+                                let Some(file) = task.await else {
+                                    return;
+                                };
+
+                                match file.write(obj.as_bytes()).await {
+                                    Ok(()) => {
+                                        scene_tx.send(SceneCommand::Saved).ok();
+                                    }
+                                    Err(e) => log::error!("Export annotations: {e}"),
+                                }
+                            });
+
+                            ui.close_menu();
                         }
                     });
+                });
 
-                    ui.close_menu();
-                }
+                ui.separator();
 
-                if ui
-                    .add_enabled(self.state.gs.is_loaded(), egui::Button::new("Close models"))
-                    .clicked()
-                {
-                    self.state.gs = Loadable::unloaded();
+                if ui.button("Open project").clicked() {
+                    self.discard(ctx, DiscardAction::OpenProject);
                     ui.close_menu();
                 }
 
+                let saveable = match &self.viewer.state.gs {
+                    Loadable::Loaded(gs) => gs.is_saveable(),
+                    Loadable::Unloaded(..) => false,
+                };
+
                 if ui
-                    .add_enabled(self.state.gs.is_loaded(), egui::Button::new("Export model"))
+                    .add_enabled(saveable, egui::Button::new("Save project"))
+                    .on_disabled_hover_text("Models must be loaded to save a project")
                     .clicked()
                 {
-                    let Loadable::Loaded(gs) = &mut self.state.gs else {
+                    let Loadable::Loaded(gs) = &mut self.viewer.state.gs else {
                         unreachable!()
                     };
 
-                    gs.export_modal = Some(ExportModal::new(gs.models.len()));
+                    gs.scene_tx
+                        .send(SceneCommand::SaveProject)
+                        .expect("send save project");
 
                     ui.close_menu();
                 }
 
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    ui.separator();
+
+                    ui.menu_button("Script", |ui| {
+                        if ui
+                            .button("Run script")
+                            .on_hover_text("Run the commands in a script file one by one")
+                            .clicked()
+                        {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .set_title("Run a script file")
+                                .pick_file()
+                            {
+                                match std::fs::read_to_string(&path)
+                                    .map_err(|e| e.to_string())
+                                    .and_then(|source| script::Script::parse(&source))
+                                {
+                                    Ok(script) => {
+                                        log::info!("Running script {}", path.display());
+                                        self.viewer.state.script = Some(script);
+                                    }
+                                    Err(e) => log::error!("Script {}: {e}", path.display()),
+                                }
+                            }
+
+                            ui.close_menu();
+                        }
+
+                        if ui
+                            .add_enabled(
+                                self.viewer.state.script.is_some(),
+                                egui::Button::new("Stop script"),
+                            )
+                            .clicked()
+                        {
+                            self.viewer.state.script = None;
+                            ui.close_menu();
+                        }
+
+                        if ui
+                            .button("Copy example script")
+                            .on_hover_text("Copy an example script documenting all the commands")
+                            .clicked()
+                        {
+                            ui.ctx().copy_text(script::Script::EXAMPLE.to_string());
+                            ui.close_menu();
+                        }
+                    });
+                }
+
                 ui.separator();
 
                 ui.menu_button("Compression Settings", |ui| {
@@ -160,7 +686,12 @@ impl App {
 
                     ui.menu_button("Spherical Harmonics", |ui| {
                         for sh in ShCompression::iter() {
-                            value!(ui, self.state.compressions.sh, sh, sh.to_string().as_str());
+                            value!(
+                                ui,
+                                self.viewer.state.default_compressions.sh,
+                                sh,
+                                sh.to_string().as_str()
+                            );
                         }
                     });
 
@@ -168,12 +699,22 @@ impl App {
                         for cov3d in Cov3dCompression::iter() {
                             value!(
                                 ui,
-                                self.state.compressions.cov3d,
+                                self.viewer.state.default_compressions.cov3d,
                                 cov3d,
                                 cov3d.to_string().as_str()
                             );
                         }
                     });
+
+                    ui.separator();
+
+                    ui.checkbox(
+                        &mut self.viewer.state.settings.confirm_initialize,
+                        "Confirm When Loading",
+                    )
+                    .on_hover_text(
+                        "Ask to confirm the compression settings for every loaded model",
+                    );
                 });
 
                 if !cfg!(target_arch = "wasm32") {
@@ -185,13 +726,35 @@ impl App {
                 }
             });
 
-            ui.menu_button("View", |ui| self.tab_manager.menu(ui));
+            ui.menu_button("View", |ui| {
+                self.tab_manager.menu(ui);
+
+                ui.separator();
+
+                ui.toggle_value(&mut self.viewer.state.settings.camera_hud, "Camera HUD")
+                    .on_hover_text("Show the camera quick controls in the viewport");
+
+                ui.toggle_value(&mut self.viewer.state.settings.minimap, "Minimap")
+                    .on_hover_text(
+                        "Show a top-down overview of the models and the camera in the viewport, \
+                    click on it to move the camera",
+                    );
+
+                if ui
+                    .button("Presentation Mode")
+                    .on_hover_text("Show only the scene in fullscreen, press F11 to leave")
+                    .clicked()
+                {
+                    self.set_presenting(ctx, true);
+                    ui.close_menu();
+                }
+            });
 
-            ui.menu_button("About", |ui| self.about(ui));
+            ui.menu_button("About", |ui| self.about(ui, frame));
 
             ui.separator();
 
-            egui::widgets::global_theme_preference_buttons(ui);
+            self.viewer.state.settings.theme.radio_buttons(ui);
 
             ui.separator();
 
@@ -222,61 +785,269 @@ impl App {
                 egui::warn_if_debug_build(ui);
             }
         });
+    }
 
-        if let Loadable::Loaded(gs) = &mut self.state.gs {
-            if let Some(export_modal) = &mut gs.export_modal {
-                macro_rules! case {
-                    ($sh:ident, $cov3d:ident) => {
-                        Compressions {
-                            sh: ShCompression::$sh,
-                            cov3d: Cov3dCompression::$cov3d,
-                        }
-                    };
-                }
+    /// Run the action which discards the current session.
+    ///
+    /// If there are unsaved changes, the action waits for confirmation in
+    /// [`App::discard_confirm`] instead.
+    fn discard(&mut self, ctx: &egui::Context, action: DiscardAction) {
+        if let Loadable::Loaded(gs) = &mut self.viewer.state.gs {
+            if gs.unsaved {
+                gs.discard_confirm = Some(action);
+                return;
+            }
+        }
 
-                macro_rules! ui {
-                    ($sh:ident, $cov3d:ident) => {
-                        paste::paste! {
-                            if !export_modal.ui::<
-                                gs::[<GaussianPodWithSh $sh Cov3d $cov3d Configs>]
-                            >(ui, frame, &gs.models) {
-                                gs.export_modal = None;
-                            }
-                        }
-                    };
-                }
+        match action {
+            DiscardAction::OpenModel => self.open_model(ctx),
+            #[cfg(not(target_arch = "wasm32"))]
+            DiscardAction::OpenFolder => self.open_folder(ctx),
+            DiscardAction::OpenProject => self.open_project(ctx),
+            DiscardAction::CloseModels => self.viewer.close(),
+        }
+    }
 
-                match &gs.compressions {
-                    case!(Single, Single) => ui!(Single, Single),
-                    case!(Single, Half) => ui!(Single, Half),
-                    case!(Half, Single) => ui!(Half, Single),
-                    case!(Half, Half) => ui!(Half, Half),
-                    case!(Norm8, Single) => ui!(Norm8, Single),
-                    case!(Norm8, Half) => ui!(Norm8, Half),
-                    case!(Remove, Single) => ui!(None, Single),
-                    case!(Remove, Half) => ui!(None, Half),
-                }
+    /// Pick a PLY file and open it as a new session.
+    fn open_model(&mut self, ctx: &egui::Context) {
+        self.viewer.state.gs = Loadable::unloaded();
+        let Loadable::Unloaded(unloaded) = &mut self.viewer.state.gs else {
+            unreachable!()
+        };
+
+        let tx = unloaded.tx.clone();
+        let ctx = ctx.clone();
+        let task = rfd::AsyncFileDialog::new()
+            .set_title("Open a PLY file")
+            .add_filter("PLY", &util::PLY_EXTENSIONS)
+            .pick_file();
+        let compressions = self.viewer.state.compressions.clone();
+
+        util::exec_task(async move {
+            if let Some(file) = task.await {
+                let filename = match file.file_name().trim().is_empty() {
+                    true => "Unnamed".to_string(),
+                    false => file.file_name().trim().to_string(),
+                };
+                let path = util::file_handle_path(&file);
+                let reader = Cursor::new(file.read().await);
+                let gs = GaussianSplatting::new(filename, path, reader, compressions)
+                    .map_err(|e| e.to_string());
+
+                tx.send(gs).expect("send gs");
+                ctx.request_repaint();
             }
-        }
+        });
     }
 
-    /// Show the about dialog.
-    fn about(&mut self, ui: &mut egui::Ui) {
-        ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-            ui.spacing_mut().item_spacing.y *= 2.0;
+    /// Pick a folder and open its PLY models as a new session.
+    ///
+    /// This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_folder(&mut self, ctx: &egui::Context) {
+        self.viewer.state.gs = Loadable::unloaded();
+        let Loadable::Unloaded(unloaded) = &mut self.viewer.state.gs else {
+            unreachable!()
+        };
 
-            ui.horizontal_wrapped(|ui| {
-                ui.spacing_mut().item_spacing.x = 0.0;
+        let tx = unloaded.tx.clone();
+        let ctx = ctx.clone();
+        let task = rfd::AsyncFileDialog::new()
+            .set_title("Open a folder of PLY files")
+            .pick_folder();
+        let compressions = self.viewer.state.compressions.clone();
 
-                ui.label("This viewer app is built for ");
-                ui.add(
-                    egui::Hyperlink::from_label_and_url(
-                        "[3D Gaussian Splatting]",
-                        "https://en.wikipedia.org/wiki/Gaussian_splatting",
-                    )
-                    .open_in_new_tab(true),
-                );
-                ui.label(". It supports the PLY file format from the ");
+        util::exec_task(async move {
+            if let Some(folder) = task.await {
+                GaussianSplatting::open_folder(folder.path(), compressions, tx, ctx);
+            }
+        });
+    }
+
+    /// Pick a project file and open it as a new session.
+    fn open_project(&mut self, ctx: &egui::Context) {
+        self.viewer.state.gs = Loadable::unloaded();
+        let Loadable::Unloaded(unloaded) = &mut self.viewer.state.gs else {
+            unreachable!()
+        };
+
+        let tx = unloaded.tx.clone();
+        let ctx = ctx.clone();
+        let task = rfd::AsyncFileDialog::new()
+            .set_title("Open a project file")
+            .add_filter("Project", &[project::Project::EXTENSION])
+            .pick_file();
+        let compressions = self.viewer.state.compressions.clone();
+
+        util::exec_task(async move {
+            if let Some(file) = task.await {
+                let gs = project::Project::from_json(&file.read().await)
+                    .and_then(|project| project.open(compressions));
+
+                tx.send(gs).expect("send gs");
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// Show the modal to confirm resetting the app to defaults.
+    ///
+    /// The persisted layout, compression, performance and settings are reset, the models are
+    /// only closed if chosen.
+    fn reset_confirm(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let Some(mut close_models) = self.reset_confirm else {
+            return;
+        };
+
+        let mut confirmed = None;
+
+        egui::Modal::new(egui::Id::new("reset_confirm_modal")).show(ctx, |ui| {
+            ui.add(egui::Label::new(
+                egui::RichText::new("Reset app to defaults").heading(),
+            ));
+            ui.separator();
+
+            ui.label("The layout, compression, performance and settings will be reset.");
+            ui.label("This cannot be undone.");
+            ui.label("");
+
+            ui.add_enabled(
+                self.viewer.state.gs.is_loaded(),
+                egui::Checkbox::new(&mut close_models, "Also close the models"),
+            );
+            ui.label("");
+
+            ui.horizontal(|ui| {
+                if ui.button("Reset").clicked() {
+                    confirmed = Some(true);
+                }
+
+                if ui.button("Cancel").clicked() {
+                    confirmed = Some(false);
+                }
+            });
+        });
+
+        self.reset_confirm = Some(close_models);
+
+        let Some(confirmed) = confirmed else {
+            return;
+        };
+        self.reset_confirm = None;
+        if !confirmed {
+            return;
+        }
+
+        log::info!("Resetting the app to defaults");
+
+        self.tab_manager = tab::Manager::default();
+        self.viewer.state.compressions = Compressions::default();
+        self.viewer.state.default_compressions = Compressions::default();
+        self.viewer.state.performance = Performance::default();
+        self.viewer.state.settings = Settings::default();
+        if let Some(config) = &self.config {
+            config.apply(&mut self.viewer.state, &mut self.tab_manager);
+        }
+
+        if let Some(storage) = frame.storage_mut() {
+            eframe::set_value(storage, eframe::APP_KEY, self);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        Self::save_vsync(self.viewer.state.performance.vsync);
+
+        if close_models && self.viewer.state.gs.is_loaded() {
+            self.discard(ctx, DiscardAction::CloseModels);
+        }
+    }
+
+    /// Show the modal to confirm discarding the unsaved changes.
+    fn discard_confirm(&mut self, ctx: &egui::Context) {
+        let Loadable::Loaded(gs) = &mut self.viewer.state.gs else {
+            return;
+        };
+        let Some(action) = gs.discard_confirm else {
+            return;
+        };
+
+        let mut confirmed = None;
+
+        egui::Modal::new(egui::Id::new("discard_confirm_modal")).show(ctx, |ui| {
+            ui.add(egui::Label::new(
+                egui::RichText::new("Unsaved changes").heading(),
+            ));
+            ui.separator();
+
+            ui.label("The masks, selection edits or measurements have not been saved.");
+            ui.label(format!(
+                "They will be lost if you {}.",
+                match action {
+                    DiscardAction::OpenModel => "open another model",
+                    #[cfg(not(target_arch = "wasm32"))]
+                    DiscardAction::OpenFolder => "open a folder",
+                    DiscardAction::OpenProject => "open another project",
+                    DiscardAction::CloseModels => "close the models",
+                }
+            ));
+            ui.label("");
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(gs.is_saveable(), egui::Button::new("Save project"))
+                    .clicked()
+                {
+                    gs.scene_tx
+                        .send(SceneCommand::SaveProject)
+                        .expect("send save project");
+                    confirmed = Some(false);
+                }
+
+                if ui.button("Export model").clicked() {
+                    gs.export_modal = Some(ExportModal::new(
+                        gs,
+                        self.viewer.state.settings.coordinate_system,
+                    ));
+                    confirmed = Some(false);
+                }
+
+                if ui.button("Discard").clicked() {
+                    confirmed = Some(true);
+                }
+
+                if ui.button("Cancel").clicked() {
+                    confirmed = Some(false);
+                }
+            });
+        });
+
+        match confirmed {
+            Some(true) => {
+                gs.discard_confirm = None;
+                gs.unsaved = false;
+                self.discard(ctx, action);
+            }
+            Some(false) => gs.discard_confirm = None,
+            None => {}
+        }
+    }
+
+    /// Show the about dialog.
+    fn about(&mut self, ui: &mut egui::Ui, frame: &eframe::Frame) {
+        ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+            ui.spacing_mut().item_spacing.y *= 2.0;
+
+            ui.horizontal_wrapped(|ui| {
+                ui.spacing_mut().item_spacing.x = 0.0;
+
+                ui.label("This viewer app is built for ");
+                ui.add(
+                    egui::Hyperlink::from_label_and_url(
+                        "[3D Gaussian Splatting]",
+                        "https://en.wikipedia.org/wiki/Gaussian_splatting",
+                    )
+                    .open_in_new_tab(true),
+                );
+                ui.label(". It supports the PLY file format from the ");
                 ui.add(
                     egui::Hyperlink::from_label_and_url(
                         "[3D Gaussian Splatting for Real-Time Radiance Field Rendering]",
@@ -290,53 +1061,852 @@ impl App {
             ui.horizontal_wrapped(|ui| {
                 ui.spacing_mut().item_spacing.x = 0.0;
 
-                ui.label("Made by ");
-                ui.hyperlink_to("[Lio Qing]", "https://lioqing.com");
-                ui.label(" with ");
-                ui.add(
-                    egui::Hyperlink::from_label_and_url("[wgpu]", "https://wgpu.rs")
-                        .open_in_new_tab(true),
-                );
-                ui.label(" and ");
-                ui.add(
-                    egui::Hyperlink::from_label_and_url("[egui]", "https://github.com/emilk/egui")
-                        .open_in_new_tab(true),
-                );
-                ui.label(". ");
-            });
-        });
+                ui.label("Made by ");
+                ui.hyperlink_to("[Lio Qing]", "https://lioqing.com");
+                ui.label(" with ");
+                ui.add(
+                    egui::Hyperlink::from_label_and_url("[wgpu]", "https://wgpu.rs")
+                        .open_in_new_tab(true),
+                );
+                ui.label(" and ");
+                ui.add(
+                    egui::Hyperlink::from_label_and_url("[egui]", "https://github.com/emilk/egui")
+                        .open_in_new_tab(true),
+                );
+                ui.label(". ");
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui
+                    .button("📋 Copy Diagnostics")
+                    .on_hover_text(
+                        "Copy the app, platform, GPU, model and recent log information for bug \
+                        reports",
+                    )
+                    .clicked()
+                {
+                    ui.ctx().copy_text(diagnostics::collect(
+                        frame,
+                        &self.viewer.state,
+                        self.viewer.state.settings.diagnostics_hide_paths,
+                    ));
+                    ui.close_menu();
+                }
+
+                ui.checkbox(
+                    &mut self.viewer.state.settings.diagnostics_hide_paths,
+                    "Hide File Paths",
+                );
+            });
+
+            if ui
+                .button("⟲ Reset App to Defaults")
+                .on_hover_text("Reset the layout, compression, performance and settings")
+                .clicked()
+            {
+                self.reset_confirm = Some(false);
+                ui.close_menu();
+            }
+        });
+    }
+}
+
+impl eframe::App for App {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, self);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Self::save_vsync(self.viewer.state.performance.vsync);
+
+            if let Some(window) = &self.window {
+                Self::save_window(window);
+            }
+        }
+    }
+
+    fn on_exit(&mut self) {
+        // The app is closed properly, so the recovery project is no longer needed
+        project::Project::clear_recovery();
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.viewer.update(ctx, frame);
+
+        if ctx.input_mut(|input| input.consume_key(egui::Modifiers::NONE, egui::Key::F11)) {
+            let presenting = !self.tab_manager.is_presenting();
+            self.set_presenting(ctx, presenting);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        self.sync_web_fullscreen(ctx);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_window(ctx);
+
+        self.update_auto_hide(ctx);
+        self.update_title_stats(ctx);
+
+        if !self.tab_manager.is_presenting() && !self.interface_hidden {
+            egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+                self.menu_bar(ctx, ui, frame);
+            });
+        }
+
+        self.discard_confirm(ctx);
+        self.reset_confirm(ctx, frame);
+
+        let layout_changed = egui::CentralPanel::default()
+            .frame(egui::Frame::central_panel(&ctx.style()).inner_margin(0.))
+            .show(ctx, |ui| {
+                self.tab_manager
+                    .dock_area(ui, frame, &mut self.viewer, self.interface_hidden)
+            })
+            .inner;
+
+        // Store the layout right away instead of waiting for the auto save, the browser may be
+        // closed without saving on exit, losing the active tabs
+        if layout_changed {
+            if let Some(storage) = frame.storage_mut() {
+                eframe::set_value(storage, eframe::APP_KEY, self);
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.limit_frame_rate();
+    }
+}
+
+/// The window geometry of [`App`], saved to restore the window on the next start.
+///
+/// This is only available on native.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+struct WindowGeometry {
+    /// The inner size of the normal window in points.
+    inner_size: egui::Vec2,
+
+    /// The outer position of the normal window in points, [`None`] if not available, e.g. on
+    /// Wayland.
+    position: Option<egui::Pos2>,
+
+    /// Whether the window is maximized.
+    maximized: bool,
+}
+
+/// The live statistics in the window title of [`App`].
+#[derive(Debug)]
+struct TitleStats {
+    /// The title without the statistics.
+    base: String,
+
+    /// The time of the last title update.
+    time: f64,
+
+    /// The number of frames since the last title update.
+    frames: u32,
+}
+
+/// The state of the main application.
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct State {
+    /// The Gaussian splatting model, which can be loaded from a file.
+    #[serde(skip)]
+    pub gs: Loadable<GaussianSplatting, String>,
+
+    /// The compression settings of the model being initialized.
+    pub compressions: Compressions,
+
+    /// The default compression settings that each opened model starts from.
+    #[serde(default)]
+    pub default_compressions: Compressions,
+
+    /// The performance settings.
+    #[serde(default)]
+    pub performance: Performance,
+
+    /// The global settings.
+    #[serde(default)]
+    pub settings: Settings,
+
+    /// The running script.
+    #[serde(skip)]
+    pub script: Option<script::Script>,
+
+    /// The copied model transform to paste onto other models.
+    #[serde(skip)]
+    pub transform_clipboard: Option<GaussianSplattingModelTransform>,
+}
+
+impl State {
+    /// Load the sample model, replacing the current session.
+    pub fn load_sample(&mut self) {
+        self.gs = Loadable::unloaded();
+        let Loadable::Unloaded(unloaded) = &mut self.gs else {
+            unreachable!()
+        };
+
+        unloaded
+            .tx
+            .send(sample::load(self.default_compressions.clone()))
+            .expect("send gs");
+    }
+
+    /// Open a PLY model from its bytes, replacing the current session.
+    ///
+    /// The model is parsed right away, the Gaussians are streamed into the scene after.
+    pub fn open(&mut self, file_name: String, data: Vec<u8>) {
+        self.gs = Loadable::unloaded();
+        let Loadable::Unloaded(unloaded) = &mut self.gs else {
+            unreachable!()
+        };
+
+        unloaded
+            .tx
+            .send(
+                GaussianSplatting::new(
+                    file_name,
+                    None,
+                    Cursor::new(data),
+                    self.default_compressions.clone(),
+                )
+                .map_err(|e| e.to_string()),
+            )
+            .expect("send gs");
+    }
+
+    /// Handle the global keyboard shortcuts of the scene.
+    pub fn shortcuts(&mut self, ctx: &egui::Context) {
+        if let Loadable::Loaded(gs) = &mut self.gs {
+            if !ctx.wants_keyboard_input()
+                && ctx.input_mut(|input| input.consume_key(egui::Modifiers::NONE, egui::Key::M))
+            {
+                gs.measurement.toggle_hidden(&gs.scene_tx);
+            }
+        }
+    }
+}
+
+/// The global settings.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Settings {
+    /// The UI scale.
+    pub ui_scale: f32,
+
+    /// The theme preference.
+    pub theme: egui::ThemePreference,
+
+    /// The length unit for displaying distances.
+    pub length_unit: LengthUnit,
+
+    /// The colors of the X, Y and Z labels of the numeric fields.
+    pub axis_colors: AxisColors,
+
+    /// The coordinate system of imported models, which sets their initial transform.
+    pub coordinate_system: CoordinateSystem,
+
+    /// Whether to flip the handedness of imported models, see
+    /// [`GaussianSplattingModel::flip_handedness`].
+    pub flip_handedness: bool,
+
+    /// Whether to periodically save the session for recovery after a crash.
+    pub autosave: bool,
+
+    /// The scroll wheel zoom of the orbit camera.
+    pub zoom: CameraZoom,
+
+    /// The rotation in degrees of the orbit camera on each press of the arrow keys, at the
+    /// default sensitivity.
+    pub orbit_key_step: f32,
+
+    /// Whether to show the camera quick controls in the viewport.
+    pub camera_hud: bool,
+
+    /// Whether to show the top-down minimap in the viewport.
+    pub minimap: bool,
+
+    /// Whether to confirm the compression settings when initializing the scene.
+    pub confirm_initialize: bool,
+
+    /// Whether to hide the menu bar and the other tabs when the pointer is inactive.
+    pub auto_hide: bool,
+
+    /// The delay in seconds of inactivity before hiding the interface.
+    pub auto_hide_delay: f32,
+
+    /// Whether to load the sample model on start.
+    ///
+    /// This is cleared once another model is opened.
+    pub load_sample: bool,
+
+    /// Whether to leave out the file paths from the copied diagnostics.
+    pub diagnostics_hide_paths: bool,
+
+    /// The distance in points the pointer has to move while pressed to start a drag.
+    ///
+    /// A press and release within this distance is a click.
+    pub drag_threshold: f32,
+
+    /// The maximum delay in seconds between the clicks of a double click.
+    pub double_click_delay: f32,
+
+    /// The UI scale last synced with the zoom factor of the context, [`None`] if not synced yet.
+    #[serde(skip)]
+    synced_ui_scale: Option<f32>,
+}
+
+impl Settings {
+    /// Create a new global settings.
+    pub const fn new() -> Self {
+        Self {
+            ui_scale: 1.0,
+            theme: egui::ThemePreference::System,
+            length_unit: LengthUnit::Meter,
+            axis_colors: AxisColors::Standard,
+            coordinate_system: CoordinateSystem::YDown,
+            flip_handedness: false,
+            autosave: true,
+            zoom: CameraZoom::new(),
+            orbit_key_step: 5.0,
+            camera_hud: false,
+            minimap: false,
+            confirm_initialize: true,
+            load_sample: true,
+            auto_hide: false,
+            auto_hide_delay: 3.0,
+            // Touch screens on the web need more room to tell a tap from a drag
+            drag_threshold: if cfg!(target_arch = "wasm32") {
+                10.0
+            } else {
+                6.0
+            },
+            // The defaults of the desktop environments
+            double_click_delay: if cfg!(any(target_os = "windows", target_os = "macos")) {
+                0.5
+            } else {
+                0.4
+            },
+            diagnostics_hide_paths: true,
+            synced_ui_scale: None,
+        }
+    }
+
+    /// Apply the settings to the context.
+    ///
+    /// The UI scale follows the zoom shortcuts of egui, and is only applied to the context when
+    /// it is changed in the settings.
+    pub fn apply(&mut self, ctx: &egui::Context) {
+        let zoom_factor = ctx.zoom_factor();
+        match self.synced_ui_scale {
+            // Zoomed with the shortcuts
+            Some(synced) if synced != zoom_factor => self.ui_scale = zoom_factor,
+            Some(synced) if synced == self.ui_scale => {}
+            // Changed in the settings, or not synced yet
+            _ => ctx.set_zoom_factor(self.ui_scale),
+        }
+        self.synced_ui_scale = Some(self.ui_scale);
+
+        if ctx.options(|options| options.theme_preference) != self.theme {
+            ctx.set_theme(self.theme);
+        }
+
+        if ctx.options(|options| options.input_options.max_click_dist) != self.drag_threshold {
+            ctx.options_mut(|options| options.input_options.max_click_dist = self.drag_threshold);
+        }
+
+        let double_click_delay = self.double_click_delay as f64;
+        if ctx.options(|options| options.input_options.max_double_click_delay) != double_click_delay
+        {
+            ctx.options_mut(|options| {
+                options.input_options.max_double_click_delay = double_click_delay
+            });
+        }
+
+        ctx.data_mut(|data| data.insert_temp(AxisColors::id(), self.axis_colors));
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The global tone mapping of the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    /// Whether the tone mapping is enabled.
+    pub enabled: bool,
+
+    /// The exposure in stops.
+    pub exposure: f32,
+
+    /// The tone mapping operator.
+    pub operator: ToneMappingOperator,
+}
+
+impl ToneMapping {
+    /// Create a new tone mapping.
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            exposure: 0.0,
+            operator: ToneMappingOperator::None,
+        }
+    }
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The tone mapping operator.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+pub enum ToneMappingOperator {
+    #[default]
+    #[strum(to_string = "None")]
+    None,
+    #[strum(to_string = "Reinhard")]
+    Reinhard,
+    #[strum(to_string = "ACES")]
+    Aces,
+}
+
+/// The stereo display mode of the scene.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+pub enum StereoMode {
+    /// No stereo.
+    #[default]
+    #[strum(to_string = "Off")]
+    Off,
+
+    /// The eyes in two viewports arranged by [`VrLayout`], for VR viewers.
+    #[strum(to_string = "VR Split")]
+    Split,
+
+    /// The eyes composited into the color channels of one viewport, for anaglyph glasses.
+    #[strum(to_string = "Anaglyph")]
+    Anaglyph,
+}
+
+/// The fixed aspect ratio of the scene viewport, letterboxed to fit the tab.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+pub enum ViewportAspect {
+    /// Fill the tab.
+    #[default]
+    #[strum(to_string = "Free")]
+    Free,
+    #[strum(to_string = "16:9")]
+    Wide,
+    #[strum(to_string = "4:3")]
+    Standard,
+    #[strum(to_string = "1:1")]
+    Square,
+    #[strum(to_string = "9:16")]
+    Portrait,
+    #[strum(to_string = "21:9")]
+    UltraWide,
+}
+
+impl ViewportAspect {
+    /// Get the width over height, [`None`] for [`ViewportAspect::Free`].
+    pub const fn ratio(&self) -> Option<f32> {
+        match self {
+            Self::Free => None,
+            Self::Wide => Some(16.0 / 9.0),
+            Self::Standard => Some(4.0 / 3.0),
+            Self::Square => Some(1.0),
+            Self::Portrait => Some(9.0 / 16.0),
+            Self::UltraWide => Some(21.0 / 9.0),
+        }
+    }
+
+    /// Fit the largest rect of this aspect ratio centered in `rect`.
+    pub fn fit(&self, rect: egui::Rect) -> egui::Rect {
+        let Some(ratio) = self.ratio() else {
+            return rect;
+        };
+
+        let size = match rect.width() / rect.height().max(1.0) > ratio {
+            true => egui::vec2(rect.height() * ratio, rect.height()),
+            false => egui::vec2(rect.width(), rect.width() / ratio),
+        };
+
+        egui::Rect::from_center_size(rect.center(), size)
+    }
+}
+
+/// The layout of the eyes in [`StereoMode::Split`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+pub enum VrLayout {
+    /// The left eye on the left and the right eye on the right.
+    #[default]
+    #[strum(to_string = "Side by Side")]
+    SideBySide,
+
+    /// The left eye on the top and the right eye on the bottom.
+    #[strum(to_string = "Top Bottom")]
+    TopBottom,
+}
+
+/// The color channels of the left and right eyes in [`StereoMode::Anaglyph`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+pub enum AnaglyphColors {
+    /// Red for the left eye and cyan for the right eye.
+    #[default]
+    #[strum(to_string = "Red/Cyan")]
+    RedCyan,
+
+    /// Green for the left eye and magenta for the right eye.
+    #[strum(to_string = "Green/Magenta")]
+    GreenMagenta,
+}
+
+/// The gamma correction of the final scene color.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+pub enum GammaCorrection {
+    /// Gamma correct only if the target format is not sRGB.
+    #[default]
+    #[strum(to_string = "Auto")]
+    Auto,
+    #[strum(to_string = "Enabled")]
+    Enabled,
+    #[strum(to_string = "Disabled")]
+    Disabled,
+}
+
+impl GammaCorrection {
+    /// Whether the linear to sRGB conversion should be applied explicitly for the target format.
+    ///
+    /// sRGB target formats are converted by the hardware when written to.
+    pub fn is_enabled(&self, target_format: eframe::wgpu::TextureFormat) -> bool {
+        match self {
+            Self::Auto => !target_format.is_srgb(),
+            Self::Enabled => true,
+            Self::Disabled => false,
+        }
+    }
+}
+
+/// The colors of the X, Y and Z labels of the numeric fields.
+///
+/// This is stored in the context by [`Settings::apply`] for [`util::axis_label`].
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, EnumIter, Display, serde::Deserialize, serde::Serialize,
+)]
+pub enum AxisColors {
+    /// Red, green and blue as in most 3D tools.
+    #[default]
+    #[strum(to_string = "Standard")]
+    Standard,
+    /// Vermillion, bluish green and blue from the Okabe-Ito palette.
+    #[strum(to_string = "Colorblind")]
+    Colorblind,
+    /// No colors.
+    #[strum(to_string = "Off")]
+    Off,
+}
+
+impl AxisColors {
+    /// Get the context data ID of the axis colors.
+    pub fn id() -> egui::Id {
+        egui::Id::new("axis_colors")
+    }
+
+    /// Get the axis colors stored in the context.
+    pub fn get(ctx: &egui::Context) -> Self {
+        ctx.data(|data| data.get_temp(Self::id()))
+            .unwrap_or_default()
+    }
+
+    /// Get the color of the axis, X is 0, Y is 1 and Z is 2.
+    ///
+    /// The colors are darker in light mode to keep the labels readable.
+    pub fn color(&self, axis: usize, dark_mode: bool) -> Option<egui::Color32> {
+        let [r, g, b] = match self {
+            Self::Standard => [[230, 80, 80], [100, 200, 90], [90, 140, 240]],
+            Self::Colorblind => [[213, 94, 0], [0, 158, 115], [0, 114, 178]],
+            Self::Off => return None,
+        }
+        .get(axis)?
+        .map(|c: u8| match dark_mode {
+            true => c,
+            false => (c as f32 * 0.75) as u8,
+        });
+
+        Some(egui::Color32::from_rgb(r, g, b))
+    }
+}
+
+/// The length unit.
+///
+/// The scene unit is assumed to be meter.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, EnumIter, Display, serde::Deserialize, serde::Serialize,
+)]
+pub enum LengthUnit {
+    #[default]
+    #[strum(to_string = "Meter")]
+    Meter,
+    #[strum(to_string = "Centimeter")]
+    Centimeter,
+    #[strum(to_string = "Millimeter")]
+    Millimeter,
+    #[strum(to_string = "Foot")]
+    Foot,
+    #[strum(to_string = "Inch")]
+    Inch,
+}
+
+impl LengthUnit {
+    /// Get the symbol of the unit.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Meter => "m",
+            Self::Centimeter => "cm",
+            Self::Millimeter => "mm",
+            Self::Foot => "ft",
+            Self::Inch => "in",
+        }
+    }
+
+    /// Convert a length in meter to this unit.
+    pub fn convert(&self, length: f32) -> f32 {
+        length
+            * match self {
+                Self::Meter => 1.0,
+                Self::Centimeter => 100.0,
+                Self::Millimeter => 1000.0,
+                Self::Foot => 1.0 / 0.3048,
+                Self::Inch => 1.0 / 0.0254,
+            }
+    }
+
+    /// Format a length in meter with this unit.
+    pub fn format(&self, length: f32) -> String {
+        format!("{:.4} {}", self.convert(length), self.symbol())
+    }
+}
+
+/// The coordinate system of the source of an imported model.
+///
+/// The scene is right-handed with Y up.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, EnumIter, Display, serde::Deserialize, serde::Serialize,
+)]
+pub enum CoordinateSystem {
+    /// The COLMAP convention of the original 3D Gaussian Splatting training output.
+    #[default]
+    #[strum(to_string = "Y-Down (3DGS)")]
+    YDown,
+    #[strum(to_string = "Y-Up")]
+    YUp,
+    #[strum(to_string = "Z-Up")]
+    ZUp,
+    #[strum(to_string = "Y-Up Left-Handed")]
+    YUpLeftHanded,
+    #[strum(to_string = "Z-Up Left-Handed")]
+    ZUpLeftHanded,
+}
+
+impl CoordinateSystem {
+    /// Get the initial transform of a model in this coordinate system.
+    ///
+    /// Left-handed models are mirrored by a negative scale.
+    pub const fn transform(&self) -> GaussianSplattingModelTransform {
+        let (rot, scale) = match self {
+            Self::YDown => (Vec3::new(0.0, 0.0, 180.0), Vec3::ONE),
+            Self::YUp => (Vec3::ZERO, Vec3::ONE),
+            Self::ZUp => (Vec3::new(-90.0, 0.0, 0.0), Vec3::ONE),
+            Self::YUpLeftHanded => (Vec3::ZERO, Vec3::new(1.0, 1.0, -1.0)),
+            Self::ZUpLeftHanded => (Vec3::new(-90.0, 0.0, 0.0), Vec3::new(1.0, -1.0, 1.0)),
+        };
+
+        GaussianSplattingModelTransform {
+            pos: Vec3::ZERO,
+            rot,
+            scale,
+        }
+    }
+
+    /// Get the linear map from this coordinate system to the scene.
+    pub fn matrix(&self) -> Mat3 {
+        let transform = self.transform();
+        let matrix = Mat3::from_quat(transform.quat()) * Mat3::from_diagonal(transform.scale);
+
+        // The conventions only swap and flip the axes
+        Mat3::from_cols_array(&matrix.to_cols_array().map(f32::round))
+    }
+
+    /// Convert the Gaussians from this coordinate system to `to`.
+    ///
+    /// See [`GaussianAxisMap::apply`] for how each Gaussian is mapped.
+    pub fn convert(&self, to: CoordinateSystem, gaussians: &gs::Gaussians) -> gs::Gaussians {
+        let map = GaussianAxisMap::new(to.matrix().inverse() * self.matrix());
+
+        gs::Gaussians {
+            gaussians: gaussians.gaussians.iter().map(|g| map.apply(g)).collect(),
+        }
     }
 }
 
-impl eframe::App for App {
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+/// A linear map of the Gaussians which only swaps and flips the axes.
+struct GaussianAxisMap {
+    /// The matrix.
+    matrix: Mat3,
+
+    /// Whether the map mirrors, i.e. flips the handedness.
+    mirror: bool,
+
+    /// The rotations of the spherical harmonics bands in [`SH_BANDS`].
+    sh_bands: [Vec<Vec<f64>>; 3],
+}
+
+impl GaussianAxisMap {
+    /// Create a new map of the matrix.
+    fn new(matrix: Mat3) -> Self {
+        Self {
+            matrix,
+            mirror: matrix.determinant() < 0.0,
+            sh_bands: SH_BANDS.map(|band| sh_band_rotation(matrix.as_dmat3(), band)),
+        }
     }
 
-    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            self.menu_bar(ctx, ui, frame);
-        });
+    /// Map a Gaussian.
+    ///
+    /// The position is mapped, the rotation is composed with the map, and the spherical
+    /// harmonics are rotated band by band. When mirroring, the rotation is flipped about its
+    /// local Z axis to keep it a proper rotation, which leaves the covariance unchanged.
+    fn apply(&self, gaussian: &gs::Gaussian) -> gs::Gaussian {
+        let mut rotation = self.matrix * Mat3::from_quat(gaussian.rotation);
+        if self.mirror {
+            rotation.z_axis = -rotation.z_axis;
+        }
 
-        egui::CentralPanel::default()
-            .frame(egui::Frame::central_panel(&ctx.style()).inner_margin(0.))
-            .show(ctx, |ui| {
-                self.tab_manager.dock_area(ui, frame, &mut self.state);
-            });
+        let mut sh = gaussian.sh;
+        for (band, rotation) in SH_BANDS.iter().zip(self.sh_bands.iter()) {
+            for (i, row) in band.clone().zip(rotation.iter()) {
+                sh[i] = band
+                    .clone()
+                    .zip(row.iter())
+                    .map(|(j, r)| gaussian.sh[j] * *r as f32)
+                    .sum();
+            }
+        }
 
-        ctx.request_repaint();
+        gs::Gaussian {
+            rotation: Quat::from_mat3(&rotation).normalize(),
+            pos: self.matrix * gaussian.pos,
+            sh,
+            ..*gaussian
+        }
     }
 }
 
-/// The state of the main application.
-#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
-pub struct State {
-    /// The Gaussian splatting model, which can be loaded from a file.
-    #[serde(skip)]
-    pub gs: Loadable<GaussianSplatting, String>,
+/// The ranges of the spherical harmonics coefficients of degree 1 to 3 in [`gs::Gaussian::sh`].
+const SH_BANDS: [Range<usize>; 3] = [0..3, 3..8, 8..15];
 
-    /// The compression settings.
-    pub compressions: Compressions,
+/// Evaluate the spherical harmonics basis of degree 1 to 3 in direction `dir`.
+///
+/// The basis is in the order and sign of [`gs::Gaussian::sh`].
+fn sh_basis(dir: DVec3) -> [f64; 15] {
+    const SH_C1: f64 = 0.4886025119029199;
+    const SH_C2: [f64; 5] = [
+        1.0925484305920792,
+        -1.0925484305920792,
+        0.31539156525252005,
+        -1.0925484305920792,
+        0.5462742152960396,
+    ];
+    const SH_C3: [f64; 7] = [
+        -0.5900435899266435,
+        2.890611442640554,
+        -0.4570457994644658,
+        0.3731763325901154,
+        -0.4570457994644658,
+        1.445305721320277,
+        -0.5900435899266435,
+    ];
+
+    let DVec3 { x, y, z } = dir;
+    let (xx, yy, zz) = (x * x, y * y, z * z);
+
+    [
+        -SH_C1 * y,
+        SH_C1 * z,
+        -SH_C1 * x,
+        SH_C2[0] * x * y,
+        SH_C2[1] * y * z,
+        SH_C2[2] * (2.0 * zz - xx - yy),
+        SH_C2[3] * x * z,
+        SH_C2[4] * (xx - yy),
+        SH_C3[0] * y * (3.0 * xx - yy),
+        SH_C3[1] * x * y * z,
+        SH_C3[2] * y * (4.0 * zz - xx - yy),
+        SH_C3[3] * z * (2.0 * zz - 3.0 * xx - 3.0 * yy),
+        SH_C3[4] * x * (4.0 * zz - xx - yy),
+        SH_C3[5] * z * (xx - yy),
+        SH_C3[6] * x * (xx - 3.0 * yy),
+    ]
+}
+
+/// Get the rotation of the spherical harmonics coefficients in `band` for the orthogonal
+/// `matrix`.
+///
+/// The rotated function satisfies `f'(d) = f(matrix⁻¹ d)`, which is fitted by least squares on
+/// directions spread over the sphere, and is exact since each band is closed under rotation.
+fn sh_band_rotation(matrix: DMat3, band: Range<usize>) -> Vec<Vec<f64>> {
+    const SAMPLE_COUNT: usize = 64;
+
+    let n = band.len();
+    let inverse = matrix.transpose();
+
+    // Normal equations `a x = b` of the fit, solved by Gauss-Jordan elimination
+    let mut a = vec![vec![0.0; n]; n];
+    let mut b = vec![vec![0.0; n]; n];
+    for k in 0..SAMPLE_COUNT {
+        // Fibonacci sphere
+        let y = 1.0 - 2.0 * (k as f64 + 0.5) / SAMPLE_COUNT as f64;
+        let r = (1.0 - y * y).sqrt();
+        let theta = k as f64 * std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+        let dir = DVec3::new(r * theta.cos(), y, r * theta.sin());
+
+        let basis = sh_basis(dir);
+        let rotated = sh_basis(inverse * dir);
+        for i in 0..n {
+            for j in 0..n {
+                a[i][j] += basis[band.start + i] * basis[band.start + j];
+                b[i][j] += basis[band.start + i] * rotated[band.start + j];
+            }
+        }
+    }
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .expect("pivot");
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let scale = a[col][col];
+        a[col].iter_mut().for_each(|x| *x /= scale);
+        b[col].iter_mut().for_each(|x| *x /= scale);
+
+        for row in (0..n).filter(|&row| row != col) {
+            let factor = a[row][col];
+            for j in 0..n {
+                a[row][j] -= factor * a[col][j];
+                b[row][j] -= factor * b[col][j];
+            }
+        }
+    }
+
+    b
 }
 
 /// The compression settings.
@@ -381,6 +1951,52 @@ impl Compressions {
                 compressions_case!(Remove, Half) => compressed_size!(None, Half),
             }
     }
+
+    /// Estimate the GPU memory usage.
+    ///
+    /// This includes the compressed Gaussians and the other per Gaussian buffers used by the
+    /// viewer, e.g. sorting, query results, and edits.
+    pub fn gpu_memory_estimate(&self, gaussian_count: usize) -> usize {
+        const BUFFER_SIZE_PER_GAUSSIAN: usize = std::mem::size_of::<u32>() // Indirect indices
+            + 2 * std::mem::size_of::<u32>() // Radix sort keys and payloads
+            + std::mem::size_of::<gs::QueryResultPod>() // Query results
+            + 4 * std::mem::size_of::<gs::GaussianEditPod>(); // Edits and unedited edits
+
+        self.compressed_size(gaussian_count) + gaussian_count * BUFFER_SIZE_PER_GAUSSIAN
+    }
+
+    /// Get the next higher compression settings.
+    ///
+    /// Returns [`None`] if this is already the highest compression.
+    pub fn higher(&self) -> Option<Self> {
+        match self {
+            Self {
+                sh: ShCompression::Single | ShCompression::Half,
+                ..
+            } => Some(Self {
+                sh: ShCompression::Norm8,
+                ..self.clone()
+            }),
+            Self {
+                cov3d: Cov3dCompression::Single,
+                ..
+            } => Some(Self {
+                cov3d: Cov3dCompression::Half,
+                ..self.clone()
+            }),
+            Self {
+                sh: ShCompression::Norm8,
+                ..
+            } => Some(Self {
+                sh: ShCompression::Remove,
+                ..self.clone()
+            }),
+            Self {
+                sh: ShCompression::Remove,
+                cov3d: Cov3dCompression::Half,
+            } => None,
+        }
+    }
 }
 
 /// The spherical harmonics compression settings.
@@ -403,6 +2019,18 @@ pub enum ShCompression {
     Remove,
 }
 
+impl ShCompression {
+    /// Get the abbreviated name.
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Self::Single => "F32",
+            Self::Half => "F16",
+            Self::Norm8 => "N8",
+            Self::Remove => "None",
+        }
+    }
+}
+
 /// The covariance 3D compression settings.
 #[derive(
     Debug, Default, Clone, Copy, PartialEq, EnumIter, Display, serde::Deserialize, serde::Serialize,
@@ -417,6 +2045,277 @@ pub enum Cov3dCompression {
     Half,
 }
 
+impl Cov3dCompression {
+    /// Get the abbreviated name.
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Self::Single => "F32",
+            Self::Half => "F16",
+        }
+    }
+}
+
+/// The filter for upscaling the viewport.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    Display,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum UpscaleFilter {
+    #[strum(to_string = "Nearest")]
+    Nearest,
+    #[default]
+    #[strum(to_string = "Linear")]
+    Linear,
+}
+
+/// When the app repaints.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    Display,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum RenderMode {
+    /// Repaint every frame, even when nothing has changed.
+    #[default]
+    #[strum(to_string = "Always")]
+    Always,
+
+    /// Only repaint when there is any input or change.
+    #[strum(to_string = "On Interaction")]
+    OnInteraction,
+
+    /// Repaint on input or change, and at [`RenderMode::POWER_SAVER_FPS`] when idle.
+    #[strum(to_string = "Power Saver")]
+    PowerSaver,
+}
+
+impl RenderMode {
+    /// The idle frame rate of [`RenderMode::PowerSaver`].
+    pub const POWER_SAVER_FPS: u32 = 10;
+}
+
+/// A pass of the scene measured by [`Performance::gpu_timing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumIter, EnumCount)]
+pub enum GpuPass {
+    /// Preprocess the Gaussians.
+    Preprocess,
+
+    /// Sort the Gaussians.
+    Sort,
+
+    /// Render the Gaussians.
+    ///
+    /// This requires timestamp queries inside passes.
+    Render,
+
+    /// Postprocess the query of the last frame.
+    Postprocess,
+}
+
+/// The performance settings.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Performance {
+    /// The time budget for loading Gaussians in each frame in milliseconds.
+    pub loading_budget: f32,
+
+    /// When to repaint.
+    pub render_mode: RenderMode,
+
+    /// The GPU memory cap in megabytes to warn about before loading.
+    pub memory_cap: Option<u32>,
+
+    /// The idle minutes after which the GPU buffers of the models are released, [`None`] to
+    /// keep them.
+    ///
+    /// The buffers are rebuilt from the Gaussians kept in memory on the next interaction.
+    pub idle_release: Option<u32>,
+
+    /// The fraction of the Gaussians left after culling to render in each model, for testing the
+    /// performance.
+    #[serde(skip)]
+    pub render_limit: Option<f32>,
+
+    /// The resolution scale of the viewport.
+    pub render_scale: f32,
+
+    /// The filter for upscaling the viewport when the render scale is less than 1.
+    pub upscale_filter: UpscaleFilter,
+
+    /// Whether to render the viewport in full resolution when the camera is idle.
+    pub render_scale_refine: bool,
+
+    /// Whether to blend successive frames with subpixel jitter when the camera is idle.
+    pub temporal_accumulation: bool,
+
+    /// Whether to anti-alias the viewport with FXAA after the tone mapping.
+    ///
+    /// This renders the viewport offscreen first.
+    pub fxaa: bool,
+
+    /// The maximum frames per second, [`None`] for no limit.
+    pub max_fps: Option<u32>,
+
+    /// Whether to show the FPS and the rendered Gaussian count in the window title.
+    pub title_stats: bool,
+
+    /// The distances beyond which the SH degree of a model is reduced by one band each,
+    /// [`None`] to disable.
+    ///
+    /// The distance is from the camera to the bounds of a model, so the whole model uses the
+    /// same degree.
+    pub sh_lod: Option<[f32; 3]>,
+
+    /// Whether the Gaussians write an approximate depth at their centers.
+    ///
+    /// This lets the overlays, e.g. the measurements and mask gizmos, be occluded by dense
+    /// Gaussians, since the overlays are then rendered after the Gaussians. This is experimental.
+    pub depth_write: bool,
+
+    /// Whether to measure the GPU time of the passes with timestamp queries.
+    ///
+    /// This is only available when the GPU supports timestamp queries inside encoders.
+    pub gpu_timing: bool,
+
+    /// Whether to wait for the vertical sync when presenting.
+    ///
+    /// This is only available on native, and takes effect after restarting.
+    pub vsync: bool,
+}
+
+impl Performance {
+    /// The bit depth of the radix sort keys.
+    ///
+    /// The sort keys are the `f32` view depths of the Gaussians written by the preprocessor,
+    /// and [`gs::RadixSorter`] always sorts all of their bits in four 8-bit passes, so the
+    /// precision is fixed. Popping on flat scenes is from Gaussians with nearly equal depths
+    /// swapping order between frames, not from truncated keys.
+    pub const SORT_KEY_BITS: u32 = 32;
+
+    /// Create a new performance settings.
+    pub const fn new() -> Self {
+        Self {
+            loading_budget: 6.0,
+            render_mode: RenderMode::Always,
+            memory_cap: None,
+            idle_release: None,
+            render_limit: None,
+            render_scale: 1.0,
+            upscale_filter: UpscaleFilter::Linear,
+            render_scale_refine: true,
+            temporal_accumulation: false,
+            fxaa: false,
+            max_fps: None,
+            title_stats: false,
+            sh_lod: None,
+            depth_write: false,
+            gpu_timing: false,
+            vsync: true,
+        }
+    }
+
+    /// Get the number of Gaussians in the visible models scaled by the render limit, including the
+    /// culled ones.
+    ///
+    /// The render limit applies after culling, so this is an upper bound of the rendered count.
+    pub fn rendered_count(&self, gs: &GaussianSplatting) -> usize {
+        gs.models
+            .values()
+            .filter(|model| model.visible)
+            .map(|model| self.render_limit_count(model.gaussians.gaussians.len()))
+            .sum()
+    }
+
+    /// The default distances of [`Performance::sh_lod`].
+    pub const SH_LOD_DISTANCES: [f32; 3] = [10.0, 20.0, 40.0];
+
+    /// Get the SH degree of a model at `distance` from the camera after the SH LOD.
+    pub fn sh_lod_degree(
+        &self,
+        sh_deg: gs::GaussianShDegree,
+        distance: f32,
+    ) -> gs::GaussianShDegree {
+        match self.sh_lod {
+            Some(distances) => {
+                let max = 3 - distances.iter().filter(|d| distance > **d).count() as u8;
+                gs::GaussianShDegree::new_unchecked(sh_deg.degree().min(max))
+            }
+            None => sh_deg,
+        }
+    }
+
+    /// Get the number of Gaussians to render out of `count` after the render limit.
+    pub fn render_limit_count(&self, count: usize) -> usize {
+        match self.render_limit {
+            Some(fraction) => ((count as f64 * fraction as f64).ceil() as usize).min(count),
+            None => count,
+        }
+    }
+
+    /// Get the GPU memory warnings for loading a model.
+    ///
+    /// Returns an empty list if there is no problem.
+    pub fn memory_warnings(
+        &self,
+        limits: &eframe::wgpu::Limits,
+        compressions: &Compressions,
+        gaussian_count: usize,
+    ) -> Vec<String> {
+        const MAX_BUFFER_SIZE_FRACTION: f64 = 0.8;
+
+        let compressed_size = compressions.compressed_size(gaussian_count);
+        let estimate = compressions.gpu_memory_estimate(gaussian_count);
+        let mut warnings = Vec::new();
+
+        if compressed_size as u64 > limits.max_storage_buffer_binding_size as u64 {
+            warnings.push(format!(
+                "The Gaussians ({}) exceed the storage buffer limit of the device ({})",
+                util::human_readable_size(compressed_size),
+                util::human_readable_size(limits.max_storage_buffer_binding_size as usize),
+            ));
+        } else if estimate as f64 > limits.max_buffer_size as f64 * MAX_BUFFER_SIZE_FRACTION {
+            warnings.push(format!(
+                "The estimated GPU memory ({}) is close to the buffer limit of the device ({})",
+                util::human_readable_size(estimate),
+                util::human_readable_size(limits.max_buffer_size as usize),
+            ));
+        }
+
+        if let Some(cap) = self.memory_cap {
+            if estimate > cap as usize * 1024 * 1024 {
+                warnings.push(format!(
+                    "The estimated GPU memory ({}) exceeds the memory cap ({})",
+                    util::human_readable_size(estimate),
+                    util::human_readable_size(cap as usize * 1024 * 1024),
+                ));
+            }
+        }
+
+        warnings
+    }
+}
+
+impl Default for Performance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// An unloaded value.
 #[derive(Debug)]
 pub struct Unloaded<T, E> {
@@ -475,6 +2374,7 @@ pub enum SceneCommand {
     /// Add a new model.
     AddModel {
         file_name: String,
+        path: Option<PathBuf>,
         reader: Box<dyn BufRead + Send>,
     },
 
@@ -486,6 +2386,46 @@ pub enum SceneCommand {
 
     /// Update mask.
     EvaluateMask(Option<GaussianSplattingMaskOp>),
+
+    /// Update the transforms of all models, not only the selected model.
+    UpdateModelTransforms,
+
+    /// Flip the handedness of a model, see [`GaussianSplattingModel::flip_handedness`].
+    FlipHandedness(String),
+
+    /// Save the session to a project file.
+    SaveProject,
+
+    /// Extract the selected Gaussians of a model to a new model.
+    ExtractSelection(String),
+
+    /// Delete the selected Gaussians of a model.
+    DeleteSelection(String),
+
+    /// Undo the last deletion of selected Gaussians.
+    UndoDeleteSelection,
+
+    /// Save the selected Gaussians of a model with the current edit as a new layer.
+    SaveSelectionLayer(String),
+
+    /// Write the edits of the layers at the indices to their Gaussians.
+    ApplySelectionLayers(Vec<usize>),
+
+    /// Remove a layer and clear the edits of its Gaussians.
+    RemoveSelectionLayer(usize),
+
+    /// Turn the camera to the center of the selected Gaussians of a model.
+    LookAtSelection(String),
+
+    /// Select all Gaussians of the visible models, limited to the mask if
+    /// [`Selection::limit_to_mask`].
+    SelectAll,
+
+    /// Clear the selection of all models.
+    ClearSelection,
+
+    /// The session is saved to a project or exported.
+    Saved,
 }
 
 impl std::fmt::Debug for SceneCommand {
@@ -495,6 +2435,19 @@ impl std::fmt::Debug for SceneCommand {
             Self::RemoveModel(_) => write!(f, "RemoveModel"),
             Self::UpdateMeasurementHit => write!(f, "UpdateMeasurementHit"),
             Self::EvaluateMask(_) => write!(f, "EvaluateMasking"),
+            Self::UpdateModelTransforms => write!(f, "UpdateModelTransforms"),
+            Self::FlipHandedness(_) => write!(f, "FlipHandedness"),
+            Self::SaveProject => write!(f, "SaveProject"),
+            Self::ExtractSelection(_) => write!(f, "ExtractSelection"),
+            Self::DeleteSelection(_) => write!(f, "DeleteSelection"),
+            Self::UndoDeleteSelection => write!(f, "UndoDeleteSelection"),
+            Self::SaveSelectionLayer(_) => write!(f, "SaveSelectionLayer"),
+            Self::ApplySelectionLayers(_) => write!(f, "ApplySelectionLayers"),
+            Self::RemoveSelectionLayer(_) => write!(f, "RemoveSelectionLayer"),
+            Self::LookAtSelection(_) => write!(f, "LookAtSelection"),
+            Self::SelectAll => write!(f, "SelectAll"),
+            Self::ClearSelection => write!(f, "ClearSelection"),
+            Self::Saved => write!(f, "Saved"),
         }
     }
 }
@@ -511,6 +2464,10 @@ pub struct GaussianSplatting {
     /// The Gaussian model loader receiver.
     pub model_loader: Option<(String, mpsc::Receiver<Result<gs::Gaussian, gs::Error>>)>,
 
+    /// The number of models waiting to be loaded after the loading model, e.g. from a zip or a
+    /// folder.
+    pub queued_models: usize,
+
     /// The sender for scene to handle scene related updates.
     pub scene_tx: mpsc::Sender<SceneCommand>,
 
@@ -520,6 +2477,10 @@ pub struct GaussianSplatting {
     /// The currently selected Gaussian model.
     pub selected_model_key: String,
 
+    /// The other selected models for batch operations besides
+    /// [`GaussianSplatting::selected_model_key`].
+    pub selected_model_keys: HashSet<String>,
+
     /// The Gaussian transform.
     pub gaussian_transform: GaussianSplattingGaussianTransform,
 
@@ -529,20 +2490,50 @@ pub struct GaussianSplatting {
     /// The measurement of the Gaussian splatting.
     pub measurement: Measurement,
 
+    /// The capture camera markers.
+    pub capture_cameras: CaptureCameras,
+
     /// The selection of the Gaussian splatting.
     pub selection: Selection,
 
+    /// The point pair registration between models.
+    pub registration: Registration,
+
     /// The used compression settings.
     pub compressions: Compressions,
 
     /// The export modal.
     pub export_modal: Option<ExportModal>,
+
+    /// The project being restored.
+    pub project_restore: Option<project::ProjectRestore>,
+
+    /// The picked Gaussian for inspection.
+    pub picked_gaussian: Option<PickedGaussian>,
+
+    /// The annotations to include in the annotation export.
+    pub annotation_export: annotation::AnnotationExport,
+
+    /// The GPU time of each [`GpuPass`] in milliseconds of the last measured frame.
+    ///
+    /// [`None`] if the pass is not measured, see [`Performance::gpu_timing`].
+    pub gpu_timings: [Option<f32>; GpuPass::COUNT],
+
+    /// Whether the annotations and edits changed since the last project save or export.
+    pub unsaved: bool,
+
+    /// The hash of the annotations in the last frame, see [`GaussianSplatting::update_unsaved`].
+    pub annotations_hash: Option<u64>,
+
+    /// The action waiting for confirmation to discard the unsaved changes.
+    pub discard_confirm: Option<DiscardAction>,
 }
 
 impl GaussianSplatting {
     /// Create a Gaussian splatting model from a PLY file.
     pub fn new(
         file_name: String,
+        path: Option<PathBuf>,
         ply: impl BufRead + Send + 'static,
         compressions: Compressions,
     ) -> Result<Self, gs::Error> {
@@ -552,32 +2543,297 @@ impl GaussianSplatting {
 
         let gaussian_transform = GaussianSplattingGaussianTransform::new();
 
-        let (count, gaussian_rx) = GaussianSplattingModel::init_load(ply)?;
+        let (count, color_space, gaussian_rx) = GaussianSplattingModel::init_load(ply)?;
+
+        let mut model = GaussianSplattingModel::new(file_name, count);
+        model.set_path(path);
+        model.color_space = color_space;
+
+        let key = model.file_name.clone();
+
+        let (scene_tx, scene_rx) = mpsc::channel();
+
+        let camera = Camera::new();
+
+        log::info!("Gaussian splatting model loaded");
+
+        Ok(Self {
+            camera,
+            models: HashMap::from([(key.clone(), model)]),
+            model_loader: Some((key.clone(), gaussian_rx)),
+            queued_models: 0,
+            scene_tx,
+            scene_rx,
+            selected_model_key: key,
+            selected_model_keys: HashSet::new(),
+            gaussian_transform,
+            action: None,
+            measurement,
+            capture_cameras: CaptureCameras::new(),
+            selection,
+            registration: Registration::new(),
+            compressions,
+            export_modal: None,
+            project_restore: None,
+            picked_gaussian: None,
+            annotation_export: annotation::AnnotationExport::new(),
+            gpu_timings: [None; GpuPass::COUNT],
+            unsaved: false,
+            annotations_hash: None,
+            discard_confirm: None,
+        })
+    }
+
+    /// Whether the session can be saved to a project, all models must be loaded.
+    pub fn is_saveable(&self) -> bool {
+        self.model_loader.is_none()
+            && self
+                .project_restore
+                .as_ref()
+                .is_none_or(project::ProjectRestore::is_done)
+    }
+
+    /// Mark the session unsaved if the annotations changed since the last frame.
+    ///
+    /// The masks, selection edits, layers and measurements are compared. Edits which only live in
+    /// the GPU buffers, e.g. deleting the selection, are marked directly by setting
+    /// [`GaussianSplatting::unsaved`].
+    pub fn update_unsaved(&mut self) {
+        use std::hash::{Hash, Hasher};
+
+        // The annotations are written gradually when restoring a project, which are not changes
+        if self.model_loader.is_some()
+            || self
+                .project_restore
+                .as_ref()
+                .is_some_and(|restore| !restore.is_done())
+        {
+            self.annotations_hash = None;
+            return;
+        }
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for (key, model) in self.models.iter().sorted_by_key(|(key, _)| *key) {
+            key.hash(&mut hasher);
+            format!("{:?}", model.mask.shapes).hash(&mut hasher);
+            model.mask.op_code.hash(&mut hasher);
+        }
+        format!("{:?}", self.selection.edit).hash(&mut hasher);
+        format!("{:?}", self.selection.layers).hash(&mut hasher);
+        format!("{:?}", self.measurement.hit_pairs).hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if self.annotations_hash.is_some_and(|prev| prev != hash) {
+            self.unsaved = true;
+        }
+        self.annotations_hash = Some(hash);
+    }
+
+    /// Open the PLY models in a zip archive, such as the exported models.
+    ///
+    /// The archive is unpacked in a task. The first model that loads opens the session and is
+    /// sent to `tx`, the others are added to it by [`SceneCommand::AddModel`]. Entries that fail
+    /// to load are logged and skipped.
+    pub fn open_zip(
+        zip: impl std::io::Read + std::io::Seek + Send + 'static,
+        compressions: Compressions,
+        tx: mpsc::Sender<Result<Self, String>>,
+        ctx: egui::Context,
+    ) {
+        util::exec_task(async move {
+            let mut scene_tx = None;
+
+            let result = Self::read_zip(zip, |file_name, data| match &scene_tx {
+                Some(scene_tx) => Self::send_add_model(scene_tx, file_name, data),
+                None => match Self::new(
+                    file_name.clone(),
+                    None,
+                    Cursor::new(data),
+                    compressions.clone(),
+                ) {
+                    Ok(gs) => {
+                        scene_tx = Some(gs.scene_tx.clone());
+                        tx.send(Ok(gs)).expect("send gs");
+                        ctx.request_repaint();
+                    }
+                    Err(e) => log::error!("Error loading {file_name} from zip: {e}"),
+                },
+            })
+            .await;
+
+            if scene_tx.is_none() {
+                let err = result
+                    .err()
+                    .unwrap_or_else(|| "No PLY model in the zip archive".to_string());
+                tx.send(Err(err)).expect("send gs");
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// Open the PLY models in a folder, in the order of their file names.
+    ///
+    /// The first model that loads opens the session and is sent to `tx`, the others are added to
+    /// it by [`SceneCommand::AddModel`]. Other files and the subfolders are skipped.
+    ///
+    /// This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_folder(
+        folder: &std::path::Path,
+        compressions: Compressions,
+        tx: mpsc::Sender<Result<Self, String>>,
+        ctx: egui::Context,
+    ) {
+        let paths = match std::fs::read_dir(folder) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file())
+                .sorted()
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                tx.send(Err(e.to_string())).expect("send gs");
+                ctx.request_repaint();
+                return;
+            }
+        };
+
+        let (models, skipped) = paths.into_iter().partition::<Vec<_>, _>(|path| {
+            path.file_name()
+                .is_some_and(|name| util::is_ply(&name.to_string_lossy()))
+        });
+
+        let mut scene_tx: Option<mpsc::Sender<SceneCommand>> = None;
+        let mut opened = 0;
+
+        for path in models {
+            let file_name = path
+                .file_name()
+                .expect("file name")
+                .to_string_lossy()
+                .to_string();
+
+            let reader = match std::fs::File::open(&path) {
+                Ok(file) => Box::new(std::io::BufReader::new(file)),
+                Err(e) => {
+                    log::error!("Error opening {file_name}: {e}");
+                    continue;
+                }
+            };
+
+            match &scene_tx {
+                Some(scene_tx) => {
+                    if let Err(e) = scene_tx.send(SceneCommand::AddModel {
+                        file_name,
+                        path: Some(path),
+                        reader,
+                    }) {
+                        log::error!("Error adding model from folder: {e}");
+                        continue;
+                    }
+                }
+                None => {
+                    match Self::new(file_name.clone(), Some(path), reader, compressions.clone()) {
+                        Ok(gs) => {
+                            scene_tx = Some(gs.scene_tx.clone());
+                            tx.send(Ok(gs)).expect("send gs");
+                            ctx.request_repaint();
+                        }
+                        Err(e) => {
+                            log::error!("Error loading {file_name} from folder: {e}");
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            opened += 1;
+        }
+
+        log::info!(
+            "Opened {opened} models from {}, skipped {} other files",
+            folder.display(),
+            skipped.len(),
+        );
+
+        if scene_tx.is_none() {
+            tx.send(Err("No PLY model in the folder".to_string()))
+                .expect("send gs");
+            ctx.request_repaint();
+        }
+    }
+
+    /// Add the PLY models in a zip archive to the session.
+    ///
+    /// The archive is unpacked in a task, each model is sent as [`SceneCommand::AddModel`].
+    /// Entries that fail to read are logged and skipped.
+    pub fn add_zip(
+        zip: impl std::io::Read + std::io::Seek + Send + 'static,
+        scene_tx: mpsc::Sender<SceneCommand>,
+    ) {
+        util::exec_task(async move {
+            if let Err(e) = Self::read_zip(zip, |file_name, data| {
+                Self::send_add_model(&scene_tx, file_name, data)
+            })
+            .await
+            {
+                log::error!("Error reading zip: {e}");
+            }
+        });
+    }
 
-        let model = GaussianSplattingModel::new(file_name, count);
+    /// Read the PLY entries of a zip archive one by one.
+    ///
+    /// The entries are named by their file names without the folders.
+    async fn read_zip(
+        zip: impl std::io::Read + std::io::Seek,
+        mut f: impl FnMut(String, Vec<u8>),
+    ) -> Result<(), String> {
+        let mut zip = zip::ZipArchive::new(zip).map_err(|e| e.to_string())?;
 
-        let key = model.file_name.clone();
+        for i in 0..zip.len() {
+            let entry = zip
+                .by_index(i)
+                .map_err(|e| e.to_string())
+                .and_then(|mut file| {
+                    let file_name = std::path::Path::new(file.name())
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .filter(|name| util::is_ply(name));
+
+                    match file_name {
+                        Some(file_name) if file.is_file() => {
+                            let mut data = Vec::with_capacity(file.size() as usize);
+                            std::io::Read::read_to_end(&mut file, &mut data)
+                                .map_err(|e| format!("{file_name}: {e}"))?;
+                            Ok(Some((file_name, data)))
+                        }
+                        _ => Ok(None),
+                    }
+                });
 
-        let (scene_tx, scene_rx) = mpsc::channel();
+            match entry {
+                Ok(Some((file_name, data))) => f(file_name, data),
+                Ok(None) => {}
+                Err(e) => log::error!("Error reading zip entry {i}: {e}"),
+            }
 
-        let camera = Camera::new();
+            // Yield so a large archive does not block the UI
+            #[cfg(target_arch = "wasm32")]
+            gloo_timers::future::TimeoutFuture::new(0).await;
+        }
 
-        log::info!("Gaussian splatting model loaded");
+        Ok(())
+    }
 
-        Ok(Self {
-            camera,
-            models: HashMap::from([(key.clone(), model)]),
-            model_loader: Some((key.clone(), gaussian_rx)),
-            scene_tx,
-            scene_rx,
-            selected_model_key: key,
-            gaussian_transform,
-            action: None,
-            measurement,
-            selection,
-            compressions,
-            export_modal: None,
-        })
+    /// Send an unpacked model as [`SceneCommand::AddModel`].
+    fn send_add_model(scene_tx: &mpsc::Sender<SceneCommand>, file_name: String, data: Vec<u8>) {
+        if let Err(e) = scene_tx.send(SceneCommand::AddModel {
+            file_name,
+            path: None,
+            reader: Box::new(Cursor::new(data)),
+        }) {
+            log::error!("Error adding model from zip: {e}");
+        }
     }
 
     /// Get the currently selected model.
@@ -586,35 +2842,89 @@ impl GaussianSplatting {
             .get(&self.selected_model_key)
             .expect("selected model")
     }
+
+    /// Get the currently selected model mutably.
+    pub fn selected_model_mut(&mut self) -> &mut GaussianSplattingModel {
+        self.models
+            .get_mut(&self.selected_model_key)
+            .expect("selected model")
+    }
+
+    /// Whether more than one model is selected.
+    pub fn is_multi_selected(&self) -> bool {
+        self.selected_model_keys
+            .iter()
+            .any(|key| *key != self.selected_model_key && self.models.contains_key(key))
+    }
+
+    /// Get the keys of the models for batch operations.
+    ///
+    /// These are the selected models if more than one is selected, or all models otherwise.
+    pub fn batch_model_keys(&self) -> HashSet<String> {
+        match self.is_multi_selected() {
+            true => self
+                .selected_model_keys
+                .iter()
+                .chain(std::iter::once(&self.selected_model_key))
+                .filter(|key| self.models.contains_key(*key))
+                .cloned()
+                .collect(),
+            false => self.models.keys().cloned().collect(),
+        }
+    }
 }
 
-/// The download receiver of [`ExportStage::Edits`].
+/// The download receiver of [`ExportStage::Downloads`].
+///
+/// The buffer of each model is received as soon as it is downloaded.
 #[derive(Debug)]
 pub enum ExportDownloadReceiver<T> {
     /// Waiting for download.
-    Downloading(oneshot::Receiver<Vec<T>>),
+    Downloading {
+        /// The receiver of the buffers in order.
+        rx: mpsc::Receiver<T>,
+
+        /// The buffers received so far.
+        data: Vec<T>,
+
+        /// The number of buffers.
+        count: usize,
+    },
 
     /// Downloaded.
     Downloaded(Vec<T>),
 }
 
 impl<T> ExportDownloadReceiver<T> {
-    /// Create a new downloading receiver.
-    pub fn new(rx: oneshot::Receiver<Vec<T>>) -> Self {
-        Self::Downloading(rx)
+    /// Create a new downloading receiver of `count` buffers.
+    pub fn new(rx: mpsc::Receiver<T>, count: usize) -> Self {
+        Self::Downloading {
+            rx,
+            data: Vec::with_capacity(count),
+            count,
+        }
     }
 
     /// Try to receive the downloaded data.
     pub fn try_recv(&mut self) {
         match self {
-            Self::Downloading(rx) => {
-                if let Ok(data) = rx.try_recv() {
-                    *self = Self::Downloaded(data);
+            Self::Downloading { rx, data, count } => {
+                data.extend(rx.try_iter());
+                if data.len() >= *count {
+                    *self = Self::Downloaded(std::mem::take(data));
                 }
             }
             Self::Downloaded(_) => {}
         }
     }
+
+    /// Get the number of downloaded buffers and the number of buffers.
+    pub fn progress(&self) -> (usize, usize) {
+        match self {
+            Self::Downloading { data, count, .. } => (data.len(), *count),
+            Self::Downloaded(data) => (data.len(), data.len()),
+        }
+    }
 }
 
 /// The stages of export.
@@ -632,6 +2942,208 @@ pub enum ExportStage {
         edits: Vec<Vec<gs::GaussianEditPod>>,
         masks: Vec<Vec<u32>>,
     },
+
+    /// Exporting the models one per frame, see [`ExportModal::statuses`].
+    Export {
+        file: rfd::FileHandle,
+        writer: ExportWriter,
+        edits: Vec<Vec<gs::GaussianEditPod>>,
+        masks: Vec<Vec<u32>>,
+    },
+
+    /// Waiting for the file to be written.
+    Write {
+        rx: oneshot::Receiver<Result<(), String>>,
+    },
+}
+
+/// The writer of the exported models, a PLY file for a single model or a zip of PLY files.
+pub enum ExportWriter {
+    Ply(Cursor<Vec<u8>>),
+    Zip(Box<zip::ZipWriter<Cursor<Vec<u8>>>>),
+}
+
+impl ExportWriter {
+    /// Create a new writer for the number of models.
+    pub fn new(count: usize) -> Self {
+        match count {
+            1 => Self::Ply(Cursor::new(Vec::new())),
+            _ => Self::Zip(Box::new(zip::ZipWriter::new(Cursor::new(Vec::new())))),
+        }
+    }
+
+    /// Write the Gaussians of a model with the spherical harmonics truncated to `sh_deg`.
+    pub fn write(
+        &mut self,
+        file_name: &str,
+        gaussians: &gs::Gaussians,
+        edit: Option<&Vec<gs::GaussianEditPod>>,
+        mask: Option<&Vec<u32>>,
+        sh_deg: gs::GaussianShDegree,
+    ) -> Result<(), String> {
+        match self {
+            Self::Ply(cursor) => Self::write_ply(cursor, gaussians, edit, mask, sh_deg),
+            Self::Zip(zip) => {
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .large_file(true);
+                zip.start_file(util::ply_file_name(file_name), options)
+                    .map_err(|e| e.to_string())?;
+
+                Self::write_ply(zip.as_mut(), gaussians, edit, mask, sh_deg)
+            }
+        }
+    }
+
+    /// Write the Gaussians to a PLY file with the spherical harmonics truncated to `sh_deg`.
+    ///
+    /// [`gs::Gaussians::write_ply`] always writes all coefficients, so the header and vertices
+    /// are written here with only the `f_rest` properties of the kept degrees, in the same
+    /// channel-major layout as the original 3D Gaussian Splatting output.
+    fn write_ply(
+        writer: &mut impl std::io::Write,
+        gaussians: &gs::Gaussians,
+        edit: Option<&Vec<gs::GaussianEditPod>>,
+        mask: Option<&Vec<u32>>,
+        sh_deg: gs::GaussianShDegree,
+    ) -> Result<(), String> {
+        let mask = mask.map(|mask| mask.iter().copied());
+
+        if sh_deg.degree() == 3 {
+            return gaussians
+                .write_ply(writer, edit, mask)
+                .map_err(|e| e.to_string());
+        }
+
+        let masks = mask.map(|mask| {
+            mask.flat_map(|mask| (0..32).map(move |i| mask & (1 << i) != 0))
+                .collect::<Vec<_>>()
+        });
+        let export_gaussians = gaussians
+            .gaussians
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                masks
+                    .as_ref()
+                    .is_none_or(|masks| masks.get(*i).copied().unwrap_or(false))
+            })
+            .filter_map(|(i, gaussian)| match edit {
+                Some(edit) => gaussian.with_edit(&edit[i]),
+                None => Some(*gaussian),
+            })
+            .collect::<Vec<_>>();
+
+        let sh_count = Self::sh_count(sh_deg);
+        let write = |writer: &mut dyn std::io::Write| -> std::io::Result<()> {
+            writeln!(writer, "ply")?;
+            writeln!(writer, "format binary_little_endian 1.0")?;
+            writeln!(writer, "element vertex {}", export_gaussians.len())?;
+            for property in [
+                "x", "y", "z", "nx", "ny", "nz", "f_dc_0", "f_dc_1", "f_dc_2",
+            ] {
+                writeln!(writer, "property float {property}")?;
+            }
+            for i in 0..3 * sh_count {
+                writeln!(writer, "property float f_rest_{i}")?;
+            }
+            for property in [
+                "opacity", "scale_0", "scale_1", "scale_2", "rot_0", "rot_1", "rot_2", "rot_3",
+            ] {
+                writeln!(writer, "property float {property}")?;
+            }
+            writeln!(writer, "end_header")?;
+
+            for gaussian in export_gaussians.iter() {
+                let ply = gaussian.to_ply();
+                let mut values = Vec::with_capacity(Self::property_count(sh_deg));
+                values.extend_from_slice(&ply.pos);
+                values.extend_from_slice(&ply.normal);
+                values.extend_from_slice(&ply.color);
+                for channel in ply.sh.chunks_exact(15) {
+                    values.extend_from_slice(&channel[..sh_count]);
+                }
+                values.push(ply.alpha);
+                values.extend_from_slice(&ply.scale);
+                values.extend_from_slice(&ply.rotation);
+
+                writer.write_all(bytemuck::cast_slice(&values))?;
+            }
+
+            Ok(())
+        };
+
+        write(writer).map_err(|e| e.to_string())
+    }
+
+    /// Get the number of spherical harmonics coefficients per channel excluding the DC.
+    fn sh_count(sh_deg: gs::GaussianShDegree) -> usize {
+        (sh_deg.degree() as usize + 1).pow(2) - 1
+    }
+
+    /// Get the number of float properties of a Gaussian in the PLY file.
+    fn property_count(sh_deg: gs::GaussianShDegree) -> usize {
+        17 + 3 * Self::sh_count(sh_deg)
+    }
+
+    /// Estimate the size of the PLY file of Gaussians, excluding the header.
+    ///
+    /// This is an upper bound, since the hidden and masked Gaussians are not written.
+    pub fn estimate_ply_size(count: usize, sh_deg: gs::GaussianShDegree) -> usize {
+        count * Self::property_count(sh_deg) * std::mem::size_of::<f32>()
+    }
+
+    /// Finish writing and get the file content.
+    pub fn finish(self) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Ply(cursor) => Ok(cursor.into_inner()),
+            Self::Zip(zip) => zip
+                .finish()
+                .map(Cursor::into_inner)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Debug for ExportWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ply(..) => write!(f, "Ply"),
+            Self::Zip(..) => write!(f, "Zip"),
+        }
+    }
+}
+
+/// The status of a model in the export.
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum ExportFileStatus {
+    #[strum(to_string = "⏳ Pending")]
+    Pending,
+    #[strum(to_string = "✏ Writing")]
+    Writing,
+    #[strum(to_string = "✔ Done")]
+    Done,
+    #[strum(to_string = "❌ Failed")]
+    Failed(String),
+}
+
+/// The action which discards the current session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscardAction {
+    /// Open another model.
+    OpenModel,
+
+    /// Open the models in a folder.
+    ///
+    /// This is only available on native.
+    #[cfg(not(target_arch = "wasm32"))]
+    OpenFolder,
+
+    /// Open another project.
+    OpenProject,
+
+    /// Close the models.
+    CloseModels,
 }
 
 /// The export modal.
@@ -642,25 +3154,66 @@ pub struct ExportModal {
 
     /// The receiver for the edits download.
     pub stage: Option<ExportStage>,
+
+    /// The error of the last export attempt.
+    pub err: Option<String>,
+
+    /// Whether the models are exported successfully.
+    pub exported: bool,
+
+    /// The coordinate system of the models.
+    pub from: CoordinateSystem,
+
+    /// The coordinate system to convert the exported models to.
+    pub to: CoordinateSystem,
+
+    /// The export status of each model, [`None`] if the model is not exported.
+    ///
+    /// Empty before the models are being exported.
+    pub statuses: Vec<Option<ExportFileStatus>>,
 }
 
 impl ExportModal {
     /// Create a new export modal.
-    pub fn new(count: usize) -> Self {
+    ///
+    /// Only the selected models are exported by default if more than one is selected, see
+    /// [`GaussianSplatting::batch_model_keys`].
+    ///
+    /// The models are assumed to be in `coordinate_system`, which is also the default to export
+    /// to, i.e. without conversion.
+    pub fn new(gs: &GaussianSplatting, coordinate_system: CoordinateSystem) -> Self {
+        let keys = gs.batch_model_keys();
+
         Self {
-            settings: vec![ExportSettings::default(); count],
+            settings: gs
+                .models
+                .keys()
+                .sorted()
+                .map(|key| ExportSettings {
+                    export: keys.contains(key),
+                    ..ExportSettings::default()
+                })
+                .collect(),
             stage: None,
+            err: None,
+            exported: false,
+            from: coordinate_system,
+            to: coordinate_system,
+            statuses: Vec::new(),
         }
     }
 
     /// The ui.
     ///
+    /// `loading` is the key of the model still loading, which cannot be exported.
+    ///
     /// Returns whether the export modal should be kept alive.
-    fn ui<G: gs::GaussianPod>(
+    pub fn ui<G: gs::GaussianPod>(
         &mut self,
-        ui: &mut egui::Ui,
+        ctx: &egui::Context,
         frame: &mut eframe::Frame,
         models: &HashMap<String, GaussianSplattingModel>,
+        loading: Option<&String>,
     ) -> bool {
         let mut alive = true;
 
@@ -669,7 +3222,7 @@ impl ExportModal {
             .sorted_by_key(|(k, _)| (*k).clone())
             .collect::<Vec<_>>();
 
-        egui::Modal::new(egui::Id::new("export_modal")).show(ui.ctx(), |ui| {
+        egui::Modal::new(egui::Id::new("export_modal")).show(ctx, |ui| {
             ui.add(egui::Label::new(
                 egui::RichText::new("Export model").heading(),
             ));
@@ -689,7 +3242,10 @@ impl ExportModal {
                 .striped(true)
                 .resizable(true)
                 .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                .columns(egui_extras::Column::auto(), 4)
+                .columns(
+                    egui_extras::Column::auto(),
+                    if self.statuses.is_empty() { 6 } else { 7 },
+                )
                 .min_scrolled_height(0.0)
                 .max_scroll_height(available_height)
                 .header(20.0, |mut header| {
@@ -722,16 +3278,42 @@ impl ExportModal {
                         }
                         ui.strong("Mask");
                     });
+                    header.col(|ui| {
+                        ui.strong("SH Degree").on_hover_text(
+                            "The spherical harmonics degree to keep, lower degrees drop the \
+                            view-dependent color for smaller files",
+                        );
+                    });
+                    header.col(|ui| {
+                        ui.strong("Size").on_hover_text(
+                            "The estimated size of the PLY file before zip compression, \
+                            hidden and masked Gaussians make it smaller",
+                        );
+                    });
+                    if !self.statuses.is_empty() {
+                        header.col(|ui| {
+                            ui.strong("Status");
+                        });
+                    }
                 })
                 .body(|body| {
                     body.rows(text_height, models.len(), |mut row| {
                         let index = row.index();
 
                         let setting = &mut self.settings[index];
-                        let (_, model) = &models_ordered[index];
+                        let (key, model) = &models_ordered[index];
+
+                        let is_loading = loading == Some(*key);
+                        if is_loading {
+                            setting.export = false;
+                        }
 
                         row.col(|ui| {
-                            ui.checkbox(&mut setting.export, "");
+                            ui.add_enabled(
+                                !is_loading,
+                                egui::Checkbox::without_text(&mut setting.export),
+                            )
+                            .on_disabled_hover_text("The model is still loading");
                         });
 
                         row.col(|ui| {
@@ -745,17 +3327,161 @@ impl ExportModal {
                         row.col(|ui| {
                             ui.checkbox(&mut setting.mask, "");
                         });
+
+                        row.col(|ui| {
+                            egui::ComboBox::from_id_salt(("export_sh_deg", index))
+                                .width(40.0)
+                                .selected_text(setting.sh_deg.degree().to_string())
+                                .show_ui(ui, |ui| {
+                                    for degree in 0..=3 {
+                                        ui.selectable_value(
+                                            &mut setting.sh_deg,
+                                            gs::GaussianShDegree::new_unchecked(degree),
+                                            degree.to_string(),
+                                        );
+                                    }
+                                });
+                        });
+
+                        row.col(|ui| {
+                            ui.label(util::human_readable_size(ExportWriter::estimate_ply_size(
+                                model.gaussians.gaussians.len(),
+                                setting.sh_deg,
+                            )));
+                        });
+
+                        if let Some(status) = self.statuses.get(index) {
+                            row.col(|ui| match status {
+                                Some(ExportFileStatus::Writing) => {
+                                    ui.add(egui::Spinner::new());
+                                    ui.label("Writing");
+                                }
+                                Some(status @ ExportFileStatus::Failed(e)) => {
+                                    ui.label(
+                                        egui::RichText::new(status.to_string())
+                                            .color(egui::Color32::RED),
+                                    )
+                                    .on_hover_text(e);
+                                }
+                                Some(status) => {
+                                    ui.label(status.to_string());
+                                }
+                                None => {}
+                            });
+                        }
                     });
                 });
+
+            let size = models_ordered
+                .iter()
+                .zip(self.settings.iter())
+                .filter(|(_, setting)| setting.export)
+                .map(|((_, model), setting)| {
+                    ExportWriter::estimate_ply_size(model.gaussians.gaussians.len(), setting.sh_deg)
+                })
+                .sum::<usize>();
+            ui.label(format!(
+                "Estimated size: {}",
+                util::human_readable_size(size)
+            ));
+            ui.label("");
+
+            egui::Grid::new("export_coordinate_system_grid").show(ui, |ui| {
+                for (label, hover, value, salt) in [
+                    (
+                        "From",
+                        "The coordinate system the models were loaded in",
+                        &mut self.from,
+                        "export_from_coordinate_system",
+                    ),
+                    (
+                        "To",
+                        "The coordinate system to convert the exported models to",
+                        &mut self.to,
+                        "export_to_coordinate_system",
+                    ),
+                ] {
+                    ui.label(label).on_hover_text(hover);
+                    egui::ComboBox::from_id_salt(salt)
+                        .selected_text(value.to_string())
+                        .show_ui(ui, |ui| {
+                            for system in CoordinateSystem::iter() {
+                                ui.selectable_value(value, system, system.to_string());
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
             ui.label("");
 
+            if let Some(ExportStage::Downloads { edits, masks }) = &self.stage {
+                let (edits_done, edits_count) = edits.progress();
+                let (masks_done, masks_count) = masks.progress();
+                let (done, count) = (edits_done + masks_done, edits_count + masks_count);
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::Spinner::new());
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / count.max(1) as f32)
+                            .show_percentage()
+                            .text(format!("Downloading buffers: {done} / {count}")),
+                    );
+                });
+                ui.label("");
+            }
+
+            if let Some(ExportStage::Export { .. }) = &self.stage {
+                let count = self.statuses.iter().flatten().count();
+                let done = self
+                    .statuses
+                    .iter()
+                    .filter(|status| **status == Some(ExportFileStatus::Done))
+                    .count();
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::Spinner::new());
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / count.max(1) as f32)
+                            .show_percentage()
+                            .text(format!("Exporting models: {done} / {count}")),
+                    );
+                });
+                ui.label("");
+            }
+
+            if let Some(ExportStage::Write { .. }) = &self.stage {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Spinner::new());
+                    ui.label("Writing the file...");
+                });
+                ui.label("");
+            }
+
+            if let Some(err) = &self.err {
+                ui.label(
+                    egui::RichText::new(format!("Failed to export: {err}"))
+                        .color(egui::Color32::RED),
+                );
+                ui.label("Please try again, e.g. at a different location");
+                ui.label("");
+            }
+
             ui.horizontal(|ui| {
-                if ui.button("Confirm").clicked() {
-                    let (edits_tx, edits_rx) = oneshot::channel();
-                    let (masks_tx, masks_rx) = oneshot::channel();
+                if ui
+                    .add_enabled(
+                        self.stage.is_none() && self.settings.iter().any(|s| s.export),
+                        egui::Button::new("Confirm"),
+                    )
+                    .clicked()
+                {
+                    self.err = None;
+                    self.statuses.clear();
+
+                    let (edits_tx, edits_rx) = mpsc::channel();
+                    let (masks_tx, masks_rx) = mpsc::channel();
                     self.stage = Some(ExportStage::Downloads {
-                        edits: ExportDownloadReceiver::new(edits_rx),
-                        masks: ExportDownloadReceiver::new(masks_rx),
+                        edits: ExportDownloadReceiver::new(edits_rx, models_ordered.len()),
+                        masks: ExportDownloadReceiver::new(masks_rx, models_ordered.len()),
                     });
 
                     let render_state = frame.wgpu_render_state().expect("render state");
@@ -766,53 +3492,71 @@ impl ExportModal {
 
                     let device = render_state.device.clone();
                     let queue = render_state.queue.clone();
+                    // Only download the buffers to be applied, the others are left empty
                     let (edit_buffers, mask_buffers): (Vec<_>, Vec<_>) = models_ordered
                         .iter()
-                        .map(|(k, _)| {
+                        .zip(self.settings.iter())
+                        .map(|((k, _), setting)| {
                             let gaussian_buffers =
                                 &viewer.models.get(*k).expect("model").gaussian_buffers;
 
                             (
-                                gaussian_buffers.gaussians_edit_buffer.clone(),
-                                gaussian_buffers.mask_buffer.clone(),
+                                (setting.export && setting.edit)
+                                    .then(|| gaussian_buffers.gaussians_edit_buffer.clone()),
+                                (setting.export && setting.mask)
+                                    .then(|| gaussian_buffers.mask_buffer.clone()),
                             )
                         })
                         .unzip();
 
-                    // Download edits
+                    // Download edits, one model at a time
                     {
                         let device = device.clone();
                         let queue = queue.clone();
+                        let ctx = ui.ctx().clone();
                         util::exec_task(async move {
-                            let mut edits = Vec::with_capacity(edit_buffers.len());
                             for buffer in edit_buffers {
-                                match buffer.download(&device, &queue).await {
-                                    Ok(edit) => edits.push(edit),
-                                    Err(e) => {
-                                        log::error!("Download edit buffer: {e}");
-                                        edits.push(Vec::new());
-                                    }
+                                let edit = match buffer {
+                                    Some(buffer) => match buffer.download(&device, &queue).await {
+                                        Ok(edit) => edit,
+                                        Err(e) => {
+                                            log::error!("Download edit buffer: {e}");
+                                            Vec::new()
+                                        }
+                                    },
+                                    None => Vec::new(),
+                                };
+
+                                // The export is cancelled if the receiver is dropped
+                                if edits_tx.send(edit).is_err() {
+                                    return;
                                 }
+                                ctx.request_repaint();
                             }
-
-                            edits_tx.send(edits).expect("send edits");
                         });
                     }
 
-                    // Download masks
+                    // Download masks, one model at a time
+                    let ctx = ui.ctx().clone();
                     util::exec_task(async move {
-                        let mut masks = Vec::with_capacity(mask_buffers.len());
                         for buffer in mask_buffers {
-                            match buffer.download(&device, &queue).await {
-                                Ok(mask) => masks.push(mask),
-                                Err(e) => {
-                                    log::error!("Download mask buffer: {e}");
-                                    masks.push(Vec::new());
-                                }
+                            let mask = match buffer {
+                                Some(buffer) => match buffer.download(&device, &queue).await {
+                                    Ok(mask) => mask,
+                                    Err(e) => {
+                                        log::error!("Download mask buffer: {e}");
+                                        Vec::new()
+                                    }
+                                },
+                                None => Vec::new(),
+                            };
+
+                            // The export is cancelled if the receiver is dropped
+                            if masks_tx.send(mask).is_err() {
+                                return;
                             }
+                            ctx.request_repaint();
                         }
-
-                        masks_tx.send(masks).expect("send masks");
                     });
                 }
 
@@ -840,10 +3584,7 @@ impl ExportModal {
                     let task = rfd::AsyncFileDialog::new()
                         .set_title("Save the exported models")
                         .set_file_name(match edits.len() {
-                            1 if models_ordered[0].0.to_lowercase().ends_with(".ply") => {
-                                models_ordered[0].0.clone()
-                            }
-                            1 => format!("{}.ply", models_ordered[0].0),
+                            1 => util::ply_file_name(models_ordered[0].0),
                             _ => "models.zip".to_string(),
                         })
                         .save_file();
@@ -867,83 +3608,145 @@ impl ExportModal {
                     });
                 }
             }
-            Some(ExportStage::Save { rx, edits, masks }) => {
-                if let Ok(Some(file)) = rx.try_recv() {
-                    let mut cursor = Cursor::new(Vec::new());
-                    self.export_models(
-                        &mut cursor,
-                        models_ordered.iter().map(|(_, m)| *m),
+            Some(ExportStage::Save { rx, .. }) => match rx.try_recv() {
+                Ok(Some(file)) => {
+                    let Some(ExportStage::Save { edits, masks, .. }) =
+                        std::mem::take(&mut self.stage)
+                    else {
+                        // Variant of stage has been matched
+                        unreachable!()
+                    };
+
+                    self.statuses = self
+                        .settings
+                        .iter()
+                        .map(|setting| setting.export.then_some(ExportFileStatus::Pending))
+                        .collect();
+                    self.write_next_status();
+
+                    self.stage = Some(ExportStage::Export {
+                        file,
+                        writer: ExportWriter::new(edits.len()),
                         edits,
                         masks,
-                    )
-                    .expect("export models");
-
-                    util::exec_task(async move {
-                        if let Err(e) = file.write(cursor.into_inner().as_slice()).await {
-                            log::error!("Save file: {e}");
-                        }
                     });
+                    ctx.request_repaint();
+                }
+                // Save location dialog is cancelled
+                Ok(None) | Err(oneshot::TryRecvError::Disconnected) => {
+                    self.stage = None;
+                }
+                Err(oneshot::TryRecvError::Empty) => {}
+            },
+            Some(ExportStage::Export { .. }) => {
+                let Some(ExportStage::Export {
+                    file,
+                    mut writer,
+                    edits,
+                    masks,
+                }) = std::mem::take(&mut self.stage)
+                else {
+                    // Variant of stage has been matched
+                    unreachable!()
+                };
 
-                    alive = false;
+                match self
+                    .statuses
+                    .iter()
+                    .position(|status| *status == Some(ExportFileStatus::Writing))
+                {
+                    // Export one model per frame to show the progress
+                    Some(index) => {
+                        let model = models_ordered[index].1;
+                        let setting = &self.settings[index];
+                        let gaussians = match self.from == self.to {
+                            true => std::borrow::Cow::Borrowed(&model.gaussians),
+                            false => std::borrow::Cow::Owned(
+                                self.from.convert(self.to, &model.gaussians),
+                            ),
+                        };
+
+                        match writer.write(
+                            &model.file_name,
+                            &gaussians,
+                            setting.edit.then_some(&edits[index]),
+                            setting.mask.then_some(&masks[index]),
+                            setting.sh_deg,
+                        ) {
+                            Ok(()) => {
+                                self.statuses[index] = Some(ExportFileStatus::Done);
+                                self.write_next_status();
+
+                                self.stage = Some(ExportStage::Export {
+                                    file,
+                                    writer,
+                                    edits,
+                                    masks,
+                                });
+                                ctx.request_repaint();
+                            }
+                            Err(e) => {
+                                log::error!("Export model {}: {e}", model.file_name);
+                                self.statuses[index] = Some(ExportFileStatus::Failed(e.clone()));
+                                self.err = Some(format!("{}: {e}", model.file_name));
+                            }
+                        }
+                    }
+                    None => match writer.finish() {
+                        Ok(content) => {
+                            let (tx, rx) = oneshot::channel();
+                            self.stage = Some(ExportStage::Write { rx });
+
+                            let ctx = ctx.clone();
+                            util::exec_task(async move {
+                                let result = file
+                                    .write(content.as_slice())
+                                    .await
+                                    .map_err(|e| e.to_string());
+
+                                // The modal may have been closed
+                                tx.send(result).ok();
+                                ctx.request_repaint();
+                            });
+                        }
+                        Err(e) => {
+                            log::error!("Export models: {e}");
+                            self.err = Some(e);
+                        }
+                    },
                 }
             }
+            Some(ExportStage::Write { rx }) => match rx.try_recv() {
+                Ok(Ok(())) => {
+                    self.exported = true;
+                    alive = false;
+                }
+                Ok(Err(e)) => {
+                    log::error!("Save file: {e}");
+                    self.err = Some(e);
+                    self.stage = None;
+                }
+                Err(oneshot::TryRecvError::Disconnected) => {
+                    self.err = Some("The write task stopped unexpectedly".to_string());
+                    self.stage = None;
+                }
+                Err(oneshot::TryRecvError::Empty) => {}
+            },
             _ => {}
         }
 
         alive
     }
 
-    /// Export the models.
-    pub fn export_models<'a, W: std::io::Write + std::io::Seek>(
-        &self,
-        writer: &mut W,
-        models: impl IntoIterator<Item = &'a GaussianSplattingModel>,
-        edits: &[Vec<gs::GaussianEditPod>],
-        masks: &[Vec<u32>],
-    ) -> Result<(), String> {
-        if edits.len() == 1 {
-            return models
-                .into_iter()
-                .next()
-                .expect("model")
-                .gaussians
-                .write_ply(
-                    writer,
-                    self.settings[0].edit.then_some(&edits[0]),
-                    self.settings[0].mask.then_some(masks[0].iter().copied()),
-                )
-                .map_err(|e| e.to_string());
+    /// Mark the next pending model as writing.
+    fn write_next_status(&mut self) {
+        if let Some(status) = self
+            .statuses
+            .iter_mut()
+            .find(|status| **status == Some(ExportFileStatus::Pending))
+        {
+            *status = Some(ExportFileStatus::Writing);
         }
-
-        let mut zip = zip::ZipWriter::new(writer);
-        let options = zip::write::SimpleFileOptions::default()
-            .compression_method(zip::CompressionMethod::Deflated)
-            .large_file(true);
-
-        itertools::multizip((
-            models.into_iter(),
-            self.settings.iter(),
-            edits.iter(),
-            masks.iter(),
-        ))
-        .filter(|(_, s, _, _)| s.export)
-        .try_for_each(|(model, setting, edit, mask)| {
-            zip.start_file(model.file_name.clone(), options)
-                .map_err(|e| e.to_string())?;
-
-            model
-                .gaussians
-                .write_ply(
-                    &mut zip,
-                    setting.edit.then_some(edit),
-                    setting.mask.then_some(mask.iter().copied()),
-                )
-                .map_err(|e| e.to_string())
-        })?;
-
-        zip.finish().map_err(|e| e.to_string())?;
-
-        Ok(())
     }
 }
 
@@ -958,6 +3761,9 @@ pub struct ExportSettings {
 
     /// Apply the mask or not.
     pub mask: bool,
+
+    /// The spherical harmonics degree to keep, the higher coefficients are dropped.
+    pub sh_deg: gs::GaussianShDegree,
 }
 
 impl ExportSettings {
@@ -967,6 +3773,7 @@ impl ExportSettings {
             export: true,
             edit: true,
             mask: true,
+            sh_deg: gs::GaussianShDegree::new_unchecked(3),
         }
     }
 }
@@ -987,7 +3794,7 @@ pub enum Action {
 
         /// The index of the hit.
         ///
-        /// Must be 0 or 1.
+        /// Must be less than the [`MeasurementKind::hit_count`] of the hit pair.
         hit_index: usize,
 
         /// The sender to send the result.
@@ -997,8 +3804,53 @@ pub enum Action {
         rx: mpsc::Receiver<Vec3>,
     },
 
-    /// Selecting.
-    Selection,
+    /// Selecting.
+    Selection,
+
+    /// Locating a point for registration.
+    RegistrationLocatePoint {
+        /// The index of the point pair.
+        pair_index: usize,
+
+        /// The index of the point.
+        ///
+        /// Must be 0 for the source or 1 for the reference.
+        point_index: usize,
+
+        /// The key of the model to locate on.
+        model_key: String,
+
+        /// The sender to send the result.
+        tx: mpsc::Sender<Vec3>,
+
+        /// The receiver to receive the result.
+        rx: mpsc::Receiver<Vec3>,
+    },
+
+    /// Moving the selected model by dragging in the viewport.
+    MoveModel {
+        /// The position of the model when the drag started and the accumulated movement.
+        drag: Option<(Vec3, Vec3)>,
+    },
+
+    /// Picking a Gaussian for inspection.
+    PickGaussian {
+        /// The sender to send the result.
+        tx: mpsc::Sender<Option<PickedGaussian>>,
+
+        /// The receiver to receive the result.
+        rx: mpsc::Receiver<Option<PickedGaussian>>,
+    },
+}
+
+/// The picked Gaussian.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PickedGaussian {
+    /// The key of the model.
+    pub key: String,
+
+    /// The index of the Gaussian in the model.
+    pub index: usize,
 }
 
 /// The Gaussian splatting model.
@@ -1007,20 +3859,51 @@ pub struct GaussianSplattingModel {
     /// The file name.
     pub file_name: String,
 
+    /// The path of the file.
+    ///
+    /// This is only available on native, and is used to reference the model in project files.
+    pub path: Option<PathBuf>,
+
     /// The Gaussians.
     pub gaussians: gs::Gaussians,
 
+    /// The number of Gaussians in the PLY header.
+    ///
+    /// The Gaussians are loaded in the background, so this is the count when fully loaded.
+    pub count: usize,
+
     /// The transform.
     pub transform: GaussianSplattingModelTransform,
 
     /// The mask.
     pub mask: GaussianSplattingMask,
 
-    /// The center of the bounding box.
+    /// The center of the bounding box in model space.
     pub center: Vec3,
 
+    /// The size of the bounding box in model space.
+    ///
+    /// This is [`None`] until the bounds are computed on the GPU after the model is loaded.
+    pub size: Option<Vec3>,
+
     /// Whether the model is visible.
     pub visible: bool,
+
+    /// Whether the model is locked against transform, mask and selection edits.
+    pub locked: bool,
+
+    /// Whether the Gaussians are mirrored along the X axis from the file to flip their
+    /// handedness.
+    pub flipped: bool,
+
+    /// The color space of the colors in the file, which are corrected to sRGB when uploaded.
+    pub color_space: ModelColorSpace,
+
+    /// The display mode of the model, [`None`] to use the global display mode.
+    pub display_mode: Option<gs::GaussianDisplayMode>,
+
+    /// The capture metadata from the sidecar file next to the model, [`None`] if there is none.
+    pub capture: Option<Result<capture::CaptureMetadata, String>>,
 }
 
 impl GaussianSplattingModel {
@@ -1032,12 +3915,66 @@ impl GaussianSplattingModel {
 
         Self {
             file_name,
+            path: None,
             gaussians,
+            count,
             transform: GaussianSplattingModelTransform::new(),
             mask: GaussianSplattingMask::new(),
             center: Vec3::ZERO,
+            size: None,
             visible: true,
+            locked: false,
+            flipped: false,
+            color_space: ModelColorSpace::Srgb,
+            display_mode: None,
+            capture: None,
+        }
+    }
+
+    /// Get the fraction of the Gaussians loaded, in `0.0..=1.0`.
+    ///
+    /// A model without Gaussians is fully loaded.
+    pub fn load_progress(&self) -> f32 {
+        match self.count {
+            0 => 1.0,
+            count => (self.gaussians.gaussians.len() as f32 / count as f32).min(1.0),
+        }
+    }
+
+    /// Flip the handedness by mirroring the Gaussians along the X axis.
+    ///
+    /// Unlike a negative scale, the rotations and spherical harmonics of the Gaussians are
+    /// mirrored consistently. The Gaussians loaded afterwards are mirrored by
+    /// [`GaussianSplattingModel::flip_loaded`].
+    pub fn flip_handedness(&mut self) {
+        self.flipped = !self.flipped;
+        self.center.x = -self.center.x;
+        Self::flip(&mut self.gaussians.gaussians);
+    }
+
+    /// Mirror the Gaussians loaded from `start` if the model is flipped.
+    pub fn flip_loaded(&mut self, start: usize) {
+        if self.flipped {
+            Self::flip(&mut self.gaussians.gaussians[start..]);
+        }
+    }
+
+    /// Mirror the Gaussians along the X axis.
+    fn flip(gaussians: &mut [gs::Gaussian]) {
+        if gaussians.is_empty() {
+            return;
         }
+
+        let map = GaussianAxisMap::new(Mat3::from_diagonal(Vec3::new(-1.0, 1.0, 1.0)));
+        gaussians
+            .iter_mut()
+            .for_each(|gaussian| *gaussian = map.apply(gaussian));
+    }
+
+    /// Set the path of the file, and load the capture metadata next to it.
+    pub fn set_path(&mut self, path: Option<PathBuf>) {
+        self.capture = path.as_deref().and_then(capture::CaptureMetadata::load);
+        self.path = path;
     }
 
     /// Get the center in world space.
@@ -1045,16 +3982,53 @@ impl GaussianSplattingModel {
         self.transform.quat() * (self.center * self.transform.scale) + self.transform.pos
     }
 
+    /// Get the approximate radius in world space.
+    ///
+    /// This is the 90th percentile distance of a sample of the Gaussians from the center, to
+    /// ignore outliers.
+    pub fn world_radius(&self) -> f32 {
+        const SAMPLE_COUNT: usize = 4096;
+
+        let center = self.world_center();
+        let stride = self.gaussians.gaussians.len().div_ceil(SAMPLE_COUNT).max(1);
+        let mut distances = self
+            .gaussians
+            .gaussians
+            .iter()
+            .step_by(stride)
+            .map(|gaussian| self.transform.apply(gaussian.pos).distance(center))
+            .collect::<Vec<_>>();
+        distances.sort_by(f32::total_cmp);
+
+        distances
+            .get(distances.len() * 9 / 10)
+            .copied()
+            .unwrap_or(1.0)
+            .max(1e-3)
+    }
+
     /// Initialize loading a model.
     ///
     /// This starts a task and sends to the returned [`mpsc::Receiver`].
     ///
-    /// Returns the number of Gaussians and the receiver.
+    /// Returns the number of Gaussians, the color space detected from the header and the
+    /// receiver.
+    #[allow(clippy::type_complexity)]
     pub fn init_load(
-        mut ply: impl BufRead + Send + 'static,
-    ) -> Result<(usize, mpsc::Receiver<Result<gs::Gaussian, gs::Error>>), gs::Error> {
+        ply: impl BufRead + Send + 'static,
+    ) -> Result<
+        (
+            usize,
+            ModelColorSpace,
+            mpsc::Receiver<Result<gs::Gaussian, gs::Error>>,
+        ),
+        gs::Error,
+    > {
+        // Gzipped PLY is decompressed while streaming the Gaussians
+        let mut ply = util::gunzip_reader(ply)?;
         let ply_header = gs::Gaussians::read_ply_header(&mut ply)?;
         let count = ply_header.count()?;
+        let color_space = ModelColorSpace::detect(&ply_header);
 
         let (tx, rx) = mpsc::channel();
 
@@ -1092,10 +4066,89 @@ impl GaussianSplattingModel {
             }
         });
 
-        Ok((count, rx))
+        Ok((count, color_space, rx))
+    }
+}
+
+/// The color space of the colors of a [`GaussianSplattingModel`].
+///
+/// The renderer treats the colors as sRGB encoded, so the colors of the other color spaces are
+/// converted when uploaded. Only the base colors are converted, the view dependent spherical
+/// harmonics are kept.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    Display,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum ModelColorSpace {
+    /// The colors are sRGB encoded, which are not corrected.
+    #[default]
+    #[strum(to_string = "sRGB")]
+    Srgb,
+
+    /// The colors are linear, which are encoded to sRGB.
+    #[strum(to_string = "Linear")]
+    Linear,
+}
+
+impl ModelColorSpace {
+    /// Detect the color space from the comments of a PLY header.
+    ///
+    /// The header has no standard color space, so this is [`ModelColorSpace::Linear`] only if a
+    /// comment mentions linear colors, e.g. `comment color_space linear`, and
+    /// [`ModelColorSpace::Srgb`] otherwise.
+    pub fn detect(header: &gs::PlyHeader) -> Self {
+        match header {
+            gs::PlyHeader::Custom(header)
+                if header
+                    .comments
+                    .iter()
+                    .any(|comment| comment.to_lowercase().contains("linear")) =>
+            {
+                Self::Linear
+            }
+            _ => Self::Srgb,
+        }
+    }
+
+    /// Correct the color of a Gaussian to sRGB.
+    pub fn correct(self, gaussian: &mut gs::Gaussian) {
+        match self {
+            Self::Srgb => {}
+            Self::Linear => {
+                let color = egui::Rgba::from_rgb(
+                    gaussian.color.x as f32 / 255.0,
+                    gaussian.color.y as f32 / 255.0,
+                    gaussian.color.z as f32 / 255.0,
+                );
+                let [r, g, b, _] = egui::Color32::from(color).to_array();
+                gaussian.color = U8Vec4::new(r, g, b, gaussian.color.w);
+            }
+        }
     }
 }
 
+/// The part of a [`GaussianSplattingModelTransform`] to paste.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+pub enum TransformPart {
+    #[default]
+    #[strum(to_string = "All")]
+    All,
+    #[strum(to_string = "Position")]
+    Position,
+    #[strum(to_string = "Rotation")]
+    Rotation,
+    #[strum(to_string = "Scale")]
+    Scale,
+}
+
 /// The Gaussian splatting model transform.
 #[derive(Debug, Clone)]
 pub struct GaussianSplattingModelTransform {
@@ -1111,12 +4164,10 @@ pub struct GaussianSplattingModelTransform {
 
 impl GaussianSplattingModelTransform {
     /// Create a new Gaussian splatting model transform.
+    ///
+    /// This is the transform of the default [`CoordinateSystem`].
     pub const fn new() -> Self {
-        Self {
-            pos: Vec3::ZERO,
-            rot: Vec3::new(0.0, 0.0, 180.0),
-            scale: Vec3::ONE,
-        }
+        CoordinateSystem::YDown.transform()
     }
 
     /// Get the rotation in quaternion.
@@ -1128,6 +4179,48 @@ impl GaussianSplattingModelTransform {
             self.rot.x.to_radians(),
         )
     }
+
+    /// Set the rotation from quaternion.
+    pub fn set_quat(&mut self, quat: Quat) {
+        let (z, y, x) = quat.to_euler(EulerRot::ZYX);
+        self.rot = Vec3::new(x.to_degrees(), y.to_degrees(), z.to_degrees());
+    }
+
+    /// Remove the 180° rotation of the default [`CoordinateSystem::YDown`] from the rotation.
+    ///
+    /// The rotation is undone in model space, so the other rotations applied since are kept.
+    pub fn remove_default_rotation(&mut self) {
+        let default = CoordinateSystem::YDown.transform().quat();
+        self.set_quat(self.quat() * default.inverse());
+    }
+
+    /// Copy a part of another transform to this transform.
+    pub fn paste(&mut self, source: &Self, part: TransformPart) {
+        match part {
+            TransformPart::All => *self = source.clone(),
+            TransformPart::Position => self.pos = source.pos,
+            TransformPart::Rotation => self.rot = source.rot,
+            TransformPart::Scale => self.scale = source.scale,
+        }
+    }
+
+    /// Transform a position from model space to world space.
+    pub fn apply(&self, pos: Vec3) -> Vec3 {
+        self.quat() * (pos * self.scale) + self.pos
+    }
+
+    /// Apply a rigid transform in world space after this transform.
+    pub fn apply_rigid(&mut self, rigid: &registration::RigidTransform) {
+        self.pos = rigid.apply(self.pos);
+        self.set_quat(rigid.rotation * self.quat());
+    }
+
+    /// Apply a similarity transform in world space after this transform.
+    pub fn apply_similarity(&mut self, similarity: &registration::SimilarityTransform) {
+        self.pos = similarity.apply(self.pos);
+        self.set_quat(similarity.rotation * self.quat());
+        self.scale *= similarity.scale;
+    }
 }
 
 impl Default for GaussianSplattingModelTransform {
@@ -1140,8 +4233,14 @@ impl Default for GaussianSplattingModelTransform {
 #[derive(Debug, Clone)]
 pub struct GaussianSplattingGaussianTransform {
     /// The size.
+    ///
+    /// This is not used in [`gs::GaussianDisplayMode::Point`], see
+    /// [`GaussianSplattingGaussianTransform::point_size`].
     pub size: f32,
 
+    /// The point size in pixels for [`gs::GaussianDisplayMode::Point`].
+    pub point_size: f32,
+
     /// The display mode.
     pub display_mode: gs::GaussianDisplayMode,
 
@@ -1150,16 +4249,58 @@ pub struct GaussianSplattingGaussianTransform {
 
     /// Whether the SH0 is disabled.
     pub no_sh0: bool,
+
+    /// The opacity multiplier applied to all Gaussians.
+    pub opacity: f32,
+
+    /// The maximum scale of the Gaussians, larger scales are clamped when uploaded.
+    ///
+    /// [`None`] if the scales are not clamped.
+    pub max_scale: Option<f32>,
+
+    /// The projection of the Gaussians.
+    pub projection: SplatProjection,
 }
 
 impl GaussianSplattingGaussianTransform {
+    /// The point size in normalized device coordinates at unit distance and size of the render
+    /// shader of [`gs`].
+    const SHADER_POINT_SIZE: f32 = 0.01;
+
+    /// The default maximum scale when clamping is enabled.
+    pub const DEFAULT_MAX_SCALE: f32 = 1.0;
+
     /// Create a new Gaussian splatting Gaussian transform.
     pub const fn new() -> Self {
         Self {
             size: 1.0,
+            point_size: 4.0,
             display_mode: gs::GaussianDisplayMode::Splat,
             sh_deg: gs::GaussianShDegree::new_unchecked(3),
             no_sh0: false,
+            opacity: 1.0,
+            max_scale: None,
+            projection: SplatProjection::Perspective,
+        }
+    }
+
+    /// Get the size for the shader in a display mode.
+    ///
+    /// The points of the shader shrink with the distance, so
+    /// [`GaussianSplattingGaussianTransform::point_size`] is converted to the size at `distance`
+    /// from the camera, e.g. of the model center, in a viewport of `viewport_height` pixels.
+    pub fn shader_size(
+        &self,
+        display_mode: gs::GaussianDisplayMode,
+        distance: f32,
+        viewport_height: u32,
+    ) -> f32 {
+        match display_mode {
+            gs::GaussianDisplayMode::Point => {
+                self.point_size * distance.max(1e-3)
+                    / (Self::SHADER_POINT_SIZE * viewport_height.max(1) as f32)
+            }
+            _ => self.size,
         }
     }
 }
@@ -1170,6 +4311,33 @@ impl Default for GaussianSplattingGaussianTransform {
     }
 }
 
+/// The projection of the Gaussians to the screen.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    Display,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum SplatProjection {
+    /// The perspective-correct projection of the covariance.
+    #[default]
+    #[strum(to_string = "Perspective")]
+    Perspective,
+
+    /// A round splat facing the camera for each Gaussian, with the volume of its ellipsoid.
+    ///
+    /// This ignores the rotation and anisotropy of the Gaussians, and skips the projection of
+    /// their covariance in the vertex shader.
+    #[strum(to_string = "Billboard")]
+    Billboard,
+}
+
 /// The camera to view the Gaussian splatting.
 #[derive(Debug, Clone)]
 pub struct Camera {
@@ -1179,11 +4347,35 @@ pub struct Camera {
     /// The movement speed.
     pub speed: f32,
 
+    /// The scale of the first person movement for the size of the scene.
+    ///
+    /// This is multiplied with [`Camera::speed`], the orbit control is already relative to the
+    /// distance to the target.
+    pub scale: f32,
+
+    /// Whether to fit [`Camera::scale`] to the models when they load.
+    pub auto_scale: bool,
+
     /// The rotation sensitivity.
     pub sensitivity: f32,
+
+    /// The smoothing of the first person mouse-look from 0 to 1, 0 is raw.
+    pub smoothing: f32,
+
+    /// The limits of the orbit distance.
+    pub distance_limits: CameraDistanceLimits,
+
+    /// The running turn to look at a point, see [`Camera::look_at`].
+    pub look_at: Option<CameraLookAt>,
 }
 
 impl Camera {
+    /// The model radius with a scale of 1.
+    const SCALE_RADIUS: f32 = 5.0;
+
+    /// The time constant in seconds of the mouse-look at full [`Camera::smoothing`].
+    pub const MAX_SMOOTHING_TIME: f32 = 0.05;
+
     /// Create a new camera.
     pub fn new() -> Self {
         Self {
@@ -1194,8 +4386,69 @@ impl Camera {
                 60f32.to_radians(),
             )),
             speed: 1.0,
+            scale: 1.0,
+            auto_scale: true,
             sensitivity: 0.5,
+            smoothing: 0.0,
+            distance_limits: CameraDistanceLimits::new(),
+            look_at: None,
+        }
+    }
+
+    /// Start turning the camera to look at a point, keeping its position.
+    ///
+    /// The orbit target is moved to the point.
+    pub fn look_at(&mut self, point: Vec3) {
+        let pos = self.control.pos();
+        let distance = pos.distance(point);
+        if distance < 1e-6 || !point.is_finite() {
+            log::warn!("Cannot look at the camera position");
+            return;
+        }
+
+        self.look_at = Some(CameraLookAt {
+            start: self.control.to_orbit(distance),
+            point,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Advance the turn of [`Camera::look_at`] by `dt` seconds.
+    ///
+    /// Returns whether the camera is still turning.
+    pub fn update_look_at(&mut self, dt: f32) -> bool {
+        let Some(look_at) = &mut self.look_at else {
+            return false;
+        };
+
+        look_at.elapsed += dt;
+        let t = (look_at.elapsed / CameraLookAt::DURATION).min(1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+
+        let start = &look_at.start;
+        let start_diff = start.target - start.pos;
+        let end_diff = look_at.point - start.pos;
+
+        let rotation = Quat::IDENTITY.slerp(
+            Quat::from_rotation_arc(start_diff.normalize(), end_diff.normalize()),
+            t,
+        );
+        let distance = start_diff.length().lerp(end_diff.length(), t);
+
+        let mut orbit = start.clone();
+        orbit.target = start.pos + rotation * start_diff.normalize() * distance;
+        self.control.set_orbit(orbit);
+
+        if t >= 1.0 {
+            self.look_at = None;
         }
+
+        self.look_at.is_some()
+    }
+
+    /// Get the scale fitted to models with the radius.
+    pub fn fit_scale(radius: f32) -> f32 {
+        (radius / Self::SCALE_RADIUS).max(1e-6)
     }
 }
 
@@ -1205,6 +4458,173 @@ impl Default for Camera {
     }
 }
 
+/// The turn of a [`Camera`] to look at a point.
+#[derive(Debug, Clone)]
+pub struct CameraLookAt {
+    /// The orbit when the turn started.
+    pub start: CameraOrbitControl,
+
+    /// The point to look at.
+    pub point: Vec3,
+
+    /// The elapsed time in seconds.
+    pub elapsed: f32,
+}
+
+impl CameraLookAt {
+    /// The duration of the turn in seconds.
+    pub const DURATION: f32 = 0.4;
+}
+
+/// The limits of the distance from the orbit camera to its target.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CameraDistanceLimits {
+    /// The minimum distance.
+    pub min: f32,
+
+    /// The maximum distance, [`None`] for no limit.
+    pub max: Option<f32>,
+}
+
+impl CameraDistanceLimits {
+    /// The minimum distance relative to the model radius.
+    const MIN_RATIO: f32 = 0.01;
+
+    /// The maximum distance relative to the model radius.
+    const MAX_RATIO: f32 = 100.0;
+
+    /// Create new limits.
+    pub const fn new() -> Self {
+        Self {
+            min: 0.1,
+            max: None,
+        }
+    }
+
+    /// Create the limits fitted to models with the radius.
+    pub fn fit(radius: f32) -> Self {
+        let radius = radius.max(1e-6);
+
+        Self {
+            min: radius * Self::MIN_RATIO,
+            max: Some(radius * Self::MAX_RATIO),
+        }
+    }
+
+    /// Clamp the distance within the limits.
+    pub fn clamp(&self, distance: f32) -> f32 {
+        distance
+            .min(self.max.unwrap_or(f32::INFINITY))
+            .max(self.min)
+    }
+}
+
+impl Default for CameraDistanceLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The scroll wheel zoom of the orbit camera.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CameraZoom {
+    /// The zoom speed multiplier.
+    pub speed: f32,
+
+    /// Whether to invert the scroll direction.
+    pub invert: bool,
+
+    /// The zoom mode.
+    pub mode: CameraZoomMode,
+
+    /// Whether to zoom toward the point under the cursor instead of the target.
+    pub to_cursor: bool,
+}
+
+impl CameraZoom {
+    /// Create a new camera zoom.
+    pub const fn new() -> Self {
+        Self {
+            speed: 1.0,
+            invert: false,
+            mode: CameraZoomMode::Proportional,
+            to_cursor: false,
+        }
+    }
+
+    /// The scroll delta per second of holding a zoom key.
+    const KEY_RATE: f32 = 1000.0;
+
+    /// Get the new distance to the target after scrolling by `delta`.
+    ///
+    /// The distance is clamped within `limits`.
+    pub fn zoom(&self, distance: f32, delta: f32, limits: &CameraDistanceLimits) -> f32 {
+        let delta = match self.invert {
+            true => -delta,
+            false => delta,
+        };
+
+        self.zoom_by(distance, delta, limits)
+    }
+
+    /// Get the new distance to the target after holding a zoom key for `dt` seconds.
+    ///
+    /// `direction` is positive to zoom in. Unlike scrolling, this is never inverted.
+    pub fn zoom_by_key(
+        &self,
+        distance: f32,
+        direction: f32,
+        dt: f32,
+        limits: &CameraDistanceLimits,
+    ) -> f32 {
+        self.zoom_by(distance, direction * Self::KEY_RATE * dt, limits)
+    }
+
+    /// Get the new distance to the target after zooming by the scroll `delta`.
+    fn zoom_by(&self, distance: f32, delta: f32, limits: &CameraDistanceLimits) -> f32 {
+        let delta = delta * self.speed * 0.001;
+
+        let distance = match self.mode {
+            CameraZoomMode::Proportional => distance - delta * distance,
+            CameraZoomMode::Logarithmic => distance * (-delta).exp(),
+        };
+
+        limits.clamp(distance)
+    }
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The zoom mode of [`CameraZoom`].
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    Display,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum CameraZoomMode {
+    /// The zoom step is proportional to the distance to the target.
+    #[default]
+    #[strum(to_string = "Proportional")]
+    Proportional,
+
+    /// The distance to the target changes exponentially, slowing down smoothly near the target.
+    #[strum(to_string = "Logarithmic")]
+    Logarithmic,
+}
+
 /// The orbit camera control.
 #[derive(Debug, Clone)]
 pub struct CameraOrbitControl {
@@ -1231,6 +4651,28 @@ impl CameraOrbitControl {
             vertical_fov,
         }
     }
+
+    /// Get the point under a viewport position on the plane through the target facing the camera.
+    ///
+    /// `pos` is relative to the top left corner of the viewport. Returns [`None`] if the ray from
+    /// the camera does not intersect the plane.
+    pub fn target_plane_point(&self, pos: Vec2, viewport_size: Vec2) -> Option<Vec3> {
+        use gs::CameraTrait;
+
+        let ndc = vec2(pos.x, viewport_size.y - pos.y) / viewport_size * 2.0 - 1.0;
+        let inv_view_proj =
+            (self.projection(viewport_size.x / viewport_size.y) * self.view()).inverse();
+        let dir = (inv_view_proj.project_point3(ndc.extend(1.0)) - self.pos).normalize_or_zero();
+
+        let diff = self.target - self.pos;
+        let normal = diff.normalize_or_zero();
+        let cos = dir.dot(normal);
+        if cos <= 1e-6 {
+            return None;
+        }
+
+        Some(self.pos + dir * diff.dot(normal) / cos).filter(|point| point.is_finite())
+    }
 }
 
 impl gs::CameraTrait for CameraOrbitControl {
@@ -1306,6 +4748,14 @@ impl CameraControl {
         }
     }
 
+    /// Set the orbit, keeping the control mode.
+    pub fn set_orbit(&mut self, orbit: CameraOrbitControl) {
+        *self = match self {
+            Self::Orbit(..) => Self::Orbit(orbit),
+            Self::FirstPerson(..) => Self::FirstPerson(Self::Orbit(orbit).to_first_person()),
+        };
+    }
+
     /// Convert into orbit control.
     pub fn to_orbit(&self, arm_length: f32) -> CameraOrbitControl {
         match self {
@@ -1324,6 +4774,24 @@ impl CameraControl {
             Self::Orbit(orbit) => orbit.clone(),
         }
     }
+
+    /// Project a world position to the viewport.
+    ///
+    /// Returns the position relative to the top left corner of the viewport, or [`None`] if the
+    /// position is behind the camera.
+    pub fn project(&self, pos: Vec3, viewport_size: Vec2) -> Option<Vec2> {
+        use gs::CameraTrait;
+
+        let clip =
+            self.projection(viewport_size.x / viewport_size.y) * self.view() * pos.extend(1.0);
+
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        let ndc = clip.xy() / clip.w;
+        Some(vec2(ndc.x + 1.0, 1.0 - ndc.y) * 0.5 * viewport_size)
+    }
 }
 
 impl gs::CameraTrait for CameraControl {
@@ -1342,35 +4810,402 @@ impl gs::CameraTrait for CameraControl {
     }
 }
 
+/// The capture camera markers of the Gaussian splatting.
+#[derive(Debug)]
+pub struct CaptureCameras {
+    /// Whether the markers are visible.
+    pub visible: bool,
+
+    /// The depth of the marker frustums relative to the radius of the model.
+    pub size: f32,
+
+    /// The pick radius of the markers in points.
+    pub pick_radius: f32,
+
+    /// The selected capture camera, the model key and the camera index.
+    pub selected: Option<(String, usize)>,
+}
+
+impl CaptureCameras {
+    /// The marker color.
+    pub const COLOR: egui::Color32 = egui::Color32::from_rgb(86, 180, 233);
+
+    /// The selected marker color.
+    pub const SELECTED_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 159, 0);
+
+    /// Create new capture camera markers.
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            size: 0.05,
+            pick_radius: 12.0,
+            selected: None,
+        }
+    }
+
+    /// Get the markers of the visible models, or nothing if the markers are hidden.
+    pub fn markers(
+        &self,
+        models: &HashMap<String, GaussianSplattingModel>,
+    ) -> Vec<CaptureCameraMarker> {
+        if !self.visible {
+            return Vec::new();
+        }
+
+        models
+            .iter()
+            .filter(|(_, model)| model.visible)
+            .filter_map(|(key, model)| match &model.capture {
+                Some(Ok(metadata)) => Some((key, model, metadata)),
+                _ => None,
+            })
+            .flat_map(|(key, model, metadata)| {
+                let depth = model.world_radius() * self.size;
+
+                metadata.cameras.iter().enumerate().map(move |(i, camera)| {
+                    let (pos, forward, up) = camera.world_pose(model);
+                    let half_height =
+                        depth * (camera.vertical_fov.unwrap_or(60f32.to_radians()) / 2.0).tan();
+                    let aspect_ratio = camera
+                        .size
+                        .map(|size| size.x as f32 / size.y.max(1) as f32)
+                        .unwrap_or(1.0);
+
+                    CaptureCameraMarker {
+                        key: key.clone(),
+                        index: i,
+                        pos,
+                        right: forward.cross(up).normalize_or_zero() * half_height * aspect_ratio,
+                        up: up * half_height,
+                        forward: forward * depth,
+                        color: match self
+                            .selected
+                            .as_ref()
+                            .is_some_and(|(k, j)| k == key && *j == i)
+                        {
+                            true => Self::SELECTED_COLOR,
+                            false => Self::COLOR,
+                        },
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Get the marker under `pos` relative to the viewport, within [`CaptureCameras::pick_radius`].
+    pub fn pick<'a>(
+        &self,
+        markers: &'a [CaptureCameraMarker],
+        camera: &CameraControl,
+        viewport_size: Vec2,
+        pos: Vec2,
+    ) -> Option<&'a CaptureCameraMarker> {
+        markers
+            .iter()
+            .filter_map(|marker| {
+                camera
+                    .project(marker.pos, viewport_size)
+                    .map(|screen| (marker, screen.distance(pos)))
+            })
+            .filter(|(_, distance)| *distance <= self.pick_radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(marker, _)| marker)
+    }
+}
+
+impl Default for CaptureCameras {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The frustum marker of a capture camera in world space.
+#[derive(Debug, Clone)]
+pub struct CaptureCameraMarker {
+    /// The model key.
+    pub key: String,
+
+    /// The camera index in the capture metadata of the model.
+    pub index: usize,
+
+    /// The position, i.e. the apex of the frustum.
+    pub pos: Vec3,
+
+    /// The half width of the frustum base along the right direction.
+    pub right: Vec3,
+
+    /// The half height of the frustum base along the up direction.
+    pub up: Vec3,
+
+    /// The depth of the frustum along the forward direction.
+    pub forward: Vec3,
+
+    /// The color.
+    pub color: egui::Color32,
+}
+
 /// The measurement of the Gaussian splatting.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Measurement {
     /// The measurement hits.
     pub hit_pairs: Vec<MeasurementHitPair>,
 
-    /// The hit method.
+    /// The default hit method of new hit pairs.
     pub hit_method: MeasurementHitMethod,
+
+    /// The depth range of [`MeasurementHitMethod::MostAlpha`] in world units.
+    ///
+    /// The hit is where the blended alpha summed over this range along the ray is the most.
+    /// Lowering this locates thin surfaces more precisely but is more easily caught by floaters,
+    /// raising this is more robust to noise but may land inside thick surfaces.
+    pub alpha_range: f32,
+
+    /// Whether to locate a hit at the center of the hit Gaussian instead of the position derived
+    /// from the hit method, for reproducible measurement points.
+    pub locate_center: bool,
+
+    /// Whether to snap to existing hits when locating a hit.
+    ///
+    /// Holding Alt temporarily inverts this.
+    pub snap: bool,
+
+    /// The snap radius in points.
+    pub snap_radius: f32,
+
+    /// The step for nudging a hit position with the arrow keys.
+    ///
+    /// Holding Shift multiplies this by 10.
+    pub nudge_step: f32,
+
+    /// Whether the measurements are drawn on top of the Gaussians.
+    pub always_on_top: bool,
+
+    /// Whether all hit pairs are hidden, regardless of their own visibility.
+    pub hidden: bool,
 }
 
 impl Measurement {
     /// Create a new measurement.
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            hit_pairs: Vec::new(),
+            hit_method: MeasurementHitMethod::default(),
+            alpha_range: 0.05,
+            locate_center: false,
+            snap: true,
+            snap_radius: 10.0,
+            nudge_step: 0.01,
+            always_on_top: false,
+            hidden: false,
+        }
+    }
+
+    /// Get the hit pairs which are shown.
+    pub fn visible_hit_pairs(&self) -> impl Iterator<Item = (usize, &MeasurementHitPair)> {
+        self.hit_pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, hit_pair)| hit_pair.visible && !self.hidden)
+    }
+
+    /// Toggle hiding all hit pairs.
+    ///
+    /// The shortcut is M when no text is being edited.
+    pub fn toggle_hidden(&mut self, scene_tx: &mpsc::Sender<SceneCommand>) {
+        self.hidden = !self.hidden;
+        scene_tx
+            .send(SceneCommand::UpdateMeasurementHit)
+            .expect("send update measurement hit");
+    }
+
+    /// Find the existing hit to snap to.
+    ///
+    /// `pos` is relative to the top left corner of the viewport. `exclude` is the hit pair index
+    /// and hit index of the hit being located.
+    ///
+    /// Returns the position of the hit and its position in the viewport.
+    pub fn snap_target(
+        &self,
+        camera: &CameraControl,
+        viewport_size: Vec2,
+        pos: Vec2,
+        exclude: (usize, usize),
+    ) -> Option<(Vec3, Vec2)> {
+        self.visible_hit_pairs()
+            .flat_map(|(i, hit_pair)| {
+                hit_pair
+                    .used_hits()
+                    .iter()
+                    .enumerate()
+                    .filter(move |(j, _)| (i, *j) != exclude)
+                    .map(|(_, hit)| hit.pos)
+            })
+            .filter_map(|hit| {
+                camera
+                    .project(hit, viewport_size)
+                    .map(|screen| (hit, screen, screen.distance(pos)))
+            })
+            .filter(|(.., distance)| *distance <= self.snap_radius)
+            .min_by(|(.., a), (.., b)| a.total_cmp(b))
+            .map(|(hit, screen, _)| (hit, screen))
+    }
+
+    /// The colorblind-safe palette for new hit pairs (Okabe-Ito).
+    pub const PALETTE: [egui::Color32; 7] = [
+        egui::Color32::from_rgb(230, 159, 0),
+        egui::Color32::from_rgb(86, 180, 233),
+        egui::Color32::from_rgb(0, 158, 115),
+        egui::Color32::from_rgb(240, 228, 66),
+        egui::Color32::from_rgb(0, 114, 178),
+        egui::Color32::from_rgb(213, 94, 0),
+        egui::Color32::from_rgb(204, 121, 167),
+    ];
+
+    /// Add a new hit pair with a color from [`Measurement::PALETTE`].
+    ///
+    /// The first palette color not used by any existing hit pair is picked, so manually set
+    /// colors are respected. When all colors are used, the palette cycles.
+    pub fn add_hit_pair(&mut self) {
+        let color = Self::PALETTE
+            .iter()
+            .find(|color| {
+                !self
+                    .hit_pairs
+                    .iter()
+                    .any(|hit_pair| hit_pair.color == **color)
+            })
+            .copied()
+            .unwrap_or(Self::PALETTE[self.hit_pairs.len() % Self::PALETTE.len()]);
+
+        let mut hit_pair = MeasurementHitPair::new(format!("Measurement {}", self.hit_pairs.len()));
+        hit_pair.color = color;
+        hit_pair.hit_method = self.hit_method;
+
+        self.hit_pairs.push(hit_pair);
+    }
+}
+
+impl Default for Measurement {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// The measurement hit method.
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumCount, EnumIter)]
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumCount,
+    EnumIter,
+    Display,
+    serde::Deserialize,
+    serde::Serialize,
+)]
 pub enum MeasurementHitMethod {
     /// The most alpha hit.
     #[default]
+    #[strum(to_string = "Most Alpha")]
     MostAlpha,
 
     /// The closest hit.
+    #[strum(to_string = "Closest")]
     Closest,
 }
 
+/// The unit of the line width of a [`MeasurementHitPair`].
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    Display,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum MeasurementLineWidthMode {
+    /// The width is relative to the screen.
+    #[default]
+    #[strum(to_string = "Screen")]
+    Screen,
+
+    /// The width is in world units, so the line has a physical thickness.
+    #[strum(to_string = "World")]
+    World,
+}
+
+impl MeasurementLineWidthMode {
+    /// Get the default line width of the mode.
+    pub const fn default_line_width(self) -> f32 {
+        match self {
+            Self::Screen => 1.0,
+            Self::World => 0.01,
+        }
+    }
+}
+
+/// The kind of measurement of a [`MeasurementHitPair`], i.e. the reference of the measured point.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    Display,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum MeasurementKind {
+    /// The distance between two points.
+    #[default]
+    #[strum(to_string = "Point to Point")]
+    Point,
+
+    /// The perpendicular distance of a point to a line through two points.
+    #[strum(to_string = "Point to Line")]
+    Line,
+
+    /// The perpendicular distance of a point to a plane through three points.
+    #[strum(to_string = "Point to Plane")]
+    Plane,
+}
+
+impl MeasurementKind {
+    /// The maximum number of hits of any kind.
+    pub const MAX_HIT_COUNT: usize = 4;
+
+    /// Get the number of hits, the measured point followed by the reference points.
+    pub const fn hit_count(self) -> usize {
+        match self {
+            Self::Point => 2,
+            Self::Line => 3,
+            Self::Plane => 4,
+        }
+    }
+
+    /// Get the label of a hit.
+    pub fn hit_label(self, index: usize) -> String {
+        match (self, index) {
+            (Self::Point, i) => format!("Position {}", i + 1),
+            (_, 0) => "Point".to_string(),
+            (Self::Line, i) => format!("Line Point {i}"),
+            (Self::Plane, i) => format!("Plane Point {i}"),
+        }
+    }
+}
+
 /// The measurement hit pair.
+///
+/// Despite the name, this has a hit for the measured point followed by the reference points of
+/// the [`MeasurementKind`], only the first [`MeasurementKind::hit_count`] hits are used.
 #[derive(Debug, Clone)]
 pub struct MeasurementHitPair {
     /// The label.
@@ -1382,11 +5217,23 @@ pub struct MeasurementHitPair {
     /// The color of the hit pair.
     pub color: egui::Color32,
 
-    /// The line width.
+    /// The line width, in the unit of [`MeasurementHitPair::line_width_mode`].
     pub line_width: f32,
 
+    /// The unit of the line width.
+    pub line_width_mode: MeasurementLineWidthMode,
+
+    /// The size of the endpoint markers.
+    pub endpoint_size: f32,
+
+    /// The kind.
+    pub kind: MeasurementKind,
+
+    /// The method to locate the hits.
+    pub hit_method: MeasurementHitMethod,
+
     /// The hits.
-    pub hits: [MeasurementHit; 2],
+    pub hits: [MeasurementHit; MeasurementKind::MAX_HIT_COUNT],
 }
 
 impl MeasurementHitPair {
@@ -1396,14 +5243,77 @@ impl MeasurementHitPair {
             label,
             visible: true,
             color: egui::Color32::RED,
-            line_width: 1.0,
-            hits: [MeasurementHit::default(), MeasurementHit::default()],
+            line_width: MeasurementLineWidthMode::default().default_line_width(),
+            line_width_mode: MeasurementLineWidthMode::default(),
+            endpoint_size: 2.0,
+            kind: MeasurementKind::default(),
+            hit_method: MeasurementHitMethod::default(),
+            hits: std::array::from_fn(|_| MeasurementHit::default()),
+        }
+    }
+
+    /// Duplicate the hit pair with a copy label.
+    pub fn duplicate(&self) -> Self {
+        Self {
+            label: format!("{} (copy)", self.label),
+            ..self.clone()
+        }
+    }
+
+    /// Get the used hits.
+    pub fn used_hits(&self) -> &[MeasurementHit] {
+        &self.hits[..self.kind.hit_count()]
+    }
+
+    /// Get the foot of the perpendicular from the measured point to the reference.
+    ///
+    /// For [`MeasurementKind::Point`], this is the other point. When the reference points
+    /// are degenerate, i.e. coincident or collinear, the first reference point is used.
+    pub fn foot(&self) -> Vec3 {
+        let point = self.hits[0].pos;
+        let origin = self.hits[1].pos;
+
+        match self.kind {
+            MeasurementKind::Point => origin,
+            MeasurementKind::Line => match (self.hits[2].pos - origin).try_normalize() {
+                Some(dir) => origin + dir * (point - origin).dot(dir),
+                None => origin,
+            },
+            MeasurementKind::Plane => {
+                match (self.hits[2].pos - origin)
+                    .cross(self.hits[3].pos - origin)
+                    .try_normalize()
+                {
+                    Some(normal) => point - normal * (point - origin).dot(normal),
+                    None => origin,
+                }
+            }
         }
     }
 
-    /// Ge the distance between the hits.
+    /// Get the distance from the measured point to the reference.
     pub fn distance(&self) -> f32 {
-        (self.hits[0].pos - self.hits[1].pos).length()
+        (self.hits[0].pos - self.foot()).length()
+    }
+
+    /// Get the line segments to draw.
+    ///
+    /// These are the reference line or the reference triangle, followed by the line from the
+    /// measured point to the foot of the perpendicular.
+    pub fn segments(&self) -> Vec<[Vec3; 2]> {
+        let hits = self.used_hits();
+        let reference = &hits[1..];
+
+        let mut segments = match reference.len() {
+            1 => Vec::new(),
+            2 => vec![[reference[0].pos, reference[1].pos]],
+            _ => (0..reference.len())
+                .map(|i| [reference[i].pos, reference[(i + 1) % reference.len()].pos])
+                .collect(),
+        };
+        segments.push([hits[0].pos, self.foot()]);
+
+        segments
     }
 }
 
@@ -1420,6 +5330,50 @@ impl Default for MeasurementHit {
     }
 }
 
+/// The point pair registration.
+#[derive(Debug)]
+pub struct Registration {
+    /// The point pairs of the source and reference positions in world space.
+    pub pairs: Vec<[Option<Vec3>; 2]>,
+
+    /// Whether to also solve for a uniform scale.
+    pub with_scale: bool,
+}
+
+impl Registration {
+    /// Create a new registration.
+    pub fn new() -> Self {
+        Self {
+            pairs: vec![[None; 2]; 3],
+            with_scale: false,
+        }
+    }
+
+    /// Get the complete point pairs.
+    pub fn complete_pairs(&self) -> Vec<(Vec3, Vec3)> {
+        self.pairs
+            .iter()
+            .filter_map(|pair| match pair {
+                [Some(source), Some(reference)] => Some((*source, *reference)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Solve for the transform from the source to the reference.
+    ///
+    /// Returns [`None`] if there are less than 3 complete pairs.
+    pub fn solve(&self) -> Option<registration::SimilarityTransform> {
+        registration::SimilarityTransform::fit(&self.complete_pairs(), self.with_scale)
+    }
+}
+
+impl Default for Registration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// The selection.
 #[derive(Debug)]
 pub struct Selection {
@@ -1432,7 +5386,7 @@ pub struct Selection {
     /// Whether the selection is immediate.
     pub immediate: bool,
 
-    /// The brush radius.
+    /// The brush radius in points.
     pub brush_radius: u32,
 
     /// The highlight color.
@@ -1443,6 +5397,22 @@ pub struct Selection {
 
     /// Whether to show unedited.
     pub show_unedited: bool,
+
+    /// Whether to limit the selection to the mask.
+    pub limit_to_mask: bool,
+
+    /// Whether to limit the selection to [`Selection::depth_tolerance`] behind the nearest
+    /// selected Gaussian, so the background is not selected along with the foreground.
+    pub limit_to_depth: bool,
+
+    /// The depth tolerance in world units.
+    pub depth_tolerance: f32,
+
+    /// The last deleted selection for undo.
+    pub deleted: Option<DeletedSelection>,
+
+    /// The saved selections with their own edits.
+    pub layers: Vec<SelectionLayer>,
 }
 
 impl Selection {
@@ -1456,8 +5426,34 @@ impl Selection {
             highlight_color: egui::Color32::from_rgba_unmultiplied(255, 0, 255, 127),
             edit: None,
             show_unedited: false,
+            limit_to_mask: false,
+            limit_to_depth: false,
+            depth_tolerance: 0.5,
+            deleted: None,
+            layers: Vec::new(),
         }
     }
+
+    /// Copy the linked parameters of the layer at `index` to the other linked layers.
+    ///
+    /// Returns the indices of the layers that are changed, excluding `index`.
+    pub fn sync_linked_layers(&mut self, index: usize) -> Vec<usize> {
+        let Some(layer) = self.layers.get(index).filter(|layer| layer.linked) else {
+            return Vec::new();
+        };
+        let (exposure, contrast) = (layer.edit.exposure, layer.edit.contrast);
+
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .filter(|(i, layer)| *i != index && layer.linked)
+            .map(|(i, layer)| {
+                layer.edit.exposure = exposure;
+                layer.edit.contrast = contrast;
+                i
+            })
+            .collect()
+    }
 }
 
 impl Default for Selection {
@@ -1466,6 +5462,64 @@ impl Default for Selection {
     }
 }
 
+/// The deleted selection.
+///
+/// This keeps the Gaussians of the model before the deletion for undo.
+#[derive(Debug)]
+pub struct DeletedSelection {
+    /// The key of the model.
+    pub key: String,
+
+    /// The Gaussians before the deletion.
+    pub gaussians: Vec<gs::Gaussian>,
+
+    /// The edits before the deletion, see [`crate::renderer::EditRestore::sparse`].
+    pub edits: Vec<[u32; 3]>,
+
+    /// The file path before the deletion.
+    pub path: Option<PathBuf>,
+
+    /// The number of deleted Gaussians.
+    pub count: usize,
+}
+
+/// A saved selection of a model with its own edit.
+///
+/// Changing the edit writes it to the saved Gaussians, so regions can be graded separately
+/// after the selection has moved on. The exposure and contrast of the linked layers are kept
+/// the same.
+#[derive(Debug, Clone)]
+pub struct SelectionLayer {
+    /// The name.
+    pub name: String,
+
+    /// The key of the model.
+    pub key: String,
+
+    /// The indices of the selected Gaussians.
+    pub indices: Vec<u32>,
+
+    /// The edit.
+    pub edit: SelectionEdit,
+
+    /// Whether the exposure and contrast are linked to the other linked layers.
+    pub linked: bool,
+}
+
+impl SelectionLayer {
+    /// Get the edits of the Gaussians, see [`crate::renderer::EditRestore::restore`].
+    ///
+    /// If `enabled` is false, the edits are cleared instead.
+    pub fn edits(&self, enabled: bool) -> Vec<[u32; 3]> {
+        let [a, b] = match enabled {
+            true => bytemuck::cast::<_, [u32; 2]>(self.edit.to_pod()),
+            false => [0, 0],
+        };
+
+        self.indices.iter().map(|i| [*i, a, b]).collect()
+    }
+}
+
 /// The selection method.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SelectionMethod {
@@ -1581,6 +5635,20 @@ pub struct GaussianSplattingMask {
 
     /// The operations code.
     pub op_code: String,
+
+    /// The number of Gaussians kept by the last evaluation.
+    ///
+    /// [`None`] if the mask is not evaluated yet.
+    pub coverage: Option<usize>,
+
+    /// Whether the mask is being evaluated on the GPU.
+    pub evaluating: bool,
+
+    /// Whether the gizmos of the shapes are shown, the mask applies either way.
+    pub gizmos_visible: bool,
+
+    /// The index of the selected shape, which is highlighted and can be dragged in the scene.
+    pub selected: Option<usize>,
 }
 
 impl GaussianSplattingMask {
@@ -1590,9 +5658,35 @@ impl GaussianSplattingMask {
             shapes: Vec::new(),
             op_shape_pods: Vec::new(),
             op_code: String::new(),
+            coverage: None,
+            evaluating: false,
+            gizmos_visible: true,
+            selected: None,
         }
     }
 
+    /// Get the valid operation parsed from the operations code.
+    ///
+    /// Returns [`None`] if the code is empty or invalid.
+    pub fn op(&self) -> Option<GaussianSplattingMaskOp> {
+        GaussianSplattingMaskOp::parse(&self.op_code)
+            .ok()
+            .flatten()
+            .filter(|op| op.validate_shapes(self.shapes.len()).is_ok())
+    }
+
+    /// Count the number of kept Gaussians in the downloaded mask.
+    pub fn count_kept(mask: &[u32], count: usize) -> usize {
+        mask.iter()
+            .enumerate()
+            .map(|(i, word)| match count.saturating_sub(i * 32) {
+                0 => 0,
+                n if n >= 32 => word.count_ones(),
+                n => (word & ((1 << n) - 1)).count_ones(),
+            } as usize)
+            .sum()
+    }
+
     /// Update the PODs.
     pub fn update_pods(&mut self) {
         self.op_shape_pods = self
@@ -1603,6 +5697,12 @@ impl GaussianSplattingMask {
     }
 }
 
+impl Default for GaussianSplattingMask {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// The mask shape.
 #[derive(Debug, Clone)]
 pub struct GaussianSplattingMaskShape {
@@ -1627,12 +5727,131 @@ impl GaussianSplattingMaskShape {
     }
 }
 
+impl GaussianSplattingMaskShape {
+    /// Duplicate the shape with its position slightly offset.
+    pub fn duplicate(&self) -> Self {
+        let mut shape = self.clone();
+        shape.shape.pos += Vec3::X * 0.1;
+        shape
+    }
+
+    /// Serialize the shape to JSON for the clipboard.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.to_data()).expect("serialize mask shape")
+    }
+
+    /// Deserialize the shape from JSON from the clipboard.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let data = serde_json::from_str::<GaussianSplattingMaskShapeData>(json.trim())
+            .map_err(|e| e.to_string())?;
+
+        Self::from_data(data)
+    }
+
+    /// Convert the shape to its serializable data.
+    pub fn to_data(&self) -> GaussianSplattingMaskShapeData {
+        GaussianSplattingMaskShapeData {
+            kind: match self.shape.kind {
+                gs::MaskShapeKind::Box => "Box",
+                gs::MaskShapeKind::Ellipsoid => "Ellipsoid",
+            }
+            .to_string(),
+            pos: self.shape.pos,
+            rotation: self.shape.rotation,
+            rot: self.rot,
+            scale: self.shape.scale,
+            color: self.shape.color,
+            visible: self.visible,
+        }
+    }
+
+    /// Convert the serializable data to a shape.
+    pub fn from_data(data: GaussianSplattingMaskShapeData) -> Result<Self, String> {
+        let kind = match data.kind.as_str() {
+            "Box" => gs::MaskShapeKind::Box,
+            "Ellipsoid" => gs::MaskShapeKind::Ellipsoid,
+            kind => return Err(format!("unknown shape kind: {kind}")),
+        };
+
+        let mut shape = gs::MaskShape::new(kind);
+        shape.pos = data.pos;
+        shape.rotation = data.rotation;
+        shape.scale = data.scale;
+        shape.color = data.color;
+
+        Ok(Self {
+            shape,
+            rot: data.rot,
+            visible: data.visible,
+        })
+    }
+}
+
 impl Default for GaussianSplattingMaskShape {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// The serializable data of [`GaussianSplattingMaskShape`].
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct GaussianSplattingMaskShapeData {
+    kind: String,
+    pos: Vec3,
+    rotation: Quat,
+    rot: Vec3,
+    scale: Vec3,
+    color: Vec4,
+    visible: bool,
+}
+
+/// The operations code, which can be parsed back with [`GaussianSplattingMaskOp::parse`].
+impl std::fmt::Display for GaussianSplattingMaskOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (left, op, right) = match self {
+            Self::Union(left, right) => (left, '|', right),
+            Self::Intersection(left, right) => (left, '&', right),
+            Self::Difference(left, right) => (left, '-', right),
+            Self::SymmetricDifference(left, right) => (left, '^', right),
+            Self::Complement(op) => {
+                return match op.precedence() {
+                    4 => write!(f, "!{op}"),
+                    _ => write!(f, "!({op})"),
+                };
+            }
+            Self::Shape(index) => return write!(f, "{index}"),
+        };
+
+        // The operations are left associative
+        let precedence = self.precedence();
+        match left.precedence() < precedence {
+            true => write!(f, "({left})")?,
+            false => write!(f, "{left}")?,
+        }
+        write!(f, " {op} ")?;
+        match right.precedence() <= precedence {
+            true => write!(f, "({right})"),
+            false => write!(f, "{right}"),
+        }
+    }
+}
+
+/// The error of [`GaussianSplattingMaskOp::parse`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaskOpParseError {
+    /// The byte range of the offending text in the input.
+    pub span: std::ops::Range<usize>,
+
+    /// The message with the line and column.
+    pub message: String,
+}
+
+impl std::fmt::Display for MaskOpParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
 /// The syntax tree representing the mask operation.
 #[derive(Debug, Clone)]
 pub enum GaussianSplattingMaskOp {
@@ -1656,8 +5875,31 @@ pub enum GaussianSplattingMaskOp {
 }
 
 impl GaussianSplattingMaskOp {
+    /// Combine all shapes with a binary operation, e.g. [`GaussianSplattingMaskOp::Union`].
+    ///
+    /// Returns [`None`] if there is no shape.
+    pub fn fold_shapes(
+        shape_count: usize,
+        op: impl Fn(Box<Self>, Box<Self>) -> Self,
+    ) -> Option<Self> {
+        (0..shape_count)
+            .map(Self::Shape)
+            .reduce(|acc, shape| op(Box::new(acc), Box::new(shape)))
+    }
+
+    /// Get the precedence of the operation, higher binds tighter.
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Union(..) => 0,
+            Self::Intersection(..) => 1,
+            Self::Difference(..) => 2,
+            Self::SymmetricDifference(..) => 3,
+            Self::Complement(..) | Self::Shape(..) => 4,
+        }
+    }
+
     /// Parse from a string.
-    pub fn parse(input: &str) -> Result<Option<Self>, String> {
+    pub fn parse(input: &str) -> Result<Option<Self>, MaskOpParseError> {
         use nom::{
             Finish, IResult, Parser,
             branch::alt,
@@ -1778,10 +6020,62 @@ impl GaussianSplattingMaskOp {
         // Apply the main parser and convert the result
         match all_consuming(parse_expr).parse(input.trim()).finish() {
             Ok((_, op)) => Ok(Some(op)),
-            Err(e) => Err(format!("Failed to parse mask operation: {e}")),
+            Err(e) => {
+                // The remaining input is a suffix of the trimmed input
+                let offset = input.trim_end().len() - e.input.len();
+                let (span, unexpected) = match input[offset..].chars().next() {
+                    Some(c) => (offset..offset + c.len_utf8(), format!("unexpected \"{c}\"")),
+                    None => (
+                        input[..offset]
+                            .char_indices()
+                            .last()
+                            .map_or(0..0, |(i, c)| i..i + c.len_utf8()),
+                        "unexpected end".to_string(),
+                    ),
+                };
+
+                let line = input[..offset].matches('\n').count() + 1;
+                let column = input[..offset]
+                    .rsplit('\n')
+                    .next()
+                    .map_or(0, |line| line.chars().count())
+                    + 1;
+
+                Err(MaskOpParseError {
+                    span,
+                    message: format!(
+                        "Failed to parse mask operation at line {line}, column {column}: \
+                        {unexpected}"
+                    ),
+                })
+            }
         }
     }
 
+    /// Find the byte range of the first occurrence of a shape index in an operations code.
+    ///
+    /// The numbers in the code are always shape indices.
+    pub fn shape_span(input: &str, index: usize) -> Option<std::ops::Range<usize>> {
+        let mut start = None;
+        for (i, c) in input
+            .char_indices()
+            .chain(std::iter::once((input.len(), ' ')))
+        {
+            match (c.is_ascii_digit(), start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    if input[s..i].parse() == Ok(index) {
+                        return Some(s..i);
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
     /// Validate shape indices.
     pub fn validate_shapes(&self, shape_count: usize) -> Result<(), usize> {
         match self {
@@ -1836,3 +6130,137 @@ impl GaussianSplattingMaskOp {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The tolerance of the comparisons.
+    const EPSILON: f32 = 1e-4;
+
+    /// Create a Gaussian with distinct spherical harmonics coefficients.
+    fn gaussian() -> gs::Gaussian {
+        gs::Gaussian {
+            rotation: Quat::from_euler(EulerRot::XYZ, 0.3, -0.7, 1.1),
+            pos: Vec3::new(1.0, -2.0, 3.0),
+            color: U8Vec4::new(10, 20, 30, 40),
+            sh: std::array::from_fn(|i| {
+                Vec3::new(
+                    i as f32 * 0.1 - 0.7,
+                    (i as f32 * 0.37).sin(),
+                    0.5 - i as f32 * 0.05,
+                )
+            }),
+            scale: Vec3::new(0.1, 0.2, 0.3),
+        }
+    }
+
+    /// Evaluate the spherical harmonics of degree 1 to 3 in direction `dir`.
+    fn eval_sh(sh: &[Vec3; 15], dir: Vec3) -> Vec3 {
+        sh_basis(dir.normalize().as_dvec3())
+            .iter()
+            .zip(sh.iter())
+            .map(|(basis, coeff)| *coeff * *basis as f32)
+            .sum()
+    }
+
+    /// Directions spread over the sphere, which differ from the fitting samples.
+    fn dirs() -> impl Iterator<Item = Vec3> {
+        (0..20).map(|i| {
+            let i = i as f32;
+            Vec3::new((i * 1.3).sin(), (i * 0.7).cos(), (i * 2.1 + 0.4).sin()).normalize()
+        })
+    }
+
+    #[test]
+    fn identity_map_leaves_sh_unchanged() {
+        let gaussian = gaussian();
+        let mapped = GaussianAxisMap::new(Mat3::IDENTITY).apply(&gaussian);
+
+        for (a, b) in gaussian.sh.iter().zip(mapped.sh.iter()) {
+            assert!(a.abs_diff_eq(*b, EPSILON), "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn sh_band_rotations_are_orthogonal() {
+        for from in CoordinateSystem::iter() {
+            for to in CoordinateSystem::iter() {
+                let matrix = (to.matrix().inverse() * from.matrix()).as_dmat3();
+
+                for band in SH_BANDS {
+                    let rotation = sh_band_rotation(matrix, band.clone());
+
+                    for i in 0..band.len() {
+                        for j in 0..band.len() {
+                            let dot = (0..band.len())
+                                .map(|k| rotation[i][k] * rotation[j][k])
+                                .sum::<f64>();
+                            let expected = if i == j { 1.0 } else { 0.0 };
+                            assert!(
+                                (dot - expected).abs() < EPSILON as f64,
+                                "{from} to {to}, band {band:?}: row {i} . row {j} = {dot}",
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn convert_round_trips() {
+        let gaussians = gs::Gaussians {
+            gaussians: vec![gaussian()],
+        };
+
+        for a in CoordinateSystem::iter() {
+            for b in CoordinateSystem::iter() {
+                let round_trip = b.convert(a, &a.convert(b, &gaussians));
+                let (original, converted) = (&gaussians.gaussians[0], &round_trip.gaussians[0]);
+
+                assert!(
+                    original.pos.abs_diff_eq(converted.pos, EPSILON),
+                    "{a} to {b} and back: position {} != {}",
+                    original.pos,
+                    converted.pos,
+                );
+                assert!(
+                    original.rotation.dot(converted.rotation).abs() > 1.0 - EPSILON,
+                    "{a} to {b} and back: rotation {} != {}",
+                    original.rotation,
+                    converted.rotation,
+                );
+                for (x, y) in original.sh.iter().zip(converted.sh.iter()) {
+                    assert!(
+                        x.abs_diff_eq(*y, EPSILON),
+                        "{a} to {b} and back: SH {x} != {y}",
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn y_up_to_z_up_rotates_sh_with_directions() {
+        let (from, to) = (CoordinateSystem::YUp, CoordinateSystem::ZUp);
+        let matrix = to.matrix().inverse() * from.matrix();
+        let gaussian = gaussian();
+        let converted = from.convert(
+            to,
+            &gs::Gaussians {
+                gaussians: vec![gaussian],
+            },
+        );
+        let converted = &converted.gaussians[0];
+
+        for dir in dirs() {
+            let original = eval_sh(&gaussian.sh, dir);
+            let rotated = eval_sh(&converted.sh, matrix * dir);
+            assert!(
+                original.abs_diff_eq(rotated, EPSILON),
+                "direction {dir}: {original} != {rotated}",
+            );
+        }
+    }
+}
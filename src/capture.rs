@@ -0,0 +1,192 @@
+use std::path::{Path, PathBuf};
+
+use glam::*;
+
+use crate::app;
+
+/// The capture cameras from a sidecar file next to a model.
+///
+/// Two formats are supported:
+/// - `cameras.json` of the original 3D Gaussian Splatting training output, with COLMAP camera
+///   axes.
+/// - `transforms.json` of NeRF style datasets, with OpenGL camera axes.
+///
+/// The poses are in the model space, like the Gaussians.
+#[derive(Debug, Clone)]
+pub struct CaptureMetadata {
+    /// The path of the sidecar file.
+    pub path: PathBuf,
+
+    /// The cameras.
+    pub cameras: Vec<CaptureCamera>,
+}
+
+impl CaptureMetadata {
+    /// The file names of the supported sidecar files, in the order they are looked for.
+    pub const FILE_NAMES: [&str; 2] = ["cameras.json", "transforms.json"];
+
+    /// Load the sidecar file in the directory of the model.
+    ///
+    /// Returns [`None`] if there is no sidecar file.
+    pub fn load(model_path: &Path) -> Option<Result<Self, String>> {
+        let dir = model_path.parent()?;
+        let path = Self::FILE_NAMES
+            .iter()
+            .map(|file_name| dir.join(file_name))
+            .find(|path| path.is_file())?;
+
+        let cameras = std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                match path.file_name().and_then(|name| name.to_str()) {
+                    Some("cameras.json") => Self::parse_cameras(&json),
+                    _ => Self::parse_transforms(&json),
+                }
+                .map_err(|e| format!("Invalid {}: {e}", path.display()))
+            });
+
+        Some(cameras.map(|cameras| Self { path, cameras }))
+    }
+
+    /// Parse the `cameras.json` of the 3D Gaussian Splatting training output.
+    fn parse_cameras(json: &[u8]) -> Result<Vec<CaptureCamera>, String> {
+        /// A camera in `cameras.json`.
+        #[derive(serde::Deserialize)]
+        struct Camera {
+            img_name: String,
+            width: u32,
+            height: u32,
+            position: [f32; 3],
+            /// The camera to world rotation, in rows.
+            rotation: [[f32; 3]; 3],
+            fy: f32,
+        }
+
+        let cameras = serde_json::from_slice::<Vec<Camera>>(json).map_err(|e| e.to_string())?;
+
+        Ok(cameras
+            .into_iter()
+            .map(|camera| {
+                let rotation = Mat3::from_cols_array_2d(&camera.rotation).transpose();
+
+                // COLMAP camera looks at +Z with +Y down
+                CaptureCamera {
+                    name: camera.img_name,
+                    pos: Vec3::from_array(camera.position),
+                    forward: rotation.z_axis,
+                    up: -rotation.y_axis,
+                    size: Some(UVec2::new(camera.width, camera.height)),
+                    vertical_fov: Some(2.0 * (camera.height as f32 / (2.0 * camera.fy)).atan()),
+                }
+            })
+            .collect())
+    }
+
+    /// Parse the `transforms.json` of NeRF style datasets.
+    fn parse_transforms(json: &[u8]) -> Result<Vec<CaptureCamera>, String> {
+        /// A frame in `transforms.json`.
+        #[derive(serde::Deserialize)]
+        struct Frame {
+            file_path: String,
+            /// The camera to world transform, in rows.
+            transform_matrix: [[f32; 4]; 4],
+        }
+
+        /// The `transforms.json`.
+        #[derive(serde::Deserialize)]
+        struct Transforms {
+            camera_angle_x: Option<f32>,
+            fl_y: Option<f32>,
+            w: Option<u32>,
+            h: Option<u32>,
+            frames: Vec<Frame>,
+        }
+
+        let transforms = serde_json::from_slice::<Transforms>(json).map_err(|e| e.to_string())?;
+
+        let size = transforms
+            .w
+            .zip(transforms.h)
+            .map(|(w, h)| UVec2::new(w, h));
+        let vertical_fov = match (transforms.fl_y, size, transforms.camera_angle_x) {
+            (Some(fl_y), Some(size), _) => Some(2.0 * (size.y as f32 / (2.0 * fl_y)).atan()),
+            (None, Some(size), Some(angle_x)) => {
+                Some(2.0 * ((angle_x / 2.0).tan() * size.y as f32 / size.x as f32).atan())
+            }
+            _ => None,
+        };
+
+        Ok(transforms
+            .frames
+            .into_iter()
+            .map(|frame| {
+                let transform = Mat4::from_cols_array_2d(&frame.transform_matrix).transpose();
+
+                // OpenGL camera looks at -Z with +Y up
+                CaptureCamera {
+                    name: frame.file_path,
+                    pos: transform.w_axis.truncate(),
+                    forward: -transform.z_axis.truncate(),
+                    up: transform.y_axis.truncate(),
+                    size,
+                    vertical_fov,
+                }
+            })
+            .collect())
+    }
+}
+
+/// A capture camera of [`CaptureMetadata`].
+#[derive(Debug, Clone)]
+pub struct CaptureCamera {
+    /// The name, usually the image file.
+    pub name: String,
+
+    /// The position in model space.
+    pub pos: Vec3,
+
+    /// The forward direction in model space.
+    pub forward: Vec3,
+
+    /// The up direction in model space.
+    pub up: Vec3,
+
+    /// The image size in pixels.
+    pub size: Option<UVec2>,
+
+    /// The vertical field of view in radians.
+    pub vertical_fov: Option<f32>,
+}
+
+impl CaptureCamera {
+    /// Move the viewer camera to this capture camera of a model.
+    ///
+    /// The camera looks along the capture camera, with the orbit target at the depth of the
+    /// model center. The roll of the capture camera is not kept.
+    pub fn apply(&self, camera: &mut app::Camera, model: &app::GaussianSplattingModel) {
+        let (pos, forward, _) = self.world_pose(model);
+        let forward = forward.normalize_or(Vec3::Z);
+
+        let depth = (model.world_center() - pos)
+            .dot(forward)
+            .max(camera.distance_limits.min);
+
+        let mut orbit = camera.control.to_orbit(depth);
+        orbit.pos = pos;
+        orbit.target = pos + forward * depth;
+        if let Some(vertical_fov) = self.vertical_fov {
+            orbit.vertical_fov = vertical_fov.clamp(30f32.to_radians(), 120f32.to_radians());
+        }
+
+        camera.control.set_orbit(orbit);
+    }
+
+    /// Get the position, forward and up directions in world space of this capture camera of a
+    /// model.
+    pub fn world_pose(&self, model: &app::GaussianSplattingModel) -> (Vec3, Vec3, Vec3) {
+        let transform = &model.transform;
+        let dir = |dir: Vec3| (transform.quat() * (dir * transform.scale)).normalize_or_zero();
+
+        (transform.apply(self.pos), dir(self.forward), dir(self.up))
+    }
+}
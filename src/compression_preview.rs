@@ -0,0 +1,268 @@
+use glam::*;
+use wgpu_3dgs_viewer as gs;
+
+use crate::app;
+
+/// A rough CPU rendered thumbnail of a sample of Gaussians, for previewing the quality loss of
+/// the compression settings.
+#[derive(Debug, Clone)]
+pub struct CompressionPreview {
+    /// The sampled Gaussians in model space.
+    gaussians: Vec<gs::Gaussian>,
+
+    /// The model transform.
+    transform: app::GaussianSplattingModelTransform,
+
+    /// The camera position.
+    eye: Vec3,
+
+    /// The camera target.
+    target: Vec3,
+}
+
+impl CompressionPreview {
+    /// The width and height of the thumbnail.
+    pub const SIZE: usize = 128;
+
+    /// The maximum number of sampled Gaussians.
+    pub const SAMPLE_COUNT: usize = 32_768;
+
+    /// The vertical field of view.
+    const FOV: f32 = 60.0 * std::f32::consts::PI / 180.0;
+
+    /// Create a new preview of a model.
+    ///
+    /// The Gaussians are sampled with a fixed stride, and the camera looks at the center of the
+    /// model along the negative Z axis, like the default camera.
+    pub fn new(model: &app::GaussianSplattingModel) -> Self {
+        let gaussians = &model.gaussians.gaussians;
+        let stride = gaussians.len().div_ceil(Self::SAMPLE_COUNT).max(1);
+        let gaussians = gaussians
+            .iter()
+            .step_by(stride)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let transform = model.transform.clone();
+        let target = transform.apply(model.center);
+
+        // Use the 90th percentile distance as the radius to ignore outliers.
+        let mut distances = gaussians
+            .iter()
+            .map(|gaussian| transform.apply(gaussian.pos).distance(target))
+            .collect::<Vec<_>>();
+        distances.sort_by(f32::total_cmp);
+        let radius = distances
+            .get(distances.len() * 9 / 10)
+            .copied()
+            .unwrap_or(1.0)
+            .max(1e-3);
+
+        let eye = target + Vec3::Z * radius / (Self::FOV / 2.0).tan();
+
+        Self {
+            gaussians,
+            transform,
+            eye,
+            target,
+        }
+    }
+
+    /// Render the thumbnail.
+    ///
+    /// [`None`] renders the uncompressed Gaussians.
+    pub fn render(&self, compressions: Option<&app::Compressions>) -> egui::ColorImage {
+        const SIZE: usize = CompressionPreview::SIZE;
+
+        let view = Mat4::look_at_rh(self.eye, self.target, Vec3::Y);
+        let view_rot = Mat3::from_mat4(view);
+        let focal = SIZE as f32 / 2.0 / (Self::FOV / 2.0).tan();
+        let quat = self.transform.quat();
+        let model_mat = Mat3::from_quat(quat) * Mat3::from_diagonal(self.transform.scale);
+
+        // Project the Gaussians.
+        let mut splats = self
+            .gaussians
+            .iter()
+            .filter_map(|gaussian| {
+                let world = self.transform.apply(gaussian.pos);
+                let t = view.transform_point3(world);
+                let depth = -t.z;
+                if depth < 1e-2 {
+                    return None;
+                }
+
+                let center = Vec2::new(
+                    SIZE as f32 / 2.0 + focal * t.x / depth,
+                    SIZE as f32 / 2.0 - focal * t.y / depth,
+                );
+
+                let sigma = Self::cov3d(gaussian, compressions);
+                let w = view_rot * model_mat;
+                let j = Mat3::from_cols(
+                    Vec3::new(focal / depth, 0.0, 0.0),
+                    Vec3::new(0.0, -focal / depth, 0.0),
+                    Vec3::new(
+                        focal * t.x / (depth * depth),
+                        -focal * t.y / (depth * depth),
+                        0.0,
+                    ),
+                );
+                let cov2d = (j * w) * sigma * (j * w).transpose();
+                let cov2d = Vec3::new(cov2d.x_axis.x + 0.3, cov2d.x_axis.y, cov2d.y_axis.y + 0.3);
+
+                let det = cov2d.x * cov2d.z - cov2d.y * cov2d.y;
+                if det <= 0.0 {
+                    return None;
+                }
+                let conic = Vec3::new(cov2d.z, -cov2d.y, cov2d.x) / det;
+
+                let mid = 0.5 * (cov2d.x + cov2d.z);
+                let lambda = mid + (mid * mid - det).max(0.1).sqrt();
+                let radius = 3.0 * lambda.sqrt();
+
+                let dir = quat.inverse() * (world - self.eye).normalize();
+                let color = gaussian.color.as_vec4() / 255.0;
+                let rgb = Self::sh_color(color.xyz(), dir, &Self::sh(gaussian, compressions));
+
+                Some((depth, center, radius, conic, rgb, color.w))
+            })
+            .collect::<Vec<_>>();
+
+        // Blend from back to front.
+        splats.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut pixels = vec![Vec3::ZERO; SIZE * SIZE];
+        for (_, center, radius, conic, rgb, alpha) in splats {
+            let min = (center - radius).floor().max(Vec2::ZERO);
+            let max = (center + radius).ceil().min(Vec2::splat(SIZE as f32));
+
+            for y in min.y as usize..max.y as usize {
+                for x in min.x as usize..max.x as usize {
+                    let d = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+                    let power =
+                        -0.5 * (conic.x * d.x * d.x + conic.z * d.y * d.y) - conic.y * d.x * d.y;
+                    if power > 0.0 {
+                        continue;
+                    }
+
+                    let a = (alpha * power.exp()).min(0.99);
+                    if a < 1.0 / 255.0 {
+                        continue;
+                    }
+
+                    let pixel = &mut pixels[y * SIZE + x];
+                    *pixel = pixel.lerp(rgb, a);
+                }
+            }
+        }
+
+        egui::ColorImage::from_rgb(
+            [SIZE, SIZE],
+            &pixels
+                .iter()
+                .flat_map(|pixel| {
+                    (pixel.clamp(Vec3::ZERO, Vec3::ONE) * 255.0)
+                        .round()
+                        .as_u8vec3()
+                        .to_array()
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Calculate the peak signal-to-noise ratio between two thumbnails in dB.
+    ///
+    /// Returns [`f32::INFINITY`] if the thumbnails are identical.
+    pub fn psnr(a: &egui::ColorImage, b: &egui::ColorImage) -> f32 {
+        let mse = a
+            .pixels
+            .iter()
+            .zip(b.pixels.iter())
+            .flat_map(|(a, b)| {
+                (0..3).map(move |i| (a[i] as f32 - b[i] as f32).powi(2) / (255.0 * 255.0))
+            })
+            .sum::<f32>()
+            / (a.pixels.len() * 3).max(1) as f32;
+
+        if mse == 0.0 {
+            f32::INFINITY
+        } else {
+            -10.0 * mse.log10()
+        }
+    }
+
+    /// Get the covariance 3D in model space as stored with the compression.
+    fn cov3d(gaussian: &gs::Gaussian, compressions: Option<&app::Compressions>) -> Mat3 {
+        let m = Mat3::from_quat(gaussian.rotation) * Mat3::from_diagonal(gaussian.scale);
+        let sigma = m * m.transpose();
+
+        match compressions.map(|c| c.cov3d) {
+            Some(app::Cov3dCompression::Half) => Mat3::from_cols_array(
+                &sigma
+                    .to_cols_array()
+                    .map(|x| half::f16::from_f32(x).to_f32()),
+            ),
+            Some(app::Cov3dCompression::Single) | None => sigma,
+        }
+    }
+
+    /// Get the spherical harmonics as stored with the compression.
+    fn sh(gaussian: &gs::Gaussian, compressions: Option<&app::Compressions>) -> [Vec3; 15] {
+        match compressions.map(|c| c.sh) {
+            Some(app::ShCompression::Single) | None => gaussian.sh,
+            Some(app::ShCompression::Half) => gaussian
+                .sh
+                .map(|sh| sh.map(|x| half::f16::from_f32(x).to_f32())),
+            Some(app::ShCompression::Norm8) => {
+                // Same as the viewer, the min and max are stored in half precision.
+                let (min, max) = gaussian
+                    .sh
+                    .iter()
+                    .fold((f32::MAX, f32::MIN), |(min, max), sh| {
+                        (min.min(sh.min_element()), max.max(sh.max_element()))
+                    });
+                let stored_min = half::f16::from_f32(min).to_f32();
+                let stored_max = half::f16::from_f32(max).to_f32();
+
+                gaussian.sh.map(|sh| {
+                    sh.map(|x| {
+                        let q = ((x - min) / (max - min) * 255.0).round();
+                        stored_min + q / 255.0 * (stored_max - stored_min)
+                    })
+                })
+            }
+            Some(app::ShCompression::Remove) => [Vec3::ZERO; 15],
+        }
+    }
+
+    /// Evaluate the color in direction `dir` with spherical harmonics degree 3.
+    fn sh_color(base: Vec3, dir: Vec3, sh: &[Vec3; 15]) -> Vec3 {
+        const SH_C1: f32 = 0.4886025;
+        const SH_C2: [f32; 5] = [1.0925484, -1.0925484, 0.3153916, -1.0925484, 0.5462742];
+        const SH_C3: [f32; 7] = [
+            -0.5900436, 2.8906114, -0.4570458, 0.3731763, -0.4570458, 1.4453057, -0.5900436,
+        ];
+
+        let Vec3 { x, y, z } = dir;
+        let (xx, yy, zz) = (x * x, y * y, z * z);
+        let (xy, yz, xz) = (x * y, y * z, x * z);
+
+        let color = base
+            + SH_C1 * (-sh[0] * y + sh[1] * z - sh[2] * x)
+            + SH_C2[0] * xy * sh[3]
+            + SH_C2[1] * yz * sh[4]
+            + SH_C2[2] * (2.0 * zz - xx - yy) * sh[5]
+            + SH_C2[3] * xz * sh[6]
+            + SH_C2[4] * (xx - yy) * sh[7]
+            + SH_C3[0] * y * (3.0 * xx - yy) * sh[8]
+            + SH_C3[1] * xy * z * sh[9]
+            + SH_C3[2] * y * (4.0 * zz - xx - yy) * sh[10]
+            + SH_C3[3] * z * (2.0 * zz - 3.0 * xx - 3.0 * yy) * sh[11]
+            + SH_C3[4] * x * (4.0 * zz - xx - yy) * sh[12]
+            + SH_C3[5] * z * (xx - yy) * sh[13]
+            + SH_C3[6] * x * (xx - 3.0 * yy) * sh[14];
+
+        color.max(Vec3::ZERO)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{app, tab};
+
+/// The file name of the config.
+pub const FILE_NAME: &str = "config.json";
+
+/// The deployment config that overrides the built-in defaults.
+///
+/// This is loaded on startup from [`FILE_NAME`] next to the executable on native, or next to
+/// the page on the web. It only applies when there is no saved app state, e.g. on the first run,
+/// and when resetting to defaults. Every field is optional, the missing fields use the built-in
+/// defaults, as do the missing fields of [`Config::settings`] and [`Config::performance`].
+///
+/// The fields have the same names and values as in the saved app state, e.g.:
+///
+/// ```json
+/// {
+///     "compressions": { "sh": "Half", "cov3d": "Single" },
+///     "settings": { "theme": "Dark", "load_sample": false, "auto_hide": true },
+///     "performance": { "render_mode": "OnInteraction" },
+///     "tabs": ["Scene", "Camera", "Settings"]
+/// }
+/// ```
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The default compression settings that each opened model starts from.
+    pub compressions: Option<app::Compressions>,
+
+    /// The global settings.
+    pub settings: Option<app::Settings>,
+
+    /// The performance settings.
+    pub performance: Option<app::Performance>,
+
+    /// The tabs shown in the default layout.
+    pub tabs: Option<Vec<tab::Type>>,
+}
+
+impl Config {
+    /// Load the config.
+    ///
+    /// Returns [`None`] if there is no config, or if it is invalid.
+    pub fn load() -> Option<Self> {
+        let json = Self::read()?;
+
+        match serde_json::from_str(&json) {
+            Ok(config) => {
+                log::info!("Config loaded from {FILE_NAME}");
+                Some(config)
+            }
+            Err(e) => {
+                log::warn!("Ignoring invalid {FILE_NAME}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Apply the config to the app state and the tab manager.
+    pub fn apply(&self, state: &mut app::State, tab_manager: &mut tab::Manager) {
+        if let Some(compressions) = &self.compressions {
+            state.compressions = compressions.clone();
+            state.default_compressions = compressions.clone();
+        }
+
+        if let Some(settings) = &self.settings {
+            state.settings = settings.clone();
+        }
+
+        if let Some(performance) = &self.performance {
+            state.performance = performance.clone();
+        }
+
+        if let Some(tabs) = &self.tabs {
+            tab_manager.set_default_tabs(tabs);
+        }
+    }
+
+    /// Read the config next to the executable.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read() -> Option<String> {
+        let path = std::env::current_exe().ok()?.parent()?.join(FILE_NAME);
+        std::fs::read_to_string(path).ok()
+    }
+
+    /// Read the config next to the page.
+    ///
+    /// The request is synchronous, so the config is available before the app state is loaded.
+    #[cfg(target_arch = "wasm32")]
+    fn read() -> Option<String> {
+        let request = web_sys::XmlHttpRequest::new().ok()?;
+        request.open_with_async("GET", FILE_NAME, false).ok()?;
+        request.send().ok()?;
+
+        match request.status().ok()? {
+            200 => request.response_text().ok()?,
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::VecDeque, fmt::Write, sync::Mutex};
+
+use itertools::Itertools;
+
+use crate::app;
+
+/// The maximum number of captured log entries.
+const LOG_LEN: usize = 1000;
+
+/// The number of recent log entries included in the diagnostics.
+const DIAGNOSTICS_LOG_LEN: usize = 100;
+
+/// The captured log entries.
+static LOG: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+/// A captured log record.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// The local time.
+    pub time: String,
+
+    /// The level.
+    pub level: log::Level,
+
+    /// The target.
+    pub target: String,
+
+    /// The message.
+    pub message: String,
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}: {}",
+            self.time, self.level, self.target, self.message
+        )
+    }
+}
+
+/// Get the captured log entries with at least the severity of `level`.
+pub fn log_entries(level: log::LevelFilter) -> Vec<LogEntry> {
+    LOG.lock()
+        .map(|log| {
+            log.iter()
+                .filter(|entry| entry.level <= level)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Clear the captured log entries.
+pub fn clear_log() {
+    if let Ok(mut log) = LOG.lock() {
+        log.clear();
+    }
+}
+
+/// A logger capturing the records and forwarding them to the inner logger.
+struct CaptureLogger<L> {
+    /// The inner logger.
+    inner: L,
+}
+
+impl<L: log::Log> log::Log for CaptureLogger<L> {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.inner.enabled(record.metadata()) {
+            let entry = LogEntry {
+                time: chrono::Local::now().format("%H:%M:%S").to_string(),
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+            };
+
+            if let Ok(mut log) = LOG.lock() {
+                if log.len() == LOG_LEN {
+                    log.pop_front();
+                }
+                log.push_back(entry);
+            }
+        }
+
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Set the global logger, capturing the records for the logs tab and the diagnostics.
+pub fn init_logger(
+    logger: impl log::Log + 'static,
+    max_level: log::LevelFilter,
+) -> Result<(), log::SetLoggerError> {
+    log::set_boxed_logger(Box::new(CaptureLogger { inner: logger }))?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+/// Collect the diagnostics for bug reports.
+///
+/// If `hide_paths` is true, the model paths are left out, and they and the home directory are
+/// replaced in the log lines.
+pub fn collect(frame: &eframe::Frame, state: &app::State, hide_paths: bool) -> String {
+    let mut out = String::new();
+
+    macro_rules! line {
+        ($($arg:tt)*) => {
+            writeln!(out, $($arg)*).expect("write diagnostics")
+        };
+    }
+
+    line!("# {} Diagnostics", app::App::NAME);
+    line!();
+    line!("Version: {}", env!("CARGO_PKG_VERSION"));
+    line!(
+        "Platform: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+
+    #[cfg(target_arch = "wasm32")]
+    line!(
+        "User Agent: {}",
+        web_sys::window()
+            .and_then(|window| window.navigator().user_agent().ok())
+            .unwrap_or_else(|| "Unknown".to_string())
+    );
+
+    line!();
+    line!("## GPU");
+    match frame.wgpu_render_state() {
+        Some(render_state) => {
+            let info = render_state.adapter.get_info();
+            line!("Adapter: {} ({:?})", info.name, info.device_type);
+            line!("Backend: {}", info.backend);
+            line!("Driver: {} {}", info.driver, info.driver_info);
+
+            #[cfg(target_arch = "wasm32")]
+            line!(
+                "Web Graphics: {}",
+                match info.backend {
+                    eframe::wgpu::Backend::BrowserWebGpu => "WebGPU",
+                    eframe::wgpu::Backend::Gl => "WebGL",
+                    _ => "Unknown",
+                }
+            );
+
+            let limits = render_state.device.limits();
+            line!("Max Buffer Size: {}", limits.max_buffer_size);
+            line!(
+                "Max Storage Buffer Binding Size: {}",
+                limits.max_storage_buffer_binding_size
+            );
+            line!(
+                "Max Storage Buffers per Shader Stage: {}",
+                limits.max_storage_buffers_per_shader_stage
+            );
+            line!(
+                "Max Texture Dimension 2D: {}",
+                limits.max_texture_dimension_2d
+            );
+        }
+        None => line!("No render state"),
+    }
+
+    line!();
+    line!("## Models");
+    let mut paths = Vec::new();
+    match &state.gs {
+        app::Loadable::Loaded(gs) => {
+            line!(
+                "Compressions: {} / {}",
+                gs.compressions.sh,
+                gs.compressions.cov3d
+            );
+            line!("Count: {}", gs.models.len());
+
+            for (key, model) in gs.models.iter().sorted_by_key(|(key, _)| *key) {
+                line!(
+                    "- {key}: {} / {} Gaussians",
+                    model.gaussians.gaussians.len(),
+                    model.gaussians.gaussians.capacity()
+                );
+
+                if let Some(path) = &model.path {
+                    if !hide_paths {
+                        line!("  Path: {}", path.display());
+                    }
+                    paths.push(path.display().to_string());
+                }
+            }
+        }
+        app::Loadable::Unloaded { .. } => line!("No model loaded"),
+    }
+
+    line!();
+    line!("## Log");
+
+    if hide_paths {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+            paths.push(home.to_string_lossy().into_owned());
+        }
+
+        // Replace the longer paths first, since they may contain the shorter ones
+        paths.retain(|path| !path.is_empty());
+        paths.sort_by_key(|path| std::cmp::Reverse(path.len()));
+    }
+
+    let entries = log_entries(log::LevelFilter::Trace);
+    for entry in entries
+        .iter()
+        .skip(entries.len().saturating_sub(DIAGNOSTICS_LOG_LEN))
+    {
+        let line = entry.to_string();
+        match hide_paths {
+            true => line!(
+                "{}",
+                paths
+                    .iter()
+                    .fold(line, |line, path| line.replace(path, "<path>"))
+            ),
+            false => line!("{line}"),
+        }
+    }
+
+    out
+}
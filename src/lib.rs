@@ -1,6 +1,18 @@
+mod annotation;
 mod app;
+mod capture;
+mod compression_preview;
+mod config;
+mod diagnostics;
+mod project;
+mod registration;
 mod renderer;
+mod sample;
+mod script;
 mod tab;
 mod util;
+mod viewer;
 
 pub use app::App;
+pub use diagnostics::init_logger;
+pub use viewer::ViewerWidget;
@@ -10,31 +10,49 @@ use eframe::{egui_wgpu, wgpu};
 // 针对非 WebAssembly 目标的主函数实现
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result {
-    // 初始化日志记录器，设置默认的日志级别为 info
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    // 初始化日志记录器，设置默认的日志级别为 info，并保留最近的日志用于诊断信息
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let max_level = logger.filter();
+    wgpu_3dgs_viewer_app::init_logger(logger, max_level).expect("Failed to init logger");
+
+    // 读取垂直同步设置
+    let vsync = wgpu_3dgs_viewer_app::App::load_vsync();
 
     // 定义原生应用选项
     let native_options = eframe::NativeOptions {
         // 设置深度缓冲区位数
         depth_buffer: 32,
         // 设置视口属性：初始大小、最小尺寸和图标
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([400.0, 300.0]) // 初始窗口大小为 400x300 像素
-            .with_min_inner_size([300.0, 220.0]) // 最小窗口大小为 300x220 像素
-            .with_icon(
-                // 加载应用程序图标（从 assets/icon-256.png）
-                eframe::icon_data::from_png_bytes(&include_bytes!("../assets/icon-256.png")[..])
+        // 保存过窗口大小和位置时会恢复上次的窗口，并限制在显示器大小内
+        viewport: wgpu_3dgs_viewer_app::App::restore_window(
+            egui::ViewportBuilder::default()
+                .with_inner_size([400.0, 300.0]) // 首次运行的窗口大小为 400x300 像素
+                .with_min_inner_size([300.0, 220.0]) // 最小窗口大小为 300x220 像素
+                .with_icon(
+                    // 加载应用程序图标（从 assets/icon-256.png）
+                    eframe::icon_data::from_png_bytes(
+                        &include_bytes!("../assets/icon-256.png")[..],
+                    )
                     .expect("Failed to load icon"), // 如果加载失败则 panic
-            ),
-        // 设置 WebGPU 选项
-        wgpu_options: wgpu_configuration(),
+                ),
+        ),
+        // 垂直同步设置需要在创建窗口前读取
+        vsync,
+        // 窗口大小和位置由应用自行保存，见 App::restore_window
+        persist_window: false,
+        // 设置 WebGPU 选项，根据垂直同步设置选择呈现模式
+        wgpu_options: wgpu_configuration(match vsync {
+            true => wgpu::PresentMode::AutoVsync,
+            false => wgpu::PresentMode::AutoNoVsync,
+        }),
         ..Default::default() // 其余选项使用默认值
     };
 
     // 使用 eframe 运行原生应用程序
     eframe::run_native(
-        "3D Gaussian Splatting Viewer", // 应用程序名称
-        native_options,                 // 原生选项配置
+        wgpu_3dgs_viewer_app::App::NAME, // 应用程序名称
+        native_options,                  // 原生选项配置
         Box::new(|cc| Ok(Box::new(wgpu_3dgs_viewer_app::App::new(cc)))), // 创建 App 实例的闭包
     )
 }
@@ -42,14 +60,18 @@ fn main() -> eframe::Result {
 // 针对 WebAssembly 目标的主函数实现
 #[cfg(target_arch = "wasm32")]
 fn main() {
-    // 初始化 Web 端的日志记录器
-    eframe::WebLogger::init(log::LevelFilter::Debug).ok();
+    // 初始化 Web 端的日志记录器，并保留最近的日志用于诊断信息
+    wgpu_3dgs_viewer_app::init_logger(
+        eframe::WebLogger::new(log::LevelFilter::Debug),
+        log::LevelFilter::Debug,
+    )
+    .ok();
 
     // 设置 Web 端选项
     let web_options = eframe::WebOptions {
-        depth_buffer: 32,                   // 深度缓冲区位数
-        wgpu_options: wgpu_configuration(), // WebGPU 配置
-        ..Default::default()                // 其余选项使用默认值
+        depth_buffer: 32,                                               // 深度缓冲区位数
+        wgpu_options: wgpu_configuration(wgpu::PresentMode::AutoVsync), // WebGPU 配置
+        ..Default::default()                                            // 其余选项使用默认值
     };
 
     // 在 Web 环境中启动异步任务
@@ -102,8 +124,10 @@ fn main() {
 }
 
 // 定义 WebGPU 配置函数
-fn wgpu_configuration() -> egui_wgpu::WgpuConfiguration {
+fn wgpu_configuration(present_mode: wgpu::PresentMode) -> egui_wgpu::WgpuConfiguration {
     egui_wgpu::WgpuConfiguration {
+        // 呈现模式
+        present_mode,
         // 配置 WGPU 设置：创建新实例
         wgpu_setup: egui_wgpu::WgpuSetup::CreateNew(egui_wgpu::WgpuSetupCreateNew {
             // 优先选择高性能 GPU
@@ -112,7 +136,14 @@ fn wgpu_configuration() -> egui_wgpu::WgpuConfiguration {
             device_descriptor: Arc::new(|adapter| wgpu::DeviceDescriptor {
                 label: Some("Device"),             // 设备标签
                 required_limits: adapter.limits(), // 使用适配器的限制
-                ..Default::default()               // 其他属性使用默认值
+                // 支持时启用时间戳查询，用于测量各通道的 GPU 耗时；
+                // 以及间接绘制的起始实例，用于渲染限制保留最近的高斯
+                required_features: adapter.features()
+                    & (wgpu::Features::TIMESTAMP_QUERY
+                        | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS
+                        | wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES
+                        | wgpu::Features::INDIRECT_FIRST_INSTANCE),
+                ..Default::default() // 其他属性使用默认值
             }),
             ..Default::default() // 其余选项使用默认值
         }),
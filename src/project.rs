@@ -0,0 +1,746 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::BufRead,
+    ops::Range,
+    path::PathBuf,
+};
+
+use eframe::wgpu;
+use glam::*;
+use wgpu_3dgs_viewer as gs;
+
+use crate::{app, renderer};
+
+/// The project file of a session.
+///
+/// The Gaussians are not included, the models are referenced by their file paths instead.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Project {
+    /// The version of the project format.
+    pub version: u32,
+
+    /// The models.
+    pub models: Vec<ProjectModel>,
+
+    /// The key of the selected model.
+    pub selected_model_key: String,
+
+    /// The camera.
+    pub camera: ProjectCamera,
+
+    /// The Gaussian transform.
+    pub gaussian_transform: ProjectGaussianTransform,
+
+    /// The measurement hit pairs.
+    pub hit_pairs: Vec<ProjectHitPair>,
+}
+
+impl Project {
+    /// The current version of the project format.
+    pub const VERSION: u32 = 1;
+
+    /// The file extension of project files.
+    pub const EXTENSION: &str = "project";
+
+    /// Create a project from the session.
+    ///
+    /// The edits of the models are empty, they have to be set by [`ProjectModel::set_edits`]
+    /// after downloading.
+    pub fn new(gs: &app::GaussianSplatting) -> Self {
+        let mut models = gs
+            .models
+            .iter()
+            .map(|(key, model)| ProjectModel::new(key.clone(), model, &gs.selection.layers))
+            .collect::<Vec<_>>();
+        models.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Self {
+            version: Self::VERSION,
+            models,
+            selected_model_key: gs.selected_model_key.clone(),
+            camera: ProjectCamera::new(&gs.camera),
+            gaussian_transform: ProjectGaussianTransform::new(&gs.gaussian_transform),
+            hit_pairs: gs
+                .measurement
+                .hit_pairs
+                .iter()
+                .map(ProjectHitPair::new)
+                .collect(),
+        }
+    }
+
+    /// Download the edits of the models.
+    ///
+    /// `edit_buffers` are the edit buffers of the models in the same order.
+    pub async fn download_edits(
+        &mut self,
+        edit_buffers: Vec<gs::GaussiansEditBuffer>,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        for (model, buffer) in self.models.iter_mut().zip(edit_buffers) {
+            match buffer.download(device, queue).await {
+                Ok(edits) => model.set_edits(&edits),
+                Err(e) => log::error!("Download edit buffer: {e}"),
+            }
+        }
+    }
+
+    /// Get the path of the recovery project.
+    ///
+    /// This is only available on native, [`None`] is returned on the web.
+    pub fn recovery_path() -> Option<PathBuf> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return eframe::storage_dir(app::App::NAME)
+            .map(|dir| dir.join(format!("recovery.{}", Self::EXTENSION)));
+
+        #[cfg(target_arch = "wasm32")]
+        None
+    }
+
+    /// Save the recovery project.
+    ///
+    /// The file is written to a temporary file first, so a crash while saving does not leave
+    /// a corrupted recovery project.
+    pub fn save_recovery(json: &str) -> Result<(), String> {
+        let path = Self::recovery_path().ok_or("recovery is not available")?;
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, json).map_err(|e| e.to_string())?;
+        std::fs::rename(&temp_path, &path).map_err(|e| e.to_string())
+    }
+
+    /// Load the recovery project.
+    ///
+    /// Returns [`None`] if there is no valid recovery project.
+    pub fn load_recovery() -> Option<Self> {
+        let json = std::fs::read(Self::recovery_path()?).ok()?;
+
+        match Self::from_json(&json) {
+            Ok(project) => Some(project),
+            Err(e) => {
+                log::warn!("Ignoring recovery project: {e}");
+                Self::clear_recovery();
+                None
+            }
+        }
+    }
+
+    /// Remove the recovery project.
+    pub fn clear_recovery() {
+        if let Some(path) = Self::recovery_path() {
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    log::error!("Remove recovery project: {e}");
+                }
+            }
+        }
+    }
+
+    /// Serialize the project to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("serialize project")
+    }
+
+    /// Deserialize the project from JSON.
+    pub fn from_json(json: &[u8]) -> Result<Self, String> {
+        let project =
+            serde_json::from_slice::<Self>(json).map_err(|e| format!("Invalid project: {e}"))?;
+
+        if project.version > Self::VERSION {
+            return Err(format!(
+                "Unsupported project version {}, the latest supported version is {}",
+                project.version,
+                Self::VERSION
+            ));
+        }
+
+        Ok(project)
+    }
+
+    /// Open the project.
+    ///
+    /// The first model that can be opened creates the session, the other models are loaded
+    /// one by one by the scene with [`ProjectRestore`].
+    ///
+    /// Returns an error if none of the models can be opened.
+    pub fn open(self, compressions: app::Compressions) -> Result<app::GaussianSplatting, String> {
+        let mut pending = VecDeque::from(self.models);
+        let mut missing = Vec::new();
+
+        while let Some(model) = pending.pop_front() {
+            let mut gs = match model.open().and_then(|reader| {
+                app::GaussianSplatting::new(
+                    model.file_name.clone(),
+                    model.path.clone(),
+                    reader,
+                    compressions.clone(),
+                )
+                .map_err(|e| e.to_string())
+            }) {
+                Ok(gs) => gs,
+                Err(e) => {
+                    log::warn!("Error opening model {} in project: {e}", model.file_name);
+                    missing.push(format!("{}: {e}", model.file_name));
+                    continue;
+                }
+            };
+
+            let key = gs.selected_model_key.clone();
+            model.apply(gs.models.get_mut(&key).expect("model"));
+
+            self.camera.apply(&mut gs.camera);
+            self.gaussian_transform.apply(&mut gs.gaussian_transform);
+            gs.measurement.hit_pairs = self
+                .hit_pairs
+                .iter()
+                .map(ProjectHitPair::to_hit_pair)
+                .collect();
+            gs.scene_tx
+                .send(app::SceneCommand::UpdateMeasurementHit)
+                .expect("send update measurement hit");
+
+            gs.project_restore = Some(ProjectRestore {
+                pending,
+                loading: Some((key.clone(), model.clone())),
+                keys: HashMap::from([(model.key, key)]),
+                selected_model_key: self.selected_model_key,
+                missing,
+            });
+
+            return Ok(gs);
+        }
+
+        Err(format!(
+            "None of the models in the project could be opened\n{}",
+            missing.join("\n")
+        ))
+    }
+}
+
+/// A model in the project.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ProjectModel {
+    /// The key of the model.
+    pub key: String,
+
+    /// The file name.
+    pub file_name: String,
+
+    /// The path of the file.
+    pub path: Option<PathBuf>,
+
+    /// The position.
+    pub pos: Vec3,
+
+    /// The Euler rotation.
+    pub rot: Vec3,
+
+    /// The scale.
+    pub scale: Vec3,
+
+    /// Whether the model is visible.
+    pub visible: bool,
+
+    /// Whether the model is locked against edits.
+    #[serde(default)]
+    pub locked: bool,
+
+    /// The mask shapes.
+    pub mask_shapes: Vec<app::GaussianSplattingMaskShapeData>,
+
+    /// The mask operations code.
+    pub mask_op_code: String,
+
+    /// Whether the mask gizmos are hidden.
+    #[serde(default)]
+    pub mask_gizmos_hidden: bool,
+
+    /// Whether the handedness is flipped.
+    #[serde(default)]
+    pub flipped: bool,
+
+    /// The color space, [`None`] for the older projects to keep the detected color space.
+    #[serde(default)]
+    pub color_space: Option<app::ModelColorSpace>,
+
+    /// The selection layers.
+    #[serde(default)]
+    pub selection_layers: Vec<ProjectSelectionLayer>,
+
+    /// The non-empty Gaussian edits.
+    ///
+    /// Each edit is the index of the Gaussian followed by the two words of
+    /// [`gs::GaussianEditPod`].
+    pub edits: Vec<[u32; 3]>,
+}
+
+impl ProjectModel {
+    /// Create a project model from a model and the selection layers of the session.
+    pub fn new(
+        key: String,
+        model: &app::GaussianSplattingModel,
+        layers: &[app::SelectionLayer],
+    ) -> Self {
+        Self {
+            selection_layers: layers
+                .iter()
+                .filter(|layer| layer.key == key)
+                .map(ProjectSelectionLayer::new)
+                .collect(),
+            key,
+            file_name: model.file_name.clone(),
+            path: model.path.clone(),
+            pos: model.transform.pos,
+            rot: model.transform.rot,
+            scale: model.transform.scale,
+            visible: model.visible,
+            locked: model.locked,
+            mask_shapes: model
+                .mask
+                .shapes
+                .iter()
+                .map(app::GaussianSplattingMaskShape::to_data)
+                .collect(),
+            mask_op_code: model.mask.op_code.clone(),
+            mask_gizmos_hidden: !model.mask.gizmos_visible,
+            flipped: model.flipped,
+            color_space: Some(model.color_space),
+            edits: Vec::new(),
+        }
+    }
+
+    /// Set the edits from the downloaded edits.
+    pub fn set_edits(&mut self, edits: &[gs::GaussianEditPod]) {
+        self.edits = renderer::EditRestore::sparse(edits);
+    }
+
+    /// Open the file of the model.
+    pub fn open(&self) -> Result<Box<dyn BufRead + Send + 'static>, String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = self.path.as_ref().ok_or("the file path is unknown")?;
+            let file = std::fs::File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            Ok(Box::new(std::io::BufReader::new(file)))
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        Err("files cannot be opened by path on the web".to_string())
+    }
+
+    /// Apply the transform, visibility, lock, handedness and color space to the model.
+    ///
+    /// The mask and edits are applied by the scene after the model is loaded.
+    pub fn apply(&self, model: &mut app::GaussianSplattingModel) {
+        model.transform.pos = self.pos;
+        model.transform.rot = self.rot;
+        model.transform.scale = self.scale;
+        model.visible = self.visible;
+        model.locked = self.locked;
+        if model.flipped != self.flipped {
+            model.flip_handedness();
+        }
+        if let Some(color_space) = self.color_space {
+            model.color_space = color_space;
+        }
+    }
+
+    /// Apply the mask shapes, operations code and gizmo visibility to the mask.
+    ///
+    /// Invalid shapes are skipped.
+    pub fn apply_mask(&self, mask: &mut app::GaussianSplattingMask) {
+        mask.shapes = self
+            .mask_shapes
+            .iter()
+            .cloned()
+            .filter_map(
+                |data| match app::GaussianSplattingMaskShape::from_data(data) {
+                    Ok(shape) => Some(shape),
+                    Err(e) => {
+                        log::warn!("Invalid mask shape in project: {e}");
+                        None
+                    }
+                },
+            )
+            .collect();
+        mask.op_code = self.mask_op_code.clone();
+        mask.gizmos_visible = !self.mask_gizmos_hidden;
+        mask.update_pods();
+    }
+
+    /// Get the selection layers of the model with `key`.
+    ///
+    /// The edits of the layers are already in the edits of the model.
+    pub fn selection_layers(&self, key: &str) -> Vec<app::SelectionLayer> {
+        self.selection_layers
+            .iter()
+            .map(|layer| layer.to_selection_layer(key.to_string()))
+            .collect()
+    }
+}
+
+/// The selection layer of a model in the project.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ProjectSelectionLayer {
+    /// The name.
+    pub name: String,
+
+    /// The indices of the selected Gaussians.
+    pub indices: Vec<u32>,
+
+    /// Whether the Gaussians are hidden.
+    pub hidden: bool,
+
+    /// Whether the color overrides the color instead of being a HSV adjustment.
+    pub override_color: bool,
+
+    /// The HSV adjustment or the override RGB color.
+    pub color: Vec3,
+
+    /// The contrast.
+    pub contrast: f32,
+
+    /// The exposure.
+    pub exposure: f32,
+
+    /// The gamma.
+    pub gamma: f32,
+
+    /// The alpha.
+    pub alpha: f32,
+
+    /// Whether the exposure and contrast are linked to the other linked layers.
+    pub linked: bool,
+}
+
+impl ProjectSelectionLayer {
+    /// Create a project selection layer from a selection layer.
+    pub fn new(layer: &app::SelectionLayer) -> Self {
+        let edit = &layer.edit;
+
+        Self {
+            name: layer.name.clone(),
+            indices: layer.indices.clone(),
+            hidden: edit.hidden,
+            override_color: matches!(edit.color, app::SelectionColorEdit::OverrideColor(..)),
+            color: edit.color.into(),
+            contrast: edit.contrast,
+            exposure: edit.exposure,
+            gamma: edit.gamma,
+            alpha: edit.alpha,
+            linked: layer.linked,
+        }
+    }
+
+    /// Convert to a selection layer of the model with `key`.
+    pub fn to_selection_layer(&self, key: String) -> app::SelectionLayer {
+        app::SelectionLayer {
+            name: self.name.clone(),
+            key,
+            indices: self.indices.clone(),
+            edit: app::SelectionEdit {
+                hidden: self.hidden,
+                color: match self.override_color {
+                    true => app::SelectionColorEdit::OverrideColor(self.color),
+                    false => app::SelectionColorEdit::Hsv(self.color),
+                },
+                contrast: self.contrast,
+                exposure: self.exposure,
+                gamma: self.gamma,
+                alpha: self.alpha,
+            },
+            linked: self.linked,
+        }
+    }
+}
+
+/// The camera in the project.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ProjectCamera {
+    /// The control.
+    pub control: ProjectCameraControl,
+
+    /// The movement speed.
+    pub speed: f32,
+
+    /// The movement scale, [`None`] in projects saved before it was added.
+    #[serde(default)]
+    pub scale: Option<f32>,
+
+    /// Whether the movement scale is fitted to the models, [`None`] in projects saved before it
+    /// was added.
+    #[serde(default)]
+    pub auto_scale: Option<bool>,
+
+    /// The rotation sensitivity.
+    pub sensitivity: f32,
+
+    /// The mouse-look smoothing, [`None`] in projects saved before it was added.
+    #[serde(default)]
+    pub smoothing: Option<f32>,
+
+    /// The limits of the orbit distance.
+    #[serde(default)]
+    pub distance_limits: app::CameraDistanceLimits,
+}
+
+impl ProjectCamera {
+    /// Create a project camera from a camera.
+    pub fn new(camera: &app::Camera) -> Self {
+        Self {
+            control: match &camera.control {
+                app::CameraControl::Orbit(control) => ProjectCameraControl::Orbit {
+                    target: control.target,
+                    pos: control.pos,
+                    z: control.z.clone(),
+                    vertical_fov: control.vertical_fov,
+                },
+                app::CameraControl::FirstPerson(control) => ProjectCameraControl::FirstPerson {
+                    pos: control.pos,
+                    pitch: control.pitch,
+                    yaw: control.yaw,
+                    z: control.z.clone(),
+                    vertical_fov: control.vertical_fov,
+                },
+            },
+            speed: camera.speed,
+            scale: Some(camera.scale),
+            auto_scale: Some(camera.auto_scale),
+            sensitivity: camera.sensitivity,
+            smoothing: Some(camera.smoothing),
+            distance_limits: camera.distance_limits,
+        }
+    }
+
+    /// Apply to the camera.
+    pub fn apply(&self, camera: &mut app::Camera) {
+        camera.control = match &self.control {
+            ProjectCameraControl::Orbit {
+                target,
+                pos,
+                z,
+                vertical_fov,
+            } => app::CameraControl::Orbit(app::CameraOrbitControl::new(
+                *target,
+                *pos,
+                z.clone(),
+                *vertical_fov,
+            )),
+            ProjectCameraControl::FirstPerson {
+                pos,
+                pitch,
+                yaw,
+                z,
+                vertical_fov,
+            } => {
+                let mut control = app::CameraFirstPersonControl::new(z.clone(), *vertical_fov);
+                control.pos = *pos;
+                control.pitch = *pitch;
+                control.yaw = *yaw;
+                app::CameraControl::FirstPerson(control)
+            }
+        };
+        camera.speed = self.speed;
+        if let Some(scale) = self.scale {
+            camera.scale = scale;
+        }
+        if let Some(auto_scale) = self.auto_scale {
+            camera.auto_scale = auto_scale;
+        }
+        camera.sensitivity = self.sensitivity;
+        if let Some(smoothing) = self.smoothing {
+            camera.smoothing = smoothing;
+        }
+        camera.distance_limits = self.distance_limits;
+    }
+}
+
+/// The camera control in the project.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub enum ProjectCameraControl {
+    /// The orbit.
+    Orbit {
+        target: Vec3,
+        pos: Vec3,
+        z: Range<f32>,
+        vertical_fov: f32,
+    },
+
+    /// The first person.
+    FirstPerson {
+        pos: Vec3,
+        pitch: f32,
+        yaw: f32,
+        z: Range<f32>,
+        vertical_fov: f32,
+    },
+}
+
+/// The Gaussian transform in the project.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ProjectGaussianTransform {
+    /// The size.
+    pub size: f32,
+
+    /// The point size in pixels, [`None`] in projects saved before it was added.
+    #[serde(default)]
+    pub point_size: Option<f32>,
+
+    /// The display mode, 0 for splat, 1 for ellipse, and 2 for point.
+    pub display_mode: u8,
+
+    /// The spherical harmonics degree.
+    pub sh_deg: u8,
+
+    /// Whether the SH0 is disabled.
+    pub no_sh0: bool,
+
+    /// The opacity multiplier, [`None`] in projects saved before it was added.
+    #[serde(default)]
+    pub opacity: Option<f32>,
+
+    /// The maximum scale, [`None`] if not clamped or saved before it was added.
+    #[serde(default)]
+    pub max_scale: Option<f32>,
+
+    /// The projection.
+    #[serde(default)]
+    pub projection: app::SplatProjection,
+}
+
+impl ProjectGaussianTransform {
+    /// Create a project Gaussian transform from a Gaussian transform.
+    pub fn new(transform: &app::GaussianSplattingGaussianTransform) -> Self {
+        Self {
+            size: transform.size,
+            point_size: Some(transform.point_size),
+            display_mode: transform.display_mode as u8,
+            sh_deg: transform.sh_deg.degree(),
+            no_sh0: transform.no_sh0,
+            opacity: Some(transform.opacity),
+            max_scale: transform.max_scale,
+            projection: transform.projection,
+        }
+    }
+
+    /// Apply to the Gaussian transform.
+    pub fn apply(&self, transform: &mut app::GaussianSplattingGaussianTransform) {
+        transform.size = self.size;
+        if let Some(point_size) = self.point_size {
+            transform.point_size = point_size;
+        }
+        transform.display_mode = match self.display_mode {
+            1 => gs::GaussianDisplayMode::Ellipse,
+            2 => gs::GaussianDisplayMode::Point,
+            _ => gs::GaussianDisplayMode::Splat,
+        };
+        transform.sh_deg = gs::GaussianShDegree::new(self.sh_deg).unwrap_or(transform.sh_deg);
+        transform.no_sh0 = self.no_sh0;
+        if let Some(opacity) = self.opacity {
+            transform.opacity = opacity;
+        }
+        transform.max_scale = self.max_scale;
+        transform.projection = self.projection;
+    }
+}
+
+/// The measurement hit pair in the project.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ProjectHitPair {
+    /// The label.
+    pub label: String,
+
+    /// Whether the hit pair is visible.
+    pub visible: bool,
+
+    /// The color.
+    pub color: [u8; 4],
+
+    /// The line width.
+    pub line_width: f32,
+
+    /// The unit of the line width.
+    #[serde(default)]
+    pub line_width_mode: app::MeasurementLineWidthMode,
+
+    /// The endpoint size.
+    pub endpoint_size: f32,
+
+    /// The kind.
+    #[serde(default)]
+    pub kind: app::MeasurementKind,
+
+    /// The hit method.
+    #[serde(default)]
+    pub hit_method: app::MeasurementHitMethod,
+
+    /// The positions of the used hits.
+    pub hits: Vec<Vec3>,
+}
+
+impl ProjectHitPair {
+    /// Create a project hit pair from a hit pair.
+    pub fn new(hit_pair: &app::MeasurementHitPair) -> Self {
+        Self {
+            label: hit_pair.label.clone(),
+            visible: hit_pair.visible,
+            color: hit_pair.color.to_array(),
+            line_width: hit_pair.line_width,
+            line_width_mode: hit_pair.line_width_mode,
+            endpoint_size: hit_pair.endpoint_size,
+            kind: hit_pair.kind,
+            hit_method: hit_pair.hit_method,
+            hits: hit_pair.used_hits().iter().map(|hit| hit.pos).collect(),
+        }
+    }
+
+    /// Convert to a hit pair.
+    pub fn to_hit_pair(&self) -> app::MeasurementHitPair {
+        let [r, g, b, a] = self.color;
+
+        let mut hit_pair = app::MeasurementHitPair::new(self.label.clone());
+        hit_pair.visible = self.visible;
+        hit_pair.color = egui::Color32::from_rgba_premultiplied(r, g, b, a);
+        hit_pair.line_width = self.line_width;
+        hit_pair.line_width_mode = self.line_width_mode;
+        hit_pair.endpoint_size = self.endpoint_size;
+        hit_pair.kind = self.kind;
+        hit_pair.hit_method = self.hit_method;
+        for (hit, pos) in hit_pair.hits.iter_mut().zip(&self.hits) {
+            hit.pos = *pos;
+        }
+        hit_pair
+    }
+}
+
+/// The state of restoring a project.
+#[derive(Debug)]
+pub struct ProjectRestore {
+    /// The models waiting to be loaded.
+    pub pending: VecDeque<ProjectModel>,
+
+    /// The key and the project model of the model currently loading.
+    ///
+    /// The mask and edits are applied when the loading is finished.
+    pub loading: Option<(String, ProjectModel)>,
+
+    /// The mapping from the keys in the project to the keys of the loaded models.
+    pub keys: HashMap<String, String>,
+
+    /// The key of the selected model in the project.
+    pub selected_model_key: String,
+
+    /// The models that could not be opened, with the reason.
+    pub missing: Vec<String>,
+}
+
+impl ProjectRestore {
+    /// Check if all the models are restored.
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty() && self.loading.is_none()
+    }
+}
@@ -0,0 +1,604 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+    mpsc,
+};
+
+use glam::*;
+
+/// A rigid transform, i.e. rotation followed by translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RigidTransform {
+    /// The rotation.
+    pub rotation: Quat,
+
+    /// The translation.
+    pub translation: Vec3,
+}
+
+impl RigidTransform {
+    /// The identity transform.
+    pub const IDENTITY: Self = Self {
+        rotation: Quat::IDENTITY,
+        translation: Vec3::ZERO,
+    };
+
+    /// Apply the transform to a point.
+    pub fn apply(&self, point: Vec3) -> Vec3 {
+        self.rotation * point + self.translation
+    }
+
+    /// Apply `other` after this transform.
+    pub fn then(&self, other: &Self) -> Self {
+        Self {
+            rotation: (other.rotation * self.rotation).normalize(),
+            translation: other.apply(self.translation),
+        }
+    }
+
+    /// Estimate the rigid transform that best maps the sources to the targets.
+    ///
+    /// This minimizes the sum of squared distances between the pairs.
+    ///
+    /// Returns [`None`] if there are less than 3 pairs.
+    pub fn fit(pairs: &[(Vec3, Vec3)]) -> Option<Self> {
+        let (rotation, source_centroid, target_centroid) = fit_rotation(pairs)?;
+        let translation = target_centroid - rotation * source_centroid;
+
+        Some(Self {
+            rotation: rotation.as_quat(),
+            translation: translation.as_vec3(),
+        })
+    }
+}
+
+impl Default for RigidTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// A similarity transform, i.e. uniform scale, rotation, then translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimilarityTransform {
+    /// The uniform scale.
+    pub scale: f32,
+
+    /// The rotation.
+    pub rotation: Quat,
+
+    /// The translation.
+    pub translation: Vec3,
+}
+
+impl SimilarityTransform {
+    /// Apply the transform to a point.
+    pub fn apply(&self, point: Vec3) -> Vec3 {
+        self.rotation * (point * self.scale) + self.translation
+    }
+
+    /// Estimate the similarity transform that best maps the sources to the targets.
+    ///
+    /// This minimizes the sum of squared distances between the pairs, the scale is fixed to 1
+    /// if `with_scale` is false.
+    ///
+    /// Returns [`None`] if there are less than 3 pairs.
+    pub fn fit(pairs: &[(Vec3, Vec3)], with_scale: bool) -> Option<Self> {
+        let (rotation, source_centroid, target_centroid) = fit_rotation(pairs)?;
+
+        let scale = match with_scale {
+            true => {
+                let (dot, source_norm_sq) =
+                    pairs
+                        .iter()
+                        .fold((0.0, 0.0), |(dot, source_norm_sq), (source, target)| {
+                            let source = rotation * (source.as_dvec3() - source_centroid);
+                            let target = target.as_dvec3() - target_centroid;
+                            (
+                                dot + source.dot(target),
+                                source_norm_sq + source.length_squared(),
+                            )
+                        });
+
+                if source_norm_sq <= f64::EPSILON {
+                    return None;
+                }
+
+                dot / source_norm_sq
+            }
+            false => 1.0,
+        };
+
+        let translation = target_centroid - rotation * (source_centroid * scale);
+
+        Some(Self {
+            scale: scale as f32,
+            rotation: rotation.as_quat(),
+            translation: translation.as_vec3(),
+        })
+    }
+
+    /// The RMS error of the pairs after applying the transform.
+    pub fn rms_error(&self, pairs: &[(Vec3, Vec3)]) -> f32 {
+        (pairs
+            .iter()
+            .map(|(source, target)| self.apply(*source).distance_squared(*target))
+            .sum::<f32>()
+            / pairs.len().max(1) as f32)
+            .sqrt()
+    }
+}
+
+/// Estimate the rotation that best maps the centered sources to the centered targets.
+///
+/// This uses the closed-form solution by Horn with unit quaternions.
+///
+/// Returns the rotation, the source centroid, and the target centroid, or [`None`] if there
+/// are less than 3 pairs.
+fn fit_rotation(pairs: &[(Vec3, Vec3)]) -> Option<(DQuat, DVec3, DVec3)> {
+    if pairs.len() < 3 {
+        return None;
+    }
+
+    let count = pairs.len() as f64;
+    let (source_centroid, target_centroid) = pairs.iter().fold(
+        (DVec3::ZERO, DVec3::ZERO),
+        |(source_sum, target_sum), (source, target)| {
+            (
+                source_sum + source.as_dvec3(),
+                target_sum + target.as_dvec3(),
+            )
+        },
+    );
+    let source_centroid = source_centroid / count;
+    let target_centroid = target_centroid / count;
+
+    // Cross covariance, s[i][j] = sum of source[i] * target[j]
+    let mut s = [[0.0f64; 3]; 3];
+    for (source, target) in pairs {
+        let source = source.as_dvec3() - source_centroid;
+        let target = target.as_dvec3() - target_centroid;
+        for (i, row) in s.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value += source[i] * target[j];
+            }
+        }
+    }
+
+    let [[sxx, sxy, sxz], [syx, syy, syz], [szx, szy, szz]] = s;
+    let n = [
+        [sxx + syy + szz, syz - szy, szx - sxz, sxy - syx],
+        [syz - szy, sxx - syy - szz, sxy + syx, szx + sxz],
+        [szx - sxz, sxy + syx, -sxx + syy - szz, syz + szy],
+        [sxy - syx, szx + sxz, syz + szy, -sxx - syy + szz],
+    ];
+
+    let [w, x, y, z] = max_eigenvector(n);
+    let rotation = DQuat::from_xyzw(x, y, z, w).normalize();
+
+    Some((rotation, source_centroid, target_centroid))
+}
+
+/// The eigenvector of the largest eigenvalue of a symmetric 4x4 matrix.
+///
+/// This uses the cyclic Jacobi eigenvalue algorithm.
+fn max_eigenvector(mut a: [[f64; 4]; 4]) -> [f64; 4] {
+    let mut v = [[0.0f64; 4]; 4];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for _ in 0..50 {
+        let off_diagonal = (0..4)
+            .flat_map(|i| (0..4).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum::<f64>();
+        if off_diagonal < 1e-20 {
+            break;
+        }
+
+        for p in 0..4 {
+            for q in (p + 1)..4 {
+                if a[p][q].abs() < 1e-30 {
+                    continue;
+                }
+
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                for k in 0..4 {
+                    let (apk, aqk) = (a[p][k], a[q][k]);
+                    a[p][k] = c * apk - s * aqk;
+                    a[q][k] = s * apk + c * aqk;
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let max = (0..4)
+        .max_by(|i, j| a[*i][*i].total_cmp(&a[*j][*j]))
+        .expect("max eigenvalue");
+
+    [v[0][max], v[1][max], v[2][max], v[3][max]]
+}
+
+/// A k-d tree for nearest neighbor search of points.
+#[derive(Debug)]
+pub struct KdTree {
+    /// The points, ordered such that each subtree is a contiguous range.
+    points: Vec<Vec3>,
+}
+
+impl KdTree {
+    /// Create a new k-d tree.
+    pub fn new(mut points: Vec<Vec3>) -> Self {
+        Self::build(&mut points, 0);
+        Self { points }
+    }
+
+    /// Find the nearest point to the query.
+    ///
+    /// Returns the nearest point and its squared distance.
+    pub fn nearest(&self, query: Vec3) -> Option<(Vec3, f32)> {
+        let mut best = None;
+        Self::search(&self.points, 0, query, &mut best);
+        best
+    }
+
+    /// Build the tree in place.
+    fn build(points: &mut [Vec3], depth: usize) {
+        if points.len() <= 1 {
+            return;
+        }
+
+        let axis = depth % 3;
+        let mid = points.len() / 2;
+        points.select_nth_unstable_by(mid, |a, b| a[axis].total_cmp(&b[axis]));
+
+        let (left, right) = points.split_at_mut(mid);
+        Self::build(left, depth + 1);
+        Self::build(&mut right[1..], depth + 1);
+    }
+
+    /// Search the nearest point recursively.
+    fn search(points: &[Vec3], depth: usize, query: Vec3, best: &mut Option<(Vec3, f32)>) {
+        if points.is_empty() {
+            return;
+        }
+
+        let axis = depth % 3;
+        let mid = points.len() / 2;
+        let point = points[mid];
+
+        let distance_sq = point.distance_squared(query);
+        if best.is_none_or(|(_, best_distance_sq)| distance_sq < best_distance_sq) {
+            *best = Some((point, distance_sq));
+        }
+
+        let diff = query[axis] - point[axis];
+        let (near, far) = match diff < 0.0 {
+            true => (&points[..mid], &points[mid + 1..]),
+            false => (&points[mid + 1..], &points[..mid]),
+        };
+
+        Self::search(near, depth + 1, query, best);
+        if best.is_none_or(|(_, best_distance_sq)| diff * diff < best_distance_sq) {
+            Self::search(far, depth + 1, query, best);
+        }
+    }
+}
+
+/// The ICP (iterative closest point) settings.
+#[derive(Debug, Clone)]
+pub struct IcpSettings {
+    /// The maximum number of iterations.
+    pub max_iterations: usize,
+
+    /// The convergence threshold of the change in RMS error between iterations.
+    pub threshold: f32,
+
+    /// The number of sampled source points.
+    pub sample_count: usize,
+
+    /// The fraction of the closest pairs used in each iteration, rejecting the outliers.
+    pub inlier_ratio: f32,
+}
+
+impl IcpSettings {
+    /// Create a new ICP settings.
+    pub fn new() -> Self {
+        Self {
+            max_iterations: 50,
+            threshold: 1e-5,
+            sample_count: 5000,
+            inlier_ratio: 0.9,
+        }
+    }
+}
+
+impl Default for IcpSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The ICP progress.
+#[derive(Debug, Clone)]
+pub enum IcpProgress {
+    /// An iteration is done.
+    Iteration {
+        /// The iteration index.
+        iteration: usize,
+
+        /// The RMS error of the inlier pairs.
+        error: f32,
+    },
+
+    /// The ICP is finished.
+    Done(RigidTransform),
+
+    /// The ICP failed.
+    Failed(String),
+}
+
+/// Run ICP to estimate the rigid transform from the source points to the reference points.
+///
+/// The progress is sent to `tx`, ending with [`IcpProgress::Done`] or [`IcpProgress::Failed`].
+/// The ICP stops early if `cancel` is set.
+///
+/// On the web, this yields between iterations so the UI keeps updating.
+pub async fn icp(
+    source: &[Vec3],
+    reference: Vec<Vec3>,
+    settings: &IcpSettings,
+    cancel: Arc<AtomicBool>,
+    tx: mpsc::Sender<IcpProgress>,
+) {
+    let send = |progress| {
+        if let Err(e) = tx.send(progress) {
+            log::error!("Error sending ICP progress: {e}");
+        }
+    };
+
+    if source.is_empty() || reference.is_empty() {
+        send(IcpProgress::Failed("No points to align".to_string()));
+        return;
+    }
+
+    let step = source.len().div_ceil(settings.sample_count.max(1));
+    let source = source.iter().step_by(step).copied().collect::<Vec<_>>();
+    let tree = KdTree::new(reference);
+
+    let mut transform = RigidTransform::IDENTITY;
+    let mut prev_error = f32::INFINITY;
+    for iteration in 0..settings.max_iterations {
+        if cancel.load(Ordering::Relaxed) {
+            send(IcpProgress::Failed("Cancelled".to_string()));
+            return;
+        }
+
+        let mut pairs = source
+            .iter()
+            .filter_map(|point| {
+                let point = transform.apply(*point);
+                tree.nearest(point)
+                    .map(|(nearest, distance_sq)| (point, nearest, distance_sq))
+            })
+            .collect::<Vec<_>>();
+
+        let inlier_count =
+            ((pairs.len() as f32 * settings.inlier_ratio).ceil() as usize).clamp(1, pairs.len());
+        pairs.select_nth_unstable_by(inlier_count - 1, |a, b| a.2.total_cmp(&b.2));
+        pairs.truncate(inlier_count);
+
+        let error = (pairs.iter().map(|(.., d)| d).sum::<f32>() / pairs.len() as f32).sqrt();
+        send(IcpProgress::Iteration { iteration, error });
+
+        let Some(delta) = RigidTransform::fit(
+            &pairs
+                .into_iter()
+                .map(|(point, nearest, _)| (point, nearest))
+                .collect::<Vec<_>>(),
+        ) else {
+            send(IcpProgress::Failed(
+                "Not enough points to align".to_string(),
+            ));
+            return;
+        };
+
+        transform = transform.then(&delta);
+
+        if (prev_error - error).abs() < settings.threshold {
+            break;
+        }
+        prev_error = error;
+
+        // Yield so the UI shows the progress and handles the cancel
+        #[cfg(target_arch = "wasm32")]
+        gloo_timers::future::TimeoutFuture::new(0).await;
+    }
+
+    send(IcpProgress::Done(transform));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The tolerance of the comparisons.
+    const EPSILON: f32 = 1e-4;
+
+    /// Points spread in all directions, not on a plane.
+    fn points() -> Vec<Vec3> {
+        (0..64)
+            .map(|i| {
+                let i = i as f32;
+                Vec3::new(
+                    (i * 0.73).sin() * 3.0,
+                    (i * 1.31).cos() * 2.0,
+                    (i * 0.37).sin() * (i * 0.11).cos() * 4.0,
+                )
+            })
+            .collect()
+    }
+
+    /// A rotation and translation that is not aligned with any axis.
+    fn transform() -> RigidTransform {
+        RigidTransform {
+            rotation: Quat::from_euler(EulerRot::XYZ, 0.4, -0.9, 1.3),
+            translation: Vec3::new(1.5, -2.0, 0.5),
+        }
+    }
+
+    #[test]
+    fn rigid_fit_recovers_transform() {
+        let expected = transform();
+        let pairs = points()
+            .into_iter()
+            .map(|point| (point, expected.apply(point)))
+            .collect::<Vec<_>>();
+
+        let fitted = RigidTransform::fit(&pairs).expect("fit");
+
+        assert!(
+            fitted.rotation.dot(expected.rotation).abs() > 1.0 - EPSILON,
+            "{fitted:?} != {expected:?}"
+        );
+        assert!(
+            fitted
+                .translation
+                .abs_diff_eq(expected.translation, EPSILON),
+            "{fitted:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn rigid_fit_needs_three_pairs() {
+        assert!(RigidTransform::fit(&[(Vec3::X, Vec3::Y), (Vec3::Y, Vec3::Z)]).is_none());
+    }
+
+    #[test]
+    fn similarity_fit_recovers_scale() {
+        let rigid = transform();
+        let expected = SimilarityTransform {
+            scale: 2.5,
+            rotation: rigid.rotation,
+            translation: rigid.translation,
+        };
+        let pairs = points()
+            .into_iter()
+            .map(|point| (point, expected.apply(point)))
+            .collect::<Vec<_>>();
+
+        let fitted = SimilarityTransform::fit(&pairs, true).expect("fit");
+
+        assert!(
+            (fitted.scale - expected.scale).abs() < EPSILON,
+            "{fitted:?}"
+        );
+        assert!(fitted.rms_error(&pairs) < EPSILON, "{fitted:?}");
+    }
+
+    #[test]
+    fn max_eigenvector_of_symmetric_matrix() {
+        // Rotate diag(1, 5, -2, 3) by an orthogonal basis, the largest eigenvalue is 5
+        let basis = DMat4::from_quat(DQuat::from_euler(EulerRot::XYZ, 0.2, 0.5, -0.8))
+            * DMat4::from_cols_array(&[
+                0.5, 0.5, 0.5, 0.5, //
+                0.5, -0.5, 0.5, -0.5, //
+                0.5, 0.5, -0.5, -0.5, //
+                0.5, -0.5, -0.5, 0.5,
+            ]);
+        let matrix =
+            basis * DMat4::from_diagonal(DVec4::new(1.0, 5.0, -2.0, 3.0)) * basis.transpose();
+        let expected = basis.col(1);
+
+        let vector = DVec4::from_array(max_eigenvector(matrix.transpose().to_cols_array_2d()));
+
+        assert!((vector.length() - 1.0).abs() < 1e-9, "{vector}");
+        assert!(
+            vector.dot(expected).abs() > 1.0 - 1e-9,
+            "{vector} != {expected}"
+        );
+    }
+
+    #[test]
+    fn kd_tree_nearest_matches_brute_force() {
+        let reference = points();
+        let tree = KdTree::new(reference.clone());
+
+        for query in points()
+            .into_iter()
+            .map(|point| point * 1.3 + Vec3::splat(0.2))
+        {
+            let expected = reference
+                .iter()
+                .map(|point| point.distance_squared(query))
+                .min_by(f32::total_cmp)
+                .expect("nearest");
+
+            let (nearest, distance_sq) = tree.nearest(query).expect("nearest");
+
+            assert_eq!(distance_sq, expected, "{query}");
+            assert_eq!(nearest.distance_squared(query), distance_sq, "{query}");
+        }
+    }
+
+    #[test]
+    fn icp_recovers_small_transform() {
+        let reference = points();
+        let expected = RigidTransform {
+            rotation: Quat::from_euler(EulerRot::XYZ, 0.02, -0.03, 0.01),
+            translation: Vec3::new(0.05, -0.02, 0.03),
+        };
+        let inverse = expected.rotation.inverse();
+        let source = reference
+            .iter()
+            .map(|point| inverse * (*point - expected.translation))
+            .collect::<Vec<_>>();
+        let settings = IcpSettings {
+            inlier_ratio: 1.0,
+            ..IcpSettings::new()
+        };
+        let (tx, rx) = mpsc::channel();
+
+        futures::executor::block_on(icp(
+            &source,
+            reference,
+            &settings,
+            Arc::new(AtomicBool::new(false)),
+            tx,
+        ));
+
+        let Some(IcpProgress::Done(fitted)) = rx.try_iter().last() else {
+            panic!("ICP not done");
+        };
+        assert!(
+            fitted.rotation.dot(expected.rotation).abs() > 1.0 - EPSILON,
+            "{fitted:?} != {expected:?}"
+        );
+        assert!(
+            fitted
+                .translation
+                .abs_diff_eq(expected.translation, EPSILON),
+            "{fitted:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn kd_tree_empty() {
+        assert!(KdTree::new(Vec::new()).nearest(Vec3::ZERO).is_none());
+    }
+}
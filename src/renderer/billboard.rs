@@ -0,0 +1,91 @@
+use eframe::wgpu;
+use wgpu_3dgs_viewer::{self as gs, GaussianCov3dConfig, GaussianShConfig};
+
+/// The renderer for the billboard projection of the Gaussians.
+///
+/// This is the [`gs::Renderer`] with a vertex shader drawing each Gaussian as a circle facing
+/// the camera, see [`crate::app::SplatProjection::Billboard`]. It uses the same bind group
+/// layout, so the renderer bind groups of the models are drawn with it directly.
+#[derive(Debug)]
+pub struct BillboardRenderer {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl BillboardRenderer {
+    /// Create a new billboard renderer.
+    pub fn new<G: gs::GaussianPod>(
+        device: &wgpu::Device,
+        texture_format: wgpu::TextureFormat,
+        depth_stencil: Option<wgpu::DepthStencilState>,
+    ) -> Self {
+        log::debug!("Creating billboard renderer bind group layout");
+        let bind_group_layout =
+            device.create_bind_group_layout(&gs::Renderer::BIND_GROUP_LAYOUT_DESCRIPTOR);
+
+        log::debug!("Creating billboard renderer pipeline");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Billboard Renderer Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Billboard Renderer Shader"),
+            source: wgpu::ShaderSource::Wgsl(Self::shader_source::<G>().into()),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Billboard Renderer Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vert_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("frag_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        log::info!("Billboard renderer created");
+
+        Self { pipeline }
+    }
+
+    /// Render the Gaussians of a model with a [`wgpu::RenderPass`].
+    ///
+    /// `bind_group` is the renderer bind group of the model, see
+    /// [`gs::MultiModelViewerBindGroups::renderer`].
+    pub fn render_with_pass(
+        &self,
+        pass: &mut wgpu::RenderPass<'_>,
+        bind_group: &wgpu::BindGroup,
+        indirect_args: &gs::IndirectArgsBuffer,
+    ) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.draw_indirect(indirect_args.buffer(), 0);
+    }
+
+    /// Get the shader source for the Gaussian configs.
+    fn shader_source<G: gs::GaussianPod>() -> String {
+        include_str!("../shader/billboard.wgsl")
+            .replace("{{gaussian_sh_field}}", G::ShConfig::sh_field())
+            .replace("{{gaussian_sh_unpack}}", G::ShConfig::sh_unpack())
+            .replace("{{gaussian_cov3d_field}}", G::Cov3dConfig::cov3d_field())
+            .replace("{{gaussian_cov3d_unpack}}", G::Cov3dConfig::cov3d_unpack())
+    }
+}
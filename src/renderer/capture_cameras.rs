@@ -0,0 +1,219 @@
+use eframe::wgpu::{self, util::DeviceExt};
+use glam::*;
+use wgpu_3dgs_viewer as gs;
+
+use crate::app;
+
+/// The renderer for capture camera markers.
+#[derive(Debug)]
+pub struct CaptureCameras {
+    markers_buffer: wgpu::Buffer,
+    /// The number of markers in the markers buffer.
+    marker_count: u32,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl CaptureCameras {
+    /// The number of vertices of a marker.
+    const VERTEX_COUNT: u32 = 22;
+
+    /// Create a new capture camera markers renderer.
+    pub fn new(
+        device: &wgpu::Device,
+        texture_format: wgpu::TextureFormat,
+        camera: &gs::CameraBuffer,
+    ) -> Self {
+        log::debug!("Creating capture cameras markers buffer");
+        let markers_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture Cameras Markers Buffer"),
+            size: std::mem::size_of::<Marker>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        log::debug!("Creating capture cameras bind group layout");
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Capture Cameras Bind Group Layout"),
+            entries: &[
+                // The markers storage buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // The camera uniform buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group =
+            Self::create_bind_group(device, &bind_group_layout, &markers_buffer, camera);
+
+        log::debug!("Creating capture cameras pipeline");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Capture Cameras Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Capture Cameras Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shader/capture_cameras.wgsl").into()),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Capture Cameras Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vert_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("frag_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        log::info!("Capture cameras renderer created");
+
+        Self {
+            markers_buffer,
+            marker_count: 0,
+            bind_group_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    /// Update the markers buffer.
+    pub fn update_markers(
+        &mut self,
+        device: &wgpu::Device,
+        markers: &[app::CaptureCameraMarker],
+        camera: &gs::CameraBuffer,
+    ) {
+        let markers = markers.iter().map(Marker::new).collect::<Vec<_>>();
+        self.marker_count = markers.len() as u32;
+
+        if markers.is_empty() {
+            return;
+        }
+
+        self.markers_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Capture Cameras Markers Buffer"),
+            contents: bytemuck::cast_slice(&markers),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        self.bind_group = Self::create_bind_group(
+            device,
+            &self.bind_group_layout,
+            &self.markers_buffer,
+            camera,
+        );
+    }
+
+    /// Render the markers.
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass) {
+        if self.marker_count == 0 {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..Self::VERTEX_COUNT, 0..self.marker_count);
+    }
+
+    /// Create the bind group.
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        markers_buffer: &wgpu::Buffer,
+        camera: &gs::CameraBuffer,
+    ) -> wgpu::BindGroup {
+        log::debug!("Creating capture cameras bind group");
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Capture Cameras Bind Group"),
+            layout,
+            entries: &[
+                // The markers storage buffer.
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: markers_buffer.as_entire_binding(),
+                },
+                // The camera uniform buffer.
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: camera.buffer().as_entire_binding(),
+                },
+            ],
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Marker {
+    pos: Vec3,
+    color: U8Vec4,
+    right: Vec3,
+    _padding_0: f32,
+    up: Vec3,
+    _padding_1: f32,
+    forward: Vec3,
+    _padding_2: f32,
+}
+
+impl Marker {
+    /// Create a marker.
+    fn new(marker: &app::CaptureCameraMarker) -> Self {
+        Self {
+            pos: marker.pos,
+            color: U8Vec4::from_array(marker.color.to_array()),
+            right: marker.right,
+            _padding_0: 0.0,
+            up: marker.up,
+            _padding_1: 0.0,
+            forward: marker.forward,
+            _padding_2: 0.0,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use eframe::wgpu::{self, util::DeviceExt};
+use wgpu_3dgs_viewer as gs;
+
+/// The compute pass for restoring the Gaussian edits from a project.
+#[derive(Debug)]
+pub struct EditRestore {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl EditRestore {
+    /// The workgroup size.
+    const WORKGROUP_SIZE: u32 = 256;
+
+    /// Create a new edit restore compute pass.
+    pub fn new(device: &wgpu::Device) -> Self {
+        log::debug!("Creating edit restore bind group layout");
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Edit Restore Bind Group Layout"),
+            entries: &[
+                // The restored edits storage buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // The Gaussians edit storage buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        log::debug!("Creating edit restore pipeline");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Edit Restore Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Edit Restore Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shader/edit_restore.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Edit Restore Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        log::info!("Edit restore compute pass created");
+
+        Self {
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    /// Convert the downloaded edits to the edits for [`EditRestore::restore`].
+    ///
+    /// Unedited Gaussians are skipped.
+    pub fn sparse<'a>(edits: impl IntoIterator<Item = &'a gs::GaussianEditPod>) -> Vec<[u32; 3]> {
+        edits
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, edit)| match bytemuck::cast::<_, [u32; 2]>(*edit) {
+                [0, 0] => None,
+                [a, b] => Some([i as u32, a, b]),
+            })
+            .collect()
+    }
+
+    /// Write the edits into the Gaussians edit buffer.
+    ///
+    /// Each edit is the index of the Gaussian followed by the two words of
+    /// [`gs::GaussianEditPod`].
+    pub fn restore(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        gaussians_edit: &gs::GaussiansEditBuffer,
+        edits: &[[u32; 3]],
+    ) {
+        if edits.is_empty() {
+            return;
+        }
+
+        let edits_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Edit Restore Buffer"),
+            contents: bytemuck::cast_slice(edits),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Edit Restore Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                // The restored edits storage buffer.
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: edits_buffer.as_entire_binding(),
+                },
+                // The Gaussians edit storage buffer.
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: gaussians_edit.buffer().as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Edit Restore Encoder"),
+        });
+
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Edit Restore Compute Pass"),
+                timestamp_writes: None,
+            });
+
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((edits.len() as u32).div_ceil(Self::WORKGROUP_SIZE), 1, 1);
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+}
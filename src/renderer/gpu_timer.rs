@@ -0,0 +1,201 @@
+use std::sync::{Arc, Mutex};
+
+use eframe::wgpu;
+use strum::EnumCount;
+
+use crate::app;
+
+/// The timestamp queries for measuring the GPU time of the passes.
+///
+/// A timestamp is written at the start and end of each [`app::GpuPass`]. The timestamps of a
+/// frame are resolved at the start of the next frame, since the render pass is submitted by
+/// eframe, and read back without waiting.
+#[derive(Debug)]
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// The state of the readback buffer, shared with the map callback.
+    readback: Arc<Mutex<GpuTimerReadback>>,
+    /// The nanoseconds per timestamp tick.
+    period: f32,
+    /// Whether timestamps can be written inside passes, for [`app::GpuPass::Render`].
+    inside_passes: bool,
+    /// The timings of the last read frame.
+    timings: [Option<f32>; app::GpuPass::COUNT],
+}
+
+/// The state of the readback buffer of [`GpuTimer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GpuTimerReadback {
+    /// The buffer is free.
+    Idle,
+
+    /// The timestamps are copied, waiting for the submission to map.
+    Copied,
+
+    /// The buffer is being mapped.
+    Mapping,
+
+    /// The buffer is mapped and can be read.
+    Mapped,
+}
+
+impl GpuTimer {
+    /// The number of timestamps, the start and end of each pass.
+    const COUNT: u32 = app::GpuPass::COUNT as u32 * 2;
+
+    /// The size of the resolved timestamps.
+    const SIZE: wgpu::BufferAddress =
+        Self::COUNT as wgpu::BufferAddress * wgpu::QUERY_SIZE as wgpu::BufferAddress;
+
+    /// The required features.
+    pub const FEATURES: wgpu::Features =
+        wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+
+    /// Create a new GPU timer.
+    ///
+    /// Returns [`None`] if the device does not have [`GpuTimer::FEATURES`].
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(Self::FEATURES) {
+            log::info!("GPU timer is not supported by the device");
+            return None;
+        }
+
+        log::debug!("Creating GPU timer query set");
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("GPU Timer Query Set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: Self::COUNT,
+        });
+
+        log::debug!("Creating GPU timer buffers");
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Resolve Buffer"),
+            size: Self::SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Readback Buffer"),
+            size: Self::SIZE,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        log::info!("GPU timer created");
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            readback: Arc::new(Mutex::new(GpuTimerReadback::Idle)),
+            period: queue.get_timestamp_period(),
+            inside_passes: device
+                .features()
+                .contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES),
+            timings: [None; app::GpuPass::COUNT],
+        })
+    }
+
+    /// Check whether a pass can be measured with the features of the device.
+    pub fn is_supported(features: wgpu::Features, pass: app::GpuPass) -> bool {
+        features.contains(Self::FEATURES)
+            && (pass != app::GpuPass::Render
+                || features.contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES))
+    }
+
+    /// Get the timings in milliseconds of the last read frame.
+    pub fn timings(&self) -> [Option<f32>; app::GpuPass::COUNT] {
+        self.timings
+    }
+
+    /// Write the start or end timestamp of a pass in an encoder.
+    pub fn write(&self, encoder: &mut wgpu::CommandEncoder, pass: app::GpuPass, end: bool) {
+        encoder.write_timestamp(&self.query_set, Self::index(pass, end));
+    }
+
+    /// Write the start or end timestamp of a pass in a render pass.
+    ///
+    /// This does nothing if timestamps cannot be written inside passes.
+    pub fn write_in_pass(&self, render_pass: &mut wgpu::RenderPass, pass: app::GpuPass, end: bool) {
+        if self.inside_passes {
+            render_pass.write_timestamp(&self.query_set, Self::index(pass, end));
+        }
+    }
+
+    /// Read the timestamps of the previous frame if they are mapped, then resolve and copy the
+    /// timestamps written since if the readback buffer is free.
+    ///
+    /// This must be called before writing any timestamp of the frame, and followed by
+    /// [`GpuTimer::map`] after the encoder is submitted.
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let mut readback = self.readback.lock().expect("readback");
+
+        if *readback == GpuTimerReadback::Mapped {
+            let timestamps: Vec<u64> = bytemuck::allocation::pod_collect_to_vec(
+                &self.readback_buffer.slice(..).get_mapped_range(),
+            );
+            self.readback_buffer.unmap();
+
+            self.timings = std::array::from_fn(|i| {
+                match (timestamps[i * 2], timestamps[i * 2 + 1]) {
+                    // Unwritten timestamps are resolved as zero
+                    (start, end) if start > 0 && end >= start => {
+                        Some((end - start) as f32 * self.period / 1e6)
+                    }
+                    _ => None,
+                }
+            });
+            if !self.inside_passes {
+                self.timings[app::GpuPass::Render as usize] = None;
+            }
+
+            *readback = GpuTimerReadback::Idle;
+        }
+
+        if *readback == GpuTimerReadback::Idle {
+            encoder.resolve_query_set(&self.query_set, 0..Self::COUNT, &self.resolve_buffer, 0);
+            encoder.copy_buffer_to_buffer(
+                &self.resolve_buffer,
+                0,
+                &self.readback_buffer,
+                0,
+                Self::SIZE,
+            );
+
+            *readback = GpuTimerReadback::Copied;
+        }
+    }
+
+    /// Map the copied timestamps after the encoder of [`GpuTimer::resolve`] is submitted.
+    pub fn map(&self) {
+        let mut readback = self.readback.lock().expect("readback");
+        if *readback != GpuTimerReadback::Copied {
+            return;
+        }
+
+        *readback = GpuTimerReadback::Mapping;
+        std::mem::drop(readback);
+
+        let state = self.readback.clone();
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let mut readback = state.lock().expect("readback");
+                match result {
+                    Ok(()) => *readback = GpuTimerReadback::Mapped,
+                    Err(e) => {
+                        log::error!("Error occurred while mapping GPU timestamps: {e:?}");
+                        *readback = GpuTimerReadback::Idle;
+                    }
+                }
+            });
+    }
+
+    /// Get the query index of the start or end of a pass.
+    fn index(pass: app::GpuPass, end: bool) -> u32 {
+        pass as u32 * 2 + end as u32
+    }
+}
@@ -8,9 +8,12 @@ use crate::app;
 #[derive(Debug)]
 pub struct Measurement {
     hit_pairs_buffer: wgpu::Buffer,
+    /// The number of line segments in the hit pairs buffer.
+    segment_count: u32,
     bind_group_layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
-    pipeline: wgpu::RenderPipeline,
+    /// The line and endpoint pipelines, with depth test and always on top.
+    pipelines: [[wgpu::RenderPipeline; 2]; 2],
 }
 
 impl Measurement {
@@ -23,7 +26,7 @@ impl Measurement {
         log::debug!("Creating measurement hit pairs buffer");
         let hit_pairs_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Measurement Hit Pairs Buffer"),
-            size: std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+            size: std::mem::size_of::<HitPair>() as wgpu::BufferAddress,
             usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -87,64 +90,59 @@ impl Measurement {
             source: wgpu::ShaderSource::Wgsl(include_str!("../shader/measurement.wgsl").into()),
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Measurement Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vert_main"),
-                buffers: &[],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("frag_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: texture_format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
+        let pipelines =
+            [wgpu::CompareFunction::Less, wgpu::CompareFunction::Always].map(|depth_compare| {
+                [
+                    ("vert_main", "frag_main"),
+                    ("vert_endpoint_main", "frag_endpoint_main"),
+                ]
+                .map(|(vert_entry_point, frag_entry_point)| {
+                    Self::create_pipeline(
+                        device,
+                        texture_format,
+                        &pipeline_layout,
+                        &shader,
+                        vert_entry_point,
+                        frag_entry_point,
+                        depth_compare,
+                    )
+                })
+            });
 
         log::info!("Measurement renderer created");
 
         Self {
             hit_pairs_buffer,
+            segment_count: 0,
             bind_group_layout,
             bind_group,
-            pipeline,
+            pipelines,
         }
     }
 
     /// Update the hit pairs buffer.
+    ///
+    /// Each hit pair is drawn as its [`app::MeasurementHitPair::segments`].
     pub fn update_hit_pairs(
         &mut self,
         device: &wgpu::Device,
         hit_pairs: &[app::MeasurementHitPair],
         camera: &gs::CameraBuffer,
     ) {
+        let segments = hit_pairs
+            .iter()
+            .flat_map(|hit_pair| {
+                hit_pair
+                    .segments()
+                    .into_iter()
+                    .map(|segment| HitPair::new(hit_pair, segment))
+            })
+            .collect::<Vec<_>>();
+        self.segment_count = segments.len() as u32;
+
         self.hit_pairs_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Measurement Hit Pairs Buffer"),
-            contents: bytemuck::cast_slice(
-                &hit_pairs
-                    .iter()
-                    .cloned()
-                    .map(HitPair::from)
-                    .collect::<Vec<_>>(),
-            ),
+            contents: bytemuck::cast_slice(&segments),
             usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
         });
 
@@ -167,10 +165,62 @@ impl Measurement {
     }
 
     /// Render the measurement.
-    pub fn render(&self, render_pass: &mut wgpu::RenderPass, hit_pair_count: u32) {
-        render_pass.set_pipeline(&self.pipeline);
+    ///
+    /// If `always_on_top` is true, the measurement is not occluded by the Gaussians, which
+    /// requires it to be rendered after the Gaussians.
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass, always_on_top: bool) {
+        let [pipeline, endpoint_pipeline] = &self.pipelines[always_on_top as usize];
+
+        render_pass.set_pipeline(pipeline);
         render_pass.set_bind_group(0, &self.bind_group, &[]);
-        render_pass.draw(0..6, 0..hit_pair_count);
+        render_pass.draw(0..6, 0..self.segment_count);
+
+        render_pass.set_pipeline(endpoint_pipeline);
+        render_pass.draw(0..6, 0..self.segment_count * 2);
+    }
+
+    /// Create a render pipeline.
+    fn create_pipeline(
+        device: &wgpu::Device,
+        texture_format: wgpu::TextureFormat,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        vert_entry_point: &str,
+        frag_entry_point: &str,
+        depth_compare: wgpu::CompareFunction,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Measurement Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some(vert_entry_point),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some(frag_entry_point),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                // Always on top neither tests nor writes depth, so it does not occlude anything
+                depth_write_enabled: depth_compare != wgpu::CompareFunction::Always,
+                depth_compare,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
     }
 }
 
@@ -181,15 +231,25 @@ struct HitPair {
     color: U8Vec4,
     hit_1: Vec3,
     line_width: f32,
+    endpoint_size: f32,
+    line_width_mode: u32,
+    _padding: [f32; 2],
 }
 
-impl From<app::MeasurementHitPair> for HitPair {
-    fn from(hit_pair: app::MeasurementHitPair) -> Self {
+impl HitPair {
+    /// Create a line segment of a hit pair.
+    fn new(hit_pair: &app::MeasurementHitPair, [hit_0, hit_1]: [Vec3; 2]) -> Self {
         Self {
-            hit_0: hit_pair.hits[0].pos,
+            hit_0,
             color: U8Vec4::from_array(hit_pair.color.to_array()),
-            hit_1: hit_pair.hits[1].pos,
+            hit_1,
             line_width: hit_pair.line_width,
+            endpoint_size: hit_pair.endpoint_size,
+            line_width_mode: match hit_pair.line_width_mode {
+                app::MeasurementLineWidthMode::Screen => 0,
+                app::MeasurementLineWidthMode::World => 1,
+            },
+            _padding: [0.0; 2],
         }
     }
 }
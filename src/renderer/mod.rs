@@ -1,3 +1,23 @@
+mod billboard;
+mod capture_cameras;
+mod edit_restore;
+mod gpu_timer;
 mod measurement;
+mod model_bounds;
+mod render_limit;
+mod selection_depth;
+mod selection_download;
+mod selection_mask;
+mod tone_mapping;
 
+pub use billboard::*;
+pub use capture_cameras::*;
+pub use edit_restore::*;
+pub use gpu_timer::*;
 pub use measurement::*;
+pub use model_bounds::*;
+pub use render_limit::*;
+pub use selection_depth::*;
+pub use selection_download::*;
+pub use selection_mask::*;
+pub use tone_mapping::*;
@@ -0,0 +1,193 @@
+use eframe::wgpu::{self, util::DeviceExt};
+use glam::*;
+use wgpu_3dgs_viewer as gs;
+
+/// The compute pass for computing the bounding box of a model.
+///
+/// The Gaussian positions are reduced to the minimum and maximum on the GPU, so large models do
+/// not need to be scanned on the CPU. The bounds are in model space.
+#[derive(Debug)]
+pub struct ModelBounds {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl ModelBounds {
+    /// The workgroup size.
+    const WORKGROUP_SIZE: u32 = 256;
+
+    /// The size of the bounds buffer, the minimum and maximum of each axis.
+    const BOUNDS_SIZE: wgpu::BufferAddress =
+        (6 * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+
+    /// Create a new model bounds compute pass.
+    pub fn new(device: &wgpu::Device) -> Self {
+        log::debug!("Creating model bounds bind group layout");
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Model Bounds Bind Group Layout"),
+            entries: &[
+                // The count and stride uniform buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // The Gaussians storage buffer.
+                storage(1, true),
+                // The bounds storage buffer.
+                storage(2, false),
+            ],
+        });
+
+        log::debug!("Creating model bounds pipeline");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Model Bounds Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Model Bounds Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shader/model_bounds.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Model Bounds Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        log::info!("Model bounds compute pass created");
+
+        Self {
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    /// Compute the bounds of the Gaussians to a mappable buffer.
+    ///
+    /// The returned buffer can be read with [`ModelBounds::map`].
+    pub fn prepare<G: gs::GaussianPod>(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        gaussians: &gs::GaussiansBuffer<G>,
+    ) -> wgpu::Buffer {
+        let count = gaussians.len() as u32;
+        let stride = (std::mem::size_of::<G>() / std::mem::size_of::<u32>()) as u32;
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Model Bounds Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[count, stride]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        // The keys of the minimum start at the largest and the maximum at the smallest
+        let bounds = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Model Bounds Buffer"),
+            contents: bytemuck::cast_slice(&[u32::MAX, u32::MAX, u32::MAX, 0, 0, 0]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        });
+
+        let download = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Model Bounds Download Buffer"),
+            size: Self::BOUNDS_SIZE,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Model Bounds Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[&uniform_buffer, gaussians.buffer(), &bounds]
+                .into_iter()
+                .enumerate()
+                .map(|(i, buffer)| wgpu::BindGroupEntry {
+                    binding: i as u32,
+                    resource: buffer.as_entire_binding(),
+                })
+                .collect::<Vec<_>>(),
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Model Bounds Encoder"),
+        });
+
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Model Bounds Compute Pass"),
+                timestamp_writes: None,
+            });
+
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+
+            // Each invocation reduces more than one Gaussian for very large models
+            pass.dispatch_workgroups(
+                count
+                    .div_ceil(Self::WORKGROUP_SIZE)
+                    .clamp(1, device.limits().max_compute_workgroups_per_dimension),
+                1,
+                1,
+            );
+        }
+
+        encoder.copy_buffer_to_buffer(&bounds, 0, &download, 0, Self::BOUNDS_SIZE);
+
+        queue.submit(Some(encoder.finish()));
+
+        download
+    }
+
+    /// Map the buffer returned by [`ModelBounds::prepare`] to read the minimum and maximum.
+    pub async fn map(
+        device: &wgpu::Device,
+        download: &wgpu::Buffer,
+    ) -> Result<(Vec3, Vec3), String> {
+        let (tx, rx) = oneshot::channel();
+        let buffer_slice = download.slice(..);
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            if let Err(e) = tx.send(result) {
+                log::error!("Error occurred while sending model bounds: {e:?}");
+            }
+        });
+        device.poll(wgpu::Maintain::Wait);
+        rx.await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+
+        let keys: Vec<u32> =
+            bytemuck::allocation::pod_collect_to_vec(&buffer_slice.get_mapped_range());
+        download.unmap();
+
+        // Reverse the order preserving mapping of the shader
+        let value = |key: u32| match key >> 31 {
+            1 => f32::from_bits(key & 0x7fffffff),
+            _ => f32::from_bits(!key),
+        };
+
+        Ok((
+            Vec3::new(value(keys[0]), value(keys[1]), value(keys[2])),
+            Vec3::new(value(keys[3]), value(keys[4]), value(keys[5])),
+        ))
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use eframe::wgpu::{self, util::DeviceExt};
+use wgpu_3dgs_viewer as gs;
+
+/// The compute pass for limiting the number of rendered Gaussians.
+///
+/// The instance count of the draw indirect args is scaled from the culled count. The Gaussians
+/// are drawn back to front, so if the device supports a first instance in the indirect args, the
+/// farthest Gaussians are skipped to keep the nearest, otherwise the nearest are cut off.
+#[derive(Debug)]
+pub struct RenderLimit {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+
+    /// Whether the nearest Gaussians are kept, see [`wgpu::Features::INDIRECT_FIRST_INSTANCE`].
+    nearest: bool,
+
+    /// The limit of each indirect args buffer that has been limited.
+    targets: HashMap<wgpu::Buffer, RenderLimitTarget>,
+}
+
+impl RenderLimit {
+    /// Create a new render limit compute pass.
+    pub fn new(device: &wgpu::Device) -> Self {
+        log::debug!("Creating render limit bind group layout");
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Render Limit Bind Group Layout"),
+            entries: &[
+                // The indirect args storage buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // The limit uniform buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        log::debug!("Creating render limit pipeline");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Render Limit Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Render Limit Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shader/render_limit.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Render Limit Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        log::info!("Render limit compute pass created");
+
+        Self {
+            bind_group_layout,
+            pipeline,
+            nearest: device
+                .features()
+                .contains(wgpu::Features::INDIRECT_FIRST_INSTANCE),
+            targets: HashMap::new(),
+        }
+    }
+
+    /// Limit the instance count of the indirect args to `fraction` of the culled Gaussians.
+    ///
+    /// This must be recorded after the preprocess and sort, which reset the instance count. The
+    /// preprocess does not reset the first instance, so after being limited, the indirect args
+    /// are reset once with [`None`].
+    pub fn limit(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        indirect_args: &gs::IndirectArgsBuffer,
+        fraction: Option<f32>,
+    ) {
+        let buffer = indirect_args.buffer();
+        let pod = RenderLimitPod {
+            fraction: fraction.unwrap_or(1.0),
+            nearest: self.nearest as u32,
+            _padding: [0; 2],
+        };
+
+        let bind_group = match (fraction, self.targets.get(buffer)) {
+            (None, None) => return,
+            // Reset the first instance once after the limit is removed
+            (None, Some(..)) => self
+                .targets
+                .remove(buffer)
+                .expect("render limit target")
+                .update(queue, &pod),
+            (Some(..), Some(target)) => target.update(queue, &pod),
+            (Some(..), None) => {
+                let target = RenderLimitTarget::new(device, &self.bind_group_layout, buffer, &pod);
+                let bind_group = target.bind_group.clone();
+                self.targets.insert(buffer.clone(), target);
+                bind_group
+            }
+        };
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Render Limit Compute Pass"),
+            timestamp_writes: None,
+        });
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(1, 1, 1);
+    }
+
+    /// Remove the limit of the indirect args, e.g. when its model is removed.
+    pub fn remove(&mut self, indirect_args: &gs::IndirectArgsBuffer) {
+        self.targets.remove(indirect_args.buffer());
+    }
+}
+
+/// The limit of an indirect args buffer of [`RenderLimit`].
+#[derive(Debug)]
+struct RenderLimitTarget {
+    /// The limit uniform buffer.
+    limit_buffer: wgpu::Buffer,
+
+    /// The bind group of the indirect args and the limit.
+    bind_group: wgpu::BindGroup,
+}
+
+impl RenderLimitTarget {
+    /// Create the limit of an indirect args buffer.
+    fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        indirect_args: &wgpu::Buffer,
+        pod: &RenderLimitPod,
+    ) -> Self {
+        let limit_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Render Limit Buffer"),
+            contents: bytemuck::bytes_of(pod),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Render Limit Bind Group"),
+            layout: bind_group_layout,
+            entries: &[
+                // The indirect args storage buffer.
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: indirect_args.as_entire_binding(),
+                },
+                // The limit uniform buffer.
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: limit_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            limit_buffer,
+            bind_group,
+        }
+    }
+
+    /// Update the limit, returns the bind group to dispatch with.
+    fn update(&self, queue: &wgpu::Queue, pod: &RenderLimitPod) -> wgpu::BindGroup {
+        queue.write_buffer(&self.limit_buffer, 0, bytemuck::bytes_of(pod));
+        self.bind_group.clone()
+    }
+}
+
+/// The limit uniform of [`RenderLimit`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct RenderLimitPod {
+    fraction: f32,
+    nearest: u32,
+    _padding: [u32; 2],
+}
@@ -0,0 +1,192 @@
+use eframe::wgpu::{self, util::DeviceExt};
+use wgpu_3dgs_viewer as gs;
+
+/// The compute pass for limiting the selection to a depth window.
+///
+/// The query results of the selection are filtered before they are applied by the postprocessor,
+/// only the Gaussians within the tolerance behind the nearest selected Gaussian are kept. The
+/// depths are from the depth buffer the viewer keeps for sorting.
+#[derive(Debug)]
+pub struct SelectionDepth {
+    bind_group_layout: wgpu::BindGroupLayout,
+    /// The pre, depth, nearest, nearest index and filter pipelines.
+    pipelines: [wgpu::ComputePipeline; 5],
+    /// The view depths by Gaussian index, reallocated when too small.
+    depths_buffer: Option<wgpu::Buffer>,
+    /// The nearest depth and Gaussian index.
+    state_buffer: wgpu::Buffer,
+}
+
+impl SelectionDepth {
+    /// The workgroup size.
+    const WORKGROUP_SIZE: u32 = 256;
+
+    /// Create a new selection depth compute pass.
+    pub fn new(device: &wgpu::Device) -> Self {
+        log::debug!("Creating selection depth bind group layout");
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let uniform = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Selection Depth Bind Group Layout"),
+            entries: &[
+                // The query result count storage buffer.
+                storage(0, true),
+                // The query results storage buffer.
+                storage(1, false),
+                // The indirect args storage buffer.
+                storage(2, true),
+                // The indirect indices storage buffer.
+                storage(3, true),
+                // The Gaussians depth storage buffer.
+                storage(4, true),
+                // The camera uniform buffer.
+                uniform(5),
+                // The tolerance uniform buffer.
+                uniform(6),
+                // The depths storage buffer.
+                storage(7, false),
+                // The state storage buffer.
+                storage(8, false),
+            ],
+        });
+
+        log::debug!("Creating selection depth pipelines");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Selection Depth Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Selection Depth Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shader/selection_depth.wgsl").into()),
+        });
+
+        let pipelines = [
+            "pre_main",
+            "depth_main",
+            "nearest_main",
+            "nearest_index_main",
+            "filter_main",
+        ]
+        .map(|entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Selection Depth Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point: Some(entry_point),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            })
+        });
+
+        log::debug!("Creating selection depth state buffer");
+        let state_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Selection Depth State Buffer"),
+            size: 2 * std::mem::size_of::<u32>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        log::info!("Selection depth compute pass created");
+
+        Self {
+            bind_group_layout,
+            pipelines,
+            depths_buffer: None,
+            state_buffer,
+        }
+    }
+
+    /// Limit the query results of the selection to `tolerance` in world units behind the
+    /// nearest result.
+    pub fn limit<G: gs::GaussianPod>(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        buffers: &gs::MultiModelViewerGaussianBuffers<G>,
+        camera: &gs::CameraBuffer,
+        tolerance: f32,
+        gaussian_count: u32,
+    ) {
+        let tolerance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Selection Depth Tolerance Buffer"),
+            contents: bytemuck::cast_slice(&[tolerance, 0.0, 0.0, 0.0]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let depths_size =
+            (gaussian_count.max(1) as usize * std::mem::size_of::<f32>()) as wgpu::BufferAddress;
+        if self
+            .depths_buffer
+            .as_ref()
+            .is_none_or(|buffer| buffer.size() < depths_size)
+        {
+            log::debug!("Creating selection depth depths buffer");
+            self.depths_buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Selection Depth Depths Buffer"),
+                size: depths_size,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            }));
+        }
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Selection Depth Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                buffers.query_result_count_buffer.buffer(),
+                buffers.query_results_buffer.buffer(),
+                buffers.indirect_args_buffer.buffer(),
+                buffers.indirect_indices_buffer.buffer(),
+                buffers.gaussians_depth_buffer.buffer(),
+                camera.buffer(),
+                &tolerance_buffer,
+                self.depths_buffer.as_ref().expect("depths buffer"),
+                &self.state_buffer,
+            ]
+            .into_iter()
+            .enumerate()
+            .map(|(i, buffer)| wgpu::BindGroupEntry {
+                binding: i as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect::<Vec<_>>(),
+        });
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Selection Depth Compute Pass"),
+            timestamp_writes: None,
+        });
+
+        pass.set_bind_group(0, &bind_group, &[]);
+
+        let [pre, passes @ ..] = &self.pipelines;
+        pass.set_pipeline(pre);
+        pass.dispatch_workgroups(1, 1, 1);
+
+        // The query results are at most the Gaussian count
+        for pipeline in passes {
+            pass.set_pipeline(pipeline);
+            pass.dispatch_workgroups(gaussian_count.div_ceil(Self::WORKGROUP_SIZE), 1, 1);
+        }
+    }
+}
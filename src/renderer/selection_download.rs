@@ -0,0 +1,203 @@
+use eframe::wgpu::{self, util::DeviceExt};
+use wgpu_3dgs_viewer as gs;
+
+/// The compute pass for downloading and uploading the selection.
+///
+/// The selection buffer cannot be copied from or written to directly, so it is copied to or
+/// from a storage buffer that can be.
+#[derive(Debug)]
+pub struct SelectionDownload {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl SelectionDownload {
+    /// The workgroup size.
+    const WORKGROUP_SIZE: u32 = 256;
+
+    /// Create a new selection download compute pass.
+    pub fn new(device: &wgpu::Device) -> Self {
+        log::debug!("Creating selection download bind group layout");
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Selection Download Bind Group Layout"),
+            entries: &[
+                // The source storage buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // The destination storage buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        log::debug!("Creating selection download pipeline");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Selection Download Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Selection Download Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("../shader/selection_download.wgsl").into(),
+            ),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Selection Download Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        log::info!("Selection download compute pass created");
+
+        Self {
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    /// Copy the selection to a mappable buffer.
+    ///
+    /// The returned buffer can be read with [`SelectionDownload::map`].
+    pub fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        selection: &gs::SelectionBuffer,
+    ) -> wgpu::Buffer {
+        let size = selection.buffer().size();
+
+        let dest = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Selection Download Destination Buffer"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let download = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Selection Download Buffer"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Selection Download Encoder"),
+        });
+
+        self.copy(device, &mut encoder, selection.buffer(), &dest);
+
+        encoder.copy_buffer_to_buffer(&dest, 0, &download, 0, size);
+
+        queue.submit(Some(encoder.finish()));
+
+        download
+    }
+
+    /// Write the selection from its words, the reverse of [`SelectionDownload::prepare`].
+    ///
+    /// Missing words are unselected, extra words are ignored.
+    pub fn upload(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        selection: &gs::SelectionBuffer,
+        words: &[u32],
+    ) {
+        let mut words = words.to_vec();
+        words.resize(
+            (selection.buffer().size() / std::mem::size_of::<u32>() as wgpu::BufferAddress)
+                as usize,
+            0,
+        );
+
+        let source = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Selection Upload Source Buffer"),
+            contents: bytemuck::cast_slice(&words),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        self.copy(device, encoder, &source, selection.buffer());
+    }
+
+    /// Copy the words of `source` to `dest` with the compute pass.
+    fn copy(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::Buffer,
+        dest: &wgpu::Buffer,
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Selection Download Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                // The source storage buffer.
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: source.as_entire_binding(),
+                },
+                // The destination storage buffer.
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: dest.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Selection Download Compute Pass"),
+            timestamp_writes: None,
+        });
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(
+            ((dest.size() / std::mem::size_of::<u32>() as wgpu::BufferAddress) as u32)
+                .div_ceil(Self::WORKGROUP_SIZE),
+            1,
+            1,
+        );
+    }
+
+    /// Map the buffer returned by [`SelectionDownload::prepare`] to read the selection.
+    pub async fn map(device: &wgpu::Device, download: &wgpu::Buffer) -> Result<Vec<u32>, String> {
+        let (tx, rx) = oneshot::channel();
+        let buffer_slice = download.slice(..);
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            if let Err(e) = tx.send(result) {
+                log::error!("Error occurred while sending selection: {e:?}");
+            }
+        });
+        device.poll(wgpu::Maintain::Wait);
+        rx.await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+
+        let selection = bytemuck::allocation::pod_collect_to_vec(&buffer_slice.get_mapped_range());
+        download.unmap();
+
+        Ok(selection)
+    }
+}
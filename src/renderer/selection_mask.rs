@@ -0,0 +1,114 @@
+use eframe::wgpu;
+use wgpu_3dgs_viewer as gs;
+
+/// The compute pass for limiting the selection to the mask.
+#[derive(Debug)]
+pub struct SelectionMask {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl SelectionMask {
+    /// The workgroup size.
+    const WORKGROUP_SIZE: u32 = 256;
+
+    /// Create a new selection mask compute pass.
+    pub fn new(device: &wgpu::Device) -> Self {
+        log::debug!("Creating selection mask bind group layout");
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Selection Mask Bind Group Layout"),
+            entries: &[
+                // The selection storage buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // The mask storage buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        log::debug!("Creating selection mask pipeline");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Selection Mask Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Selection Mask Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shader/selection_mask.wgsl").into()),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Selection Mask Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        log::info!("Selection mask compute pass created");
+
+        Self {
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    /// Intersect the selection with the mask.
+    pub fn intersect(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        selection: &gs::SelectionBuffer,
+        mask: &gs::MaskBuffer,
+        gaussian_count: u32,
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Selection Mask Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                // The selection storage buffer.
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: selection.buffer().as_entire_binding(),
+                },
+                // The mask storage buffer.
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: mask.buffer().as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Selection Mask Compute Pass"),
+            timestamp_writes: None,
+        });
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(
+            gaussian_count.div_ceil(32).div_ceil(Self::WORKGROUP_SIZE),
+            1,
+            1,
+        );
+    }
+}
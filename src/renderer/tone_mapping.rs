@@ -0,0 +1,551 @@
+use eframe::wgpu::{self, util::DeviceExt};
+use glam::*;
+use strum::IntoEnumIterator;
+
+use crate::app;
+
+/// The renderer for the global tone mapping, gamma correction, render scale and temporal
+/// accumulation.
+///
+/// The scene is rendered to an offscreen target first, which is then drawn to the viewport with
+/// the tone mapping and gamma correction applied, and upscaled if the target is smaller.
+///
+/// For temporal accumulation, the offscreen target is blended into an accumulation texture, which
+/// is drawn to the viewport instead.
+///
+/// For anaglyph stereo, each eye is drawn to the same viewport with a color write mask.
+///
+/// FXAA can be applied to the tone mapped colors when drawing to the viewport.
+#[derive(Debug)]
+pub struct ToneMapping {
+    format: wgpu::TextureFormat,
+    uniform_buffer: wgpu::Buffer,
+    nearest_sampler: wgpu::Sampler,
+    linear_sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    /// The pipelines writing only some color channels, for anaglyph stereo.
+    channel_pipelines: Vec<(wgpu::ColorWrites, wgpu::RenderPipeline)>,
+    accumulation_pipeline: wgpu::RenderPipeline,
+    /// The offscreen targets, one for each viewport.
+    targets: [Option<ToneMappingTarget>; 2],
+}
+
+impl ToneMapping {
+    /// The texture format of the accumulation texture.
+    const ACCUMULATION_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    /// Create a new tone mapping renderer.
+    pub fn new(device: &wgpu::Device, texture_format: wgpu::TextureFormat) -> Self {
+        log::debug!("Creating tone mapping uniform buffer");
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Tone Mapping Uniform Buffer"),
+            contents: bytemuck::bytes_of(&ToneMappingPod::new(
+                &app::ToneMapping::default(),
+                app::GammaCorrection::default().is_enabled(texture_format),
+                false,
+            )),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        log::debug!("Creating tone mapping samplers");
+        let nearest_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Tone Mapping Nearest Sampler"),
+            ..Default::default()
+        });
+        let linear_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Tone Mapping Linear Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        log::debug!("Creating tone mapping bind group layout");
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Tone Mapping Bind Group Layout"),
+            entries: &[
+                // The tone mapping uniform buffer.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // The offscreen color texture.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                // The sampler.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        log::debug!("Creating tone mapping pipeline");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tone Mapping Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Tone Mapping Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shader/tone_mapping.wgsl").into()),
+        });
+
+        let create_pipeline = |write_mask: wgpu::ColorWrites| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Tone Mapping Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vert_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("frag_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: texture_format,
+                        blend: None,
+                        write_mask,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Always,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let pipeline = create_pipeline(wgpu::ColorWrites::ALL);
+        let channel_pipelines = app::AnaglyphColors::iter()
+            .flat_map(Self::anaglyph_write_masks)
+            .map(|write_mask| (write_mask, create_pipeline(write_mask)))
+            .collect();
+
+        let accumulation_pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Tone Mapping Accumulation Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vert_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("frag_copy"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: Self::ACCUMULATION_FORMAT,
+                        // The weight of the new frame is the blend constant
+                        blend: Some(wgpu::BlendState {
+                            color: wgpu::BlendComponent {
+                                src_factor: wgpu::BlendFactor::Constant,
+                                dst_factor: wgpu::BlendFactor::OneMinusConstant,
+                                operation: wgpu::BlendOperation::Add,
+                            },
+                            alpha: wgpu::BlendComponent {
+                                src_factor: wgpu::BlendFactor::Constant,
+                                dst_factor: wgpu::BlendFactor::OneMinusConstant,
+                                operation: wgpu::BlendOperation::Add,
+                            },
+                        }),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        log::info!("Tone mapping renderer created");
+
+        Self {
+            format: texture_format,
+            uniform_buffer,
+            nearest_sampler,
+            linear_sampler,
+            bind_group_layout,
+            pipeline,
+            channel_pipelines,
+            accumulation_pipeline,
+            targets: [None, None],
+        }
+    }
+
+    /// Update the tone mapping uniform buffer.
+    ///
+    /// FXAA is applied after the tone mapping if `fxaa` is true.
+    pub fn update(
+        &self,
+        queue: &wgpu::Queue,
+        tone_mapping: &app::ToneMapping,
+        gamma_correction: app::GammaCorrection,
+        fxaa: bool,
+    ) {
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::bytes_of(&ToneMappingPod::new(
+                tone_mapping,
+                gamma_correction.is_enabled(self.format),
+                fxaa,
+            )),
+        );
+    }
+
+    /// Get the color write masks of the left and right eyes for anaglyph stereo.
+    pub fn anaglyph_write_masks(colors: app::AnaglyphColors) -> [wgpu::ColorWrites; 2] {
+        match colors {
+            app::AnaglyphColors::RedCyan => [
+                wgpu::ColorWrites::RED,
+                wgpu::ColorWrites::GREEN | wgpu::ColorWrites::BLUE,
+            ],
+            app::AnaglyphColors::GreenMagenta => [
+                wgpu::ColorWrites::GREEN,
+                wgpu::ColorWrites::RED | wgpu::ColorWrites::BLUE,
+            ],
+        }
+    }
+
+    /// Make sure the offscreen target at `index` exists and has the `size` in physical pixels.
+    ///
+    /// `filter` is used for upscaling the target to the viewport.
+    pub fn prepare_target(
+        &mut self,
+        device: &wgpu::Device,
+        index: usize,
+        size: UVec2,
+        filter: wgpu::FilterMode,
+    ) {
+        let size = size.max(UVec2::ONE);
+
+        if self.targets[index]
+            .as_ref()
+            .is_some_and(|target| target.size == size && target.filter == filter)
+        {
+            return;
+        }
+
+        log::debug!("Creating tone mapping target {index} of size {size}");
+        self.targets[index] = Some(ToneMappingTarget::new(
+            device,
+            self.format,
+            &self.bind_group_layout,
+            &self.uniform_buffer,
+            match filter {
+                wgpu::FilterMode::Nearest => &self.nearest_sampler,
+                wgpu::FilterMode::Linear => &self.linear_sampler,
+            },
+            size,
+            filter,
+        ));
+    }
+
+    /// Convert the color to the clear color of the offscreen target.
+    ///
+    /// The offscreen target is always treated as sRGB encoded regardless of the format, same as
+    /// the rendered Gaussians.
+    pub fn clear_color(color: egui::Color32) -> wgpu::Color {
+        let [r, g, b, a] = color.to_normalized_gamma_f32();
+
+        wgpu::Color {
+            r: r as f64,
+            g: g as f64,
+            b: b as f64,
+            a: a as f64,
+        }
+    }
+
+    /// Begin a render pass on the offscreen target at `index`.
+    ///
+    /// The color is cleared to `clear_color`, see [`ToneMapping::clear_color`].
+    pub fn begin_render_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        index: usize,
+        clear_color: wgpu::Color,
+    ) -> wgpu::RenderPass<'static> {
+        let target = self.targets[index].as_ref().expect("tone mapping target");
+
+        encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Tone Mapping Offscreen Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(clear_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &target.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            })
+            .forget_lifetime()
+    }
+
+    /// Blend the offscreen target at `index` into its accumulation texture.
+    ///
+    /// The accumulation restarts if `reset` is true, and the last `max_frames` frames are averaged.
+    pub fn accumulate(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        index: usize,
+        reset: bool,
+        max_frames: u32,
+    ) {
+        let target = self.targets[index].as_mut().expect("tone mapping target");
+
+        if reset {
+            target.accumulated_frames = 0;
+        }
+
+        let weight = 1.0 / (target.accumulated_frames.min(max_frames.max(1) - 1) + 1) as f64;
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Tone Mapping Accumulation Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target.accumulation_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: match target.accumulated_frames {
+                        0 => wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        _ => wgpu::LoadOp::Load,
+                    },
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        pass.set_pipeline(&self.accumulation_pipeline);
+        pass.set_bind_group(0, &target.bind_group, &[]);
+        pass.set_blend_constant(wgpu::Color {
+            r: weight,
+            g: weight,
+            b: weight,
+            a: weight,
+        });
+        pass.draw(0..3, 0..1);
+
+        target.accumulated_frames += 1;
+    }
+
+    /// Render the tone mapped offscreen target at `index` to the viewport.
+    ///
+    /// The accumulation texture is drawn instead if `accumulated` is true, see
+    /// [`ToneMapping::accumulate`]. Only the channels in `write_mask` are written, which must be
+    /// all channels or one of [`ToneMapping::anaglyph_write_masks`].
+    pub fn render_with_pass(
+        &self,
+        render_pass: &mut wgpu::RenderPass,
+        index: usize,
+        accumulated: bool,
+        write_mask: wgpu::ColorWrites,
+    ) {
+        let Some(target) = &self.targets[index] else {
+            return;
+        };
+
+        let pipeline = match write_mask {
+            wgpu::ColorWrites::ALL => &self.pipeline,
+            _ => self
+                .channel_pipelines
+                .iter()
+                .find(|(mask, _)| *mask == write_mask)
+                .map(|(_, pipeline)| pipeline)
+                .expect("channel pipeline"),
+        };
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(
+            0,
+            match accumulated {
+                true => &target.accumulation_bind_group,
+                false => &target.bind_group,
+            },
+            &[],
+        );
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+/// The offscreen target of [`ToneMapping`].
+#[derive(Debug)]
+struct ToneMappingTarget {
+    size: UVec2,
+    filter: wgpu::FilterMode,
+    color_view: wgpu::TextureView,
+    depth_view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    accumulation_view: wgpu::TextureView,
+    accumulation_bind_group: wgpu::BindGroup,
+    accumulated_frames: u32,
+}
+
+impl ToneMappingTarget {
+    /// Create a new offscreen target.
+    fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        sampler: &wgpu::Sampler,
+        size: UVec2,
+        filter: wgpu::FilterMode,
+    ) -> Self {
+        let extent = wgpu::Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        };
+
+        let color_view = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Tone Mapping Color Texture"),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let depth_view = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Tone Mapping Depth Texture"),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Depth32Float,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let accumulation_view = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Tone Mapping Accumulation Texture"),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: ToneMapping::ACCUMULATION_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let create_bind_group = |view: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Tone Mapping Bind Group"),
+                layout: bind_group_layout,
+                entries: &[
+                    // The tone mapping uniform buffer.
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                    // The offscreen color texture.
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    // The sampler.
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+            })
+        };
+
+        let bind_group = create_bind_group(&color_view);
+        let accumulation_bind_group = create_bind_group(&accumulation_view);
+
+        Self {
+            size,
+            filter,
+            color_view,
+            depth_view,
+            bind_group,
+            accumulation_view,
+            accumulation_bind_group,
+            accumulated_frames: 0,
+        }
+    }
+}
+
+/// The POD representation of [`app::ToneMapping`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct ToneMappingPod {
+    exposure: f32,
+    op: u32,
+    gamma_correction: u32,
+    fxaa: u32,
+}
+
+impl ToneMappingPod {
+    /// Create a new tone mapping POD.
+    ///
+    /// The exposure and operator are ignored if the tone mapping is disabled.
+    fn new(tone_mapping: &app::ToneMapping, gamma_correction: bool, fxaa: bool) -> Self {
+        let (exposure, op) = match tone_mapping.enabled {
+            true => (tone_mapping.exposure.exp2(), tone_mapping.operator),
+            false => (1.0, app::ToneMappingOperator::None),
+        };
+
+        Self {
+            exposure,
+            op: op as u32,
+            gamma_correction: gamma_correction as u32,
+            fxaa: fxaa as u32,
+        }
+    }
+}
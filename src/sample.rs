@@ -0,0 +1,90 @@
+use std::io::Cursor;
+
+use glam::*;
+use wgpu_3dgs_viewer as gs;
+
+use crate::app;
+
+/// The file name of the sample model.
+pub const NAME: &str = "Sample";
+
+/// The number of Gaussians around the ring of the torus.
+const RING_COUNT: usize = 256;
+
+/// The number of Gaussians around the tube of the torus.
+const TUBE_COUNT: usize = 64;
+
+/// The radius of the ring.
+const RING_RADIUS: f32 = 0.35;
+
+/// The radius of the tube.
+const TUBE_RADIUS: f32 = 0.12;
+
+/// The center, which is the target of the default camera.
+const CENTER: Vec3 = Vec3::NEG_Z;
+
+/// Create the sample model.
+///
+/// The sample is a rainbow torus generated on the fly, so it is available both on native and
+/// on the web without bundling or fetching a file.
+pub fn load(compressions: app::Compressions) -> Result<app::GaussianSplatting, String> {
+    app::GaussianSplatting::new(NAME.to_string(), None, Cursor::new(ply()?), compressions)
+        .map_err(|e| e.to_string())
+}
+
+/// Write the sample model as a PLY file.
+fn ply() -> Result<Vec<u8>, String> {
+    let gaussians = gs::Gaussians {
+        gaussians: (0..RING_COUNT)
+            .flat_map(|i| (0..TUBE_COUNT).map(move |j| gaussian(i, j)))
+            .collect(),
+    };
+
+    let mut ply = Vec::new();
+    gaussians
+        .write_ply(
+            &mut ply,
+            None::<Vec<&gs::GaussianEditPod>>,
+            None::<Vec<u32>>,
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(ply)
+}
+
+/// Create the Gaussian at ring index `i` and tube index `j`.
+///
+/// Each Gaussian is a flat disk tangent to the surface, colored by the angle around the ring.
+fn gaussian(i: usize, j: usize) -> gs::Gaussian {
+    let u = i as f32 / RING_COUNT as f32 * std::f32::consts::TAU;
+    let v = j as f32 / TUBE_COUNT as f32 * std::f32::consts::TAU;
+
+    let ring_dir = Vec3::new(u.cos(), u.sin(), 0.0);
+    let normal = ring_dir * v.cos() + Vec3::Z * v.sin();
+    let tangent_u = Vec3::new(-u.sin(), u.cos(), 0.0);
+    let tangent_v = normal.cross(tangent_u);
+
+    let pos = CENTER + ring_dir * RING_RADIUS + normal * TUBE_RADIUS;
+
+    let spacing_u =
+        (RING_RADIUS + TUBE_RADIUS * v.cos()) * std::f32::consts::TAU / RING_COUNT as f32;
+    let spacing_v = TUBE_RADIUS * std::f32::consts::TAU / TUBE_COUNT as f32;
+    let scale = Vec3::new(spacing_u * 0.75, spacing_v * 0.75, 1e-3);
+
+    // Shade the inside of the tube darker
+    let rgb = hue(u / std::f32::consts::TAU) * (0.6 + 0.4 * (v.cos() * 0.5 + 0.5));
+
+    gs::Gaussian {
+        rotation: Quat::from_mat3(&Mat3::from_cols(tangent_u, tangent_v, normal)),
+        pos,
+        color: (rgb * 255.0).round().as_u8vec3().extend(230),
+        sh: [Vec3::ZERO; 15],
+        scale,
+    }
+}
+
+/// Get the fully saturated color of a hue in `[0, 1)`.
+fn hue(h: f32) -> Vec3 {
+    let k = Vec3::new(5.0, 3.0, 1.0);
+    Vec3::ONE - ((k + h * 6.0) % 6.0).map(|k| k.min(4.0 - k).clamp(0.0, 1.0))
+}
@@ -0,0 +1,253 @@
+// Scripts are only run from files on native, the parser is unused on the web.
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
+
+use std::{collections::VecDeque, path::PathBuf};
+
+use glam::*;
+use wgpu_3dgs_viewer as gs;
+
+/// A script of commands for repeatable workflows.
+///
+/// Each line is a command followed by its arguments separated by whitespace, see
+/// [`Script::EXAMPLE`] for the grammar. The commands are run one by one by the scene, and a
+/// command waits for the previous one to finish, e.g. loading a model.
+#[derive(Debug)]
+pub struct Script {
+    /// The remaining commands with their line numbers.
+    pub commands: VecDeque<(usize, ScriptCommand)>,
+
+    /// What the script is waiting for before running the next command.
+    pub wait: Option<ScriptWait>,
+}
+
+impl Script {
+    /// The example script, which also documents the commands.
+    pub const EXAMPLE: &str = "\
+# Lines starting with # are comments, vectors are written as x y z.
+
+# Open a model, replacing the current session.
+open /path/to/model.ply
+
+# Add a model to the session and select it.
+add /path/to/other.ply
+
+# Select a model by its name in the models tab.
+select model.ply
+
+# Set the transform of the selected model, rotation is in degrees.
+position 0 0 0
+rotation 0 0 180
+scale 1 1 1
+
+# Add a mask shape (box or ellipsoid) with a position and scale to the selected model.
+mask box 0 0 0 2 2 2
+mask ellipsoid 1 0 0 0.5 0.5 0.5
+
+# Set the mask operation of the selected model, same as the mask tab.
+mask_op 0 | 1
+
+# Orbit the camera at a position around a target.
+camera 0 0 5 0 0 0
+
+# Wait in seconds.
+wait 1
+
+# Save the window as a PNG image.
+screenshot /path/to/screenshot.png
+";
+
+    /// Parse a script.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let commands = source
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                ScriptCommand::parse(line)
+                    .map_err(|e| format!("Line {}: {e}", i + 1))
+                    .transpose()
+                    .map(|command| command.map(|command| (i + 1, command)))
+            })
+            .collect::<Result<VecDeque<_>, _>>()?;
+
+        Ok(Self {
+            commands,
+            wait: None,
+        })
+    }
+}
+
+/// The command of a [`Script`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptCommand {
+    /// Open a model, replacing the current session.
+    Open(PathBuf),
+
+    /// Add a model to the session and select it.
+    Add(PathBuf),
+
+    /// Select a model.
+    Select(String),
+
+    /// Set the position of the selected model.
+    Position(Vec3),
+
+    /// Set the Euler rotation in degrees of the selected model.
+    Rotation(Vec3),
+
+    /// Set the scale of the selected model.
+    Scale(Vec3),
+
+    /// Add a mask shape to the selected model.
+    Mask {
+        /// The kind.
+        kind: gs::MaskShapeKind,
+
+        /// The position.
+        pos: Vec3,
+
+        /// The scale.
+        scale: Vec3,
+    },
+
+    /// Set the mask operation code of the selected model.
+    MaskOp(String),
+
+    /// Orbit the camera at a position around a target.
+    Camera {
+        /// The position.
+        pos: Vec3,
+
+        /// The target.
+        target: Vec3,
+    },
+
+    /// Wait in seconds.
+    Wait(f64),
+
+    /// Save the window as a PNG image.
+    Screenshot(PathBuf),
+}
+
+impl ScriptCommand {
+    /// Parse a line.
+    ///
+    /// Returns [`None`] if the line is empty or a comment.
+    pub fn parse(line: &str) -> Result<Option<Self>, String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let args = rest.split_whitespace().collect::<Vec<_>>();
+
+        /// Parse the floats.
+        fn floats<const N: usize>(args: &[&str]) -> Result<[f32; N], String> {
+            if args.len() != N {
+                return Err(format!("Expected {N} numbers, found {}", args.len()));
+            }
+
+            let mut floats = [0.0; N];
+            for (float, arg) in floats.iter_mut().zip(args) {
+                *float = arg
+                    .parse()
+                    .map_err(|_| format!("Invalid number \"{arg}\""))?;
+            }
+
+            Ok(floats)
+        }
+
+        /// Get the rest of the line, which must not be empty.
+        fn text(rest: &str, what: &str) -> Result<String, String> {
+            match rest.is_empty() {
+                true => Err(format!("Expected {what}")),
+                false => Ok(rest.to_string()),
+            }
+        }
+
+        let command = match name {
+            "open" => Self::Open(text(rest, "a path")?.into()),
+            "add" => Self::Add(text(rest, "a path")?.into()),
+            "select" => Self::Select(text(rest, "a model name")?),
+            "position" => Self::Position(Vec3::from_array(floats(&args)?)),
+            "rotation" => Self::Rotation(Vec3::from_array(floats(&args)?)),
+            "scale" => Self::Scale(Vec3::from_array(floats(&args)?)),
+            "mask" => {
+                let kind = match args.first() {
+                    Some(&"box") => gs::MaskShapeKind::Box,
+                    Some(&"ellipsoid") => gs::MaskShapeKind::Ellipsoid,
+                    _ => return Err("Expected box or ellipsoid".to_string()),
+                };
+                let [px, py, pz, sx, sy, sz] = floats(&args[1..])?;
+
+                Self::Mask {
+                    kind,
+                    pos: Vec3::new(px, py, pz),
+                    scale: Vec3::new(sx, sy, sz),
+                }
+            }
+            "mask_op" => Self::MaskOp(rest.to_string()),
+            "camera" => {
+                let [px, py, pz, tx, ty, tz] = floats(&args)?;
+
+                Self::Camera {
+                    pos: Vec3::new(px, py, pz),
+                    target: Vec3::new(tx, ty, tz),
+                }
+            }
+            "wait" => {
+                let [seconds] = floats(&args)?;
+                Self::Wait(seconds.max(0.0) as f64)
+            }
+            "screenshot" => Self::Screenshot(text(rest, "a path")?.into()),
+            _ => return Err(format!("Unknown command \"{name}\"")),
+        };
+
+        Ok(Some(command))
+    }
+}
+
+/// What a [`Script`] is waiting for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptWait {
+    /// The opened model to be initialized and loaded.
+    Open,
+
+    /// The added model to be loaded.
+    Add,
+
+    /// The time to be reached.
+    Time(f64),
+
+    /// The screenshot to be saved to the path.
+    Screenshot(PathBuf),
+}
+
+/// Save an image as PNG.
+///
+/// This is only available on native.
+pub fn save_png(path: &std::path::Path, image: &egui::ColorImage) -> Result<(), String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        let mut encoder = png::Encoder::new(
+            std::io::BufWriter::new(file),
+            image.width() as u32,
+            image.height() as u32,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(image.as_raw()))
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (path, image);
+        Err("Saving files by path is not available on the web".to_string())
+    }
+}
@@ -0,0 +1,576 @@
+// The render shader of wgpu-3dgs-viewer with the billboard projection.
+//
+// Each Gaussian is drawn as a circle facing the camera with the volume of its ellipsoid, which
+// skips the projection of the covariance and its eigen decomposition in the vertex shader.
+
+// Vertex
+
+const max_radius = 2.0;
+const point_size = 0.01;
+
+struct Camera {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+    size: vec2<f32>,
+}
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+fn camera_coords(ndc_pos: vec2<f32>) -> vec2<f32> {
+    return (ndc_pos * vec2<f32>(1.0, -1.0) + vec2<f32>(1.0)) * camera.size * 0.5;
+}
+
+fn camera_aspect_ratio() -> f32 {
+    return camera.size.y / camera.size.x;
+}
+
+struct ModelTransform {
+    pos: vec3<f32>,
+    quat: vec4<f32>,
+    scale: vec3<f32>,
+}
+@group(0) @binding(1)
+var<uniform> model_transform: ModelTransform;
+
+fn model_transform_mat() -> mat4x4<f32> {
+    let pos = model_transform.pos;
+    let quat = model_transform.quat;
+    let scale = model_transform.scale;
+
+    let x2 = quat.x + quat.x;
+    let y2 = quat.y + quat.y;
+    let z2 = quat.z + quat.z;
+    let xx = quat.x * x2;
+    let xy = quat.x * y2;
+    let xz = quat.x * z2;
+    let yy = quat.y * y2;
+    let yz = quat.y * z2;
+    let zz = quat.z * z2;
+    let wx = quat.w * x2;
+    let wy = quat.w * y2;
+    let wz = quat.w * z2;
+
+    let sx = scale.x;
+    let sy = scale.y;
+    let sz = scale.z;
+
+    return mat4x4<f32>(
+        vec4<f32>(
+            (1.0 - (yy + zz)) * sx,
+            (xy + wz) * sx,
+            (xz - wy) * sx,
+            0.0,
+        ),
+        vec4<f32>(
+            (xy - wz) * sy,
+            (1.0 - (xx + zz)) * sy,
+            (yz + wx) * sy,
+            0.0,
+        ),
+        vec4<f32>(
+            (xz + wy) * sz,
+            (yz - wx) * sz,
+            (1.0 - (xx + yy)) * sz,
+            0.0,
+        ),
+        vec4<f32>(pos, 1.0),
+    );
+}
+
+fn model_transform_inv_sr_mat() -> mat3x3<f32> {
+    let quat = model_transform.quat;
+    let scale = model_transform.scale;
+
+    let x2 = quat.x + quat.x;
+    let y2 = quat.y + quat.y;
+    let z2 = quat.z + quat.z;
+    let xx = quat.x * x2;
+    let xy = quat.x * y2;
+    let xz = quat.x * z2;
+    let yy = quat.y * y2;
+    let yz = quat.y * z2;
+    let zz = quat.z * z2;
+    let wx = quat.w * x2;
+    let wy = quat.w * y2;
+    let wz = quat.w * z2;
+
+    let sx = scale.x;
+    let sy = scale.y;
+    let sz = scale.z;
+
+    return mat3x3<f32>(
+        vec3<f32>(
+            (1.0 - (yy + zz)) / sx,
+            (xy - wz) / sy,
+            (xz + wy) / sz,
+        ),
+        vec3<f32>(
+            (xy + wz) / sx,
+            (1.0 - (xx + zz)) / sy,
+            (yz - wx) / sz,
+        ),
+        vec3<f32>(
+            (xz - wy) / sx,
+            (yz + wx) / sy,
+            (1.0 - (xx + yy)) / sz,
+        ),
+    );
+}
+
+fn model_scale_rotation_mat() -> mat3x3<f32> {
+    let quat = model_transform.quat;
+    let scale = model_transform.scale;
+
+    let x2 = quat.x + quat.x;
+    let y2 = quat.y + quat.y;
+    let z2 = quat.z + quat.z;
+    let xx = quat.x * x2;
+    let xy = quat.x * y2;
+    let xz = quat.x * z2;
+    let yy = quat.y * y2;
+    let yz = quat.y * z2;
+    let zz = quat.z * z2;
+    let wx = quat.w * x2;
+    let wy = quat.w * y2;
+    let wz = quat.w * z2;
+
+    let sx = scale.x;
+    let sy = scale.y;
+    let sz = scale.z;
+
+    return mat3x3<f32>(
+        vec3<f32>(
+            (1.0 - (yy + zz)) * sx,
+            (xy + wz) * sx,
+            (xz - wy) * sx,
+        ),
+        vec3<f32>(
+            (xy - wz) * sy,
+            (1.0 - (xx + zz)) * sy,
+            (yz + wx) * sy,
+        ),
+        vec3<f32>(
+            (xz + wy) * sz,
+            (yz - wx) * sz,
+            (1.0 - (xx + yy)) * sz,
+        ),
+    );
+}
+
+struct GaussianTransform {
+    size: f32,
+    flags: u32,
+}
+@group(0) @binding(2)
+var<uniform> gaussian_transform: GaussianTransform;
+
+const gaussian_display_mode_splat = 0u;
+const gaussian_display_mode_ellipse = 1u;
+const gaussian_display_mode_point = 2u;
+
+fn gaussian_transform_display_mode() -> u32 {
+    return unpack4xU8(gaussian_transform.flags).x;
+}
+
+fn gaussian_transform_sh_deg() -> u32 {
+    return unpack4xU8(gaussian_transform.flags).y;
+}
+
+fn gaussian_transform_no_sh0() -> bool {
+    return unpack4xU8(gaussian_transform.flags).z != 0u;
+}
+
+struct Gaussian {
+    pos: vec3<f32>,
+    color: u32,
+    {{gaussian_sh_field}}
+    {{gaussian_cov3d_field}}
+}
+@group(0) @binding(3)
+var<storage, read> gaussians: array<Gaussian>;
+
+fn gaussian_sh(gaussian_index: u32, sh_index: u32) -> vec3<f32> {
+    return gaussian_unpack_sh(gaussian_index, sh_index);
+}
+
+fn gaussian_variance2d(gaussian_index: u32, view_depth: f32) -> f32 {
+    let cov3d = gaussian_unpack_cov3d(gaussian_index);
+
+    // The variance of the sphere with the same volume is the geometric mean of the eigenvalues
+    let det = cov3d[0] * (cov3d[3] * cov3d[5] - cov3d[4] * cov3d[4])
+        - cov3d[1] * (cov3d[1] * cov3d[5] - cov3d[4] * cov3d[2])
+        + cov3d[2] * (cov3d[1] * cov3d[4] - cov3d[3] * cov3d[2]);
+    let scale = abs(model_transform.scale.x * model_transform.scale.y * model_transform.scale.z);
+    let variance = pow(max(det, 0.0), 1.0 / 3.0) * pow(scale, 2.0 / 3.0);
+
+    let focal = camera.proj[1][1] * camera.size.y;
+
+    let low_pass = 0.1;
+
+    return variance * focal * focal / (view_depth * view_depth) + low_pass;
+}
+
+fn gaussian_color(gaussian_index: u32, dir: vec3<f32>, sh_deg: u32, no_sh0: bool) -> vec4<f32> {
+    const sh_c1 = 0.4886025;
+    const sh_c2 = array<f32, 5>(1.0925484, -1.0925484, 0.3153916, -1.0925484, 0.5462742);
+    const sh_c3 = array<f32, 7>(
+        -0.5900436, 2.8906114, -0.4570458, 0.3731763, -0.4570458, 1.4453057, -0.5900436
+    );
+
+    let i = gaussian_index;
+    let x = dir.x;
+    let y = dir.y;
+    let z = dir.z;
+
+    let color = gaussians_edit_color(gaussian_index, unpack4x8unorm(gaussians[i].color));
+    var result = color.rgb; // 0.5 + SH_C0 * sh[0] already precomputed
+
+    if no_sh0 {
+        result = vec3<f32>(0.5);
+    }
+
+    if sh_deg >= 1u {
+        result += sh_c1 * (
+            -gaussian_sh(i, 1u) * y +
+            gaussian_sh(i, 2u) * z -
+            gaussian_sh(i, 3u) * x
+        );
+
+        if sh_deg >= 2u {
+            let xx = x * x;
+            let yy = y * y;
+            let zz = z * z;
+            let xy = x * y;
+            let yz = y * z;
+            let xz = x * z;
+
+            result += 
+                sh_c2[0] * xy * gaussian_sh(i, 4u) +
+                sh_c2[1] * yz * gaussian_sh(i, 5u) +
+                sh_c2[2] * (2.0 * zz - xx - yy) * gaussian_sh(i, 6u) +
+                sh_c2[3] * xz * gaussian_sh(i, 7u) +
+                sh_c2[4] * (xx - yy) * gaussian_sh(i, 8u);
+
+            if sh_deg >= 3u {
+                result += 
+                    sh_c3[0] * y * (3.0 * xx - yy) * gaussian_sh(i, 9u) +
+                    sh_c3[1] * xy * z * gaussian_sh(i, 10u) +
+                    sh_c3[2] * y * (4.0 * zz - xx - yy) * gaussian_sh(i, 11u) +
+                    sh_c3[3] * z * (2.0 * zz - 3.0 * xx - 3.0 * yy) * gaussian_sh(i, 12u) +
+                    sh_c3[4] * x * (4.0 * zz - xx - yy) * gaussian_sh(i, 13u) +
+                    sh_c3[5] * z * (xx - yy) * gaussian_sh(i, 14u) +
+                    sh_c3[6] * x * (xx - 3.0 * yy) * gaussian_sh(i, 15u);
+            }
+        }
+    }
+
+    return vec4<f32>(max(result, vec3<f32>(0.0)), color.a);
+}
+
+@group(0) @binding(4)
+var<storage, read> indirect_indices: array<u32>;
+
+struct Query {
+    content_u32: vec4<u32>,
+    content_f32: vec4<f32>,
+}
+@group(0) @binding(5)
+var<uniform> query: Query;
+
+const query_type_none = 0u << 24u;
+const query_type_hit = 1u << 24u;
+const query_type_rect = 2u << 24u;
+const query_type_brush = 3u << 24u;
+const query_type_texture = 4u << 24u;
+
+fn query_type() -> u32 {
+    return query.content_u32.x & 0xFF000000;
+}
+
+@group(0) @binding(6)
+var<storage, read_write> query_result_count: atomic<u32>;
+
+struct QueryResult {
+    content_u32: vec4<u32>,
+    content_f32: vec4<f32>,
+}
+@group(0) @binding(7)
+var<storage, read_write> query_results: array<QueryResult>;
+
+struct SelectionHighlight {
+    color: vec4<f32>,
+}
+@group(0) @binding(8)
+var<uniform> selection_highlight: SelectionHighlight;
+
+@group(0) @binding(9)
+var<storage, read> selection: array<u32>;
+
+fn selection_at(index: u32) -> bool {
+    let word_index = index / 32u;
+    let bit_index = index % 32u;
+    let mask = 1u << bit_index;
+    return (selection[word_index] & mask) != 0u;
+}
+
+struct GaussianEdit {
+    flag_hsv: u32,
+    contr_expo_gamma_alpha: u32,
+}
+@group(0) @binding(10)
+var<storage, read> gaussians_edit: array<GaussianEdit>;
+
+const gaussian_edit_flag_none = 0u;
+const gaussian_edit_flag_enabled = 1u << 0u;
+const gaussian_edit_flag_hidden = 1u << 1u;
+const gaussian_edit_flag_override_color = 1u << 2u;
+
+fn gaussians_edit_flag(index: u32) -> u32 {
+    return gaussians_edit[index].flag_hsv & 0x000000FF;
+}
+
+fn gaussians_edit_enabled(index: u32) -> bool {
+    return (gaussians_edit_flag(index) & gaussian_edit_flag_enabled) != 0;
+}
+
+fn gaussians_edit_flag_test(index: u32, test: u32) -> bool {
+    let mask = gaussian_edit_flag_enabled | test;
+    return (gaussians_edit_flag(index) & mask) == mask;
+}
+
+fn rgb_to_hsv(c: vec3<f32>) -> vec3<f32> {
+    const k = vec4<f32>(0.0, -1.0 / 3.0, 2.0 / 3.0, -1.0);
+    let p = select(vec4<f32>(c.bg, k.wz), vec4<f32>(c.gb, k.xy), c.b < c.g);
+    let q = select(vec4<f32>(p.xyw, c.r), vec4<f32>(c.r, p.yzx), p.x < c.r);
+
+    let d = q.x - min(q.w, q.y);
+    const e = 1.0e-10;
+    return vec3<f32>(abs(q.z + (q.w - q.y) / (6.0 * d + e)), d / (q.x + e), q.x);
+}
+
+fn hsv_to_rgb(c: vec3<f32>) -> vec3<f32> {
+    const k = vec4<f32>(1.0, 2.0 / 3.0, 1.0 / 3.0, 3.0);
+    let p = abs(fract(c.xxx + k.xyz) * 6.0 - k.www);
+    return c.z * mix(k.xxx, saturate(p - k.xxx), c.y);
+}
+
+fn gaussians_edit_base_color(index: u32, color: vec4<f32>) -> vec4<f32> {
+    let hsv_or_rgb = unpack4x8unorm(gaussians_edit[index].flag_hsv).yzw;
+
+    if gaussians_edit_flag_test(index, gaussian_edit_flag_override_color) {
+        return vec4<f32>(hsv_or_rgb, color.a);
+    }
+
+    let hsv = rgb_to_hsv(color.rgb);
+    let hsv_edit = hsv_or_rgb * vec3<f32>(1.0, 2.0, 2.0);
+    let hsv_edited = saturate(vec3<f32>(
+        (hsv.x + hsv_edit.x) % 1.0,
+        hsv.y * hsv_edit.y,
+        hsv.z * hsv_edit.z,
+    ));
+
+    return vec4<f32>(hsv_to_rgb(hsv_edited), color.a);
+}
+
+fn gaussians_edit_contr_expo_gamma_alpha_color(index: u32, color: vec4<f32>) -> vec4<f32> {
+    let unorms = unpack4x8unorm(gaussians_edit[index].contr_expo_gamma_alpha);
+
+    let contrast = unorms.x * 2.0 - 1.0;
+    const contrast_const = 259.0 / 255.0;
+    let contrast_factor = contrast_const * (contrast + 1.0) / (contrast_const - contrast);
+    let contrasted = (color.rgb - 0.5) * contrast_factor + 0.5;
+
+    let exposure = unorms.y * 10.0 - 5.0;
+    let exposed = contrasted * exp2(exposure);
+
+    let gamma = unorms.z * 5.0;
+    let gammaed = pow(exposed, vec3<f32>(gamma));
+
+    let alpha = unorms.w * 2.0;
+    let colored = vec4<f32>(gammaed, color.a * alpha);
+
+    return colored;
+}
+
+fn gaussians_edit_color(index: u32, color: vec4<f32>) -> vec4<f32> {
+    if !gaussians_edit_enabled(index) {
+        return color;
+    }
+
+    let base = gaussians_edit_base_color(index, color);
+    let edited = gaussians_edit_contr_expo_gamma_alpha_color(index, base);
+
+    return edited;
+}
+
+fn quad_offset(vert_index: u32) -> vec2<f32> {
+    switch vert_index {
+        case 0u { return vec2<f32>(1.0, -1.0); }
+        case 1u { return vec2<f32>(-1.0, -1.0); }
+        case 2u { return vec2<f32>(1.0, 1.0); }
+        case 3u { return vec2<f32>(-1.0, 1.0); }
+        case 4u { return vec2<f32>(1.0, 1.0); }
+        case 5u { return vec2<f32>(-1.0, -1.0); }
+        default { return vec2<f32>(0.0, 0.0); }
+    }
+}
+
+fn color(gaussian_index: u32, world_pos: vec3<f32>) -> vec4<f32> {
+    let selected = selection_at(gaussian_index);
+
+    if selected && selection_highlight.color.a == 1.0 {
+        let color = unpack4x8unorm(gaussians[gaussian_index].color);
+        return vec4<f32>(selection_highlight.color.rgb, color.a);
+    }
+
+    let world_camera_pos = -(transpose(mat3x3<f32>(
+        camera.view[0].xyz,
+        camera.view[1].xyz,
+        camera.view[2].xyz
+    )) * camera.view[3].xyz);
+    let world_view_dir = world_camera_pos - world_pos;
+    let model_view_dir = model_transform_inv_sr_mat() * world_view_dir;
+
+    let color = gaussian_color(
+        gaussian_index,
+        -normalize(model_view_dir),
+        gaussian_transform_sh_deg(),
+        gaussian_transform_no_sh0(),
+    );
+
+    if selected && selection_highlight.color.a > 0.0 {
+        return vec4<f32>(
+            mix(color.rgb, selection_highlight.color.rgb, selection_highlight.color.a),
+            color.a,
+        );
+    }
+
+    return color;
+}
+
+@vertex
+fn vert_main(
+    @builtin(vertex_index) vert_index: u32,
+    @builtin(instance_index) instance_index: u32,
+) -> FragmentInput {
+    var out: FragmentInput;
+
+    let gaussian_index = indirect_indices[instance_index];
+    let gaussian = gaussians[gaussian_index];
+
+    let world_pos = model_transform_mat() * vec4<f32>(gaussian.pos, 1.0);
+    let view_pos = camera.view * world_pos;
+    let proj_pos = camera.proj * view_pos;
+
+    let color = color(gaussian_index, world_pos.xyz);
+    let display_mode = gaussian_transform_display_mode();
+
+    if display_mode == gaussian_display_mode_point {
+        let quad_offset = quad_offset(vert_index) * point_size * gaussian_transform.size;
+        let aspect_ratio = camera_aspect_ratio();
+        let clip_pos = proj_pos.xy
+            + quad_offset * proj_pos.w * vec2<f32>(aspect_ratio, 1.0) / length(view_pos.xyz);
+
+        out.clip_pos = vec4<f32>(clip_pos, proj_pos.zw);
+        out.quad_offset = quad_offset;
+        out.color = color;
+        out.display_mode = display_mode;
+        out.index = gaussian_index;
+        out.coords = camera_coords(clip_pos / proj_pos.w);
+        out.depth = proj_pos.z / proj_pos.w;
+        
+        return out;
+    }
+    
+    // The splat is a circle facing the camera, so the screen axes are its axes
+    let variance = gaussian_variance2d(gaussian_index, view_pos.z);
+    let axis_len = min(max_radius * sqrt(variance), 1024.0) * gaussian_transform.size;
+
+    let quad_offset = quad_offset(vert_index) * max_radius;
+    let clip_pos = proj_pos.xy + quad_offset * proj_pos.w * axis_len / camera.size;
+
+    out.clip_pos = vec4<f32>(clip_pos, proj_pos.zw);
+    out.quad_offset = quad_offset;
+    out.color = color;
+    out.display_mode = display_mode;
+    out.index = gaussian_index;
+    out.coords = camera_coords(clip_pos / proj_pos.w);
+    out.depth = proj_pos.z / proj_pos.w;
+
+    return out;
+}
+
+// Fragment
+
+struct FragmentInput {
+    @location(0) quad_offset: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) display_mode: u32,
+    @location(3) @interpolate(flat) index: u32,
+    @location(4) coords: vec2<f32>,
+    @location(5) @interpolate(flat) depth: f32,
+
+    @builtin(position) clip_pos: vec4<f32>,
+}
+
+fn splat(in: FragmentInput) -> vec4<f32> {
+    let radius_sq = dot(in.quad_offset, in.quad_offset);
+    if radius_sq > max_radius * max_radius {
+        discard;
+    }
+
+    let alpha = in.color.a * exp(-radius_sq);
+    return vec4<f32>(in.color.rgb, alpha);
+}
+
+fn ellipse(in: FragmentInput) -> vec4<f32> {
+    let radius_sq = dot(in.quad_offset, in.quad_offset);
+    if radius_sq > max_radius * max_radius {
+        discard;
+    }
+
+    let is_outline = radius_sq > (max_radius - 0.1) * (max_radius - 0.1);
+    let alpha = in.color.a + (1.0 - in.color.a) * f32(is_outline);
+    return vec4<f32>(in.color.rgb, alpha);
+}
+
+fn point(in: FragmentInput) -> vec4<f32> {
+    return vec4<f32>(in.color.rgb, 1.0);
+}
+
+fn query_hit(in: FragmentInput, color: vec4<f32>) {
+    let coords = query.content_f32.xy;
+    let diff = coords - in.coords;
+
+    if dot(diff, diff) >= 1.0 {
+        return;
+    }
+
+    let index = atomicAdd(&query_result_count, 1u);
+    query_results[index] = QueryResult(
+        vec4<u32>(in.index, vec3<u32>(0u)),
+        vec4<f32>(in.depth, color.a, 0.0, 0.0),
+    );
+}
+
+@fragment
+fn frag_main(in: FragmentInput) -> @location(0) vec4<f32> {
+    var color: vec4<f32>;
+
+    if in.display_mode == gaussian_display_mode_splat {
+        color = splat(in);
+    } else if in.display_mode == gaussian_display_mode_ellipse {
+        color = ellipse(in);
+    } else if in.display_mode == gaussian_display_mode_point {
+        color = point(in);
+    }
+
+    if query_type() == query_type_hit {
+        query_hit(in, color);
+    }
+
+    return color;
+}
+
+{{gaussian_sh_unpack}}
+{{gaussian_cov3d_unpack}}
\ No newline at end of file
@@ -0,0 +1,74 @@
+// Vertex
+
+struct Marker {
+    pos: vec3<f32>,
+    color: u32,
+    right: vec3<f32>,
+    up: vec3<f32>,
+    forward: vec3<f32>,
+}
+@group(0) @binding(0)
+var<storage, read> markers: array<Marker>;
+
+struct Camera {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+    size: vec2<f32>,
+}
+@group(0) @binding(1)
+var<uniform> camera: Camera;
+
+// The frustum is drawn as a line list, the apex is (0, 0) and the base corners are (±1, ±1)
+const frustum_vertices = array<vec2<f32>, 22>(
+    // Apex to the base corners
+    vec2<f32>(0.0, 0.0), vec2<f32>(-1.0, -1.0),
+    vec2<f32>(0.0, 0.0), vec2<f32>(1.0, -1.0),
+    vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0),
+    vec2<f32>(0.0, 0.0), vec2<f32>(-1.0, 1.0),
+    // Base
+    vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0),
+    vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+    vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    vec2<f32>(-1.0, 1.0), vec2<f32>(-1.0, -1.0),
+    // Up triangle above the base
+    vec2<f32>(-0.5, 1.2), vec2<f32>(0.5, 1.2),
+    vec2<f32>(0.5, 1.2), vec2<f32>(0.0, 1.6),
+    vec2<f32>(0.0, 1.6), vec2<f32>(-0.5, 1.2),
+);
+
+@vertex
+fn vert_main(
+    @builtin(vertex_index) vert_index: u32,
+    @builtin(instance_index) instance_index: u32,
+) -> FragmentInput {
+    var out: FragmentInput;
+
+    let marker = markers[instance_index];
+
+    out.color = unpack4x8unorm(marker.color);
+
+    var frustum_vertices = frustum_vertices;
+    let vertex = frustum_vertices[vert_index];
+
+    var pos = marker.pos + marker.right * vertex.x + marker.up * vertex.y;
+    if vert_index % 2u == 1u || vert_index >= 8u {
+        pos += marker.forward;
+    }
+
+    out.clip_pos = camera.proj * camera.view * vec4<f32>(pos, 1.0);
+
+    return out;
+}
+
+// Fragment
+
+struct FragmentInput {
+    @location(0) color: vec4<f32>,
+
+    @builtin(position) clip_pos: vec4<f32>,
+}
+
+@fragment
+fn frag_main(in: FragmentInput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,29 @@
+struct Edit {
+    index: u32,
+    flag_hsv: u32,
+    contr_expo_gamma_alpha: u32,
+}
+@group(0) @binding(0)
+var<storage, read> restored: array<Edit>;
+
+@group(0) @binding(1)
+var<storage, read_write> edits: array<vec2<u32>>;
+
+const workgroup_size = 256u;
+
+@compute @workgroup_size(workgroup_size)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+
+    if index >= arrayLength(&restored) {
+        return;
+    }
+
+    let edit = restored[index];
+
+    if edit.index >= arrayLength(&edits) {
+        return;
+    }
+
+    edits[edit.index] = vec2<u32>(edit.flag_hsv, edit.contr_expo_gamma_alpha);
+}
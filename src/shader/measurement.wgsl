@@ -2,11 +2,15 @@
 
 const line_width_factor: f32 = 0.01;
 
+const line_width_mode_screen: u32 = 0u;
+
 struct HitPair {
     hit_0: vec3<f32>,
     color: u32,
     hit_1: vec3<f32>,
     line_width: f32,
+    endpoint_size: f32,
+    line_width_mode: u32,
 }
 @group(0) @binding(0)
 var<storage, read> hit_pairs: array<HitPair>;
@@ -44,9 +48,15 @@ fn vert_main(
         * camera.size
     );
 
+    // The half width in world units for the world mode
+    var half_width = hit_pair.line_width * 0.5;
+    if hit_pair.line_width_mode == line_width_mode_screen {
+        half_width = hit_pair.line_width * line_width_factor;
+    }
+
     let normal = vec2<f32>(dir.y, -dir.x);
     let normal_dir = f32(vert_index % 2u) * 2.0 - 1.0;
-    let normal_offset = normal * normal_dir * hit_pair.line_width * line_width_factor;
+    let normal_offset = normal * normal_dir * half_width;
 
     let pair_index = vert_index < 2u || vert_index == 3u;
     let pos_view = pos_view_pair[u32(pair_index)];
@@ -54,14 +64,19 @@ fn vert_main(
     let aspect_ratio = camera.size.y / camera.size.x;
 
     let parallel_dir = -(f32(pair_index) * 2.0 - 1.0);
-    let parallel_offset = dir * parallel_dir * hit_pair.line_width * line_width_factor;
+    let parallel_offset = dir * parallel_dir * half_width;
     let total_offset = normal_offset + parallel_offset;
 
-    out.clip_pos = pos_proj
-        + vec4<f32>(
-            total_offset * pos_proj.w * vec2<f32>(aspect_ratio, 1.0) / length(pos_view.xyz),
-            0.0, 0.0,
-        );
+    var clip_offset: vec2<f32>;
+    if hit_pair.line_width_mode == line_width_mode_screen {
+        clip_offset = total_offset * pos_proj.w * vec2<f32>(aspect_ratio, 1.0)
+            / length(pos_view.xyz);
+    } else {
+        // Project the world offset at the depth of the endpoint
+        clip_offset = total_offset * vec2<f32>(camera.proj[0][0], camera.proj[1][1]);
+    }
+
+    out.clip_pos = pos_proj + vec4<f32>(clip_offset, 0.0, 0.0);
 
     return out;
 }
@@ -77,4 +92,71 @@ struct FragmentInput {
 @fragment
 fn frag_main(in: FragmentInput) -> @location(0) vec4<f32> {
     return in.color;
-}
\ No newline at end of file
+}
+
+// Endpoint vertex
+
+@vertex
+fn vert_endpoint_main(
+    @builtin(vertex_index) vert_index: u32,
+    @builtin(instance_index) instance_index: u32,
+) -> EndpointFragmentInput {
+    var out: EndpointFragmentInput;
+
+    let hit_pair = hit_pairs[instance_index / 2u];
+
+    out.color = unpack4x8unorm(hit_pair.color);
+
+    var hit = hit_pair.hit_0;
+    if instance_index % 2u == 1u {
+        hit = hit_pair.hit_1;
+    }
+
+    let pos_view = camera.view * vec4<f32>(hit, 1.0);
+    let pos_proj = camera.proj * pos_view;
+    let aspect_ratio = camera.size.y / camera.size.x;
+
+    let corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(-1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+    );
+    let corner = corners[vert_index];
+
+    out.uv = corner;
+
+    let offset = corner * hit_pair.endpoint_size * line_width_factor;
+
+    out.clip_pos = pos_proj
+        + vec4<f32>(
+            offset * pos_proj.w * vec2<f32>(aspect_ratio, 1.0) / length(pos_view.xyz),
+            0.0, 0.0,
+        );
+
+    return out;
+}
+
+// Endpoint fragment
+
+struct EndpointFragmentInput {
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+
+    @builtin(position) clip_pos: vec4<f32>,
+}
+
+@fragment
+fn frag_endpoint_main(in: EndpointFragmentInput) -> @location(0) vec4<f32> {
+    let dist_sq = dot(in.uv, in.uv);
+    if dist_sq > 1.0 {
+        discard;
+    }
+
+    // Shade as a sphere lit from the camera
+    let shade = sqrt(1.0 - dist_sq) * 0.5 + 0.5;
+
+    return vec4<f32>(in.color.rgb * shade, in.color.a);
+}
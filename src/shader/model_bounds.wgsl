@@ -0,0 +1,56 @@
+struct Uniforms {
+    count: u32,
+    stride: u32,
+}
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(0) @binding(1)
+var<storage, read> gaussians: array<u32>;
+
+@group(0) @binding(2)
+var<storage, read_write> bounds: array<atomic<u32>, 6>;
+
+const workgroup_size = 256u;
+
+var<workgroup> local_bounds: array<atomic<u32>, 6>;
+
+// Map the float to an unsigned integer with the same order.
+fn order_key(value: f32) -> u32 {
+    let bits = bitcast<u32>(value);
+    return select(bits | 0x80000000u, ~bits, (bits >> 31u) == 1u);
+}
+
+@compute @workgroup_size(workgroup_size)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    if local_index < 3u {
+        atomicStore(&local_bounds[local_index], 0xffffffffu);
+    } else if local_index < 6u {
+        atomicStore(&local_bounds[local_index], 0u);
+    }
+
+    workgroupBarrier();
+
+    // The dispatch may be smaller than the Gaussian count
+    for (var index = id.x; index < uniforms.count; index += num_workgroups.x * workgroup_size) {
+        // The position is the first field of the Gaussian
+        let base = index * uniforms.stride;
+        for (var i = 0u; i < 3u; i++) {
+            let key = order_key(bitcast<f32>(gaussians[base + i]));
+            atomicMin(&local_bounds[i], key);
+            atomicMax(&local_bounds[i + 3u], key);
+        }
+    }
+
+    workgroupBarrier();
+
+    if local_index < 3u {
+        atomicMin(&bounds[local_index], atomicLoad(&local_bounds[local_index]));
+    } else if local_index < 6u {
+        atomicMax(&bounds[local_index], atomicLoad(&local_bounds[local_index]));
+    }
+}
@@ -0,0 +1,30 @@
+struct IndirectArgs {
+    vertex_count: u32,
+    instance_count: u32,
+    first_vertex: u32,
+    first_instance: u32,
+}
+@group(0) @binding(0)
+var<storage, read_write> indirect_args: IndirectArgs;
+
+struct Limit {
+    fraction: f32,
+    nearest: u32,
+}
+@group(0) @binding(1)
+var<uniform> limit: Limit;
+
+@compute @workgroup_size(1)
+fn main() {
+    // The preprocess resets the instance count to the culled Gaussians, but not the first instance
+    let count = indirect_args.instance_count;
+    let kept = min(u32(ceil(f32(count) * limit.fraction)), count);
+
+    // The Gaussians are sorted back to front, so the nearest are the last instances
+    if limit.nearest != 0u {
+        indirect_args.first_instance = count - kept;
+    } else {
+        indirect_args.first_instance = 0u;
+    }
+    indirect_args.instance_count = kept;
+}
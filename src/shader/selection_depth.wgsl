@@ -0,0 +1,110 @@
+@group(0) @binding(0)
+var<storage, read> query_result_count: u32;
+
+struct QueryResult {
+    content_u32: vec4<u32>,
+    content_f32: vec4<f32>,
+}
+@group(0) @binding(1)
+var<storage, read_write> query_results: array<QueryResult>;
+
+struct IndirectArgs {
+    vertex_count: u32,
+    instance_count: u32,
+    first_vertex: u32,
+    first_instance: u32,
+}
+@group(0) @binding(2)
+var<storage, read> indirect_args: IndirectArgs;
+
+@group(0) @binding(3)
+var<storage, read> indirect_indices: array<u32>;
+
+@group(0) @binding(4)
+var<storage, read> gaussians_depth: array<f32>;
+
+struct Camera {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+    size: vec2<f32>,
+}
+@group(0) @binding(5)
+var<uniform> camera: Camera;
+
+@group(0) @binding(6)
+var<uniform> tolerance: f32;
+
+@group(0) @binding(7)
+var<storage, read_write> depths: array<f32>;
+
+struct State {
+    nearest_depth: atomic<u32>,
+    nearest_index: atomic<u32>,
+}
+@group(0) @binding(8)
+var<storage, read_write> state: State;
+
+const workgroup_size = 256u;
+
+@compute @workgroup_size(1)
+fn pre_main() {
+    atomicStore(&state.nearest_depth, bitcast<u32>(3.4e38));
+}
+
+// Scatter the view depths from the draw order to the Gaussian indices
+@compute @workgroup_size(workgroup_size)
+fn depth_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+
+    if index >= indirect_args.instance_count {
+        return;
+    }
+
+    // The viewer keeps 1 - NDC depth for sorting, which is linearized by the projection
+    let ndc_depth = 1.0 - gaussians_depth[index];
+    depths[indirect_indices[index]] = camera.proj[3][2] / (ndc_depth + camera.proj[2][2]);
+}
+
+// Find the nearest depth, the view depths are positive so their bits are ordered like unsigned
+// integers
+@compute @workgroup_size(workgroup_size)
+fn nearest_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+
+    if index >= query_result_count {
+        return;
+    }
+
+    atomicMin(&state.nearest_depth, bitcast<u32>(depths[query_results[index].content_u32.x]));
+}
+
+// Find a Gaussian at the nearest depth
+@compute @workgroup_size(workgroup_size)
+fn nearest_index_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+
+    if index >= query_result_count {
+        return;
+    }
+
+    let gaussian_index = query_results[index].content_u32.x;
+    if bitcast<u32>(depths[gaussian_index]) == atomicLoad(&state.nearest_depth) {
+        atomicStore(&state.nearest_index, gaussian_index);
+    }
+}
+
+// Redirect the query results beyond the depth window to the nearest Gaussian, which is always
+// selected, so the results need not be compacted
+@compute @workgroup_size(workgroup_size)
+fn filter_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+
+    if index >= query_result_count {
+        return;
+    }
+
+    let nearest = bitcast<f32>(atomicLoad(&state.nearest_depth));
+    if depths[query_results[index].content_u32.x] > nearest + tolerance {
+        query_results[index].content_u32.x = atomicLoad(&state.nearest_index);
+    }
+}
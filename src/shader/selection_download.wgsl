@@ -0,0 +1,18 @@
+@group(0) @binding(0)
+var<storage, read> source: array<u32>;
+
+@group(0) @binding(1)
+var<storage, read_write> dest: array<u32>;
+
+const workgroup_size = 256u;
+
+@compute @workgroup_size(workgroup_size)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+
+    if index >= arrayLength(&dest) {
+        return;
+    }
+
+    dest[index] = source[index];
+}
@@ -0,0 +1,147 @@
+struct ToneMapping {
+    exposure: f32,
+    op: u32,
+    gamma_correction: u32,
+    fxaa: u32,
+}
+@group(0) @binding(0)
+var<uniform> tone_mapping: ToneMapping;
+
+@group(0) @binding(1)
+var color_texture: texture_2d<f32>;
+
+@group(0) @binding(2)
+var color_sampler: sampler;
+
+const op_none = 0u;
+const op_reinhard = 1u;
+const op_aces = 2u;
+
+const fxaa_reduce_min = 1.0 / 128.0;
+const fxaa_reduce_mul = 1.0 / 8.0;
+const fxaa_span_max = 8.0;
+
+struct VertexOutput {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vert_main(@builtin(vertex_index) vert_index: u32) -> VertexOutput {
+    // Fullscreen triangle
+    let uv = vec2<f32>(f32((vert_index << 1u) & 2u), f32(vert_index & 2u));
+
+    var out: VertexOutput;
+    out.pos = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+fn reinhard(color: vec3<f32>) -> vec3<f32> {
+    return color / (1.0 + color);
+}
+
+// Narkowicz's fit of the ACES filmic curve
+fn aces(color: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+@fragment
+fn frag_copy(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(color_texture, color_sampler, in.uv);
+}
+
+// The tone mapped color before the gamma correction
+fn tone_mapped(uv: vec2<f32>) -> vec3<f32> {
+    // The rendered colors are sRGB encoded
+    var color = srgb_to_linear(textureSampleLevel(color_texture, color_sampler, uv, 0.0).rgb);
+
+    color *= tone_mapping.exposure;
+
+    if tone_mapping.op == op_reinhard {
+        color = reinhard(color);
+    } else if tone_mapping.op == op_aces {
+        color = aces(color);
+    }
+
+    return clamp(color, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn luma(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+// FXAA on the tone mapped colors, blurring along the edge found from the luma of the corners
+fn fxaa(uv: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(color_texture));
+
+    let color_m = tone_mapped(uv);
+    let luma_nw = luma(tone_mapped(uv + vec2<f32>(-1.0, -1.0) * texel));
+    let luma_ne = luma(tone_mapped(uv + vec2<f32>(1.0, -1.0) * texel));
+    let luma_sw = luma(tone_mapped(uv + vec2<f32>(-1.0, 1.0) * texel));
+    let luma_se = luma(tone_mapped(uv + vec2<f32>(1.0, 1.0) * texel));
+    let luma_m = luma(color_m);
+
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    var dir = vec2<f32>(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se),
+    );
+    let dir_reduce = max(
+        (luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * fxaa_reduce_mul,
+        fxaa_reduce_min,
+    );
+    let dir_scale = 1.0 / (min(abs(dir.x), abs(dir.y)) + dir_reduce);
+    dir = clamp(dir * dir_scale, vec2<f32>(-fxaa_span_max), vec2<f32>(fxaa_span_max)) * texel;
+
+    let color_a = 0.5 * (
+        tone_mapped(uv + dir * (1.0 / 3.0 - 0.5))
+        + tone_mapped(uv + dir * (2.0 / 3.0 - 0.5))
+    );
+    let color_b = color_a * 0.5 + 0.25 * (
+        tone_mapped(uv - dir * 0.5)
+        + tone_mapped(uv + dir * 0.5)
+    );
+
+    // The wider blur went past the edge
+    let luma_b = luma(color_b);
+    if luma_b < luma_min || luma_b > luma_max {
+        return color_a;
+    }
+    return color_b;
+}
+
+@fragment
+fn frag_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color: vec3<f32>;
+    if tone_mapping.fxaa != 0u {
+        color = fxaa(in.uv);
+    } else {
+        color = tone_mapped(in.uv);
+    }
+
+    if tone_mapping.gamma_correction != 0u {
+        color = linear_to_srgb(color);
+    }
+
+    return vec4<f32>(color, 1.0);
+}
@@ -1,4 +1,7 @@
-use crate::app;
+use glam::*;
+use strum::IntoEnumIterator;
+
+use crate::{app, util};
 
 use super::Tab;
 
@@ -70,7 +73,8 @@ impl Tab for Camera {
                         "• Hold left mouse button to rotate around the target\n\
                          • Hold right mouse button to pan\n\
                          • Hold middle mouse button to look around\n\
-                         • Scroll to zoom in/out"
+                         • Scroll to zoom in/out\n\
+                         • Arrow keys to rotate around the target by steps"
                     );
                     value!(
                         ui,
@@ -121,12 +125,13 @@ impl Tab for Camera {
                                 $ui.horizontal(|ui| {
                                     ui.spacing_mut().item_spacing.x /= 2.0;
 
-                                    ui.label($axis);
-                                    ui.add(
+                                    util::axis_label(ui, $axis);
+                                    let response = ui.add(
                                         egui::DragValue::new(&mut $value)
                                             .speed(0.01)
                                             .fixed_decimals(4),
                                     );
+                                    util::reset_menu(response, &mut $value, 0.0);
                                 });
                             };
                         }
@@ -145,12 +150,13 @@ impl Tab for Camera {
                             $ui.horizontal(|ui| {
                                 ui.spacing_mut().item_spacing.x /= 2.0;
 
-                                ui.label($axis);
-                                ui.add(
+                                util::axis_label(ui, $axis);
+                                let response = ui.add(
                                     egui::DragValue::new(&mut $value)
                                         .speed(0.01)
                                         .fixed_decimals(4),
                                 );
+                                util::reset_menu(response, &mut $value, 0.0);
                             });
                         };
                     }
@@ -171,10 +177,185 @@ impl Tab for Camera {
                 ui.add(egui::Slider::new(&mut camera.speed, 0.0..=10.0).fixed_decimals(2));
                 ui.end_row();
 
+                let label = ui.label("Scene Scale").on_hover_text(
+                    "The scale of the first person movement, enable to fit it to the size of the \
+                    models when they load, the orbit control is already relative to the distance",
+                );
+                ui.horizontal(|ui| {
+                    ui.add(util::toggle(&mut camera.auto_scale))
+                        .labelled_by(label.id);
+
+                    let speed = camera.scale * 0.01;
+                    ui.add(
+                        egui::DragValue::new(&mut camera.scale)
+                            .speed(speed)
+                            .range(1e-6..=f32::INFINITY)
+                            .max_decimals(4),
+                    );
+                });
+                ui.end_row();
+
                 ui.label("Rotation Sensitivity");
                 ui.add(egui::Slider::new(&mut camera.sensitivity, 0.0..=1.0).fixed_decimals(2));
                 ui.end_row();
+
+                ui.label("Look Smoothing").on_hover_text(
+                    "The smoothing of the first person mouse-look over the next few frames, 0 \
+                    for raw mouse input",
+                );
+                ui.add(egui::Slider::new(&mut camera.smoothing, 0.0..=1.0).fixed_decimals(2));
+                ui.end_row();
+
+                if let app::CameraControl::Orbit(..) = camera.control {
+                    let limits = &mut camera.distance_limits;
+
+                    ui.label("Min Distance").on_hover_text(
+                        "The closest the orbit camera can zoom to the target, fitted to the \
+                        models when they load",
+                    );
+                    let speed = limits.min * 0.01;
+                    let range = 1e-6..=limits.max.unwrap_or(f32::INFINITY);
+                    ui.add(
+                        egui::DragValue::new(&mut limits.min)
+                            .speed(speed)
+                            .range(range),
+                    );
+                    ui.end_row();
+
+                    ui.label("Max Distance").on_hover_text(
+                        "The farthest the orbit camera can zoom from the target, fitted to the \
+                        models when they load",
+                    );
+                    ui.horizontal(|ui| {
+                        let mut limited = limits.max.is_some();
+                        ui.checkbox(&mut limited, "");
+
+                        let mut max = limits.max.unwrap_or(limits.min * 1e4);
+                        let speed = max * 0.01;
+                        ui.add_enabled(
+                            limited,
+                            egui::DragValue::new(&mut max)
+                                .speed(speed)
+                                .range(limits.min..=f32::INFINITY),
+                        );
+
+                        limits.max = limited.then_some(max);
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+
+        ui.separator();
+
+        self.look_at(ui, &mut state.gs);
+
+        ui.separator();
+
+        self.zoom(ui, &mut state.settings.zoom);
+
+        egui::Grid::new("camera_keys_grid").show(ui, |ui| {
+            ui.label("Orbit Key Step").on_hover_text(
+                "The rotation of the orbit control on each press of the arrow keys while the \
+                pointer is over the viewport, scaled by the rotation sensitivity",
+            );
+            ui.add(
+                egui::Slider::new(&mut state.settings.orbit_key_step, 1.0..=45.0)
+                    .suffix("°")
+                    .fixed_decimals(0),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+impl Camera {
+    /// Create the look at actions.
+    fn look_at(
+        &mut self,
+        ui: &mut egui::Ui,
+        gs: &mut app::Loadable<app::GaussianSplatting, String>,
+    ) {
+        let gs = match gs {
+            app::Loadable::Loaded(gs) => Some(gs),
+            app::Loadable::Unloaded { .. } => None,
+        };
+
+        egui::Grid::new("camera_look_at_grid").show(ui, |ui| {
+            ui.label("Look At").on_hover_text(
+                "Turn the camera to look at the point while keeping its position, the orbit \
+                target is moved to the point",
+            );
+            ui.add_enabled_ui(gs.is_some(), |ui| {
+                ui.horizontal(|ui| {
+                    let origin = ui.button("Origin");
+                    let model = ui
+                        .button("Model Center")
+                        .on_hover_text("The center of the selected model");
+                    let selection = ui.button("Selection Center").on_hover_text(
+                        "The center of the selected Gaussians of the selected model",
+                    );
+
+                    let Some(gs) = gs else {
+                        return;
+                    };
+
+                    if origin.clicked() {
+                        gs.camera.look_at(Vec3::ZERO);
+                    } else if model.clicked() {
+                        let center = gs.selected_model().world_center();
+                        gs.camera.look_at(center);
+                    } else if selection.clicked() {
+                        gs.scene_tx
+                            .send(app::SceneCommand::LookAtSelection(
+                                gs.selected_model_key.clone(),
+                            ))
+                            .expect("send look at selection");
+                    }
+                });
+            });
+            ui.end_row();
+        });
+    }
+
+    /// Create the scroll wheel zoom settings.
+    fn zoom(&mut self, ui: &mut egui::Ui, zoom: &mut app::CameraZoom) {
+        egui::Grid::new("camera_zoom_grid").show(ui, |ui| {
+            ui.label("Zoom Speed")
+                .on_hover_text("The scroll wheel zoom speed of the orbit control");
+            ui.add(
+                egui::Slider::new(&mut zoom.speed, 0.01..=10.0)
+                    .logarithmic(true)
+                    .fixed_decimals(2),
+            );
+            ui.end_row();
+
+            let label = ui.label("Invert Scroll");
+            ui.add(util::toggle(&mut zoom.invert)).labelled_by(label.id);
+            ui.end_row();
+
+            let label = ui.label("Zoom to Cursor").on_hover_text(
+                "Zoom toward the point under the cursor instead of the orbit target",
+            );
+            ui.add(util::toggle(&mut zoom.to_cursor))
+                .labelled_by(label.id);
+            ui.end_row();
+
+            ui.label("Zoom Mode");
+            ui.horizontal(|ui| {
+                for mode in app::CameraZoomMode::iter() {
+                    ui.selectable_value(&mut zoom.mode, mode, mode.to_string())
+                        .on_hover_text(match mode {
+                            app::CameraZoomMode::Proportional => {
+                                "The zoom step is proportional to the distance to the target"
+                            }
+                            app::CameraZoomMode::Logarithmic => {
+                                "The zoom slows down smoothly when approaching the target"
+                            }
+                        });
+                }
             });
+            ui.end_row();
         });
     }
 }
@@ -0,0 +1,144 @@
+use std::sync::mpsc;
+
+use glam::*;
+
+use crate::app;
+
+use super::Tab;
+
+/// The inspector tab.
+#[derive(Debug)]
+pub struct Inspector;
+
+impl Tab for Inspector {
+    fn create(_state: &mut app::State) -> Self
+    where
+        Self: Sized,
+    {
+        Self
+    }
+
+    fn title(&mut self, _frame: &mut eframe::Frame, _state: &mut app::State) -> egui::WidgetText {
+        "Inspector".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame, state: &mut app::State) {
+        let app::Loadable::Loaded(gs) = &mut state.gs else {
+            ui.add_enabled(false, egui::Button::new("Pick"));
+            return;
+        };
+
+        ui.spacing_mut().item_spacing = egui::vec2(ui.spacing().item_spacing.x, 12.0);
+
+        ui.horizontal(|ui| {
+            match gs.action {
+                Some(app::Action::PickGaussian { .. }) => {
+                    if ui.button("Picking...").clicked() {
+                        gs.action = None;
+                    }
+                }
+                _ => {
+                    if ui
+                        .button("Pick")
+                        .on_hover_text("Click a Gaussian of the selected model to inspect")
+                        .clicked()
+                    {
+                        let (tx, rx) = mpsc::channel();
+                        gs.action = Some(app::Action::PickGaussian { tx, rx });
+                    }
+                }
+            }
+
+            if ui
+                .add_enabled(gs.picked_gaussian.is_some(), egui::Button::new("Clear"))
+                .clicked()
+            {
+                gs.picked_gaussian = None;
+            }
+        });
+
+        let Some((picked, model)) = gs.picked_gaussian.as_ref().and_then(|picked| {
+            gs.models
+                .get(&picked.key)
+                .map(|model| (picked, model))
+                .filter(|(picked, model)| picked.index < model.gaussians.gaussians.len())
+        }) else {
+            ui.label("No Gaussian picked");
+            return;
+        };
+
+        let gaussian = &model.gaussians.gaussians[picked.index];
+        let transform = &model.transform;
+
+        egui::Grid::new("inspector_grid").show(ui, |ui| {
+            ui.label("Model");
+            ui.label(&picked.key);
+            ui.end_row();
+
+            ui.label("Index");
+            ui.label(picked.index.to_string());
+            ui.end_row();
+
+            ui.label("Position");
+            ui.label(vec3_text(gaussian.pos));
+            ui.end_row();
+
+            ui.label("World Position");
+            ui.label(vec3_text(
+                transform.quat() * (gaussian.pos * transform.scale) + transform.pos,
+            ));
+            ui.end_row();
+
+            ui.label("Scale");
+            ui.label(vec3_text(gaussian.scale));
+            ui.end_row();
+
+            ui.label("Rotation")
+                .on_hover_text("The rotation quaternion in (x, y, z, w)");
+            ui.label(format!(
+                "({:.3}, {:.3}, {:.3}, {:.3})",
+                gaussian.rotation.x, gaussian.rotation.y, gaussian.rotation.z, gaussian.rotation.w
+            ));
+            ui.end_row();
+
+            ui.label("Opacity");
+            ui.label(format!("{:.3}", gaussian.color.w as f32 / 255.0));
+            ui.end_row();
+
+            ui.label("Color");
+            ui.horizontal(|ui| {
+                let [r, g, b, _] = gaussian.color.to_array();
+                let color = egui::Color32::from_rgb(r, g, b);
+                let (rect, response) = ui.allocate_exact_size(
+                    egui::vec2(ui.spacing().interact_size.y, ui.spacing().interact_size.y),
+                    egui::Sense::hover(),
+                );
+                response.widget_info(|| {
+                    egui::WidgetInfo::labeled(egui::WidgetType::Other, true, "Color swatch")
+                });
+                ui.painter().rect_filled(rect, 2.0, color);
+                ui.label(format!("({r}, {g}, {b})"));
+            });
+            ui.end_row();
+        });
+
+        egui::CollapsingHeader::new("Spherical Harmonics")
+            .id_salt("inspector_sh")
+            .show(ui, |ui| {
+                egui::Grid::new("inspector_sh_grid").show(ui, |ui| {
+                    for (i, sh) in gaussian.sh.iter().enumerate() {
+                        // The degree 0 coefficient is the color
+                        let degree = (i + 1).isqrt();
+                        ui.label(format!("Coefficient {} (Degree {degree})", i + 1));
+                        ui.label(vec3_text(*sh));
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+}
+
+/// Format a [`Vec3`] for display.
+fn vec3_text(v: Vec3) -> String {
+    format!("({:.3}, {:.3}, {:.3})", v.x, v.y, v.z)
+}
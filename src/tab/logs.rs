@@ -0,0 +1,88 @@
+use itertools::Itertools;
+
+use crate::{app, diagnostics};
+
+use super::Tab;
+
+/// The logs tab.
+#[derive(Debug)]
+pub struct Logs {
+    /// The least severe level to show.
+    level: log::LevelFilter,
+}
+
+impl Tab for Logs {
+    fn create(_state: &mut app::State) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            level: log::LevelFilter::Info,
+        }
+    }
+
+    fn title(&mut self, _frame: &mut eframe::Frame, _state: &mut app::State) -> egui::WidgetText {
+        "Logs".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame, _state: &mut app::State) {
+        // Collected before drawing, since drawing may log and the log is locked while reading
+        let entries = diagnostics::log_entries(self.level);
+
+        ui.horizontal(|ui| {
+            ui.label("Level");
+            egui::ComboBox::from_id_salt("logs_level")
+                .selected_text(self.level.to_string())
+                .show_ui(ui, |ui| {
+                    for level in log::LevelFilter::iter().skip(1) {
+                        ui.selectable_value(&mut self.level, level, level.to_string());
+                    }
+                });
+
+            if ui
+                .button("📋 Copy")
+                .on_hover_text("Copy the shown entries")
+                .clicked()
+            {
+                ui.ctx()
+                    .copy_text(entries.iter().map(|entry| entry.to_string()).join("\n"));
+            }
+
+            if ui.button("🗑 Clear").clicked() {
+                diagnostics::clear_log();
+            }
+        });
+
+        ui.separator();
+
+        if entries.is_empty() {
+            ui.weak("No log entries");
+            return;
+        }
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, entries.len(), |ui, rows| {
+                for entry in &entries[rows] {
+                    let color = match entry.level {
+                        log::Level::Error => ui.visuals().error_fg_color,
+                        log::Level::Warn => ui.visuals().warn_fg_color,
+                        log::Level::Info => ui.visuals().text_color(),
+                        log::Level::Debug | log::Level::Trace => ui.visuals().weak_text_color(),
+                    };
+
+                    ui.add(
+                        egui::Label::new(
+                            egui::RichText::new(entry.to_string())
+                                .monospace()
+                                .color(color),
+                        )
+                        .extend(),
+                    )
+                    .on_hover_text(&entry.target);
+                }
+            });
+    }
+}
@@ -1,24 +1,30 @@
-use std::sync::mpsc;
+use std::{ops::Range, sync::mpsc};
 
 use glam::*;
+use num_format::ToFormattedString;
 use wgpu_3dgs_viewer as gs;
 
 use super::Tab;
 
-use crate::app;
+use crate::{app, util};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ShapeChanged {
     Unchanged,
     Removed,
     Updated,
+    Duplicated,
+    Selected,
 }
 
 /// The mask tab.
 #[derive(Debug)]
 pub struct Mask {
     /// Op code error.
-    op_code_error: Option<String>,
+    op_code_error: Option<app::MaskOpParseError>,
+
+    /// The step of the shape values when dragging and nudging.
+    step: f32,
 }
 
 impl Tab for Mask {
@@ -28,6 +34,7 @@ impl Tab for Mask {
     {
         Self {
             op_code_error: None,
+            step: 0.01,
         }
     }
 
@@ -40,18 +47,23 @@ impl Tab for Mask {
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame, state: &mut crate::app::State) {
-        let (mask, scene_tx, ui_builder) = match &mut state.gs {
-            app::Loadable::Loaded(gs) => (
-                &mut gs
+        let (mask, count, scene_tx, ui_builder) = match &mut state.gs {
+            app::Loadable::Loaded(gs) => {
+                let model = gs
                     .models
                     .get_mut(&gs.selected_model_key)
-                    .expect("selected model")
-                    .mask,
-                &gs.scene_tx,
-                egui::UiBuilder::new(),
-            ),
+                    .expect("selected model");
+                let count = model.gaussians.gaussians.capacity();
+                let ui_builder = match model.locked {
+                    true => egui::UiBuilder::new().disabled(),
+                    false => egui::UiBuilder::new(),
+                };
+
+                (&mut model.mask, count, &gs.scene_tx, ui_builder)
+            }
             app::Loadable::Unloaded { .. } => (
                 &mut app::GaussianSplattingMask::new(),
+                0,
                 &mpsc::channel().0,
                 egui::UiBuilder::new().disabled(),
             ),
@@ -60,7 +72,30 @@ impl Tab for Mask {
         ui.spacing_mut().item_spacing = egui::vec2(ui.spacing().item_spacing.x, 12.0);
 
         ui.scope_builder(ui_builder, |ui| {
-            ui.label(egui::RichText::new("Shapes").strong());
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Shapes").strong());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.add(util::toggle(&mut mask.gizmos_visible))
+                        .on_hover_text(
+                            "Show the gizmos of the shapes, the mask applies either way",
+                        );
+                    ui.label("Gizmos");
+
+                    ui.separator();
+
+                    ui.add(
+                        egui::DragValue::new(&mut self.step)
+                            .range(1e-4..=10.0)
+                            .speed(0.001)
+                            .max_decimals(4),
+                    )
+                    .on_hover_text(
+                        "The step of the shape values when dragging and nudging, \
+                        Shift nudges by 10 steps",
+                    );
+                    ui.label("Step");
+                });
+            });
             self.shapes(ui, mask, scene_tx);
 
             ui.separator();
@@ -76,6 +111,10 @@ impl Tab for Mask {
                      • `0 | 1` - Union of shape 0 and 1",
                 );
             self.operations(ui, mask, scene_tx);
+
+            ui.separator();
+
+            self.coverage(ui, mask, count);
         });
     }
 }
@@ -90,8 +129,10 @@ impl Mask {
     ) {
         let mut updated = false;
         let mut removed = Vec::new();
+        let mut duplicated = Vec::new();
+        let mut selected = mask.selected;
         for (index, shape) in mask.shapes.iter_mut().enumerate() {
-            match self.shape(ui, index, shape) {
+            match self.shape(ui, index, shape, selected == Some(index)) {
                 ShapeChanged::Removed => {
                     removed.push(index);
                     updated = true;
@@ -99,149 +140,265 @@ impl Mask {
                 ShapeChanged::Updated => {
                     updated = true;
                 }
+                ShapeChanged::Duplicated => {
+                    duplicated.push(shape.duplicate());
+                    updated = true;
+                }
+                ShapeChanged::Selected => {
+                    selected = match selected == Some(index) {
+                        true => None,
+                        false => Some(index),
+                    };
+                    updated = true;
+                }
                 _ => {}
             }
         }
 
         for index in removed.into_iter().rev() {
             mask.shapes.remove(index);
+
+            selected = match selected {
+                Some(selected) if selected == index => None,
+                Some(selected) if selected > index => Some(selected - 1),
+                selected => selected,
+            };
         }
 
+        mask.selected = selected;
+
+        // Nudge
+        if let Some(shape) = selected.and_then(|index| mask.shapes.get_mut(index)) {
+            if ui.ui_contains_pointer() && !ui.ctx().wants_keyboard_input() {
+                let delta = Self::nudge_delta(ui, self.step);
+                if delta != Vec3::ZERO {
+                    shape.shape.pos += delta;
+                    updated = true;
+                }
+            }
+        }
+
+        mask.shapes.extend(duplicated);
+
         if ui.button("➕ Add Shape").clicked() {
             mask.shapes.push(app::GaussianSplattingMaskShape::new());
 
             updated = true;
         }
 
+        // Paste
+        if ui.ui_contains_pointer() && !ui.ctx().wants_keyboard_input() {
+            let pasted = ui.ctx().input(|input| {
+                input
+                    .events
+                    .iter()
+                    .filter_map(|event| match event {
+                        egui::Event::Paste(text) => {
+                            app::GaussianSplattingMaskShape::from_json(text)
+                                .inspect_err(|e| log::warn!("Invalid pasted mask shape: {e}"))
+                                .ok()
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            if !pasted.is_empty() {
+                mask.shapes.extend(pasted);
+                updated = true;
+            }
+        }
+
         if updated {
             self.update_mask(mask, scene_tx);
         }
     }
 
+    /// Get the nudge of the pressed arrow and page keys, consuming them.
+    fn nudge_delta(ui: &egui::Ui, step: f32) -> Vec3 {
+        ui.input_mut(|input| {
+            [
+                (egui::Key::ArrowLeft, Vec3::NEG_X),
+                (egui::Key::ArrowRight, Vec3::X),
+                (egui::Key::ArrowDown, Vec3::NEG_Y),
+                (egui::Key::ArrowUp, Vec3::Y),
+                (egui::Key::PageDown, Vec3::NEG_Z),
+                (egui::Key::PageUp, Vec3::Z),
+            ]
+            .into_iter()
+            .map(|(key, direction)| {
+                let count = input.count_and_consume_key(egui::Modifiers::NONE, key) as f32
+                    + input.count_and_consume_key(egui::Modifiers::SHIFT, key) as f32 * 10.0;
+                direction * count * step
+            })
+            .sum()
+        })
+    }
+
     /// Create the UI for a shape.
     fn shape(
         &mut self,
         ui: &mut egui::Ui,
         index: usize,
         shape: &mut app::GaussianSplattingMaskShape,
+        selected: bool,
     ) -> ShapeChanged {
-        egui::CollapsingHeader::new(format!("{index}. {:?}", shape.shape.kind))
-            .id_salt(format!("mask_{index}"))
-            .show(ui, |ui| {
-                egui::Grid::new(format!("mask_{index}_grid"))
-                    .show(ui, |ui| {
-                        let mut changed = ShapeChanged::Unchanged;
-
-                        ui.label("Shape Type");
-                        ui.horizontal(|ui| {
-                            macro_rules! value {
-                                ($ui:expr, $value:expr, $label:ident) => {
-                                    if $ui
-                                        .selectable_label(
-                                            shape.shape.kind == gs::MaskShapeKind::$label,
-                                            stringify!($label),
-                                        )
-                                        .clicked()
-                                    {
-                                        changed = ShapeChanged::Updated;
-                                        shape.shape.kind = gs::MaskShapeKind::$label;
-                                    }
-                                };
-                            }
+        let step = self.step;
+        let decimals = (-step.log10()).ceil().max(0.0) as usize + 2;
+
+        egui::CollapsingHeader::new(format!(
+            "{index}. {:?}{}",
+            shape.shape.kind,
+            if selected { " ✥" } else { "" }
+        ))
+        .id_salt(format!("mask_{index}"))
+        .show(ui, |ui| {
+            egui::Grid::new(format!("mask_{index}_grid"))
+                .show(ui, |ui| {
+                    let mut changed = ShapeChanged::Unchanged;
+
+                    ui.label("Shape Type");
+                    ui.horizontal(|ui| {
+                        macro_rules! value {
+                            ($ui:expr, $value:expr, $label:ident) => {
+                                if $ui
+                                    .selectable_label(
+                                        shape.shape.kind == gs::MaskShapeKind::$label,
+                                        stringify!($label),
+                                    )
+                                    .clicked()
+                                {
+                                    changed = ShapeChanged::Updated;
+                                    shape.shape.kind = gs::MaskShapeKind::$label;
+                                }
+                            };
+                        }
 
-                            value!(ui, shape.shape.kind, Box);
-                            value!(ui, shape.shape.kind, Ellipsoid);
-                        });
-                        ui.end_row();
+                        value!(ui, shape.shape.kind, Box);
+                        value!(ui, shape.shape.kind, Ellipsoid);
+                    });
+                    ui.end_row();
 
-                        ui.label("Color");
-                        ui.horizontal(|ui| {
-                            let mut ui_builder = egui::UiBuilder::new();
-                            if !shape.visible {
-                                ui_builder = ui_builder.disabled();
-                            }
+                    ui.label("Color");
+                    ui.horizontal(|ui| {
+                        let mut ui_builder = egui::UiBuilder::new();
+                        if !shape.visible {
+                            ui_builder = ui_builder.disabled();
+                        }
 
-                            ui.scope_builder(ui_builder, |ui| {
-                                let color_u8 = shape.shape.color.map(|c| c * 255.0).as_u8vec4();
-                                let mut color_32 = egui::Color32::from_rgba_premultiplied(
-                                    color_u8.x, color_u8.y, color_u8.z, color_u8.w,
-                                );
-                                if ui.color_edit_button_srgba(&mut color_32).changed() {
-                                    changed = ShapeChanged::Updated;
-                                    shape.shape.color =
-                                        U8Vec4::from_array(color_32.to_array()).as_vec4() / 255.0;
-                                }
-                            });
-                            if ui.checkbox(&mut shape.visible, "Visible").changed() {
+                        ui.scope_builder(ui_builder, |ui| {
+                            let color_u8 = shape.shape.color.map(|c| c * 255.0).as_u8vec4();
+                            let mut color_32 = egui::Color32::from_rgba_premultiplied(
+                                color_u8.x, color_u8.y, color_u8.z, color_u8.w,
+                            );
+                            if ui.color_edit_button_srgba(&mut color_32).changed() {
                                 changed = ShapeChanged::Updated;
+                                shape.shape.color =
+                                    U8Vec4::from_array(color_32.to_array()).as_vec4() / 255.0;
                             }
                         });
-                        ui.end_row();
-
-                        macro_rules! value {
-                            ($ui:expr, $axis:expr, $value:expr) => {
-                                $ui.horizontal(|ui| {
-                                    ui.spacing_mut().item_spacing.x /= 2.0;
-
-                                    ui.label($axis);
-                                    if ui
-                                        .add(
-                                            egui::DragValue::new(&mut $value)
-                                                .speed(0.01)
-                                                .fixed_decimals(4),
-                                        )
-                                        .changed()
-                                    {
-                                        changed = ShapeChanged::Updated;
-                                    }
-                                });
-                            };
+                        if ui.checkbox(&mut shape.visible, "Visible").changed() {
+                            changed = ShapeChanged::Updated;
                         }
-
-                        ui.label("Position");
-                        ui.horizontal(|ui| {
-                            value!(ui, "X", shape.shape.pos.x);
-                            value!(ui, "Y", shape.shape.pos.y);
-                            value!(ui, "Z", shape.shape.pos.z);
-                        });
-                        ui.end_row();
-
-                        ui.label("Rotation");
-                        ui.horizontal(|ui| {
-                            value!(ui, "X", shape.rot.x);
-                            value!(ui, "Y", shape.rot.y);
-                            value!(ui, "Z", shape.rot.z);
-
-                            if changed == ShapeChanged::Updated {
-                                shape.shape.rotation = Quat::from_euler(
-                                    EulerRot::ZYX,
-                                    shape.rot.z.to_radians(),
-                                    shape.rot.x.to_radians(),
-                                    shape.rot.y.to_radians(),
+                    });
+                    ui.end_row();
+
+                    macro_rules! value {
+                        ($ui:expr, $axis:expr, $value:expr, $default:expr) => {
+                            $ui.horizontal(|ui| {
+                                ui.spacing_mut().item_spacing.x /= 2.0;
+
+                                util::axis_label(ui, $axis);
+                                let response = ui.add(
+                                    egui::DragValue::new(&mut $value)
+                                        .speed(step)
+                                        .fixed_decimals(decimals),
                                 );
-                            }
-                        });
-                        ui.end_row();
-
-                        ui.label("Scale");
-                        ui.horizontal(|ui| {
-                            value!(ui, "X", shape.shape.scale.x);
-                            value!(ui, "Y", shape.shape.scale.y);
-                            value!(ui, "Z", shape.shape.scale.z);
-                        });
-                        ui.end_row();
+                                if util::reset_menu(response, &mut $value, $default).changed() {
+                                    changed = ShapeChanged::Updated;
+                                }
+                            });
+                        };
+                    }
 
+                    ui.label("Position");
+                    ui.horizontal(|ui| {
+                        value!(ui, "X", shape.shape.pos.x, 0.0);
+                        value!(ui, "Y", shape.shape.pos.y, 0.0);
+                        value!(ui, "Z", shape.shape.pos.z, 0.0);
+                    });
+                    ui.end_row();
+
+                    ui.label("Rotation");
+                    ui.horizontal(|ui| {
+                        value!(ui, "X", shape.rot.x, 0.0);
+                        value!(ui, "Y", shape.rot.y, 0.0);
+                        value!(ui, "Z", shape.rot.z, 0.0);
+
+                        if changed == ShapeChanged::Updated {
+                            shape.shape.rotation = Quat::from_euler(
+                                EulerRot::ZYX,
+                                shape.rot.z.to_radians(),
+                                shape.rot.x.to_radians(),
+                                shape.rot.y.to_radians(),
+                            );
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Scale");
+                    ui.horizontal(|ui| {
+                        value!(ui, "X", shape.shape.scale.x, 1.0);
+                        value!(ui, "Y", shape.shape.scale.y, 1.0);
+                        value!(ui, "Z", shape.shape.scale.z, 1.0);
+                    });
+                    ui.end_row();
+
+                    ui.horizontal(|ui| {
                         if ui.button("🗑 Remove").clicked() {
                             changed = ShapeChanged::Removed;
                         }
-                        ui.end_row();
 
-                        changed
-                    })
-                    .inner
-            })
-            .body_returned
-            .unwrap_or(ShapeChanged::Unchanged)
+                        if ui
+                            .selectable_label(selected, "✥ Select")
+                            .on_hover_text(
+                                "Select this shape to drag its gizmo in the scene, \
+                                and move it by the step with the arrow keys for X and Y \
+                                and Page Up / Down for Z while the pointer is over the mask \
+                                tab, hold Shift for 10 steps",
+                            )
+                            .clicked()
+                        {
+                            changed = ShapeChanged::Selected;
+                        }
+
+                        if ui
+                            .button("⎘ Duplicate")
+                            .on_hover_text("Append a copy of this shape")
+                            .clicked()
+                        {
+                            changed = ShapeChanged::Duplicated;
+                        }
+
+                        if ui
+                            .button("📋 Copy")
+                            .on_hover_text(
+                                "Copy this shape to the clipboard, \
+                                    paste it by pressing Ctrl+V over the mask tab",
+                            )
+                            .clicked()
+                        {
+                            ui.ctx().copy_text(shape.to_json());
+                        }
+                    });
+                    ui.end_row();
+
+                    changed
+                })
+                .inner
+        })
+        .body_returned
+        .unwrap_or(ShapeChanged::Unchanged)
     }
 
     /// Create the UI for operations.
@@ -251,37 +408,191 @@ impl Mask {
         mask: &mut app::GaussianSplattingMask,
         scene_tx: &mpsc::Sender<app::SceneCommand>,
     ) {
-        if ui
-            .add(
-                egui::TextEdit::multiline(&mut mask.op_code)
-                    .font(egui::TextStyle::Monospace)
-                    .code_editor()
-                    .desired_rows(5)
-                    .layouter(&mut |ui: &egui::Ui, string: &str, wrap_width: f32| {
-                        let mut layout_job = egui_extras::syntax_highlighting::highlight(
-                            ui.ctx(),
-                            ui.style(),
-                            &egui_extras::syntax_highlighting::CodeTheme::from_memory(
-                                ui.ctx(),
-                                ui.style(),
-                            ),
-                            string,
-                            "rs",
-                        );
-                        layout_job.wrap.max_width = wrap_width;
-                        ui.fonts(|f| f.layout_job(layout_job))
-                    }),
-            )
-            .changed()
-        {
+        ui.horizontal_wrapped(|ui| {
+            let mut template = None;
+
+            ui.add_enabled_ui(!mask.shapes.is_empty(), |ui| {
+                if ui
+                    .button("Union All")
+                    .on_hover_text("Keep the Gaussians in any shape")
+                    .clicked()
+                {
+                    template = app::GaussianSplattingMaskOp::fold_shapes(
+                        mask.shapes.len(),
+                        app::GaussianSplattingMaskOp::Union,
+                    );
+                }
+
+                if ui
+                    .button("Intersect All")
+                    .on_hover_text("Keep the Gaussians in every shape")
+                    .clicked()
+                {
+                    template = app::GaussianSplattingMaskOp::fold_shapes(
+                        mask.shapes.len(),
+                        app::GaussianSplattingMaskOp::Intersection,
+                    );
+                }
+
+                ui.menu_button("Keep Only", |ui| {
+                    for (i, shape) in mask.shapes.iter().enumerate() {
+                        if ui.button(format!("{i}. {:?}", shape.shape.kind)).clicked() {
+                            template = Some(app::GaussianSplattingMaskOp::Shape(i));
+                            ui.close_menu();
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Keep the Gaussians in one shape");
+            })
+            .response
+            .on_disabled_hover_text("Add a shape first");
+
+            // The generated code is parsed again so the mask is updated the same as typing
+            if let Some(template) = template {
+                mask.op_code = template.to_string();
+                self.update_mask(mask, scene_tx);
+            }
+        });
+
+        let error_span = self.op_code_error.as_ref().map(|e| e.span.clone());
+
+        let output = egui::TextEdit::multiline(&mut mask.op_code)
+            .font(egui::TextStyle::Monospace)
+            .code_editor()
+            .desired_rows(5)
+            .layouter(&mut |ui: &egui::Ui, string: &str, wrap_width: f32| {
+                let mut layout_job = egui_extras::syntax_highlighting::highlight(
+                    ui.ctx(),
+                    ui.style(),
+                    &egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style()),
+                    string,
+                    "rs",
+                );
+                if let Some(span) = &error_span {
+                    Self::underline(&mut layout_job, string, span.clone(), egui::Color32::RED);
+                }
+                layout_job.wrap.max_width = wrap_width;
+                ui.fonts(|f| f.layout_job(layout_job))
+            })
+            .show(ui);
+
+        let mut changed = output.response.changed();
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Shapes")
+                .on_hover_text("Insert a shape index at the cursor");
+
+            for (i, shape) in mask.shapes.iter().enumerate() {
+                if ui
+                    .small_button(i.to_string())
+                    .on_hover_text(format!("{:?}", shape.shape.kind))
+                    .clicked()
+                {
+                    // Insert at the last cursor, the editor loses focus on click
+                    let mut state = output.state.clone();
+                    let cursor = state
+                        .cursor
+                        .char_range()
+                        .map_or(mask.op_code.chars().count(), |range| range.primary.index);
+                    let byte = mask
+                        .op_code
+                        .char_indices()
+                        .nth(cursor)
+                        .map_or(mask.op_code.len(), |(byte, _)| byte);
+                    let index = i.to_string();
+                    mask.op_code.insert_str(byte, &index);
+
+                    state
+                        .cursor
+                        .set_char_range(Some(egui::text::CCursorRange::one(
+                            egui::text::CCursor::new(cursor + index.len()),
+                        )));
+                    state.store(ui.ctx(), output.response.id);
+                    output.response.request_focus();
+
+                    changed = true;
+                }
+            }
+        });
+
+        if changed {
             self.update_mask(mask, scene_tx);
         }
 
         if let Some(e) = &self.op_code_error {
-            ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+            ui.label(egui::RichText::new(&e.message).color(egui::Color32::RED));
         }
     }
 
+    /// Underline a byte range of the text in a layout job.
+    fn underline(
+        layout_job: &mut egui::text::LayoutJob,
+        text: &str,
+        range: Range<usize>,
+        color: egui::Color32,
+    ) {
+        // The error may be of the text before an edit
+        if range.end > text.len()
+            || !text.is_char_boundary(range.start)
+            || !text.is_char_boundary(range.end)
+        {
+            return;
+        }
+
+        let sections = std::mem::take(&mut layout_job.sections);
+        for section in sections {
+            let bounds = &section.byte_range;
+            let cuts = [
+                bounds.start,
+                range.start.clamp(bounds.start, bounds.end),
+                range.end.clamp(bounds.start, bounds.end),
+                bounds.end,
+            ];
+
+            for cut in cuts.windows(2).filter(|cut| cut[0] < cut[1]) {
+                let mut part = section.clone();
+                part.byte_range = cut[0]..cut[1];
+                if cut[0] != bounds.start {
+                    part.leading_space = 0.0;
+                }
+                if range.start <= cut[0] && cut[1] <= range.end {
+                    part.format.underline = egui::Stroke::new(2.0, color);
+                }
+                layout_job.sections.push(part);
+            }
+        }
+    }
+
+    /// Create the UI for coverage.
+    fn coverage(&mut self, ui: &mut egui::Ui, mask: &app::GaussianSplattingMask, count: usize) {
+        egui::Grid::new("mask_coverage_grid").show(ui, |ui| {
+            ui.label("Kept")
+                .on_hover_text("The number of Gaussians kept by the mask");
+            match mask.coverage {
+                _ if mask.evaluating => {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Spinner::new());
+                        ui.label("Evaluating...");
+                    });
+                }
+                Some(kept) => {
+                    ui.label(format!(
+                        "{} / {} ({:.2}%)",
+                        kept.to_formatted_string(&num_format::Locale::en),
+                        count.to_formatted_string(&num_format::Locale::en),
+                        kept as f64 / count.max(1) as f64 * 100.0,
+                    ));
+                }
+                None => {
+                    ui.label("N/A")
+                        .on_hover_text("Available after the mask is evaluated");
+                }
+            }
+            ui.end_row();
+        });
+    }
+
     /// Update the mask.
     fn update_mask(
         &mut self,
@@ -300,7 +611,11 @@ impl Mask {
                             .expect("send op");
                     }
                     Err(e) => {
-                        self.op_code_error = Some(format!("Invalid shape index: {e}"));
+                        self.op_code_error = Some(app::MaskOpParseError {
+                            span: app::GaussianSplattingMaskOp::shape_span(&mask.op_code, e)
+                                .unwrap_or_default(),
+                            message: format!("Invalid shape index: {e}"),
+                        });
                     }
                 }
             }
@@ -311,7 +626,7 @@ impl Mask {
                     .expect("send op");
             }
             Err(e) => {
-                self.op_code_error = Some(e.to_string());
+                self.op_code_error = Some(e);
             }
         }
     }
@@ -1,6 +1,8 @@
 use std::sync::mpsc;
 
-use crate::app;
+use strum::IntoEnumIterator;
+
+use crate::{app, util};
 
 use super::Tab;
 
@@ -21,6 +23,7 @@ impl Tab for Measurement {
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame, state: &mut app::State) {
+        let length_unit = state.settings.length_unit;
         let (measurement, action, scene_tx, ui_builder) = match &mut state.gs {
             app::Loadable::Loaded(gs) => (
                 &mut gs.measurement,
@@ -39,9 +42,13 @@ impl Tab for Measurement {
         ui.spacing_mut().item_spacing = egui::vec2(ui.spacing().item_spacing.x, 12.0);
 
         ui.scope_builder(ui_builder, |ui| {
+            let mut updated = false;
+
             egui::Grid::new("measurement_grid").show(ui, |ui| {
-                ui.label("Hit Method")
-                    .on_hover_text("Method to locate the hit position from the click");
+                ui.label("Hit Method").on_hover_text(
+                    "Default method to locate the hit position from the click for new \
+                    measurements, each measurement can use its own method",
+                );
                 ui.horizontal(|ui| {
                     macro_rules! value {
                         ($ui:expr, $value:expr, $label:ident, $display:expr, $tooltip:expr) => {
@@ -73,14 +80,125 @@ impl Tab for Measurement {
                         "The closest Gaussian"
                     );
                 });
+                ui.end_row();
+
+                let label = ui.label("Gaussian Center").on_hover_text(
+                    "Locate hits at the center of the hit Gaussian instead of the position from \
+                    the hit method, for reproducible measurement points",
+                );
+                ui.add(util::toggle(&mut measurement.locate_center))
+                    .labelled_by(label.id);
+                ui.end_row();
+
+                ui.label("Alpha Range").on_hover_text(
+                    "The depth range over which Most Alpha sums the alpha of the Gaussians along \
+                    the ray, lower to locate thin surfaces more precisely, raise to be more \
+                    robust to floaters and noise",
+                );
+                ui.add_enabled(
+                    measurement.hit_method == app::MeasurementHitMethod::MostAlpha
+                        || measurement.hit_pairs.iter().any(|hit_pair| {
+                            hit_pair.hit_method == app::MeasurementHitMethod::MostAlpha
+                        }),
+                    egui::Slider::new(&mut measurement.alpha_range, 0.001..=1.0)
+                        .logarithmic(true)
+                        .fixed_decimals(3),
+                );
+                ui.end_row();
+
+                let label = ui
+                    .label("Snap to Hits")
+                    .on_hover_text("Snap to existing hits when locating, hold Alt to invert");
+                ui.horizontal(|ui| {
+                    ui.add(util::toggle(&mut measurement.snap))
+                        .labelled_by(label.id);
+
+                    ui.add_enabled(
+                        measurement.snap,
+                        egui::Slider::new(&mut measurement.snap_radius, 1.0..=50.0)
+                            .text("Radius")
+                            .fixed_decimals(0),
+                    );
+                });
+                ui.end_row();
+
+                ui.label("Nudge Step").on_hover_text(
+                    "Step for nudging a focused position with the up and down arrow keys, \
+                    hold Shift for 10 times the step",
+                );
+                ui.add(
+                    egui::DragValue::new(&mut measurement.nudge_step)
+                        .speed(0.001)
+                        .range(0.0001..=10.0)
+                        .fixed_decimals(4),
+                );
+                ui.end_row();
+
+                let label = ui
+                    .label("Always on Top")
+                    .on_hover_text("Draw the measurements over the Gaussians instead of behind");
+                updated |= ui
+                    .add(util::toggle(&mut measurement.always_on_top))
+                    .labelled_by(label.id)
+                    .changed();
+                ui.end_row();
+
+                let label = ui.label("Show All").on_hover_text(
+                    "Show or hide all measurements at once, each keeps its own visibility for \
+                    when they are shown again, press M to toggle",
+                );
+                let mut visible = !measurement.hidden;
+                if ui
+                    .add(util::toggle(&mut visible))
+                    .labelled_by(label.id)
+                    .changed()
+                {
+                    measurement.hidden = !visible;
+                    updated = true;
+                }
+                ui.end_row();
             });
 
             ui.separator();
 
-            let mut updated = false;
             let mut removed = Vec::new();
+            let mut duplicated = Vec::new();
+            let mut moved = None;
             for (index, hit_pair) in measurement.hit_pairs.iter_mut().enumerate() {
-                match self.measurement(ui, index, action, hit_pair) {
+                let response = ui.horizontal(|ui| {
+                    ui.dnd_drag_source(ui.id().with(("measurement_drag", index)), index, |ui| {
+                        ui.label("☰");
+                    })
+                    .response
+                    .on_hover_text("Drag to reorder");
+
+                    ui.vertical(|ui| {
+                        self.measurement(
+                            ui,
+                            index,
+                            action,
+                            hit_pair,
+                            length_unit,
+                            measurement.nudge_step,
+                        )
+                    })
+                    .inner
+                });
+
+                // Drop above the hovered measurement
+                if response.response.dnd_hover_payload::<usize>().is_some() {
+                    let rect = response.response.rect;
+                    ui.painter().hline(
+                        rect.x_range(),
+                        rect.top() - ui.spacing().item_spacing.y / 2.0,
+                        ui.visuals().selection.stroke,
+                    );
+                }
+                if let Some(from) = response.response.dnd_release_payload::<usize>() {
+                    moved = Some((*from, index));
+                }
+
+                match response.inner {
                     MeasurementChanged::Removed => {
                         removed.push(index);
                         updated = true;
@@ -88,21 +206,39 @@ impl Tab for Measurement {
                     MeasurementChanged::Updated => {
                         updated = true;
                     }
+                    MeasurementChanged::Duplicated => {
+                        duplicated.push(hit_pair.duplicate());
+                        updated = true;
+                    }
                     _ => {}
                 }
             }
 
+            // The indices are no longer valid after removal
+            if !removed.is_empty() {
+                moved = None;
+            }
+
             for index in removed.into_iter().rev() {
                 measurement.hit_pairs.remove(index);
             }
 
+            measurement.hit_pairs.extend(duplicated);
+
+            if let Some((from, to)) = moved.filter(|(from, to)| from != to) {
+                let hit_pair = measurement.hit_pairs.remove(from);
+                measurement.hit_pairs.insert(to, hit_pair);
+
+                // The index of the located hit pair is no longer valid
+                if let Some(app::Action::MeasurementLocateHit { .. }) = action {
+                    *action = None;
+                }
+
+                updated = true;
+            }
+
             if ui.button("➕ Add Measurement").clicked() {
-                measurement
-                    .hit_pairs
-                    .push(app::MeasurementHitPair::new(format!(
-                        "Measurement {}",
-                        measurement.hit_pairs.len()
-                    )));
+                measurement.add_hit_pair();
 
                 updated = true;
             }
@@ -121,6 +257,7 @@ enum MeasurementChanged {
     Unchanged,
     Removed,
     Updated,
+    Duplicated,
 }
 
 impl Measurement {
@@ -133,6 +270,8 @@ impl Measurement {
         index: usize,
         action: &mut Option<app::Action>,
         hit_pair: &mut app::MeasurementHitPair,
+        length_unit: app::LengthUnit,
+        nudge_step: f32,
     ) -> MeasurementChanged {
         egui::CollapsingHeader::new(format!("{index}. {}", hit_pair.label))
             .id_salt(format!("measurement_{index}"))
@@ -174,10 +313,49 @@ impl Measurement {
                         });
                         ui.end_row();
 
-                        ui.label("Line Width");
+                        ui.label("Line Width").on_hover_text(
+                            "Screen width stays readable at any distance, \
+                            world width is a physical thickness in world units",
+                        );
+                        ui.horizontal(|ui| {
+                            let line_width = match hit_pair.line_width_mode {
+                                app::MeasurementLineWidthMode::Screen => ui.add(
+                                    egui::Slider::new(&mut hit_pair.line_width, 0.0..=5.0)
+                                        .fixed_decimals(2),
+                                ),
+                                app::MeasurementLineWidthMode::World => ui.add(
+                                    egui::DragValue::new(&mut hit_pair.line_width)
+                                        .range(0.0..=f32::MAX)
+                                        .speed(0.001)
+                                        .fixed_decimals(3),
+                                ),
+                            };
+                            if line_width.changed() {
+                                changed = MeasurementChanged::Updated;
+                            }
+
+                            for mode in app::MeasurementLineWidthMode::iter() {
+                                if ui
+                                    .selectable_label(
+                                        hit_pair.line_width_mode == mode,
+                                        mode.to_string(),
+                                    )
+                                    .clicked()
+                                    && hit_pair.line_width_mode != mode
+                                {
+                                    hit_pair.line_width_mode = mode;
+                                    hit_pair.line_width = mode.default_line_width();
+                                    changed = MeasurementChanged::Updated;
+                                }
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Endpoint Size")
+                            .on_hover_text("The size of the markers at the hits");
                         if ui
                             .add(
-                                egui::Slider::new(&mut hit_pair.line_width, 0.0..=5.0)
+                                egui::Slider::new(&mut hit_pair.endpoint_size, 0.0..=10.0)
                                     .fixed_decimals(2),
                             )
                             .changed()
@@ -191,23 +369,94 @@ impl Measurement {
                                 $ui.horizontal(|ui| {
                                     ui.spacing_mut().item_spacing.x /= 2.0;
 
-                                    ui.label($axis);
-                                    if ui
-                                        .add(
-                                            egui::DragValue::new(&mut $value)
-                                                .speed(0.001)
-                                                .fixed_decimals(4),
-                                        )
-                                        .changed()
-                                    {
+                                    util::axis_label(ui, $axis);
+
+                                    // Nudge
+                                    let id = ui.next_auto_id();
+                                    if ui.memory(|memory| memory.has_focus(id)) {
+                                        let (delta, shift) = ui.input_mut(|input| {
+                                            (
+                                                input.count_and_consume_key(
+                                                    egui::Modifiers::NONE,
+                                                    egui::Key::ArrowUp,
+                                                ) as f32
+                                                    - input.count_and_consume_key(
+                                                        egui::Modifiers::NONE,
+                                                        egui::Key::ArrowDown,
+                                                    ) as f32,
+                                                input.modifiers.shift,
+                                            )
+                                        });
+
+                                        if delta != 0.0 {
+                                            $value +=
+                                                delta * nudge_step * if shift { 10.0 } else { 1.0 };
+                                            changed = MeasurementChanged::Updated;
+
+                                            // Discard the text being edited
+                                            ui.data_mut(|data| data.remove::<String>(id));
+                                        }
+                                    }
+
+                                    let response = ui.add(
+                                        egui::DragValue::new(&mut $value)
+                                            .speed(0.001)
+                                            .fixed_decimals(4),
+                                    );
+                                    if util::reset_menu(response, &mut $value, 0.0).changed() {
                                         changed = MeasurementChanged::Updated;
                                     }
                                 });
                             };
                         }
 
-                        for i in 0..2 {
-                            ui.label(format!("Position {}", i + 1));
+                        ui.label("Kind").on_hover_text(
+                            "The reference of the measured point, the distance to a line or a \
+                            plane is the perpendicular distance",
+                        );
+                        ui.horizontal(|ui| {
+                            for kind in app::MeasurementKind::iter() {
+                                if ui
+                                    .selectable_label(hit_pair.kind == kind, kind.to_string())
+                                    .clicked()
+                                    && hit_pair.kind != kind
+                                {
+                                    hit_pair.kind = kind;
+                                    changed = MeasurementChanged::Updated;
+
+                                    // The located hit may be no longer used
+                                    if let Some(app::Action::MeasurementLocateHit {
+                                        hit_pair_index,
+                                        hit_index,
+                                        ..
+                                    }) = action
+                                    {
+                                        if *hit_pair_index == index
+                                            && *hit_index >= kind.hit_count()
+                                        {
+                                            *action = None;
+                                        }
+                                    }
+                                }
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Hit Method")
+                            .on_hover_text("Method to locate the hits of this measurement");
+                        ui.horizontal(|ui| {
+                            for hit_method in app::MeasurementHitMethod::iter() {
+                                ui.selectable_value(
+                                    &mut hit_pair.hit_method,
+                                    hit_method,
+                                    hit_method.to_string(),
+                                );
+                            }
+                        });
+                        ui.end_row();
+
+                        for i in 0..hit_pair.kind.hit_count() {
+                            ui.label(hit_pair.kind.hit_label(i));
                             ui.horizontal(|ui| {
                                 value!(ui, "X", hit_pair.hits[i].pos.x);
                                 value!(ui, "Y", hit_pair.hits[i].pos.y);
@@ -246,13 +495,26 @@ impl Measurement {
                             ui.end_row();
                         }
 
-                        ui.label("Distance");
-                        ui.label(format!("{:.4}", hit_pair.distance()));
+                        ui.label(match hit_pair.kind {
+                            app::MeasurementKind::Point => "Distance",
+                            _ => "Perpendicular Distance",
+                        });
+                        ui.label(length_unit.format(hit_pair.distance()));
                         ui.end_row();
 
-                        if ui.button("🗑 Remove").clicked() {
-                            changed = MeasurementChanged::Removed;
-                        }
+                        ui.horizontal(|ui| {
+                            if ui.button("🗑 Remove").clicked() {
+                                changed = MeasurementChanged::Removed;
+                            }
+
+                            if ui
+                                .button("⎘ Duplicate")
+                                .on_hover_text("Append a copy of this measurement")
+                                .clicked()
+                            {
+                                changed = MeasurementChanged::Duplicated;
+                            }
+                        });
                         ui.end_row();
 
                         changed
@@ -1,3 +1,4 @@
+use itertools::{Itertools, MinMaxResult};
 use num_format::ToFormattedString;
 use wgpu_3dgs_viewer as gs;
 
@@ -22,16 +23,20 @@ impl Tab for Metadata {
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame, state: &mut app::State) {
-        let (file_name, count, compressions, ui_builder) = match &state.gs {
+        let (file_name, count, bounds, compressions, ui_builder) = match &state.gs {
             app::Loadable::Loaded(gs) => (
                 gs.selected_model().file_name.as_str(),
                 gs.selected_model().gaussians.gaussians.capacity(),
+                gs.selected_model()
+                    .size
+                    .map(|size| (gs.selected_model().center, size)),
                 &gs.compressions,
                 egui::UiBuilder::new(),
             ),
             app::Loadable::Unloaded { .. } => (
                 "N/A",
                 0,
+                None,
                 &app::Compressions::default(),
                 egui::UiBuilder::new().disabled(),
             ),
@@ -49,6 +54,18 @@ impl Tab for Metadata {
                 ui.label(count.to_formatted_string(&num_format::Locale::en));
                 ui.end_row();
 
+                ui.label("Bounding Box")
+                    .on_hover_text("The center and size of the bounding box in model space");
+                ui.label(match bounds {
+                    Some((center, size)) => format!(
+                        "{:.2} {:.2} {:.2} / {:.2} × {:.2} × {:.2}",
+                        center.x, center.y, center.z, size.x, size.y, size.z
+                    ),
+                    None if count > 0 => "Computing...".to_string(),
+                    None => "N/A".to_string(),
+                });
+                ui.end_row();
+
                 ui.label("Original Size");
                 ui.label(util::human_readable_size(
                     count * std::mem::size_of::<gs::PlyGaussianPod>(),
@@ -61,6 +78,13 @@ impl Tab for Metadata {
                 ));
                 ui.end_row();
 
+                ui.label("GPU Memory")
+                    .on_hover_text("Estimated GPU memory usage including the viewer buffers");
+                ui.label(util::human_readable_size(
+                    compressions.gpu_memory_estimate(count),
+                ));
+                ui.end_row();
+
                 ui.label("SH Compression")
                     .on_hover_text("Spherical harmonics compression");
                 ui.label(compressions.sh.to_string());
@@ -71,5 +95,122 @@ impl Tab for Metadata {
                 ui.end_row();
             });
         });
+
+        Self::capture(ui, state);
+    }
+}
+
+impl Metadata {
+    /// Show the capture cameras from the sidecar file next to the selected model.
+    fn capture(ui: &mut egui::Ui, state: &mut app::State) {
+        let app::Loadable::Loaded(gs) = &mut state.gs else {
+            return;
+        };
+
+        let model = &gs.models[&gs.selected_model_key];
+        let Some(metadata) = &model.capture else {
+            return;
+        };
+
+        ui.separator();
+        ui.heading("Capture Cameras");
+
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+                return;
+            }
+        };
+
+        egui::Grid::new("metadata_capture_grid").show(ui, |ui| {
+            ui.label("Sidecar File");
+            ui.label(
+                metadata
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default(),
+            )
+            .on_hover_text(metadata.path.display().to_string());
+            ui.end_row();
+
+            ui.label("Camera Count");
+            ui.label(
+                metadata
+                    .cameras
+                    .len()
+                    .to_formatted_string(&num_format::Locale::en),
+            );
+            ui.end_row();
+
+            ui.label("Image Size");
+            ui.label(
+                match metadata
+                    .cameras
+                    .iter()
+                    .filter_map(|c| c.size)
+                    .minmax_by_key(|size| size.to_array())
+                {
+                    MinMaxResult::NoElements => "N/A".to_string(),
+                    MinMaxResult::OneElement(size) => format!("{} × {}", size.x, size.y),
+                    MinMaxResult::MinMax(min, max) if min == max => {
+                        format!("{} × {}", min.x, min.y)
+                    }
+                    MinMaxResult::MinMax(..) => "Varied".to_string(),
+                },
+            );
+            ui.end_row();
+
+            ui.label("Vertical FOV");
+            ui.label(
+                match metadata
+                    .cameras
+                    .iter()
+                    .filter_map(|c| c.vertical_fov)
+                    .map(f32::to_degrees)
+                    .minmax_by(f32::total_cmp)
+                {
+                    MinMaxResult::NoElements => "N/A".to_string(),
+                    MinMaxResult::OneElement(fov) => format!("{fov:.1}°"),
+                    MinMaxResult::MinMax(min, max) => format!("{min:.1}° - {max:.1}°"),
+                },
+            );
+            ui.end_row();
+        });
+
+        if metadata.cameras.is_empty() {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            let mut index = match &gs.capture_cameras.selected {
+                Some((key, index)) if *key == gs.selected_model_key => {
+                    (*index).min(metadata.cameras.len() - 1)
+                }
+                _ => 0,
+            };
+
+            let changed = egui::ComboBox::from_id_salt("metadata_capture_camera")
+                .width(200.0)
+                .selected_text(&metadata.cameras[index].name)
+                .show_index(ui, &mut index, metadata.cameras.len(), |i| {
+                    metadata.cameras[i].name.clone()
+                })
+                .changed();
+
+            if changed {
+                gs.capture_cameras.selected = Some((gs.selected_model_key.clone(), index));
+            }
+
+            if ui
+                .button("Go to Camera")
+                .on_hover_text("Move the viewer camera to the capture viewpoint")
+                .clicked()
+            {
+                metadata.cameras[index].apply(&mut gs.camera, model);
+                gs.capture_cameras.selected = Some((gs.selected_model_key.clone(), index));
+            }
+        });
     }
 }
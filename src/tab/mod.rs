@@ -6,23 +6,30 @@ use strum::{EnumCount, EnumIter, IntoEnumIterator};
 
 // 声明并导入各个子模块
 mod camera; // 相机相关功能模块
+mod inspector; // 检查器相关功能模块
+mod logs; // 日志相关功能模块
 mod mask; // 掩码相关功能模块
 mod measurement; // 测量相关功能模块
 mod metadata; // 元数据相关功能模块
 mod models; // 模型相关功能模块
+mod performance; // 性能相关功能模块
 pub mod scene; // 场景相关功能模块（公开导出）
 mod selection; // 选择相关功能模块
+mod settings; // 设置相关功能模块
 mod transform; // 变换相关功能模块
 
 // 导入项目中其他模块的类型
-use crate::app;
+use crate::{app, viewer::ViewerWidget};
 use camera::Camera; // 导入相机类型
+use inspector::Inspector; // 导入检查器类型
+use logs::Logs; // 导入日志类型
 use mask::Mask; // 导入掩码类型
 use measurement::Measurement; // 导入测量类型
 use metadata::Metadata; // 导入元数据类型
 use models::Models; // 导入模型类型
-use scene::Scene; // 导入场景类型
+use performance::Performance; // 导入性能类型
 use selection::Selection; // 导入选择类型
+use settings::Settings; // 导入设置类型
 use transform::Transform; // 导入变换类型
 
 /// 标签页的类型枚举
@@ -48,6 +55,10 @@ pub enum Type {
     Metadata,    // 元数据标签页
     Models,      // 模型标签页
     Mask,        // 掩码标签页
+    Performance, // 性能标签页
+    Settings,    // 设置标签页
+    Inspector,   // 检查器标签页
+    Logs,        // 日志标签页
 }
 
 // 为 Type 枚举实现方法
@@ -63,8 +74,48 @@ impl Type {
             Self::Metadata => "Metadata",       // 元数据标签页标题
             Self::Models => "Models",           // 模型标签页标题
             Self::Mask => "Mask",               // 掩码标签页标题
+            Self::Performance => "Performance", // 性能标签页标题
+            Self::Settings => "Settings",       // 设置标签页标题
+            Self::Inspector => "Inspector",     // 检查器标签页标题
+            Self::Logs => "Logs",               // 日志标签页标题
         }
     }
+
+    /// 获取标签页停靠的区域
+    pub fn region(&self) -> Region {
+        match self {
+            Self::Scene => Region::Main,
+            Self::Transform | Self::Mask | Self::Measurement | Self::Inspector => Region::Upper,
+            Self::Camera
+            | Self::Selection
+            | Self::Metadata
+            | Self::Models
+            | Self::Performance
+            | Self::Settings
+            | Self::Logs => Region::Lower,
+        }
+    }
+
+    /// 标签页是否在默认布局中显示
+    pub fn default_visible(&self) -> bool {
+        matches!(
+            self,
+            Self::Scene
+                | Self::Transform
+                | Self::Mask
+                | Self::Models
+                | Self::Camera
+                | Self::Selection
+        )
+    }
+}
+
+/// 标签页停靠的区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Main,  // 主区域，左侧
+    Upper, // 右上区域
+    Lower, // 右下区域
 }
 
 /// 标签页管理器
@@ -75,45 +126,209 @@ pub struct Manager {
     dock_state: egui_dock::DockState<Type>,
 
     /// 标签页状态，将标签页类型映射到具体的标签页实现
+    ///
+    /// 关闭标签页只会将其从停靠状态中移除，状态仍保留在此，重新打开时恢复。
     #[serde(skip)] // 序列化时跳过此字段，因为它是运行时状态
     tabs: HashMap<Type, Box<dyn Tab>>,
+
+    /// 演示模式下保存的原停靠状态，退出演示模式时恢复
+    #[serde(default)] // 旧的存储中没有此字段
+    presentation: Option<egui_dock::DockState<Type>>,
+
+    /// 新布局和重置布局时显示的标签页
+    #[serde(default = "Manager::default_tabs")] // 旧的存储中没有此字段
+    default_tabs: Vec<Type>,
 }
 
 // 为 Manager 实现方法
 impl Manager {
     /// 创建一个新的标签页管理器
     pub fn new() -> Self {
-        // 创建初始包含场景标签页的停靠状态
-        let mut dock_state = egui_dock::DockState::new(vec![Type::Scene]);
-
-        // 将主节点分割，在右侧创建包含变换和掩码标签页的区域，占据70%的空间
-        let [_, inspector] = dock_state.main_surface_mut().split_right(
-            egui_dock::NodeIndex::root(),      // 从根节点开始分割
-            0.7,                               // 右侧区域占70%
-            vec![Type::Transform, Type::Mask], // 分配的标签页
-        );
-
-        // 在 inspector 节点下方创建新区域，包含模型、相机和选择标签页，各占50%空间
-        let [_, _] = dock_state.main_surface_mut().split_below(
-            inspector,                                         // 在 inspector 节点下方分割
-            0.5,                                               // 新区域占50%
-            vec![Type::Models, Type::Camera, Type::Selection], // 分配的标签页
-        );
-
         // 创建空的标签页映射表
         let tabs = HashMap::new();
 
+        let mut manager = Self {
+            dock_state: egui_dock::DockState::new(Vec::new()),
+            tabs,
+            presentation: None,
+            default_tabs: Self::default_tabs(),
+        };
+
+        // 按默认显示的标签页创建布局
+        manager.reset_layout();
+
         // 返回新的管理器实例
-        Self { dock_state, tabs }
+        manager
+    }
+
+    /// 内置的默认显示的标签页
+    fn default_tabs() -> Vec<Type> {
+        Type::iter().filter(Type::default_visible).collect()
+    }
+
+    /// 设置默认显示的标签页，并按其重置布局
+    pub fn set_default_tabs(&mut self, tabs: &[Type]) {
+        // 保持标签页类型的顺序
+        self.default_tabs = Type::iter().filter(|tab| tabs.contains(tab)).collect();
+        self.reset_layout();
+    }
+
+    /// 按默认显示的标签页重置布局，保留标签页状态
+    fn reset_layout(&mut self) {
+        // 创建包含主区域标签页的停靠状态
+        self.dock_state = egui_dock::DockState::new(
+            Type::iter()
+                .filter(|tab| self.default_tabs.contains(tab) && tab.region() == Region::Main)
+                .collect(),
+        );
+
+        // 将其余默认显示的标签页停靠到各自的区域
+        for tab in Type::iter() {
+            if self.default_tabs.contains(&tab) && tab.region() != Region::Main {
+                self.dock(tab);
+            }
+        }
+
+        // 每个区域默认显示第一个标签页
+        for node in self.dock_state.main_surface_mut().iter_mut() {
+            if let egui_dock::Node::Leaf { active, .. } = node {
+                *active = egui_dock::TabIndex(0);
+            }
+        }
+    }
+
+    /// 将标签页停靠到其区域
+    ///
+    /// 若区域中已有标签页，则加入同一节点；否则按默认布局分割出该区域。
+    fn dock(&mut self, tab: Type) {
+        // 查找主界面中某区域的标签页所在节点
+        let find = |dock_state: &egui_dock::DockState<Type>, region: Region| {
+            Type::iter()
+                .filter(|other| other.region() == region)
+                .find_map(|other| dock_state.find_main_surface_tab(&other))
+                .map(|(node, _)| node)
+        };
+
+        let surface = self.dock_state.main_surface_mut();
+
+        // 主界面没有标签页，直接放入
+        if surface
+            .root_node()
+            .is_none_or(|root| !root.is_parent() && root.tabs_count() == 0)
+        {
+            surface.push_to_first_leaf(tab);
+            return;
+        }
+
+        // 区域已存在，加入同一节点
+        if let Some(node) = find(&self.dock_state, tab.region()) {
+            self.dock_state.main_surface_mut()[node].append_tab(tab);
+            return;
+        }
+
+        let root = egui_dock::NodeIndex::root();
+        match tab.region() {
+            // 主区域在左侧占70%
+            Region::Main => {
+                self.dock_state
+                    .main_surface_mut()
+                    .split_left(root, 0.3, vec![tab]);
+            }
+            // 右上区域在右下区域上方，或在右侧占30%
+            Region::Upper => match find(&self.dock_state, Region::Lower) {
+                Some(lower) => {
+                    self.dock_state
+                        .main_surface_mut()
+                        .split_above(lower, 0.5, vec![tab]);
+                }
+                None => {
+                    self.dock_state
+                        .main_surface_mut()
+                        .split_right(root, 0.7, vec![tab]);
+                }
+            },
+            // 右下区域在右上区域下方，或在右侧占30%
+            Region::Lower => match find(&self.dock_state, Region::Upper) {
+                Some(upper) => {
+                    self.dock_state
+                        .main_surface_mut()
+                        .split_below(upper, 0.5, vec![tab]);
+                }
+                None => {
+                    self.dock_state
+                        .main_surface_mut()
+                        .split_right(root, 0.7, vec![tab]);
+                }
+            },
+        }
+    }
+
+    /// 是否处于演示模式
+    pub fn is_presenting(&self) -> bool {
+        self.presentation.is_some()
+    }
+
+    /// 进入或退出演示模式
+    ///
+    /// 演示模式只显示场景标签页，退出时原样恢复之前的布局。
+    pub fn set_presenting(&mut self, presenting: bool) {
+        match (presenting, self.presentation.take()) {
+            // 进入：保存当前布局，替换为只有场景的布局
+            (true, None) => {
+                self.presentation = Some(std::mem::replace(
+                    &mut self.dock_state,
+                    egui_dock::DockState::new(vec![Type::Scene]),
+                ));
+            }
+            // 退出：恢复保存的布局
+            (false, Some(dock_state)) => {
+                self.dock_state = dock_state;
+            }
+            // 状态未改变
+            (_, presentation) => {
+                self.presentation = presentation;
+            }
+        }
+    }
+
+    /// 获取主界面每个节点的标签页顺序和当前激活的标签页
+    fn layout(&self) -> Vec<(Vec<Type>, egui_dock::TabIndex)> {
+        self.dock_state
+            .main_surface()
+            .iter()
+            .filter_map(|node| match node {
+                egui_dock::Node::Leaf { tabs, active, .. } => Some((tabs.clone(), *active)),
+                _ => None,
+            })
+            .collect()
     }
 
     /// 显示标签页的停靠区域
+    ///
+    /// `scene_only` 为真时（界面自动隐藏），若布局中有场景标签页，则只显示场景。
+    ///
+    /// 返回标签页的顺序或激活的标签页是否改变，以便立即保存布局。
     pub fn dock_area(
         &mut self,
         ui: &mut egui::Ui,         // egui 用户界面上下文
         frame: &mut eframe::Frame, // 当前帧对象
-        state: &mut app::State,    // 应用程序状态
-    ) {
+        widget: &mut ViewerWidget, // 场景查看器组件，包含应用程序状态
+        scene_only: bool,          // 是否只显示场景
+    ) -> bool {
+        // 只显示场景时直接绘制场景标签页，不改变停靠状态
+        if scene_only && self.dock_state.find_tab(&Type::Scene).is_some() {
+            let mut viewer = Viewer {
+                tabs: &mut self.tabs,
+                frame,
+                widget,
+            };
+            egui_dock::TabViewer::ui(&mut viewer, ui, &mut Type::Scene);
+            return false;
+        }
+
+        // 记录显示前的布局，用于检测切换或拖动标签页
+        let layout = self.layout();
+
         // 创建停靠区域，应用当前样式，并显示在给定的 UI 上下文中
         egui_dock::DockArea::new(&mut self.dock_state)
             .style(egui_dock::Style::from_egui(ui.style().as_ref())) // 从当前 UI 样式生成停靠区域样式
@@ -123,9 +338,12 @@ impl Manager {
                     // 使用自定义的 Viewer 来处理标签页内容
                     tabs: &mut self.tabs, // 传递标签页映射表的可变引用
                     frame,                // 传递帧对象的可变引用
-                    state,                // 传递应用程序状态的可变引用
+                    widget,               // 传递场景查看器组件的可变引用
                 },
             );
+
+        // 演示模式的布局是临时的，不需要保存
+        !self.is_presenting() && self.layout() != layout
     }
 
     /// 标签页菜单
@@ -158,9 +376,9 @@ impl Manager {
             ui.close_menu();
         }
 
-        // 添加新的标签页到停靠状态
-        if !added.is_empty() {
-            self.dock_state.add_window(added);
+        // 将新的标签页停靠到各自的区域
+        for tab in added {
+            self.dock(tab);
         }
 
         // 从停靠状态中移除标签页
@@ -173,10 +391,49 @@ impl Manager {
         // 绘制分隔线
         ui.separator();
 
-        // 如果点击重置布局按钮，则重新创建管理器
-        if ui.button("Reset Layout").clicked() {
-            *self = Self::new(); // 替换当前实例为新的默认实例
+        // 如果点击重置布局按钮，则恢复默认布局，保留标签页状态
+        if ui
+            .add_enabled(!self.is_presenting(), egui::Button::new("Reset Layout"))
+            .clicked()
+        {
+            self.reset_layout();
         }
+
+        // 设置新布局和重置布局时显示的标签页
+        ui.menu_button("Default Tabs", |ui| {
+            for tab in Type::iter() {
+                let mut enabled = self.default_tabs.contains(&tab);
+                if ui.toggle_value(&mut enabled, tab.menu_title()).changed() {
+                    // 保持标签页类型的顺序
+                    let default_tabs = std::mem::take(&mut self.default_tabs);
+                    self.default_tabs = Type::iter()
+                        .filter(|other| match *other == tab {
+                            true => enabled,
+                            false => default_tabs.contains(other),
+                        })
+                        .collect();
+                }
+            }
+
+            ui.separator();
+
+            // 使用当前布局中的标签页作为默认
+            if ui
+                .add_enabled(!self.is_presenting(), egui::Button::new("Use Current Tabs"))
+                .clicked()
+            {
+                self.default_tabs = Type::iter()
+                    .filter(|tab| self.dock_state.find_tab(tab).is_some())
+                    .collect();
+                ui.close_menu();
+            }
+
+            // 恢复内置的默认标签页
+            if ui.button("Restore Built-in").clicked() {
+                self.default_tabs = Self::default_tabs();
+                ui.close_menu();
+            }
+        });
     }
 }
 
@@ -221,25 +478,33 @@ struct Viewer<'a> {
     /// 当前帧对象的可变引用
     frame: &'a mut eframe::Frame,
 
-    /// 应用程序状态的可变引用
-    state: &'a mut app::State,
+    /// 场景查看器组件的可变引用，场景标签页由其显示，其他标签页使用其中的应用程序状态
+    widget: &'a mut ViewerWidget,
 }
 
 // 为 Viewer 实现方法
 impl Viewer<'_> {
     /// 确保标签页已创建
     /// 如果标签页不存在，则根据类型创建相应的标签页实例
+    ///
+    /// 场景标签页由场景查看器组件持有，不在此创建。
     fn make_sure_created(&mut self, tab: Type) {
+        let state = &mut self.widget.state;
+
         // 使用 entry API 检查标签页是否存在，如果不存在则创建
         self.tabs.entry(tab).or_insert_with(|| match tab {
-            Type::Scene => Box::new(Scene::create(self.state)) as Box<dyn Tab>,
-            Type::Transform => Box::new(Transform::create(self.state)) as Box<dyn Tab>,
-            Type::Camera => Box::new(Camera::create(self.state)) as Box<dyn Tab>,
-            Type::Measurement => Box::new(Measurement::create(self.state)) as Box<dyn Tab>,
-            Type::Selection => Box::new(Selection::create(self.state)) as Box<dyn Tab>,
-            Type::Metadata => Box::new(Metadata::create(self.state)) as Box<dyn Tab>,
-            Type::Models => Box::new(Models::create(self.state)) as Box<dyn Tab>,
-            Type::Mask => Box::new(Mask::create(self.state)) as Box<dyn Tab>,
+            Type::Scene => unreachable!("the scene tab is owned by the viewer widget"),
+            Type::Transform => Box::new(Transform::create(state)) as Box<dyn Tab>,
+            Type::Camera => Box::new(Camera::create(state)) as Box<dyn Tab>,
+            Type::Measurement => Box::new(Measurement::create(state)) as Box<dyn Tab>,
+            Type::Selection => Box::new(Selection::create(state)) as Box<dyn Tab>,
+            Type::Metadata => Box::new(Metadata::create(state)) as Box<dyn Tab>,
+            Type::Models => Box::new(Models::create(state)) as Box<dyn Tab>,
+            Type::Mask => Box::new(Mask::create(state)) as Box<dyn Tab>,
+            Type::Performance => Box::new(Performance::create(state)) as Box<dyn Tab>,
+            Type::Settings => Box::new(Settings::create(state)) as Box<dyn Tab>,
+            Type::Inspector => Box::new(Inspector::create(state)) as Box<dyn Tab>,
+            Type::Logs => Box::new(Logs::create(state)) as Box<dyn Tab>,
         });
     }
 }
@@ -251,23 +516,127 @@ impl egui_dock::TabViewer for Viewer<'_> {
 
     // 获取标签页标题
     fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
+        // 场景标签页的标题由场景查看器组件提供
+        if *tab == Type::Scene {
+            return self.widget.title(self.frame);
+        }
+
         // 确保标签页已创建
         self.make_sure_created(*tab);
         // 获取标签页实例并调用其 title 方法
         self.tabs
             .get_mut(tab)
             .expect("tab") // 如果标签页不存在则 panic
-            .title(self.frame, self.state)
+            .title(self.frame, &mut self.widget.state)
     }
 
     // 渲染标签页 UI
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
+        // 场景标签页由场景查看器组件显示
+        if *tab == Type::Scene {
+            self.widget.show(ui, self.frame);
+            return;
+        }
+
         // 确保标签页已创建
         self.make_sure_created(*tab);
         // 获取标签页实例并调用其 ui 方法
         self.tabs
             .get_mut(tab)
             .expect("tab") // 如果标签页不存在则 panic
-            .ui(ui, self.frame, self.state);
+            .ui(ui, self.frame, &mut self.widget.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    /// 测试用标签页，持有与测试共享的状态
+    struct Probe {
+        _state: Rc<Cell<u32>>,
+    }
+
+    impl Tab for Probe {
+        fn create(_state: &mut app::State) -> Self {
+            unreachable!("the probe is created by the test")
+        }
+
+        fn title(
+            &mut self,
+            _frame: &mut eframe::Frame,
+            _state: &mut app::State,
+        ) -> egui::WidgetText {
+            unreachable!("the probe is not shown")
+        }
+
+        fn ui(&mut self, _ui: &mut egui::Ui, _frame: &mut eframe::Frame, _state: &mut app::State) {
+            unreachable!("the probe is not shown")
+        }
+    }
+
+    /// 创建包含测试用标签页的管理器，返回标签页的共享状态
+    fn manager_with_probe(tab: Type) -> (Manager, Rc<Cell<u32>>) {
+        let mut manager = Manager::new();
+        let state = Rc::new(Cell::new(0));
+        manager.tabs.insert(
+            tab,
+            Box::new(Probe {
+                _state: state.clone(),
+            }),
+        );
+
+        // 修改标签页状态
+        state.set(42);
+
+        (manager, state)
+    }
+
+    /// 断言标签页状态仍由管理器持有且未改变
+    fn assert_kept(manager: &Manager, tab: Type, state: &Rc<Cell<u32>>) {
+        assert!(manager.tabs.contains_key(&tab));
+        assert_eq!(Rc::strong_count(state), 2);
+        assert_eq!(state.get(), 42);
+    }
+
+    #[test]
+    fn reset_layout_keeps_tab_state() {
+        let (mut manager, state) = manager_with_probe(Type::Transform);
+
+        manager.reset_layout();
+
+        assert_kept(&manager, Type::Transform, &state);
+        assert!(manager.dock_state.find_tab(&Type::Transform).is_some());
+    }
+
+    #[test]
+    fn set_default_tabs_keeps_tab_state() {
+        let (mut manager, state) = manager_with_probe(Type::Transform);
+
+        manager.set_default_tabs(&[Type::Scene]);
+
+        assert_kept(&manager, Type::Transform, &state);
+        assert!(manager.dock_state.find_tab(&Type::Transform).is_none());
+    }
+
+    #[test]
+    fn close_and_redock_keeps_tab_state() {
+        let (mut manager, state) = manager_with_probe(Type::Transform);
+
+        // 与菜单中关闭标签页相同，只从停靠状态中移除
+        let index = manager
+            .dock_state
+            .find_tab(&Type::Transform)
+            .expect("docked tab");
+        manager.dock_state.remove_tab(index);
+        assert!(manager.dock_state.find_tab(&Type::Transform).is_none());
+        assert_kept(&manager, Type::Transform, &state);
+
+        manager.dock(Type::Transform);
+
+        assert_kept(&manager, Type::Transform, &state);
+        assert!(manager.dock_state.find_tab(&Type::Transform).is_some());
     }
 }
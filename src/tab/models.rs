@@ -1,11 +1,13 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::File,
     io::{BufRead, BufReader, Cursor},
     sync::mpsc,
 };
 
 use itertools::Itertools;
+use num_format::ToFormattedString;
+use wgpu_3dgs_viewer as gs;
 
 use super::Tab;
 
@@ -13,14 +15,31 @@ use crate::{app, util};
 
 /// The models tab.
 #[derive(Debug)]
-pub struct Models;
+pub struct Models {
+    /// The soloed model key and the visibility of the models before soloing.
+    solo: Option<(String, HashMap<String, bool>)>,
+
+    /// The filter for the file names.
+    filter: String,
+
+    /// The column to sort by.
+    sort_by: ModelsSortBy,
+
+    /// Whether to sort in ascending order.
+    sort_ascending: bool,
+}
 
 impl Tab for Models {
     fn create(_state: &mut app::State) -> Self
     where
         Self: Sized,
     {
-        Self
+        Self {
+            solo: None,
+            filter: String::new(),
+            sort_by: ModelsSortBy::Name,
+            sort_ascending: true,
+        }
     }
 
     fn title(
@@ -32,21 +51,37 @@ impl Tab for Models {
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame, state: &mut crate::app::State) {
-        let (models, selected_model_key, scene_tx, ui_builder) = match &mut state.gs {
+        let (
+            models,
+            selected_model_key,
+            selected_model_keys,
+            compressions,
+            loading,
+            scene_tx,
+            ui_builder,
+        ) = match &mut state.gs {
             app::Loadable::Loaded(gs) => (
                 &mut gs.models,
                 &mut gs.selected_model_key,
+                &mut gs.selected_model_keys,
+                &gs.compressions,
+                gs.model_loader.as_ref().map(|(key, _)| key.clone()),
                 &gs.scene_tx,
                 egui::UiBuilder::new(),
             ),
             app::Loadable::Unloaded { .. } => (
                 &mut HashMap::new(),
                 &mut "".to_string(),
+                &mut HashSet::new(),
+                &app::Compressions::default(),
+                None,
                 &mpsc::channel().0,
                 egui::UiBuilder::new().disabled(),
             ),
         };
 
+        selected_model_keys.retain(|key| key != selected_model_key && models.contains_key(key));
+
         ui.scope_builder(ui_builder, |ui| {
             ui.horizontal(|ui| {
                 ui.label(format!("Model Count: {}", models.len()));
@@ -57,17 +92,55 @@ impl Tab for Models {
                     let tx = scene_tx.clone();
                     let task = rfd::AsyncFileDialog::new()
                         .set_title("Open a PLY file")
+                        .add_filter("PLY", &util::PLY_EXTENSIONS)
                         .pick_file();
 
                     util::exec_task(async move {
                         if let Some(file) = task.await {
                             let file_name = file.file_name();
+                            let path = util::file_handle_path(&file);
                             let reader = Box::new(Cursor::new(file.read().await));
-                            tx.send(app::SceneCommand::AddModel { file_name, reader })
-                                .expect("send gs");
+                            tx.send(app::SceneCommand::AddModel {
+                                file_name,
+                                path,
+                                reader,
+                            })
+                            .expect("send gs");
                         }
                     });
                 }
+
+                ui.separator();
+
+                if ui
+                    .button("👁 Show all")
+                    .on_hover_text("Make all models visible")
+                    .clicked()
+                {
+                    self.solo = None;
+                    models.values_mut().for_each(|model| model.visible = true);
+                }
+
+                ui.separator();
+
+                if !selected_model_keys.is_empty() {
+                    self.batch(
+                        ui,
+                        models,
+                        selected_model_key,
+                        selected_model_keys,
+                        loading.as_ref(),
+                        scene_tx,
+                    );
+
+                    ui.separator();
+                }
+
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.filter)
+                        .hint_text("🔍 Search")
+                        .desired_width(150.0),
+                );
             });
 
             let text_height = egui::TextStyle::Body
@@ -77,25 +150,55 @@ impl Tab for Models {
 
             let available_height = ui.available_height();
 
+            let filter = self.filter.to_lowercase();
             let mut models_ordered = models
                 .iter_mut()
-                .sorted_by_key(|(k, _)| (*k).clone())
+                .filter(|(_, model)| model.file_name.to_lowercase().contains(&filter))
+                .sorted_by(|(a_key, a), (b_key, b)| {
+                    let ordering = match self.sort_by {
+                        ModelsSortBy::Name => a_key.cmp(b_key),
+                        ModelsSortBy::Visible => {
+                            b.visible.cmp(&a.visible).then_with(|| a_key.cmp(b_key))
+                        }
+                    };
+
+                    match self.sort_ascending {
+                        true => ordering,
+                        false => ordering.reverse(),
+                    }
+                })
                 .collect::<Vec<_>>();
 
             let hovered = ui.ctx().input(|input| !input.raw.hovered_files.is_empty());
 
+            let dropped_zip = ui
+                .ctx()
+                .input(|input| match &input.raw.dropped_files.as_slice() {
+                    [file, ..] if util::is_zip(&file.name) => Some(file.clone()),
+                    _ => None,
+                });
+
+            if let Some(file) = dropped_zip {
+                match util::dropped_file_reader(&file) {
+                    Ok(reader) => app::GaussianSplatting::add_zip(reader, scene_tx.clone()),
+                    Err(err) => log::error!("Error opening zip: {err}"),
+                }
+            }
+
             let dropped_file = ui
                 .ctx()
                 .input(|input| match &input.raw.dropped_files.as_slice() {
+                    [file, ..] if util::is_zip(&file.name) => None,
                     [file, ..] => Some(match cfg!(target_arch = "wasm32") {
                         true => Ok((
                             file.name.clone(),
+                            None,
                             Box::new(Cursor::new(
                                 file.bytes.as_ref().expect("file bytes").clone(),
                             )) as Box<dyn BufRead + Send + 'static>,
                         )),
                         false => File::open(file.path.as_ref().expect("file path")).map(|f| {
-                            (file.name.clone(), {
+                            (file.name.clone(), file.path.clone(), {
                                 Box::new(BufReader::new(f)) as Box<dyn BufRead + Send + 'static>
                             })
                         }),
@@ -104,9 +207,13 @@ impl Tab for Models {
                 });
 
             match dropped_file {
-                Some(Ok((file_name, reader))) => {
+                Some(Ok((file_name, path, reader))) => {
                     scene_tx
-                        .send(app::SceneCommand::AddModel { file_name, reader })
+                        .send(app::SceneCommand::AddModel {
+                            file_name,
+                            path,
+                            reader,
+                        })
                         .expect("send gs");
                     ui.ctx().request_repaint();
                 }
@@ -118,21 +225,37 @@ impl Tab for Models {
 
             let row_count = models_ordered.len() + if hovered { 1 } else { 0 };
 
+            let mut solo_clicked = None;
+            let mut row_clicked = None;
+
             egui_extras::TableBuilder::new(ui)
                 .striped(true)
                 .resizable(true)
                 .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                .columns(egui_extras::Column::auto(), 4)
+                .columns(egui_extras::Column::auto(), 7)
                 .min_scrolled_height(0.0)
                 .max_scroll_height(available_height)
                 .sense(egui::Sense::click())
                 .header(20.0, |mut header| {
                     header.col(|_| {});
                     header.col(|ui| {
-                        ui.strong("File Name");
+                        self.sort_header(ui, "File Name", ModelsSortBy::Name);
+                    });
+                    header.col(|ui| {
+                        ui.strong("Gaussians");
                     });
                     header.col(|ui| {
-                        ui.strong("Visible");
+                        ui.strong("Size")
+                            .on_hover_text("The compressed size of the Gaussians");
+                    });
+                    header.col(|ui| {
+                        self.sort_header(ui, "Visible", ModelsSortBy::Visible);
+                    });
+                    header.col(|ui| {
+                        ui.strong("Display").on_hover_text(
+                            "The display mode of the model, overriding the global display mode \
+                            in the transform tab",
+                        );
                     });
                     header.col(|ui| {
                         ui.strong("Remove");
@@ -149,12 +272,19 @@ impl Tab for Models {
                             });
                             row.col(|_| {});
                             row.col(|_| {});
+                            row.col(|_| {});
+                            row.col(|_| {});
+                            row.col(|_| {});
                             return;
                         }
 
                         let (key, model) = &mut models_ordered[index];
 
-                        row.set_selected(*key == selected_model_key);
+                        row.set_selected(
+                            *key == selected_model_key || selected_model_keys.contains(*key),
+                        );
+
+                        let is_loading = loading.as_ref() == Some(*key);
 
                         row.col(|ui| {
                             ui.add(egui::Label::new((index + 1).to_string()).selectable(false));
@@ -162,23 +292,145 @@ impl Tab for Models {
                         row.col(|ui| {
                             ui.horizontal(|ui| {
                                 ui.add(egui::Label::new(&model.file_name).selectable(false));
+
+                                #[cfg(not(target_arch = "wasm32"))]
+                                if let Some(path) = &model.path {
+                                    if ui
+                                        .small_button("📂")
+                                        .on_hover_text(format!(
+                                            "Reveal in file manager\n{}",
+                                            path.display()
+                                        ))
+                                        .clicked()
+                                    {
+                                        if let Err(e) = util::reveal_in_file_manager(path) {
+                                            log::error!("Error revealing model file: {e}");
+                                        }
+                                    }
+                                }
+
+                                if is_loading {
+                                    ui.add(egui::Spinner::new());
+                                    ui.add(
+                                        egui::Label::new(
+                                            egui::RichText::new(format!(
+                                                "{:.0}%",
+                                                model.load_progress() * 100.0
+                                            ))
+                                            .weak(),
+                                        )
+                                        .selectable(false),
+                                    );
+                                }
                             });
                         });
-                        row.col(|ui| match model.visible {
-                            true => {
-                                if ui.button("👁").clicked() {
-                                    model.visible = false;
+                        row.col(|ui| {
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    ui.add(
+                                        egui::Label::new(
+                                            model
+                                                .gaussians
+                                                .gaussians
+                                                .capacity()
+                                                .to_formatted_string(&num_format::Locale::en),
+                                        )
+                                        .selectable(false),
+                                    );
+                                },
+                            );
+                        });
+                        row.col(|ui| {
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    ui.add(
+                                        egui::Label::new(util::human_readable_size(
+                                            compressions.compressed_size(
+                                                model.gaussians.gaussians.capacity(),
+                                            ),
+                                        ))
+                                        .selectable(false),
+                                    );
+                                },
+                            );
+                        });
+                        row.col(|ui| {
+                            match model.visible {
+                                true => {
+                                    if ui.button("👁").clicked() {
+                                        model.visible = false;
+                                    }
                                 }
+                                false => {
+                                    if ui.button("―").clicked() {
+                                        model.visible = true;
+                                    }
+                                }
+                            }
+
+                            if ui
+                                .selectable_label(
+                                    model.locked,
+                                    match model.locked {
+                                        true => "🔒",
+                                        false => "🔓",
+                                    },
+                                )
+                                .on_hover_text(
+                                    "Lock the model against transform, mask and selection edits",
+                                )
+                                .clicked()
+                            {
+                                model.locked = !model.locked;
                             }
-                            false => {
-                                if ui.button("―").clicked() {
-                                    model.visible = true;
+
+                            let soloed = self.solo.as_ref().is_some_and(|(solo, _)| solo == *key);
+                            if ui
+                                .selectable_label(soloed, "Solo")
+                                .on_hover_text(
+                                    "Show only this model, click again to restore the visibility",
+                                )
+                                .clicked()
+                            {
+                                solo_clicked = Some((*key).clone());
+                            }
+                        });
+
+                        row.col(|ui| {
+                            /// The text of a display mode override.
+                            fn display_mode_text(mode: Option<gs::GaussianDisplayMode>) -> String {
+                                match mode {
+                                    None => "Global".to_string(),
+                                    Some(mode) => format!("{mode:?}"),
                                 }
                             }
+
+                            egui::ComboBox::from_id_salt(("models_display_mode", *key))
+                                .selected_text(display_mode_text(model.display_mode))
+                                .show_ui(ui, |ui| {
+                                    for mode in [
+                                        None,
+                                        Some(gs::GaussianDisplayMode::Splat),
+                                        Some(gs::GaussianDisplayMode::Ellipse),
+                                        Some(gs::GaussianDisplayMode::Point),
+                                    ] {
+                                        ui.selectable_value(
+                                            &mut model.display_mode,
+                                            mode,
+                                            display_mode_text(mode),
+                                        );
+                                    }
+                                });
                         });
 
                         row.col(|ui| {
-                            if ui.button("🗑").clicked() {
+                            if ui
+                                .add_enabled(!is_loading, egui::Button::new("🗑"))
+                                .on_disabled_hover_text("The model is still loading")
+                                .clicked()
+                            {
                                 scene_tx
                                     .send(app::SceneCommand::RemoveModel(key.clone()))
                                     .unwrap();
@@ -186,10 +438,197 @@ impl Tab for Models {
                         });
 
                         if row.response().clicked() {
-                            *selected_model_key = (*key).clone();
+                            row_clicked = Some(index);
                         }
                     })
                 });
+
+            if let Some(index) = row_clicked {
+                let ordered_keys = models_ordered
+                    .iter()
+                    .map(|(key, _)| (*key).clone())
+                    .collect::<Vec<_>>();
+                let modifiers = ui.input(|input| input.modifiers);
+                Self::select(
+                    &ordered_keys,
+                    index,
+                    modifiers,
+                    selected_model_key,
+                    selected_model_keys,
+                );
+            }
+
+            if let Some(key) = solo_clicked {
+                self.solo(models, key);
+            }
         });
     }
 }
+
+/// The column to sort the models by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelsSortBy {
+    Name,
+    Visible,
+}
+
+impl Models {
+    /// Create a header that sorts the models by the column when clicked.
+    fn sort_header(&mut self, ui: &mut egui::Ui, label: &str, sort_by: ModelsSortBy) {
+        let text = match self.sort_by == sort_by {
+            true => format!("{label} {}", if self.sort_ascending { "⬆" } else { "⬇" }),
+            false => label.to_string(),
+        };
+
+        if ui
+            .add(egui::Button::new(egui::RichText::new(text).strong()).frame(false))
+            .clicked()
+        {
+            match self.sort_by == sort_by {
+                true => self.sort_ascending = !self.sort_ascending,
+                false => {
+                    self.sort_by = sort_by;
+                    self.sort_ascending = true;
+                }
+            }
+        }
+    }
+
+    /// Select the model at `index` of the listed `keys` by a click with `modifiers`.
+    ///
+    /// A click selects only the model, Ctrl toggles the model in the selection, and Shift selects
+    /// the range from the primary selected model.
+    fn select(
+        keys: &[String],
+        index: usize,
+        modifiers: egui::Modifiers,
+        selected_model_key: &mut String,
+        selected_model_keys: &mut HashSet<String>,
+    ) {
+        let key = &keys[index];
+
+        if modifiers.command {
+            match key == selected_model_key {
+                // Move the primary selection to another selected model
+                true => {
+                    if let Some(next) = selected_model_keys.iter().next().cloned() {
+                        selected_model_keys.remove(&next);
+                        *selected_model_key = next;
+                    }
+                }
+                false => {
+                    if !selected_model_keys.remove(key) {
+                        selected_model_keys
+                            .insert(std::mem::replace(selected_model_key, key.clone()));
+                    }
+                }
+            }
+        } else if modifiers.shift {
+            let anchor = keys
+                .iter()
+                .position(|key| key == selected_model_key)
+                .unwrap_or(index);
+            selected_model_keys.clear();
+            selected_model_keys.extend(
+                keys[anchor.min(index)..=anchor.max(index)]
+                    .iter()
+                    .filter(|key| *key != selected_model_key)
+                    .cloned(),
+            );
+        } else {
+            selected_model_keys.clear();
+            *selected_model_key = key.clone();
+        }
+    }
+
+    /// Create the batch operations on the selected models.
+    fn batch(
+        &mut self,
+        ui: &mut egui::Ui,
+        models: &mut HashMap<String, app::GaussianSplattingModel>,
+        selected_model_key: &str,
+        selected_model_keys: &mut HashSet<String>,
+        loading: Option<&String>,
+        scene_tx: &mpsc::Sender<app::SceneCommand>,
+    ) {
+        let keys = selected_model_keys
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(selected_model_key))
+            .collect::<Vec<_>>();
+
+        ui.label(format!("{} selected", keys.len())).on_hover_text(
+            "Ctrl click to add or remove a model, Shift click to select a range
+            The transform tab applies to all and the export to the selected models",
+        );
+
+        let any_visible = keys
+            .iter()
+            .any(|key| models.get(*key).is_some_and(|model| model.visible));
+        if ui
+            .button(match any_visible {
+                true => "― Hide",
+                false => "👁 Show",
+            })
+            .on_hover_text("Toggle the visibility of the selected models")
+            .clicked()
+        {
+            self.solo = None;
+            for key in keys.iter() {
+                if let Some(model) = models.get_mut(*key) {
+                    model.visible = !any_visible;
+                }
+            }
+        }
+
+        if ui
+            .button("🗑 Remove")
+            .on_hover_text("Remove the selected models, except the one still loading")
+            .clicked()
+        {
+            for key in keys
+                .iter()
+                .filter(|key| loading.map(String::as_str) != Some(**key))
+            {
+                scene_tx
+                    .send(app::SceneCommand::RemoveModel(key.to_string()))
+                    .expect("send remove model");
+            }
+        }
+
+        if ui
+            .button("✖")
+            .on_hover_text("Select only the primary model")
+            .clicked()
+        {
+            selected_model_keys.clear();
+        }
+    }
+
+    /// Solo the model, or restore the visibility if the model is already soloed.
+    fn solo(&mut self, models: &mut HashMap<String, app::GaussianSplattingModel>, key: String) {
+        match self.solo.take() {
+            Some((solo, visibility)) if solo == key => {
+                for (key, visible) in visibility {
+                    if let Some(model) = models.get_mut(&key) {
+                        model.visible = visible;
+                    }
+                }
+            }
+            prev => {
+                let visibility = prev.map(|(_, visibility)| visibility).unwrap_or_else(|| {
+                    models
+                        .iter()
+                        .map(|(key, model)| (key.clone(), model.visible))
+                        .collect()
+                });
+
+                for (model_key, model) in models.iter_mut() {
+                    model.visible = *model_key == key;
+                }
+
+                self.solo = Some((key, visibility));
+            }
+        }
+    }
+}
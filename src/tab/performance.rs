@@ -0,0 +1,401 @@
+use num_format::ToFormattedString;
+use strum::IntoEnumIterator;
+
+use crate::{app, renderer, util};
+
+use super::Tab;
+
+/// The performance tab.
+#[derive(Debug)]
+pub struct Performance {
+    /// The FPS update interval.
+    fps_interval: f32,
+
+    /// The number of frames in the FPS update interval.
+    fps_frames: u32,
+
+    /// The previous FPS.
+    fps: f32,
+
+    /// The previous average frame time in milliseconds.
+    frame_time: f32,
+
+    /// The vsync setting when the tab is created, which is the one in effect until restarting.
+    startup_vsync: bool,
+}
+
+impl Tab for Performance {
+    fn create(state: &mut app::State) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            fps_interval: 0.0,
+            fps_frames: 0,
+            fps: 0.0,
+            frame_time: 0.0,
+            startup_vsync: state.performance.vsync,
+        }
+    }
+
+    fn title(&mut self, _frame: &mut eframe::Frame, _state: &mut app::State) -> egui::WidgetText {
+        "Performance".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, frame: &mut eframe::Frame, state: &mut app::State) {
+        let dt = ui.ctx().input(|input| input.unstable_dt);
+        self.fps_interval += dt;
+        self.fps_frames += 1;
+        if self.fps_interval >= 1.0 {
+            self.fps = self.fps_frames as f32 / self.fps_interval;
+            self.frame_time = self.fps_interval * 1000.0 / self.fps_frames as f32;
+            self.fps_interval = 0.0;
+            self.fps_frames = 0;
+        }
+
+        ui.spacing_mut().item_spacing = egui::vec2(ui.spacing().item_spacing.x, 12.0);
+
+        ui.label(egui::RichText::new("Statistics").strong());
+        self.stats(ui, frame, &state.gs, &state.performance);
+
+        ui.separator();
+
+        ui.label(egui::RichText::new("Settings").strong());
+        self.settings(ui, &mut state.performance);
+    }
+}
+
+impl Performance {
+    /// Create the statistics.
+    fn stats(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &eframe::Frame,
+        gs: &app::Loadable<app::GaussianSplatting, String>,
+        performance: &app::Performance,
+    ) {
+        let (rendered_count, memory) = match gs {
+            app::Loadable::Loaded(gs) => (
+                performance
+                    .rendered_count(gs)
+                    .to_formatted_string(&num_format::Locale::en),
+                util::human_readable_size(
+                    gs.compressions.gpu_memory_estimate(
+                        gs.models
+                            .values()
+                            .map(|model| model.gaussians.gaussians.capacity())
+                            .sum(),
+                    ),
+                ),
+            ),
+            app::Loadable::Unloaded { .. } => ("N/A".to_string(), "N/A".to_string()),
+        };
+
+        egui::Grid::new("performance_stats_grid").show(ui, |ui| {
+            ui.label("FPS");
+            ui.label(egui::RichText::new(format!("{:.1}", self.fps)).monospace());
+            ui.end_row();
+
+            ui.label("Frame Time");
+            ui.label(egui::RichText::new(format!("{:.2} ms", self.frame_time)).monospace());
+            ui.end_row();
+
+            ui.label("Rendered Gaussians").on_hover_text(
+                "The number of Gaussians in visible models scaled by the render limit, \
+                including the culled ones, the limit applies after culling so fewer may be \
+                rendered",
+            );
+            ui.label(rendered_count);
+            ui.end_row();
+
+            ui.label("GPU Memory")
+                .on_hover_text("Estimated size of the Gaussians on the GPU");
+            ui.label(memory);
+            ui.end_row();
+
+            ui.label("Sort Keys").on_hover_text(
+                "The bit depth of the depth keys for sorting the Gaussians, \
+                which is fixed by the radix sorter",
+            );
+            ui.label(format!("{}-bit Float", app::Performance::SORT_KEY_BITS));
+            ui.end_row();
+
+            ui.label("Target Format")
+                .on_hover_text("The texture format of the render target");
+            ui.label(match frame.wgpu_render_state() {
+                Some(render_state) => util::texture_format_text(render_state.target_format),
+                None => "N/A".to_string(),
+            });
+            ui.end_row();
+
+            if performance.gpu_timing {
+                let features = frame
+                    .wgpu_render_state()
+                    .map(|render_state| render_state.device.features())
+                    .unwrap_or_default();
+
+                for pass in app::GpuPass::iter() {
+                    ui.label(format!("GPU {pass}"))
+                        .on_hover_text(format!("The GPU time of the {pass} pass"));
+                    let timing = match gs {
+                        app::Loadable::Loaded(gs) => gs.gpu_timings[pass as usize],
+                        app::Loadable::Unloaded { .. } => None,
+                    };
+                    match (renderer::GpuTimer::is_supported(features, pass), timing) {
+                        (false, _) => ui.label("Unsupported"),
+                        (true, Some(timing)) => {
+                            ui.label(egui::RichText::new(format!("{timing:.2} ms")).monospace())
+                        }
+                        (true, None) => ui.label("N/A"),
+                    };
+                    ui.end_row();
+                }
+            }
+        });
+    }
+
+    /// Create the settings.
+    fn settings(&mut self, ui: &mut egui::Ui, performance: &mut app::Performance) {
+        egui::Grid::new("performance_settings_grid").show(ui, |ui| {
+            ui.label("Loading Budget").on_hover_text(
+                "The time spent on uploading loaded Gaussians to the GPU in each frame",
+            );
+            ui.add(
+                egui::Slider::new(&mut performance.loading_budget, 1.0..=100.0)
+                    .suffix(" ms")
+                    .fixed_decimals(0),
+            );
+            ui.end_row();
+
+            ui.label("Render Mode").on_hover_text(format!(
+                "When to repaint, Always is for benchmarking, On Interaction only repaints when \
+                there is any input or change, and Power Saver also repaints at {} FPS when idle",
+                app::RenderMode::POWER_SAVER_FPS,
+            ));
+            ui.horizontal(|ui| {
+                for mode in app::RenderMode::iter() {
+                    ui.selectable_value(&mut performance.render_mode, mode, mode.to_string());
+                }
+
+                ui.label(egui::RichText::new(format!("{:.1} FPS", self.fps)).weak())
+                    .on_hover_text("The current effective frame rate");
+            });
+            ui.end_row();
+
+            let label = ui.label("Max FPS").on_hover_text(
+                "Limit the frame rate to save power, the scene still only repaints on change if \
+                the render mode is not Always",
+            );
+            ui.horizontal(|ui| {
+                let mut enabled = performance.max_fps.is_some();
+                if ui
+                    .add(util::toggle(&mut enabled))
+                    .labelled_by(label.id)
+                    .changed()
+                {
+                    performance.max_fps = enabled.then_some(60);
+                }
+
+                if let Some(max_fps) = &mut performance.max_fps {
+                    ui.add(egui::Slider::new(max_fps, 10..=240).suffix(" FPS"));
+                }
+            });
+            ui.end_row();
+
+            if !cfg!(target_arch = "wasm32") {
+                let label = ui.label("VSync").on_hover_text(
+                    "Wait for the display refresh when presenting frames, takes effect after \
+                    restarting the app",
+                );
+                ui.horizontal(|ui| {
+                    ui.add(util::toggle(&mut performance.vsync))
+                        .labelled_by(label.id);
+
+                    if performance.vsync != self.startup_vsync {
+                        ui.label(egui::RichText::new("Restart to apply").weak().italics());
+                    }
+                });
+                ui.end_row();
+            }
+
+            let label = ui.label("Release When Idle").on_hover_text(
+                "Release the GPU buffers of the models after being idle for a while, they are \
+                rebuilt from the Gaussians in memory on the next interaction",
+            );
+            ui.horizontal(|ui| {
+                let mut enabled = performance.idle_release.is_some();
+                if ui
+                    .add(util::toggle(&mut enabled))
+                    .labelled_by(label.id)
+                    .changed()
+                {
+                    performance.idle_release = enabled.then_some(10);
+                }
+
+                if let Some(idle_release) = &mut performance.idle_release {
+                    ui.add(egui::Slider::new(idle_release, 1..=120).suffix(" min"));
+                }
+            });
+            ui.end_row();
+
+            let label = ui
+                .label("Memory Cap")
+                .on_hover_text("Warn before loading a model estimated to use more GPU memory");
+            ui.horizontal(|ui| {
+                let mut enabled = performance.memory_cap.is_some();
+                if ui
+                    .add(util::toggle(&mut enabled))
+                    .labelled_by(label.id)
+                    .changed()
+                {
+                    performance.memory_cap = enabled.then_some(2048);
+                }
+
+                if let Some(cap) = &mut performance.memory_cap {
+                    ui.add(
+                        egui::DragValue::new(cap)
+                            .speed(16)
+                            .range(64..=65536)
+                            .suffix(" MB"),
+                    );
+                }
+            });
+            ui.end_row();
+
+            ui.label("Render Scale").on_hover_text(
+                "The resolution scale of the viewport, lower is faster but blurrier",
+            );
+            ui.add(
+                egui::Slider::new(&mut performance.render_scale, 0.25..=1.0)
+                    .fixed_decimals(2)
+                    .step_by(0.05),
+            );
+            ui.end_row();
+
+            ui.label("Upscale Filter")
+                .on_hover_text("The filter for upscaling the viewport to full resolution");
+            ui.add_enabled_ui(performance.render_scale < 1.0, |ui| {
+                ui.horizontal(|ui| {
+                    for filter in app::UpscaleFilter::iter() {
+                        ui.selectable_value(
+                            &mut performance.upscale_filter,
+                            filter,
+                            filter.to_string(),
+                        );
+                    }
+                });
+            });
+            ui.end_row();
+
+            let label = ui
+                .label("Refine When Idle")
+                .on_hover_text("Render in full resolution when the camera stops moving");
+            ui.add_enabled_ui(performance.render_scale < 1.0, |ui| {
+                ui.add(util::toggle(&mut performance.render_scale_refine))
+                    .labelled_by(label.id);
+            });
+            ui.end_row();
+
+            let label = ui.label("Temporal Accumulation").on_hover_text(
+                "Blend successive frames when the camera is idle for a cleaner image, \
+                changes other than the camera fade in over a few frames",
+            );
+            ui.add(util::toggle(&mut performance.temporal_accumulation))
+                .labelled_by(label.id);
+            ui.end_row();
+
+            let label = ui.label("FXAA").on_hover_text(
+                "Smooth the jagged edges of the viewport with a cheap full screen anti-aliasing \
+                after the tone mapping, which may slightly blur fine details",
+            );
+            ui.add(util::toggle(&mut performance.fxaa))
+                .labelled_by(label.id);
+            ui.end_row();
+
+            let label = ui.label("Stats in Title").on_hover_text(
+                "Show the FPS and the rendered Gaussian count in the window title, updated every \
+                second",
+            );
+            ui.add(util::toggle(&mut performance.title_stats))
+                .labelled_by(label.id);
+            ui.end_row();
+
+            let label = ui.label("SH LOD").on_hover_text(
+                "Reduce the SH degree of models far from the camera by one band beyond each \
+                distance, view dependent colors are barely visible far away",
+            );
+            ui.horizontal(|ui| {
+                let mut enabled = performance.sh_lod.is_some();
+                if ui
+                    .add(util::toggle(&mut enabled))
+                    .labelled_by(label.id)
+                    .changed()
+                {
+                    performance.sh_lod = enabled.then_some(app::Performance::SH_LOD_DISTANCES);
+                }
+
+                if let Some(distances) = &mut performance.sh_lod {
+                    for i in 0..distances.len() {
+                        // Keep the distances increasing
+                        let min = i.checked_sub(1).map_or(0.0, |j| distances[j]);
+                        let max = distances.get(i + 1).copied().unwrap_or(f32::INFINITY);
+                        ui.add(
+                            egui::DragValue::new(&mut distances[i])
+                                .speed(0.1)
+                                .range(min..=max)
+                                .fixed_decimals(1),
+                        )
+                        .on_hover_text(format!(
+                            "Beyond this distance, use at most SH degree {}",
+                            2 - i
+                        ));
+                    }
+                }
+            });
+            ui.end_row();
+
+            let label = ui.label("Render Limit").on_hover_text(
+                "Only render a fraction of the Gaussians left after culling in each model for \
+                testing the performance, the nearest Gaussians are kept if the GPU supports \
+                indirect first instance, otherwise the farthest",
+            );
+            ui.horizontal(|ui| {
+                let mut enabled = performance.render_limit.is_some();
+                if ui
+                    .add(util::toggle(&mut enabled))
+                    .labelled_by(label.id)
+                    .changed()
+                {
+                    performance.render_limit = enabled.then_some(0.5);
+                }
+
+                if let Some(fraction) = &mut performance.render_limit {
+                    let mut percent = *fraction * 100.0;
+                    ui.add(
+                        egui::Slider::new(&mut percent, 0.0..=100.0)
+                            .suffix("%")
+                            .fixed_decimals(1),
+                    );
+                    *fraction = percent / 100.0;
+                }
+            });
+            ui.end_row();
+
+            let label = ui.label("Depth Write (Experimental)").on_hover_text(
+                "Write an approximate depth at the center of each Gaussian, so the measurements \
+                and gizmos are occluded by dense Gaussians, overlapping models may be cut off",
+            );
+            ui.add(util::toggle(&mut performance.depth_write))
+                .labelled_by(label.id);
+            ui.end_row();
+
+            let label = ui.label("GPU Timing").on_hover_text(
+                "Measure the GPU time of each pass with timestamp queries, \
+                shown in the statistics when supported by the device",
+            );
+            ui.add(util::toggle(&mut performance.gpu_timing))
+                .labelled_by(label.id);
+            ui.end_row();
+        });
+    }
+}
@@ -1,9 +1,11 @@
 // 定义标准库的导入
 use std::{
-    collections::HashMap,     // 导入 HashMap 类型
-    io::Cursor,               // 导入 Cursor 类型
-    marker::PhantomData,      // 导入 PhantomData 类型
-    sync::{Arc, Mutex, mpsc}, // 导入同步原语类型
+    borrow::Cow,                     // 导入 Cow 类型
+    collections::{HashMap, HashSet}, // 导入 HashMap 和 HashSet 类型
+    io::{BufRead, Cursor},           // 导入 BufRead 和 Cursor 类型
+    marker::PhantomData,             // 导入 PhantomData 类型
+    path::PathBuf,                   // 导入 PathBuf 类型
+    sync::{Arc, Mutex, mpsc},        // 导入同步原语类型
 };
 
 // 为 WebAssembly 32 位架构导入特定类型
@@ -19,12 +21,14 @@ use itertools::Itertools;
 // 导入数字格式化功能
 use num_format::ToFormattedString;
 // 导入枚举迭代功能
-use strum::IntoEnumIterator;
+use strum::{EnumCount, IntoEnumIterator};
 // 导入 wgpu_3dgs_viewer 库并起别名 gs，以及相关类型
 use wgpu_3dgs_viewer::{self as gs, QueryVariant, Texture};
 
 // 导入项目内部模块
-use crate::{app, renderer, util};
+use crate::{
+    app, compression_preview::CompressionPreview, project, renderer, sample, script, util,
+};
 
 // 从父模块导入 Tab trait
 use super::Tab;
@@ -99,6 +103,9 @@ pub struct Scene {
     /// The FPS update interval.
     fps_interval: f32,
 
+    /// The number of frames in the FPS update interval.
+    fps_frames: u32,
+
     /// The previous FPS.
     fps: f32,
 
@@ -111,11 +118,108 @@ pub struct Scene {
     /// The pending query result.
     query_result: Option<QueryResult>,
 
-    /// VR mode toggle
-    vr_mode: bool,
+    /// The stereo display mode.
+    stereo_mode: app::StereoMode,
+
+    /// The fixed aspect ratio of the viewport, not applied in VR split.
+    aspect: app::ViewportAspect,
+
+    /// The color channels of the eyes in anaglyph stereo.
+    anaglyph_colors: app::AnaglyphColors,
 
     /// VR parallax strength (IPD multiplier)
     vr_parallax_strength: f32,
+
+    /// The layout of the eyes in VR split.
+    vr_layout: app::VrLayout,
+
+    /// Whether to swap the eyes, for viewers expecting the other order.
+    vr_swap_eyes: bool,
+
+    /// Whether to show the zero parallax guide in VR split.
+    vr_convergence_guide: bool,
+
+    /// The maximum comfortable disparity in degrees in VR split.
+    vr_comfort_disparity: f32,
+
+    /// The model key and op of the mask evaluation waiting for the running one to finish.
+    ///
+    /// A newer evaluation replaces it, so only the latest op is evaluated.
+    mask_evaluation_pending: Option<(String, Option<app::GaussianSplattingMaskOp>)>,
+
+    /// The model key and receiver of the running mask evaluation on the GPU.
+    mask_evaluation_rx: Option<(String, oneshot::Receiver<()>)>,
+
+    /// The model key and time of the last mask evaluation waiting for coverage download.
+    mask_coverage_pending: Option<(String, f64)>,
+
+    /// The model key and receiver of the mask coverage download.
+    mask_coverage_rx: Option<(String, oneshot::Receiver<usize>)>,
+
+    /// The model key and receiver of the bounding box download.
+    model_bounds_rx: Option<(String, oneshot::Receiver<(Vec3, Vec3)>)>,
+
+    /// The time of the last auto-save.
+    autosave_time: f64,
+
+    /// The hash of the last auto-saved project.
+    autosave_hash: u64,
+
+    /// The receiver of the auto-saved project hash.
+    autosave_rx: Option<oneshot::Receiver<u64>>,
+
+    /// The model key, task and receiver of the downloaded selection and edits.
+    #[allow(clippy::type_complexity)]
+    selection_task_rx: Option<(
+        String,
+        SelectionTask,
+        oneshot::Receiver<(Vec<u32>, Vec<gs::GaussianEditPod>)>,
+    )>,
+
+    /// The exposure and tone mapping of the scene.
+    tone_mapping: app::ToneMapping,
+
+    /// The gamma correction of the scene.
+    gamma_correction: app::GammaCorrection,
+
+    /// The maximum scale being dragged, applied when the drag stops.
+    max_scale_drag: Option<f32>,
+
+    /// The sorted maximum scales of the Gaussians of each model, for counting the clamped
+    /// Gaussians by binary search.
+    ///
+    /// A model is sorted again when its number of Gaussians changes.
+    max_scale_sorted: HashMap<String, Vec<f32>>,
+
+    /// The time of the last activity in the viewport, for auto-hiding the camera HUD.
+    camera_hud_time: f64,
+
+    /// The camera view of the last frame, for detecting camera movement.
+    camera_view: Mat4,
+
+    /// The time of the last camera movement.
+    camera_moved_time: f64,
+
+    /// The effective render scale of the current frame.
+    render_scale: f32,
+
+    /// The temporal accumulation frame index of the current frame, 0 when restarted.
+    accumulation_frame: Option<u32>,
+
+    /// The camera jitter of the current frame in physical pixels.
+    jitter: Vec2,
+
+    /// The pixels per point of the current frame, for detecting display scale changes.
+    pixels_per_point: f32,
+
+    /// The compression preview shown when initializing the scene.
+    compression_preview: Option<SceneCompressionPreview>,
+
+    /// The time of the last input, for releasing the GPU resources when idle.
+    input_time: f64,
+
+    /// The release of the GPU resources when idle.
+    idle: SceneIdle,
 }
 
 impl Tab for Scene {
@@ -126,12 +230,42 @@ impl Tab for Scene {
         Self {
             input: SceneInput::new(),
             fps_interval: 0.0,
+            fps_frames: 0,
             fps: 0.0,
             initialized: false,
             query: Query::none(),
             query_result: None,
-            vr_mode: false,            // 默认关闭VR模式
+            stereo_mode: app::StereoMode::Off, // 默认关闭立体显示
+            aspect: app::ViewportAspect::Free, // 默认填满标签页
+            anaglyph_colors: app::AnaglyphColors::default(),
             vr_parallax_strength: 1.0, // 默认视差强度为1.0
+            vr_layout: app::VrLayout::default(),
+            vr_swap_eyes: false,
+            vr_convergence_guide: false,
+            vr_comfort_disparity: 1.0,
+            mask_evaluation_pending: None,
+            mask_evaluation_rx: None,
+            mask_coverage_pending: None,
+            mask_coverage_rx: None,
+            model_bounds_rx: None,
+            autosave_time: 0.0,
+            autosave_hash: 0,
+            autosave_rx: None,
+            selection_task_rx: None,
+            tone_mapping: app::ToneMapping::new(),
+            gamma_correction: app::GammaCorrection::default(),
+            max_scale_drag: None,
+            max_scale_sorted: HashMap::new(),
+            camera_hud_time: 0.0,
+            camera_view: Mat4::IDENTITY,
+            camera_moved_time: 0.0,
+            render_scale: 1.0,
+            accumulation_frame: None,
+            jitter: Vec2::ZERO,
+            pixels_per_point: 1.0,
+            compression_preview: None,
+            input_time: 0.0,
+            idle: SceneIdle::Active,
         }
     }
 
@@ -140,12 +274,39 @@ impl Tab for Scene {
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, frame: &mut eframe::Frame, state: &mut app::State) {
+        self.run_script(ui, frame, state);
+
+        // 运行脚本时直接使用当前压缩设置
+        let mut confirm_initialize = state.settings.confirm_initialize && state.script.is_none();
+
         let updated_gs = match &mut state.gs {
             app::Loadable::Unloaded(unloaded) => match unloaded.rx.try_recv() {
-                Ok(Ok(gs)) => {
+                Ok(Ok(mut gs)) => {
                     log::debug!("Gaussian splatting loaded");
 
+                    // 打开其他模型后不再自动加载示例模型
+                    if gs.selected_model().file_name != sample::NAME {
+                        state.settings.load_sample = false;
+                    }
+
+                    // 每个打开的模型都从默认压缩设置开始
+                    state.compressions = state.default_compressions.clone();
+                    gs.compressions = state.compressions.clone();
+
+                    // 按导入的坐标系设置初始变换，示例模型和项目使用自己的变换
+                    if gs.selected_model().file_name != sample::NAME && gs.project_restore.is_none()
+                    {
+                        gs.selected_model_mut().transform =
+                            state.settings.coordinate_system.transform();
+                        if state.settings.flip_handedness {
+                            gs.selected_model_mut().flip_handedness();
+                        }
+                    }
+
                     self.initialized = false;
+                    self.idle = SceneIdle::Active;
+                    // 新的会话需要重新自动保存，即使与之前的会话相同
+                    self.autosave_hash = 0;
                     self.empty(ui, unloaded, &state.compressions);
 
                     Some(app::Loadable::loaded(gs))
@@ -164,22 +325,43 @@ impl Tab for Scene {
                 }
             },
             app::Loadable::Loaded(gs) => match self.initialized {
-                false => match self.initialize(ui, frame, gs, &mut state.compressions) {
+                false => match self.initialize(
+                    ui,
+                    frame,
+                    gs,
+                    &mut state.compressions,
+                    &state.performance,
+                    &mut state.settings.coordinate_system,
+                    &mut state.settings.flip_handedness,
+                    &mut confirm_initialize,
+                ) {
                     Ok(Some(true)) => {
                         self.initialized = true;
+                        self.compression_preview = None;
                         None
                     }
-                    Ok(Some(false)) => Some(app::Loadable::unloaded()),
+                    Ok(Some(false)) => {
+                        self.compression_preview = None;
+                        Some(app::Loadable::unloaded())
+                    }
                     Ok(None) => None,
                     Err(e) => Some(app::Loadable::error(e)),
                 },
-                true => match self.loaded(ui, frame, gs) {
+                true => match self.loaded(ui, frame, gs, &state.performance, &state.settings) {
                     true => None,
-                    false => Some(app::Loadable::unloaded()),
+                    false => {
+                        // 关闭模型后不再恢复自动保存的会话
+                        project::Project::clear_recovery();
+                        Some(app::Loadable::unloaded())
+                    }
                 },
             },
         };
 
+        if state.script.is_none() {
+            state.settings.confirm_initialize = confirm_initialize;
+        }
+
         if let Some(gs) = updated_gs {
             state.gs = gs;
         }
@@ -187,6 +369,23 @@ impl Tab for Scene {
 }
 
 impl Scene {
+    /// The number of frames averaged by the temporal accumulation.
+    const ACCUMULATION_FRAMES: u32 = 16;
+
+    /// 基础IPD（眼间距），由视差强度缩放
+    const BASE_IPD: f32 = 0.065;
+
+    /// 左眼模型的X偏移，右眼为其相反数
+    ///
+    /// 使用视差强度参数（从UI滑块获取），交换双眼时取反。
+    fn vr_left_eye_offset(&self) -> f32 {
+        let offset = -Self::BASE_IPD * self.vr_parallax_strength / 2.0;
+        match self.vr_swap_eyes {
+            true => -offset,
+            false => offset,
+        }
+    }
+
     /// Create an empty scene tab.
     fn empty(
         &mut self,
@@ -204,6 +403,7 @@ impl Scene {
                 let ctx = ui.ctx().clone();
                 let task = rfd::AsyncFileDialog::new()
                     .set_title("Open a PLY file")
+                    .add_filter("PLY", &util::PLY_EXTENSIONS)
                     .pick_file();
                 let compressions = compressions.clone();
 
@@ -213,8 +413,9 @@ impl Scene {
                             true => "Unnamed".to_string(),
                             false => file.file_name().trim().to_string(),
                         };
+                        let path = util::file_handle_path(&file);
                         let reader = Cursor::new(file.read().await);
-                        let gs = app::GaussianSplatting::new(filename, reader, compressions)
+                        let gs = app::GaussianSplatting::new(filename, path, reader, compressions)
                             .map_err(|e| e.to_string());
 
                         tx.send(gs).expect("send gs");
@@ -224,7 +425,20 @@ impl Scene {
             }
 
             ui.label("");
-            ui.label("to Open a PLY Model File 📦");
+            ui.label("to Open a PLY Model File (or .ply.gz) or a Zip of Models 📦");
+
+            ui.label("");
+            if ui
+                .button("Load Sample")
+                .on_hover_text("Load a small generated model to try out the viewer")
+                .clicked()
+            {
+                unloaded
+                    .tx
+                    .send(sample::load(compressions.clone()))
+                    .expect("send gs");
+                ui.ctx().request_repaint();
+            }
 
             if ui.ctx().input(|input| !input.raw.hovered_files.is_empty()) {
                 ui.label("");
@@ -234,16 +448,37 @@ impl Scene {
                 ui.label(egui::RichText::new(format!("Error: {err}")).color(egui::Color32::RED));
             }
 
+            let dropped_zip = ui
+                .ctx()
+                .input(|input| match &input.raw.dropped_files.as_slice() {
+                    [file] if util::is_zip(&file.name) => Some(file.clone()),
+                    _ => None,
+                });
+
+            if let Some(file) = dropped_zip {
+                match util::dropped_file_reader(&file) {
+                    Ok(reader) => app::GaussianSplatting::open_zip(
+                        reader,
+                        compressions.clone(),
+                        unloaded.tx.clone(),
+                        ui.ctx().clone(),
+                    ),
+                    Err(err) => unloaded.err = Some(err.to_string()),
+                }
+            }
+
             match ui
                 .ctx()
                 .input(|input| match &input.raw.dropped_files.as_slice() {
                     [_x, _xs, ..] => Some(Err("only one file is allowed")),
+                    [file] if util::is_zip(&file.name) => None,
                     [file] => Some(Ok(match cfg!(target_arch = "wasm32") {
                         true => app::GaussianSplatting::new(
                             match file.name.trim().is_empty() {
                                 true => "Unnamed".to_string(),
                                 false => file.name.trim().to_string(),
                             },
+                            None,
                             Cursor::new(file.bytes.as_ref().expect("file bytes").clone()),
                             compressions.clone(),
                         )
@@ -257,6 +492,7 @@ impl Scene {
                                         true => "Unnamed".to_string(),
                                         false => file.name.trim().to_string(),
                                     },
+                                    file.path.clone(),
                                     Cursor::new(data),
                                     compressions.clone(),
                                 )
@@ -285,6 +521,8 @@ impl Scene {
         ui: &mut egui::Ui,
         frame: &mut eframe::Frame,
         gs: &mut app::GaussianSplatting,
+        performance: &app::Performance,
+        settings: &app::Settings,
     ) -> bool {
         let mut loaded = true;
 
@@ -294,12 +532,14 @@ impl Scene {
                 ui.add(egui::Spinner::default());
 
                 ui.label(format!("Loading: {loading}"));
+                if gs.queued_models > 0 {
+                    ui.label(format!("({} more queued)", gs.queued_models));
+                }
 
                 ui.separator();
 
                 let model = gs.models.get(loading).expect("model");
-                let progress = model.gaussians.gaussians.len() as f32
-                    / model.gaussians.gaussians.capacity() as f32;
+                let progress = model.load_progress();
 
                 ui.add(egui::ProgressBar::new(progress).text(format!(
                         "{} / {}",
@@ -308,11 +548,7 @@ impl Scene {
                             .gaussians
                             .len()
                             .to_formatted_string(&num_format::Locale::en),
-                        model
-                            .gaussians
-                            .gaussians
-                            .capacity()
-                            .to_formatted_string(&num_format::Locale::en),
+                        model.count.to_formatted_string(&num_format::Locale::en),
                     )));
             });
 
@@ -321,23 +557,140 @@ impl Scene {
 
         // UI
         ui.horizontal(|ui| {
-            // 添加VR模式开关
-            let vr_changed = ui.checkbox(&mut self.vr_mode, "VR Mode").changed();
-            if vr_changed {
-                log::info!("🔄 [VR DEBUG] VR mode toggled: {}", self.vr_mode);
+            // 立体显示模式
+            let stereo_mode = self.stereo_mode;
+            ui.label("👓 Stereo:").on_hover_text(
+                "VR Split shows the eyes side by side or top and bottom, the left eye viewport \
+                controls the camera and the tools\n\
+                Anaglyph combines the eyes into one viewport for colored 3D glasses",
+            );
+            egui::ComboBox::from_id_salt("scene_stereo_mode")
+                .selected_text(self.stereo_mode.to_string())
+                .show_ui(ui, |ui| {
+                    for mode in app::StereoMode::iter() {
+                        ui.selectable_value(&mut self.stereo_mode, mode, mode.to_string());
+                    }
+                });
+            if self.stereo_mode != stereo_mode {
+                log::info!("🔄 [VR DEBUG] Stereo mode changed: {}", self.stereo_mode);
+
+                // 视口矩形改变，取消进行中的查询
+                self.query = Query::none();
+            }
+
+            if self.stereo_mode == app::StereoMode::Anaglyph {
+                egui::ComboBox::from_id_salt("scene_anaglyph_colors")
+                    .selected_text(self.anaglyph_colors.to_string())
+                    .show_ui(ui, |ui| {
+                        for colors in app::AnaglyphColors::iter() {
+                            ui.selectable_value(
+                                &mut self.anaglyph_colors,
+                                colors,
+                                colors.to_string(),
+                            );
+                        }
+                    })
+                    .response
+                    .on_hover_text("The colors of the left and right eye lenses");
             }
 
-            // 添加VR视差强度滑块（仅在VR模式下显示）
-            if self.vr_mode {
+            // VR布局（仅在VR分屏时显示）
+            if self.stereo_mode == app::StereoMode::Split {
+                let vr_layout = self.vr_layout;
+                egui::ComboBox::from_id_salt("scene_vr_layout")
+                    .selected_text(self.vr_layout.to_string())
+                    .show_ui(ui, |ui| {
+                        for layout in app::VrLayout::iter() {
+                            ui.selectable_value(&mut self.vr_layout, layout, layout.to_string());
+                        }
+                    })
+                    .response
+                    .on_hover_text("How the eyes are arranged for the headset app");
+                if self.vr_layout != vr_layout {
+                    log::info!("🔄 [VR DEBUG] VR layout changed: {}", self.vr_layout);
+
+                    // 视口矩形改变，取消进行中的查询
+                    self.query = Query::none();
+                }
+            }
+
+            // 添加VR视差强度滑块（仅在立体显示时显示）
+            if self.stereo_mode != app::StereoMode::Off {
                 ui.separator();
-                ui.label("👀 Parallax:");
+                ui.label("👀 Parallax:").on_hover_text(format!(
+                    "The eye separation is {:.1} mm",
+                    Self::BASE_IPD * self.vr_parallax_strength * 1000.0
+                ));
                 ui.add(
                     egui::Slider::new(&mut self.vr_parallax_strength, 0.0..=5.0).fixed_decimals(1),
                 );
+
+                ui.checkbox(&mut self.vr_swap_eyes, "Swap Eyes")
+                    .on_hover_text("Exchange the left and right eyes if the stereo looks inverted");
+
+                if self.stereo_mode == app::StereoMode::Split {
+                    ui.checkbox(&mut self.vr_convergence_guide, "Guide").on_hover_text(
+                        "Show a reticle at screen depth in both eyes, and where the center of the \
+                        selected model appears in each eye\n\
+                        The model is at screen depth when its markers sit on the reticles",
+                    );
+
+                    ui.label("Comfort:").on_hover_text(
+                        "The maximum comfortable disparity, a warning is shown in VR split when \
+                        the selected model is close enough to exceed it",
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut self.vr_comfort_disparity)
+                            .range(0.1..=5.0)
+                            .speed(0.05)
+                            .fixed_decimals(1)
+                            .suffix("°"),
+                    );
+                }
+            }
+
+            // 视口固定宽高比（VR分屏时不适用）
+            if self.stereo_mode != app::StereoMode::Split {
+                ui.separator();
+                let aspect = self.aspect;
+                ui.label("🖼 Aspect:").on_hover_text(
+                    "Letterbox the viewport to a fixed aspect ratio, e.g. for screenshots and \
+                    recordings",
+                );
+                egui::ComboBox::from_id_salt("scene_aspect")
+                    .selected_text(self.aspect.to_string())
+                    .show_ui(ui, |ui| {
+                        for aspect in app::ViewportAspect::iter() {
+                            ui.selectable_value(&mut self.aspect, aspect, aspect.to_string());
+                        }
+                    });
+                if self.aspect != aspect {
+                    // 视口矩形改变，取消进行中的查询
+                    self.query = Query::none();
+                }
             }
 
             ui.separator();
 
+            self.tone_mapping_menu(ui, frame);
+            self.max_scale_menu(ui, gs);
+
+            ui.separator();
+
+            if gs
+                .models
+                .values()
+                .any(|model| matches!(model.capture, Some(Ok(..))))
+            {
+                ui.checkbox(&mut gs.capture_cameras.visible, "📷 Capture Cameras")
+                    .on_hover_text(
+                        "Show the capture camera positions from the sidecar metadata\n\
+                        Click a camera to move the view to its pose",
+                    );
+
+                ui.separator();
+            }
+
             let loaded_label = ui.label(format!(
                 "📦 Loaded: {}",
                 if gs.models.len() > 1 {
@@ -355,15 +708,40 @@ impl Scene {
 
             ui.separator();
 
-            loaded &= !ui.button("🗑 Close models").clicked();
+            if ui.button("🗑 Close models").clicked() {
+                match gs.unsaved {
+                    true => gs.discard_confirm = Some(app::DiscardAction::CloseModels),
+                    false => loaded = false,
+                }
+            }
+
+            ui.separator();
+
+            let compressions = &gs.compressions;
+            ui.add(
+                egui::Label::new(format!(
+                    "🗜 {} / {}",
+                    compressions.sh.abbreviation(),
+                    compressions.cov3d.abbreviation()
+                ))
+                .sense(egui::Sense::hover()),
+            )
+            .on_hover_text(format!(
+                "Compressions of the loaded models\n\
+                 • SH: {}\n\
+                 • Covariance 3D: {}",
+                compressions.sh, compressions.cov3d
+            ));
 
             ui.separator();
 
             let dt = ui.ctx().input(|input| input.unstable_dt);
             self.fps_interval += dt;
+            self.fps_frames += 1;
             if self.fps_interval >= 1.0 {
-                self.fps_interval -= 1.0;
-                self.fps = 1.0 / dt;
+                self.fps = self.fps_frames as f32 / self.fps_interval;
+                self.fps_interval = 0.0;
+                self.fps_frames = 0;
             }
 
             ui.label("🏃 FPS:");
@@ -372,34 +750,13 @@ impl Scene {
             ));
         });
 
-        // Check for loading model
-        if let Some((loading, rx)) = &gs.model_loader {
-            let timer = chrono::Local::now();
-
-            let model = gs.models.get_mut(loading).expect("model");
-            let mut new_count = 0;
-
-            for gaussian in rx.try_iter() {
-                match gaussian {
-                    Ok(gaussian) => {
-                        model.gaussians.gaussians.push(gaussian);
-                        new_count += 1;
-                    }
-                    Err(e) => {
-                        log::error!("Error loading gaussian: {e}");
-                    }
-                }
-
-                const BATCH_SIZE: usize = 1000;
-                const MAX_TIME: f32 = 0.06;
-                if new_count % BATCH_SIZE == 0
-                    && (chrono::Local::now() - timer).num_milliseconds() as f32 / 100.0 > MAX_TIME
-                {
-                    break;
-                }
-            }
+        if self.idle(ui, frame, gs, performance) {
+            return loaded;
+        }
 
-            let start = model.gaussians.gaussians.len() - new_count;
+        // Check for loading model
+        if let Some((loading, start)) = Self::receive_gaussians(ui, gs, performance) {
+            let model = gs.models.get(&loading).expect("model");
             apply_to_scene_resource!(frame, gs.compressions, |res| {
                 res.load_model(
                     frame.wgpu_render_state().expect("render state"),
@@ -408,53 +765,49 @@ impl Scene {
                     &model.gaussians.gaussians[start..],
                 )
             });
-
-            if model.gaussians.gaussians.len() == model.gaussians.gaussians.capacity() {
-                gs.model_loader = None;
-            }
         }
 
         // Receive scene commands
-        for command in gs.scene_rx.try_iter() {
+        gs.queued_models = 0;
+        for command in gs.scene_rx.try_iter().collect::<Vec<_>>() {
             match command {
-                app::SceneCommand::AddModel { file_name, reader } => {
-                    let mut i = 0;
-                    let mut new_file_name = file_name.clone();
-                    while gs.models.contains_key(&new_file_name) {
-                        i += 1;
-                        new_file_name = format!("{} ({})", file_name, i);
-                    }
-
-                    let file_name = new_file_name;
-
-                    if let Some((other, ..)) = &gs.model_loader {
-                        log::error!("Model loader is already running for {other}");
+                app::SceneCommand::AddModel {
+                    file_name,
+                    path,
+                    reader,
+                } => {
+                    // Models are loaded one at a time, e.g. the models unpacked from a zip
+                    if gs.model_loader.is_some() {
+                        gs.queued_models += 1;
+                        gs.scene_tx
+                            .send(app::SceneCommand::AddModel {
+                                file_name,
+                                path,
+                                reader,
+                            })
+                            .expect("send gs");
                         continue;
                     }
 
-                    let (count, gaussian_rx) = match app::GaussianSplattingModel::init_load(reader)
-                    {
-                        Ok((count, rx)) => (count, rx),
-                        Err(e) => {
-                            log::error!("Error loading model: {e}");
-                            continue;
-                        }
-                    };
-                    let model = app::GaussianSplattingModel::new(file_name.clone(), count);
-
-                    gs.model_loader = Some((file_name.clone(), gaussian_rx));
-
-                    log::debug!("Additional model loaded: {file_name}");
-
-                    apply_to_scene_resource!(frame, gs.compressions, |res| {
-                        res.add_model(
-                            frame.wgpu_render_state().expect("render state"),
-                            file_name.clone(),
-                            count,
-                        )
-                    });
+                    match Self::add_model(frame, gs, file_name.clone(), path, reader) {
+                        Ok(key) => {
+                            // 按导入的坐标系设置初始变换，模型可能不是选中的模型
+                            let model = gs.models.get_mut(&key).expect("model");
+                            model.transform = settings.coordinate_system.transform();
+                            if settings.flip_handedness {
+                                model.flip_handedness();
+                            }
 
-                    gs.models.insert(file_name, model);
+                            apply_to_scene_resource!(frame, gs.compressions, |res| {
+                                res.update_model_transform(
+                                    &frame.wgpu_render_state().expect("render state").queue,
+                                    &key,
+                                    &model.transform,
+                                )
+                            });
+                        }
+                        Err(e) => log::error!("Error loading model {file_name}: {e}"),
+                    }
                 }
                 app::SceneCommand::RemoveModel(key) => {
                     if gs.models.len() == 1 && gs.models.contains_key(&key) {
@@ -468,6 +821,26 @@ impl Scene {
 
                         gs.models.remove(&key);
 
+                        if gs
+                            .selection
+                            .deleted
+                            .as_ref()
+                            .is_some_and(|deleted| deleted.key == key)
+                        {
+                            gs.selection.deleted = None;
+                        }
+
+                        gs.selection.layers.retain(|layer| layer.key != key);
+
+                        if gs
+                            .picked_gaussian
+                            .as_ref()
+                            .is_some_and(|picked| picked.key == key)
+                        {
+                            gs.picked_gaussian = None;
+                        }
+
+                        gs.selected_model_keys.remove(&key);
                         if gs.selected_model_key == key {
                             gs.selected_model_key =
                                 gs.models.keys().next().expect("first key").clone();
@@ -476,259 +849,457 @@ impl Scene {
                 }
                 app::SceneCommand::UpdateMeasurementHit => {
                     apply_to_scene_resource!(frame, gs.compressions, |res| {
-                        res.update_measurement_visible_hit_pairs(&gs.measurement.hit_pairs)
+                        res.update_measurement(&gs.measurement)
                     });
                 }
                 app::SceneCommand::EvaluateMask(op) => {
+                    self.mask_evaluation_pending = Some((gs.selected_model_key.clone(), op));
+                    gs.selected_model_mut().mask.evaluating = true;
+                }
+                app::SceneCommand::UpdateModelTransforms => {
                     apply_to_scene_resource!(frame, gs.compressions, |res| {
-                        res.evaluate_mask(
-                            frame.wgpu_render_state().expect("render state"),
-                            op.as_ref(),
-                            &gs.selected_model_key,
-                            gs.selected_model(),
-                        );
+                        let queue = &frame.wgpu_render_state().expect("render state").queue;
+                        for (key, model) in gs.models.iter() {
+                            res.update_model_transform(queue, key, &model.transform);
+                        }
+                    });
+                }
+                app::SceneCommand::FlipHandedness(key) => {
+                    self.flip_handedness(ui, frame, gs, &key);
+                }
+                app::SceneCommand::SaveProject => {
+                    Self::save_project(frame, gs);
+                }
+                app::SceneCommand::ExtractSelection(key) => {
+                    self.download_selection(ui, frame, gs, key, SelectionTask::Extract);
+                }
+                app::SceneCommand::DeleteSelection(key) => {
+                    // 删除只写入GPU缓冲区，无法通过注释比较检测
+                    gs.unsaved = true;
+                    self.download_selection(ui, frame, gs, key, SelectionTask::Delete);
+                }
+                app::SceneCommand::UndoDeleteSelection => {
+                    gs.unsaved = true;
+                    self.undo_delete_selection(ui, frame, gs);
+                }
+                app::SceneCommand::SaveSelectionLayer(key) => {
+                    self.download_selection(ui, frame, gs, key, SelectionTask::SaveLayer);
+                }
+                app::SceneCommand::LookAtSelection(key) => {
+                    self.download_selection(ui, frame, gs, key, SelectionTask::LookAt);
+                }
+                app::SceneCommand::ApplySelectionLayers(indices) => {
+                    for layer in indices.iter().filter_map(|i| gs.selection.layers.get(*i)) {
+                        apply_to_scene_resource!(frame, gs.compressions, |res| {
+                            res.restore_edits(
+                                frame.wgpu_render_state().expect("render state"),
+                                &layer.key,
+                                &layer.edits(true),
+                            )
+                        });
+                    }
+                }
+                app::SceneCommand::SelectAll => {
+                    apply_to_scene_resource!(frame, gs.compressions, |res| {
+                        let render_state = frame.wgpu_render_state().expect("render state");
+                        for (key, model) in gs.models.iter().filter(|(_, m)| m.visible) {
+                            res.fill_selection(
+                                render_state,
+                                key,
+                                model.gaussians.gaussians.len(),
+                                true,
+                                gs.selection.limit_to_mask,
+                            );
+                        }
+                    });
+                }
+                app::SceneCommand::ClearSelection => {
+                    apply_to_scene_resource!(frame, gs.compressions, |res| {
+                        let render_state = frame.wgpu_render_state().expect("render state");
+                        for (key, model) in gs.models.iter() {
+                            res.fill_selection(
+                                render_state,
+                                key,
+                                model.gaussians.gaussians.len(),
+                                false,
+                                false,
+                            );
+                        }
                     });
                 }
+                app::SceneCommand::Saved => {
+                    gs.unsaved = false;
+                }
+                app::SceneCommand::RemoveSelectionLayer(index) => {
+                    if index < gs.selection.layers.len() {
+                        let layer = gs.selection.layers.remove(index);
+
+                        // Clearing the removed layer also clears the other layers on the same
+                        // Gaussians, so apply them again in order on the overlap
+                        let removed = layer.indices.iter().copied().collect::<HashSet<_>>();
+                        let overlaps = gs
+                            .selection
+                            .layers
+                            .iter()
+                            .filter(|other| other.key == layer.key)
+                            .map(|other| {
+                                other
+                                    .edits(true)
+                                    .into_iter()
+                                    .filter(|[i, ..]| removed.contains(i))
+                                    .collect::<Vec<_>>()
+                            })
+                            .filter(|edits| !edits.is_empty())
+                            .collect::<Vec<_>>();
+
+                        apply_to_scene_resource!(frame, gs.compressions, |res| {
+                            let render_state = frame.wgpu_render_state().expect("render state");
+                            res.restore_edits(render_state, &layer.key, &layer.edits(false));
+                            for edits in overlaps.iter() {
+                                res.restore_edits(render_state, &layer.key, edits);
+                            }
+                        });
+                    }
+                }
             }
         }
 
-        // Viewport
-        if self.vr_mode {
-            // VR模式：使用水平布局创建双窗口
-            ui.centered_and_justified(|ui| {
-                ui.horizontal(|ui| {
-                    // 确保左右窗口各占一半宽度，不留间隙
-                    let total_width = ui.available_width();
-                    let window_width = (total_width / 2.0) - 2.0; // 减去一点空间避免溢出
-
-                    // 左侧窗口（左眼）
-                    ui.scope(|ui| {
-                        ui.set_min_width(window_width);
-                        ui.set_max_width(window_width);
-
-                        egui::Frame::canvas(ui.style()).show(ui, |ui| {
-                            macro_rules! case {
-                                ($sh:ident, $cov3d:ident) => {
-                                    app::Compressions {
-                                        sh: app::ShCompression::$sh,
-                                        cov3d: app::Cov3dCompression::$cov3d,
-                                    }
-                                };
-                            }
+        self.selection_task(ui, frame, gs);
 
-                            macro_rules! apply {
-                                ($macro:ident, $gs:expr, $($args:expr),*) => {
-                                    match &$gs.compressions {
-                                        case!(Single, Single) => {
-                                            $macro!(Single, Single, $($args),*)
-                                        }
-                                        case!(Single, Half) => {
-                                            $macro!(Single, Half, $($args),*)
-                                        }
-                                        case!(Half, Single) => {
-                                            $macro!(Half, Single, $($args),*)
-                                        }
-                                        case!(Half, Half) => {
-                                            $macro!(Half, Half, $($args),*)
-                                        }
-                                        case!(Norm8, Single) => {
-                                            $macro!(Norm8, Single, $($args),*)
-                                        }
-                                        case!(Norm8, Half) => {
-                                            $macro!(Norm8, Half, $($args),*)
-                                        }
-                                        case!(Remove, Single) => {
-                                            $macro!(None, Single, $($args),*)
-                                        }
-                                        case!(Remove, Half) => {
-                                            $macro!(None, Half, $($args),*)
-                                        }
-                                    }
-                                }
-                            }
+        self.project_restore(ui, frame, gs);
 
-                            let (left_rect, left_response) =
-                                ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+        gs.update_unsaved();
 
-                            macro_rules! postprocess {
-                                ($sh:ident, $cov3d:ident, $self:expr, $frame:expr, $rect:expr, $gs:expr) => {
-                                    paste::paste! {
-                                        $self.loaded_postprocess::<
-                                            gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
-                                        >($frame, $rect, $gs)
-                                    }
-                                };
-                            }
+        // 网页上没有恢复项目，不需要自动保存
+        if settings.autosave && project::Project::recovery_path().is_some() {
+            self.autosave(ui, frame, gs);
+        }
 
-                            apply!(postprocess, gs, self, frame, &left_rect, gs);
+        self.mask_evaluation(ui, frame, gs);
 
-                            if self.query_result.is_none() {
-                                self.input.handle(ui, gs, &mut self.query, &left_rect, &left_response);
-                            }
+        self.mask_coverage(ui, frame, gs);
 
-                            macro_rules! preprocess {
-                                ($sh:ident, $cov3d:ident, $self:expr, $frame:expr, $rect:expr, $gs:expr) => {
-                                    paste::paste! {
-                                        $self.loaded_preprocess::<
-                                            gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
-                                        >($frame, $rect, $gs)
-                                    }
-                                };
-                            }
+        self.model_bounds(ui, frame, gs);
 
-                            apply!(preprocess, gs, self, frame, &left_rect, gs);
+        apply_to_scene_resource!(frame, gs.compressions, |res| {
+            res.set_depth_write(
+                frame.wgpu_render_state().expect("render state"),
+                performance.depth_write,
+            );
+            res.set_opacity(
+                frame.wgpu_render_state().expect("render state"),
+                gs.gaussian_transform.opacity,
+                &gs.models,
+            );
+            res.set_max_scale(
+                frame.wgpu_render_state().expect("render state"),
+                gs.gaussian_transform.max_scale,
+                &gs.models,
+            );
+            res.set_projection(
+                frame.wgpu_render_state().expect("render state"),
+                gs.gaussian_transform.projection,
+            );
+            res.set_color_spaces(frame.wgpu_render_state().expect("render state"), &gs.models);
 
-                            let distances = gs
-                                .models
-                                .iter()
-                                .map(|(k, m)| {
-                                    (
-                                        k,
-                                        (m.world_center() - gs.camera.control.pos()).length_squared(),
-                                    )
-                                })
-                                .collect::<HashMap<_, _>>();
-
-                            macro_rules! painter {
-                                ($sh:ident, $cov3d:ident, $ui:expr, $rect:expr, $gs:expr) => {
-                                    paste::paste! {
-                                        $ui.painter().add(egui_wgpu::Callback::new_paint_callback(
-                                            $rect,
-                                            SceneCallback::<gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]> {
-                                                model_render_keys: $gs.models.iter()
-                                                    .filter(|(_, m)| m.visible)
-                                                    .sorted_by(|(a, _), (b, _)| {
-                                                        distances.get(b).expect("distance")
-                                                            .partial_cmp(&distances.get(a).expect("distance"))
-                                                            .unwrap_or(std::cmp::Ordering::Equal)
-                                                    })
-                                                    .map(|(k, _)| k.clone())
-                                                    .collect(),
-                                                query: self.query.clone(),
-                                                is_vr_right_eye: false, // 左眼
-                                                phantom: PhantomData,
-                                            },
-                                        ))
-                                    }
-                                };
-                            }
+            res.gpu_timing = performance.gpu_timing;
+            gs.gpu_timings = match res.active_gpu_timer() {
+                Some(gpu_timer) => gpu_timer.timings(),
+                None => [None; app::GpuPass::COUNT],
+            };
+        });
 
-                            apply!(painter, gs, ui, left_rect, gs);
-                        });
-                    });
+        if gs
+            .camera
+            .update_look_at(ui.ctx().input(|input| input.unstable_dt))
+        {
+            ui.ctx().request_repaint();
+        }
 
-                    // 右侧窗口（右眼）
-                    ui.scope(|ui| {
-                        ui.set_min_width(window_width);
-                        ui.set_max_width(window_width);
-
-                        egui::Frame::canvas(ui.style()).show(ui, |ui| {
-                            macro_rules! case {
-                                ($sh:ident, $cov3d:ident) => {
-                                    app::Compressions {
-                                        sh: app::ShCompression::$sh,
-                                        cov3d: app::Cov3dCompression::$cov3d,
-                                    }
-                                };
+        self.update_pixels_per_point(ui);
+        self.update_render_scale(ui, gs, performance);
+        self.update_accumulation(ui, performance);
+
+        // Viewport
+        if self.stereo_mode == app::StereoMode::Split {
+            // VR模式：按布局把视口精确分成两半，两眼无缝相接
+            let rect = ui.available_rect_before_wrap();
+            let [left_eye_rect, right_eye_rect] = match self.vr_layout {
+                app::VrLayout::SideBySide => {
+                    let mid = rect.center().x;
+                    [rect.with_max_x(mid), rect.with_min_x(mid)]
+                }
+                app::VrLayout::TopBottom => {
+                    let mid = rect.center().y;
+                    [rect.with_max_y(mid), rect.with_min_y(mid)]
+                }
+            };
+            let eye_frame = egui::Frame::canvas(ui.style())
+                .inner_margin(egui::Margin::ZERO)
+                .stroke(egui::Stroke::NONE);
+
+            // 视差舒适度检查，两眼显示相同的警告
+            let comfort_warning = self.vr_comfort_warning(gs);
+
+            // 左侧窗口（左眼）
+            ui.scope_builder(egui::UiBuilder::new().max_rect(left_eye_rect), |ui| {
+                eye_frame.show(ui, |ui| {
+                    macro_rules! case {
+                        ($sh:ident, $cov3d:ident) => {
+                            app::Compressions {
+                                sh: app::ShCompression::$sh,
+                                cov3d: app::Cov3dCompression::$cov3d,
                             }
+                        };
+                    }
 
-                            macro_rules! apply {
-                                ($macro:ident, $gs:expr, $($args:expr),*) => {
-                                    match &$gs.compressions {
-                                        case!(Single, Single) => {
-                                            $macro!(Single, Single, $($args),*)
-                                        }
-                                        case!(Single, Half) => {
-                                            $macro!(Single, Half, $($args),*)
-                                        }
-                                        case!(Half, Single) => {
-                                            $macro!(Half, Single, $($args),*)
-                                        }
-                                        case!(Half, Half) => {
-                                            $macro!(Half, Half, $($args),*)
-                                        }
-                                        case!(Norm8, Single) => {
-                                            $macro!(Norm8, Single, $($args),*)
-                                        }
-                                        case!(Norm8, Half) => {
-                                            $macro!(Norm8, Half, $($args),*)
-                                        }
-                                        case!(Remove, Single) => {
-                                            $macro!(None, Single, $($args),*)
-                                        }
-                                        case!(Remove, Half) => {
-                                            $macro!(None, Half, $($args),*)
-                                        }
-                                    }
+                    macro_rules! apply {
+                        ($macro:ident, $gs:expr, $($args:expr),*) => {
+                            match &$gs.compressions {
+                                case!(Single, Single) => {
+                                    $macro!(Single, Single, $($args),*)
+                                }
+                                case!(Single, Half) => {
+                                    $macro!(Single, Half, $($args),*)
+                                }
+                                case!(Half, Single) => {
+                                    $macro!(Half, Single, $($args),*)
+                                }
+                                case!(Half, Half) => {
+                                    $macro!(Half, Half, $($args),*)
+                                }
+                                case!(Norm8, Single) => {
+                                    $macro!(Norm8, Single, $($args),*)
+                                }
+                                case!(Norm8, Half) => {
+                                    $macro!(Norm8, Half, $($args),*)
+                                }
+                                case!(Remove, Single) => {
+                                    $macro!(None, Single, $($args),*)
+                                }
+                                case!(Remove, Half) => {
+                                    $macro!(None, Half, $($args),*)
                                 }
                             }
+                        }
+                    }
 
-                            let (right_rect, right_response) =
-                                ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+                    let (left_rect, left_response) =
+                        ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+                    left_response.widget_info(|| {
+                        egui::WidgetInfo::labeled(egui::WidgetType::Other, true, "Left eye viewport")
+                    });
 
-                            // 右眼窗口：使用相同的输入处理，但渲染时会应用相机偏移
-                            if self.query_result.is_none() {
-                                self.input.handle(ui, gs, &mut self.query, &right_rect, &right_response);
+                    macro_rules! postprocess {
+                        ($sh:ident, $cov3d:ident, $self:expr, $frame:expr, $rect:expr, $gs:expr) => {
+                            paste::paste! {
+                                $self.loaded_postprocess::<
+                                    gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
+                                >($frame, $rect, $gs)
                             }
+                        };
+                    }
 
-                            // 为右眼窗口执行相机偏移的预处理
-                            macro_rules! preprocess_vr_right {
-                                ($sh:ident, $cov3d:ident, $self:expr, $frame:expr, $rect:expr, $gs:expr) => {
-                                    paste::paste! {
-                                        $self.loaded_preprocess_with_camera_offset::<
-                                            gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
-                                        >($frame, $rect, $gs, true, true)
-                                    }
-                                };
-                            }
+                    apply!(postprocess, gs, self, frame, &left_rect, gs);
 
-                            apply!(preprocess_vr_right, gs, self, frame, &right_rect, gs);
+                    // 左眼窗口负责所有交互：查询和选择都在主查看器上以左眼矩形进行，
+                    // 右眼只显示相同的查询光标
+                    if self.query_result.is_none() {
+                        self.input.handle(ui, gs, &mut self.query, &left_rect, &left_response, settings);
+                    }
+                    self.input.context_menu(
+                        ui,
+                        gs,
+                        &mut self.query,
+                        &left_rect,
+                        &left_response,
+                        self.query_result.is_none(),
+                    );
 
-                            // 为右眼计算距离时使用偏移后的相机位置
-                            let modified_camera_pos = Vec3::new(
-                                -gs.camera.control.pos().x,
-                                gs.camera.control.pos().y,
-                                gs.camera.control.pos().z
-                            );
+                    macro_rules! preprocess {
+                        ($sh:ident, $cov3d:ident, $self:expr, $frame:expr, $rect:expr, $gs:expr) => {
+                            paste::paste! {
+                                $self.loaded_preprocess::<
+                                    gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
+                                >($frame, $rect, $gs, performance)
+                            }
+                        };
+                    }
 
-                            let distances = gs
-                                .models
-                                .iter()
-                                .map(|(k, m)| {
-                                    (
-                                        k,
-                                        (m.world_center() - modified_camera_pos).length_squared(),
-                                    )
-                                })
-                                .collect::<HashMap<_, _>>();
-
-                            macro_rules! painter {
-                                ($sh:ident, $cov3d:ident, $ui:expr, $rect:expr, $gs:expr) => {
-                                    paste::paste! {
-                                        $ui.painter().add(egui_wgpu::Callback::new_paint_callback(
-                                            $rect,
-                                            SceneCallback::<gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]> {
-                                                model_render_keys: $gs.models.iter()
-                                                    .filter(|(_, m)| m.visible)
-                                                    .sorted_by(|(a, _), (b, _)| {
-                                                        distances.get(b).expect("distance")
-                                                            .partial_cmp(&distances.get(a).expect("distance"))
-                                                            .unwrap_or(std::cmp::Ordering::Equal)
-                                                    })
-                                                    .map(|(k, _)| k.clone())
-                                                    .collect(),
-                                                query: self.query.clone(),
-                                                is_vr_right_eye: true, // 右眼
-                                                phantom: PhantomData,
-                                            },
-                                        ))
-                                    }
-                                };
+                    apply!(preprocess, gs, self, frame, &left_rect, gs);
+
+                    let distances = gs
+                        .models
+                        .iter()
+                        .map(|(k, m)| {
+                            (
+                                k,
+                                (m.world_center() - gs.camera.control.pos()).length_squared(),
+                            )
+                        })
+                        .collect::<HashMap<_, _>>();
+
+                    macro_rules! painter {
+                        ($sh:ident, $cov3d:ident, $ui:expr, $rect:expr, $gs:expr) => {
+                            paste::paste! {
+                                $ui.painter().add(egui_wgpu::Callback::new_paint_callback(
+                                    $rect,
+                                    SceneCallback::<gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]> {
+                                        model_render_keys: $gs.models.iter()
+                                            .filter(|(_, m)| m.visible)
+                                            .sorted_by(|(a, _), (b, _)| {
+                                                distances.get(b).expect("distance")
+                                                    .partial_cmp(&distances.get(a).expect("distance"))
+                                                    .unwrap_or(std::cmp::Ordering::Equal)
+                                            })
+                                            .map(|(k, _)| k.clone())
+                                            .collect(),
+                                        mask_gizmo_keys: $gs.models.iter()
+                                            .filter(|(_, m)| m.visible && m.mask.gizmos_visible)
+                                            .map(|(k, _)| k.clone())
+                                            .collect(),
+                                        query: self.query.clone(),
+                                        is_vr_right_eye: false, // 左眼
+                                        offscreen: self.offscreen_target(ui, frame, &$rect, performance, wgpu::ColorWrites::ALL),
+                                        phantom: PhantomData,
+                                    },
+                                ))
                             }
+                        };
+                    }
 
-                            apply!(painter, gs, ui, right_rect, gs);
-                        });
-                    });
+                    apply!(painter, gs, ui, left_rect, gs);
+
+                    if self.vr_convergence_guide {
+                        Self::convergence_guide(ui, gs, &left_rect, self.vr_left_eye_offset());
+                    }
+
+                    if let Some(warning) = comfort_warning {
+                        Self::vr_comfort_indicator(ui, &left_rect, warning, settings.length_unit);
+                    }
+                });
+            });
+
+            // 右侧窗口（右眼）
+            ui.scope_builder(egui::UiBuilder::new().max_rect(right_eye_rect), |ui| {
+                eye_frame.show(ui, |ui| {
+                    macro_rules! case {
+                        ($sh:ident, $cov3d:ident) => {
+                            app::Compressions {
+                                sh: app::ShCompression::$sh,
+                                cov3d: app::Cov3dCompression::$cov3d,
+                            }
+                        };
+                    }
+
+                    macro_rules! apply {
+                        ($macro:ident, $gs:expr, $($args:expr),*) => {
+                            match &$gs.compressions {
+                                case!(Single, Single) => {
+                                    $macro!(Single, Single, $($args),*)
+                                }
+                                case!(Single, Half) => {
+                                    $macro!(Single, Half, $($args),*)
+                                }
+                                case!(Half, Single) => {
+                                    $macro!(Half, Single, $($args),*)
+                                }
+                                case!(Half, Half) => {
+                                    $macro!(Half, Half, $($args),*)
+                                }
+                                case!(Norm8, Single) => {
+                                    $macro!(Norm8, Single, $($args),*)
+                                }
+                                case!(Norm8, Half) => {
+                                    $macro!(Norm8, Half, $($args),*)
+                                }
+                                case!(Remove, Single) => {
+                                    $macro!(None, Single, $($args),*)
+                                }
+                                case!(Remove, Half) => {
+                                    $macro!(None, Half, $($args),*)
+                                }
+                            }
+                        }
+                    }
+
+                    // 右眼窗口不处理输入，避免同一帧内重复处理输入，
+                    // 以及查询坐标与左眼的查询纹理不对应
+                    let (right_rect, right_response) =
+                        ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+                    right_response.widget_info(|| {
+                        egui::WidgetInfo::labeled(egui::WidgetType::Other, true, "Right eye viewport")
+                    });
+
+                    // 为右眼窗口执行相机偏移的预处理
+                    macro_rules! preprocess_vr_right {
+                        ($sh:ident, $cov3d:ident, $self:expr, $frame:expr, $rect:expr, $gs:expr) => {
+                            paste::paste! {
+                                $self.loaded_preprocess_with_camera_offset::<
+                                    gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
+                                >($frame, $rect, $gs, performance, true, true)
+                            }
+                        };
+                    }
+
+                    apply!(preprocess_vr_right, gs, self, frame, &right_rect, gs);
+
+                    // 为右眼计算距离时使用偏移后的相机位置
+                    let modified_camera_pos = Vec3::new(
+                        -gs.camera.control.pos().x,
+                        gs.camera.control.pos().y,
+                        gs.camera.control.pos().z
+                    );
+
+                    let distances = gs
+                        .models
+                        .iter()
+                        .map(|(k, m)| {
+                            (
+                                k,
+                                (m.world_center() - modified_camera_pos).length_squared(),
+                            )
+                        })
+                        .collect::<HashMap<_, _>>();
+
+                    macro_rules! painter {
+                        ($sh:ident, $cov3d:ident, $ui:expr, $rect:expr, $gs:expr) => {
+                            paste::paste! {
+                                $ui.painter().add(egui_wgpu::Callback::new_paint_callback(
+                                    $rect,
+                                    SceneCallback::<gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]> {
+                                        model_render_keys: $gs.models.iter()
+                                            .filter(|(_, m)| m.visible)
+                                            .sorted_by(|(a, _), (b, _)| {
+                                                distances.get(b).expect("distance")
+                                                    .partial_cmp(&distances.get(a).expect("distance"))
+                                                    .unwrap_or(std::cmp::Ordering::Equal)
+                                            })
+                                            .map(|(k, _)| k.clone())
+                                            .collect(),
+                                        mask_gizmo_keys: $gs.models.iter()
+                                            .filter(|(_, m)| m.visible && m.mask.gizmos_visible)
+                                            .map(|(k, _)| k.clone())
+                                            .collect(),
+                                        query: self.query.clone(),
+                                        is_vr_right_eye: true, // 右眼
+                                        offscreen: self.offscreen_target(ui, frame, &$rect, performance, wgpu::ColorWrites::ALL),
+                                        phantom: PhantomData,
+                                    },
+                                ))
+                            }
+                        };
+                    }
+
+                    apply!(painter, gs, ui, right_rect, gs);
+
+                    if self.vr_convergence_guide {
+                        Self::convergence_guide(ui, gs, &right_rect, -self.vr_left_eye_offset());
+                    }
+
+                    if let Some(warning) = comfort_warning {
+                        Self::vr_comfort_indicator(ui, &right_rect, warning, settings.length_unit);
+                    }
                 });
             });
         } else {
@@ -776,9 +1347,28 @@ impl Scene {
                     }
                 }
 
-                // 分配视口矩形和响应
-                let (rect, response) =
-                    ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+                // 分配视口矩形和响应，固定宽高比时两侧留黑边
+                let (rect, response) = match self.aspect {
+                    app::ViewportAspect::Free => ui
+                        .allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag()),
+                    aspect => {
+                        let (available, _) =
+                            ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+                        ui.painter()
+                            .rect_filled(available, 0.0, egui::Color32::BLACK);
+
+                        let rect = aspect.fit(available);
+                        let response = ui.interact(
+                            rect,
+                            ui.id().with("scene_viewport"),
+                            egui::Sense::click_and_drag(),
+                        );
+                        (rect, response)
+                    }
+                };
+                response.widget_info(|| {
+                    egui::WidgetInfo::labeled(egui::WidgetType::Other, true, "Scene viewport")
+                });
 
                 // 定义后处理宏
                 macro_rules! postprocess {
@@ -797,9 +1387,19 @@ impl Scene {
 
                 // 如果没有待处理的查询结果，处理输入
                 if self.query_result.is_none() {
-                    self.input.handle(ui, gs, &mut self.query, &rect, &response);
+                    self.input.handle(ui, gs, &mut self.query, &rect, &response, settings);
                 }
 
+                // 右键菜单
+                self.input.context_menu(
+                    ui,
+                    gs,
+                    &mut self.query,
+                    &rect,
+                    &response,
+                    self.query_result.is_none(),
+                );
+
                 // 定义预处理宏
                 macro_rules! preprocess {
                     ($sh:ident, $cov3d:ident, $self:expr, $frame:expr, $rect:expr, $gs:expr) => {
@@ -807,7 +1407,7 @@ impl Scene {
                             // 调用相应压缩类型的预处理方法
                             $self.loaded_preprocess::<
                                 gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
-                            >($frame, $rect, $gs)
+                            >($frame, $rect, $gs, performance)
                         }
                     };
                 }
@@ -815,6 +1415,22 @@ impl Scene {
                 // 应用预处理
                 apply!(preprocess, gs, self, frame, &rect, gs);
 
+                // 红蓝立体模式：右眼使用VR查看器渲染到同一视口
+                let anaglyph = self.stereo_mode == app::StereoMode::Anaglyph;
+                if anaglyph {
+                    macro_rules! preprocess_right {
+                        ($sh:ident, $cov3d:ident, $self:expr, $frame:expr, $rect:expr, $gs:expr) => {
+                            paste::paste! {
+                                $self.loaded_preprocess_with_camera_offset::<
+                                    gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
+                                >($frame, $rect, $gs, performance, true, true)
+                            }
+                        };
+                    }
+
+                    apply!(preprocess_right, gs, self, frame, &rect, gs);
+                }
+
                 // 计算模型距离相机的距离
                 let distances = gs
                     .models
@@ -829,7 +1445,7 @@ impl Scene {
 
                 // 定义绘制器宏
                 macro_rules! painter {
-                    ($sh:ident, $cov3d:ident, $ui:expr, $rect:expr, $gs:expr) => {
+                    ($sh:ident, $cov3d:ident, $ui:expr, $rect:expr, $gs:expr, $right:expr, $write_mask:expr) => {
                         paste::paste! {
                             // 添加绘制回调
                             $ui.painter().add(egui_wgpu::Callback::new_paint_callback(
@@ -845,8 +1461,13 @@ impl Scene {
                                         })
                                         .map(|(k, _)| k.clone())  // 获取键
                                         .collect(),
+                                    mask_gizmo_keys: $gs.models.iter()
+                                        .filter(|(_, m)| m.visible && m.mask.gizmos_visible)
+                                        .map(|(k, _)| k.clone())
+                                        .collect(),  // 显示遮罩小工具的可见模型
                                     query: self.query.clone(),     // 当前查询
-                                    is_vr_right_eye: false,       // 非VR模式
+                                    is_vr_right_eye: $right,      // 红蓝立体模式的右眼
+                                    offscreen: self.offscreen_target($ui, frame, &$rect, performance, $write_mask), // 离屏渲染
                                     phantom: PhantomData,          // 幽灵数据，用于泛型
                                 },
                             ))
@@ -854,190 +1475,2611 @@ impl Scene {
                     };
                 }
 
-                // 应用绘制器
-                apply!(painter, gs, ui, rect, gs);
+                // 应用绘制器，红蓝立体模式下两眼分别写入不同的颜色通道
+                let [left_write_mask, right_write_mask] = match anaglyph {
+                    true => renderer::ToneMapping::anaglyph_write_masks(self.anaglyph_colors),
+                    false => [wgpu::ColorWrites::ALL; 2],
+                };
+                apply!(painter, gs, ui, rect, gs, false, left_write_mask);
+                if anaglyph {
+                    apply!(painter, gs, ui, rect, gs, true, right_write_mask);
+                }
+
+                Self::picked_gaussian_highlight(ui, gs, &rect);
+                Self::mask_shape_gizmo(ui, gs, &rect);
+
+                if settings.camera_hud {
+                    self.camera_hud(ui, gs, &rect);
+                }
+
+                if settings.minimap {
+                    Self::minimap(ui, gs, &rect);
+                }
+
+                Self::loading_overlay(ui, gs, &rect);
             });
         }
 
         loaded // 返回加载状态
     }
 
-    /// 执行后处理
+    /// The camera quick controls in the viewport.
     ///
-    /// 由于 eframe 不允许在渲染通道之后进行任何计算通道，
-    /// 因此在此运行预处理通道之前计算上一帧的结果。
-    fn loaded_postprocess<G: gs::GaussianPod>(
+    /// The controls fade out after some time without pointer activity in the viewport.
+    fn camera_hud(
         &mut self,
-        frame: &mut eframe::Frame,
-        rect: &egui::Rect,
+        ui: &mut egui::Ui,
         gs: &mut app::GaussianSplatting,
+        rect: &egui::Rect,
     ) {
-        // 解构渲染状态
-        let egui_wgpu::RenderState {
-            device,
-            queue,
-            renderer,
-            ..
-        } = frame.wgpu_render_state().expect("render state");
+        const HIDE_DELAY: f64 = 3.0;
+
+        let (time, active) = ui.ctx().input(|input| {
+            (
+                input.time,
+                input.pointer.is_moving()
+                    && input
+                        .pointer
+                        .hover_pos()
+                        .is_some_and(|pos| rect.contains(pos)),
+            )
+        });
+        if active {
+            self.camera_hud_time = time;
+        }
 
-        let mut renderer = renderer.write(); // 锁定渲染器
-        // 获取场景资源
-        let SceneResource::<G> { viewer, .. } = renderer
-            .callback_resources
-            .get_mut()
-            .expect("scene resource");
-        let viewer = viewer.lock().expect("viewer"); // 锁定查看器
+        let visible = time - self.camera_hud_time < HIDE_DELAY;
+        if visible {
+            ui.ctx()
+                .request_repaint_after_secs((self.camera_hud_time + HIDE_DELAY - time) as f32);
+        }
 
-        // 后处理，因为 eframe 无法在渲染通道后执行任何计算通道
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Postprocess Encoder"), // 设置编码器标签
-        });
+        let opacity =
+            ui.ctx()
+                .animate_bool_with_time(egui::Id::new("camera_hud_visible"), visible, 0.3);
+        if opacity == 0.0 {
+            return;
+        }
 
-        // 对每个可见模型执行后处理
-        for key in gs.models.iter().filter(|(_, m)| m.visible).map(|(k, _)| k) {
-            let model = &viewer.models.get(key).expect("model"); // 获取模型
+        let mut ui = ui.new_child(
+            egui::UiBuilder::new()
+                .max_rect(rect.shrink(8.0))
+                .layout(egui::Layout::top_down(egui::Align::Min)),
+        );
+        ui.set_opacity(opacity);
 
-            // 执行后处理
-            viewer.postprocessor.postprocess(
-                &mut encoder,
-                &model.bind_groups.postprocessor.0, // 第一个后处理器绑定组
-                &model.bind_groups.postprocessor.1, // 第二个后处理器绑定组
-                model.gaussian_buffers.gaussians_buffer.len() as u32, // 高斯缓冲区长度
-                &model.gaussian_buffers.postprocess_indirect_args_buffer, // 后处理间接参数缓冲区
-            );
+        let response = egui::Frame::popup(ui.style())
+            .show(&mut ui, |ui| {
+                egui::CollapsingHeader::new("📷 Camera")
+                    .id_salt("camera_hud")
+                    .show(ui, |ui| {
+                        egui::Grid::new("camera_hud_grid").show(ui, |ui| {
+                            ui.label("Field of View");
+                            let mut fov_degree = gs.camera.control.vertical_fov().to_degrees();
+                            ui.add(egui::Slider::new(&mut fov_degree, 30.0..=120.0).integer());
+                            *gs.camera.control.vertical_fov_mut() = fov_degree.to_radians();
+                            ui.end_row();
+
+                            ui.label("Movement Speed");
+                            ui.add(
+                                egui::Slider::new(&mut gs.camera.speed, 0.0..=10.0)
+                                    .fixed_decimals(2),
+                            );
+                            ui.end_row();
+
+                            ui.label("Rotation Sensitivity");
+                            ui.add(
+                                egui::Slider::new(&mut gs.camera.sensitivity, 0.0..=1.0)
+                                    .fixed_decimals(2),
+                            );
+                            ui.end_row();
+
+                            if let app::CameraControl::Orbit(orbit) = &mut gs.camera.control {
+                                let limits = gs.camera.distance_limits;
+                                let diff = orbit.pos - orbit.target;
+                                let mut distance = diff.length();
+
+                                ui.label("Distance")
+                                    .on_hover_text("The distance from the camera to the target");
+                                if ui
+                                    .add(
+                                        egui::Slider::new(
+                                            &mut distance,
+                                            limits.min..=limits.max.unwrap_or(limits.min * 1e4),
+                                        )
+                                        .logarithmic(true),
+                                    )
+                                    .changed()
+                                {
+                                    orbit.pos = orbit.target
+                                        + diff.normalize_or(Vec3::Z) * limits.clamp(distance);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+            })
+            .response;
+
+        // Keep visible while being used
+        if response.contains_pointer() {
+            self.camera_hud_time = time;
         }
+    }
 
-        queue.submit(Some(encoder.finish())); // 提交命令
-        device.poll(wgpu::Maintain::Wait); // 等待设备完成操作
+    /// The loading indicator in the center of the viewport.
+    ///
+    /// This is only shown while no Gaussians are received yet, i.e. when the viewport is still
+    /// empty on the initial load, the loading bar above the viewport shows the progress after.
+    fn loading_overlay(ui: &mut egui::Ui, gs: &app::GaussianSplatting, rect: &egui::Rect) {
+        let Some((loading, ..)) = &gs.model_loader else {
+            return;
+        };
 
-        // 接收查询结果
-        match &mut self.query_result {
-            // 如果是测量定位命中查询结果
-            Some(QueryResult::MeasurementLocateHit) => {
-                if let Query::MeasurementLocateHit {
-                    pod,
-                    hit_method,
-                    tx,
-                } = &self.query
-                {
-                    let (query_result_tx, rx) = oneshot::channel(); // 创建单次通道
-                    self.query_result = Some(QueryResult::Downloading(rx)); // 设置为下载状态
+        if gs
+            .models
+            .values()
+            .any(|model| model.visible && !model.gaussians.gaussians.is_empty())
+        {
+            return;
+        }
 
-                    let device = device.clone(); // 克隆设备
-                    let queue = queue.clone(); // 克隆队列
-                    let pod = *pod; // 克隆查询POD
-                    let hit_method = *hit_method; // 克隆命中方法
-                    let tx = tx.clone(); // 克隆发送端
-                    let camera = gs.camera.control.clone(); // 克隆相机控制
-                    let viewer_size = Vec2::from_array(rect.size().into()).as_uvec2(); // 获取视图尺寸
+        let model = gs.models.get(loading).expect("model");
+        let (received, count) = (
+            model.gaussians.gaussians.len(),
+            model.gaussians.gaussians.capacity(),
+        );
 
-                    // 获取计数缓冲区
-                    let count_buffer = viewer
-                        .models
-                        .get(&gs.selected_model_key)
-                        .expect("model")
-                        .gaussian_buffers
-                        .query_result_count_buffer
-                        .clone();
-                    // 获取结果缓冲区
-                    let results_buffer = viewer
-                        .models
-                        .get(&gs.selected_model_key)
-                        .expect("model")
-                        .gaussian_buffers
-                        .query_results_buffer
-                        .clone();
+        let mut ui = ui.new_child(egui::UiBuilder::new().max_rect(*rect).layout(
+            egui::Layout::top_down(egui::Align::Center).with_main_align(egui::Align::Center),
+        ));
 
-                    // 执行阻塞任务
-                    util::exec_blocking_task(async move {
-                        // 下载查询结果
-                        let mut results =
-                            gs::query::download(&device, &queue, &count_buffer, &results_buffer)
-                                .await
-                                .expect("download")
-                                .into_iter()
-                                .map(gs::QueryHitResultPod::from) // 转换为查询命中结果POD
-                                .collect::<Vec<_>>();
+        egui::Frame::popup(ui.style()).show(&mut ui, |ui| {
+            ui.set_max_width(240.0);
+            ui.vertical_centered(|ui| {
+                ui.add(egui::Spinner::new().size(32.0));
+                ui.label(format!("Loading {}", model.file_name));
+                ui.add(
+                    egui::ProgressBar::new(received as f32 / count.max(1) as f32)
+                        .desired_width(200.0)
+                        .text(format!(
+                            "{} / {}",
+                            received.to_formatted_string(&num_format::Locale::en),
+                            count.to_formatted_string(&num_format::Locale::en),
+                        )),
+                );
+            });
+        });
+    }
 
-                        // 根据命中方法确定位置
-                        let pos = match hit_method {
-                            // 最大透明度方法
-                            app::MeasurementHitMethod::MostAlpha => {
-                                // 按alpha范围查找命中位置
-                                gs::query::hit_pos_by_alpha_range(
-                                    &pod,
-                                    &mut results,
-                                    &camera,
-                                    viewer_size,
-                                    0.05, // alpha阈值
-                                )
-                                .map(|(_, _, pos)| pos) // 提取位置
-                                .unwrap_or(Vec3::ZERO) // 默认为零向量
-                            }
-                            // 最近方法
-                            app::MeasurementHitMethod::Closest => {
-                                // 按最近距离查找命中位置
-                                gs::query::hit_pos_by_closest(&pod, &results, &camera, viewer_size)
-                                    .map(|(_, pos)| pos) // 提取位置
-                                    .unwrap_or(Vec3::ZERO) // 默认为零向量
-                            }
-                        };
+    /// The top-down minimap in the bottom right corner of the viewport.
+    ///
+    /// The bounding boxes of the visible models and the camera are projected orthographically
+    /// onto the XZ plane, clicking or dragging on it moves the camera horizontally to the point.
+    fn minimap(ui: &mut egui::Ui, gs: &mut app::GaussianSplatting, rect: &egui::Rect) {
+        const SIZE: f32 = 160.0;
+        const MARGIN: f32 = 8.0;
+        const PADDING: f32 = 12.0;
+
+        let size = SIZE
+            .min(rect.width() - 2.0 * MARGIN)
+            .min(rect.height() - 2.0 * MARGIN);
+        if size <= 2.0 * PADDING {
+            return;
+        }
 
-                        // 如果发送位置失败，记录错误
-                        if let Err(e) = tx.send(pos) {
-                            log::error!("Error sending locate hit query result: {e}");
-                        }
+        let map_rect = egui::Rect::from_min_size(
+            rect.max - egui::vec2(size + MARGIN, size + MARGIN),
+            egui::vec2(size, size),
+        );
 
-                        query_result_tx.send(None).expect("send"); // 发送查询结果
-                    });
-                } else {
-                    self.query_result = None; // 重置查询结果
+        // The edges of the bounding boxes in world space
+        let edges = gs
+            .models
+            .values()
+            .filter(|model| model.visible)
+            .filter_map(|model| {
+                let half = model.size? * 0.5;
+                let corners = (0..8)
+                    .map(|i| {
+                        let sign = vec3(
+                            if i & 1 == 0 { -1.0 } else { 1.0 },
+                            if i & 2 == 0 { -1.0 } else { 1.0 },
+                            if i & 4 == 0 { -1.0 } else { 1.0 },
+                        );
+                        model.transform.apply(model.center + half * sign)
+                    })
+                    .collect::<Vec<_>>();
+                Some(
+                    (0..8)
+                        .flat_map(|i| [1, 2, 4].map(|bit| (i, i | bit)))
+                        .filter(|(a, b)| a != b)
+                        .map(|(a, b)| (corners[a].xz(), corners[b].xz()))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let camera_pos = gs.camera.control.pos().xz();
+
+        // Fit the boxes and the camera into the map with the same scale on both axes
+        let (min, max) = edges
+            .iter()
+            .flat_map(|(a, b)| [*a, *b])
+            .fold((camera_pos, camera_pos), |(min, max), p| {
+                (min.min(p), max.max(p))
+            });
+        let center = (min + max) * 0.5;
+        let scale = (size - 2.0 * PADDING) / (max - min).max_element().max(1e-3);
+
+        let to_screen = |p: Vec2| {
+            let p = (p - center) * scale;
+            map_rect.center() + egui::vec2(p.x, p.y)
+        };
+        let to_world = |pos: egui::Pos2| {
+            let p = pos - map_rect.center();
+            center + Vec2::new(p.x, p.y) / scale
+        };
+
+        let response = ui.interact(
+            map_rect,
+            ui.id().with("minimap"),
+            egui::Sense::click_and_drag(),
+        );
+        if response.is_pointer_button_down_on() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let target = to_world(pos);
+                let delta = vec3(target.x - camera_pos.x, 0.0, target.y - camera_pos.y);
+
+                *gs.camera.control.pos_mut() += delta;
+                if let app::CameraControl::Orbit(orbit) = &mut gs.camera.control {
+                    orbit.target += delta;
                 }
+                gs.camera.look_at = None;
             }
-            None | Some(QueryResult::Downloading(..)) => {} // 其他情况无需处理
         }
 
-        // 如果正在下载查询结果
-        if let Some(QueryResult::Downloading(rx)) = &self.query_result {
-            if let Ok(query_result) = rx.try_recv() {
-                // 尝试接收结果
-                self.query_result = query_result; // 更新查询结果
-            }
+        let painter = ui.painter_at(map_rect);
+        let visuals = ui.visuals();
+        painter.rect(
+            map_rect,
+            visuals.window_corner_radius,
+            visuals.extreme_bg_color.gamma_multiply(0.8),
+            visuals.window_stroke,
+            egui::StrokeKind::Inside,
+        );
+
+        let stroke = egui::Stroke::new(1.0, visuals.strong_text_color());
+        for (a, b) in edges {
+            painter.line_segment([to_screen(a), to_screen(b)], stroke);
         }
-    }
 
-    /// 执行预处理
-    fn loaded_preprocess<G: gs::GaussianPod>(
-        &mut self,
-        frame: &mut eframe::Frame,
-        rect: &egui::Rect,
-        gs: &mut app::GaussianSplatting,
-    ) {
-        // 在VR模式下，左眼也需要偏移（向左）
-        self.loaded_preprocess_with_camera_offset::<G>(frame, rect, gs, false, self.vr_mode);
+        // The camera as a triangle pointing to the horizontal view direction
+        use gs::CameraTrait;
+
+        let forward = (-gs.camera.control.view().row(2).xyz())
+            .xz()
+            .try_normalize()
+            .unwrap_or(Vec2::NEG_Y);
+        let side = forward.perp();
+        let pos = to_screen(camera_pos);
+        let forward = egui::vec2(forward.x, forward.y);
+        let side = egui::vec2(side.x, side.y);
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                pos + forward * 8.0,
+                pos - forward * 5.0 + side * 5.0,
+                pos - forward * 5.0 - side * 5.0,
+            ],
+            visuals.selection.bg_fill,
+            egui::Stroke::new(1.0, visuals.selection.stroke.color),
+        ));
     }
 
-    /// 执行预处理（支持VR相机偏移）
-    fn loaded_preprocess_with_camera_offset<G: gs::GaussianPod>(
-        &mut self,
-        frame: &mut eframe::Frame,
+    /// The zero parallax guide of an eye in VR split.
+    ///
+    /// The reticle is at the same place in both eyes so it is fused at screen depth, and the
+    /// marker is the center of the selected model with the model offset `offset_x` of the eye.
+    fn convergence_guide(
+        ui: &mut egui::Ui,
+        gs: &app::GaussianSplatting,
         rect: &egui::Rect,
-        gs: &mut app::GaussianSplatting,
-        is_vr_right_eye: bool,
-        apply_vr_offset: bool,
+        offset_x: f32,
     ) {
-        if is_vr_right_eye {
-            // VR右眼模式：使用独立的VR viewer
-            log::info!("🎯 [VR DEBUG] *** STARTING VR RIGHT EYE PREPROCESS ***");
+        const COLOR: egui::Color32 = egui::Color32::from_rgb(0, 255, 128);
+
+        let painter = ui.painter_at(*rect);
+        let stroke = egui::Stroke::new(1.0, COLOR);
+
+        // 两眼相同位置的边框和准星，融合后位于屏幕深度
+        let center = rect.center();
+        let size = rect.size().min_elem() * 0.05;
+        painter.rect_stroke(
+            rect.shrink(rect.size().min_elem() * 0.1),
+            0.0,
+            stroke,
+            egui::StrokeKind::Inside,
+        );
+        painter.circle_stroke(center, size, stroke);
+        painter.line_segment(
+            [
+                center - egui::vec2(size * 2.0, 0.0),
+                center + egui::vec2(size * 2.0, 0.0),
+            ],
+            stroke,
+        );
+        painter.line_segment(
+            [
+                center - egui::vec2(0.0, size * 2.0),
+                center + egui::vec2(0.0, size * 2.0),
+            ],
+            stroke,
+        );
 
-            let egui_wgpu::RenderState {
-                device,
-                queue,
-                renderer,
-                ..
-            } = frame.wgpu_render_state().expect("render state");
+        // 选中模型中心在该眼中的位置（含该眼的模型偏移）
+        let model = gs.selected_model();
+        if !model.visible {
+            return;
+        }
 
-            log::debug!("📊 [VR DEBUG] Got render state for VR preprocess");
+        let viewport_size = Vec2::from_array(rect.size().into());
+        let pos = model.world_center() + Vec3::X * offset_x;
+        let Some(marker) = gs.camera.control.project(pos, viewport_size) else {
+            return;
+        };
+
+        let marker = rect.min + egui::vec2(marker.x, marker.y);
+        painter.circle_filled(marker, 3.0, COLOR);
+        painter.line_segment([marker, egui::pos2(center.x, marker.y)], stroke);
+    }
+
+    /// Check whether the selected model is too close for comfortable stereo.
+    ///
+    /// Only the selected model is offset between the eyes, so its disparity is about the eye
+    /// separation over its distance. The distance is from the camera to the nearest point of the
+    /// approximate bounding sphere of the model.
+    ///
+    /// Returns the nearest distance and the comfortable distance if it is too close.
+    fn vr_comfort_warning(&self, gs: &app::GaussianSplatting) -> Option<(f32, f32)> {
+        let model = gs.selected_model();
+        let ipd = Self::BASE_IPD * self.vr_parallax_strength;
+        if !model.visible || ipd <= 0.0 {
+            return None;
+        }
+
+        let comfort = ipd / self.vr_comfort_disparity.to_radians().tan();
+        let nearest = ((model.world_center() - gs.camera.control.pos()).length()
+            - model.world_radius())
+        .max(0.0);
+
+        (nearest < comfort).then_some((nearest, comfort))
+    }
+
+    /// Show the too close warning of [`Scene::vr_comfort_warning`] in an eye.
+    fn vr_comfort_indicator(
+        ui: &mut egui::Ui,
+        rect: &egui::Rect,
+        (nearest, comfort): (f32, f32),
+        length_unit: app::LengthUnit,
+    ) {
+        let mut ui = ui.new_child(
+            egui::UiBuilder::new()
+                .max_rect(rect.shrink(8.0))
+                .layout(egui::Layout::top_down(egui::Align::Center)),
+        );
+
+        ui.label(
+            egui::RichText::new("⚠ Too close for comfortable stereo")
+                .small()
+                .color(ui.visuals().warn_fg_color),
+        )
+        .on_hover_text(format!(
+            "The selected model is {} away, keep it at least {} away or lower the parallax",
+            length_unit.format(nearest),
+            length_unit.format(comfort),
+        ));
+    }
+
+    /// Highlight the picked Gaussian in the viewport.
+    fn picked_gaussian_highlight(
+        ui: &mut egui::Ui,
+        gs: &app::GaussianSplatting,
+        rect: &egui::Rect,
+    ) {
+        let Some(picked) = &gs.picked_gaussian else {
+            return;
+        };
+
+        let Some(model) = gs.models.get(&picked.key).filter(|model| model.visible) else {
+            return;
+        };
+
+        let Some(gaussian) = model.gaussians.gaussians.get(picked.index) else {
+            return;
+        };
+
+        use gs::CameraTrait;
+
+        let viewport_size = Vec2::from_array(rect.size().into());
+        let transform = &model.transform;
+        let pos = transform.quat() * (gaussian.pos * transform.scale) + transform.pos;
+
+        let Some(center) = gs.camera.control.project(pos, viewport_size) else {
+            return;
+        };
+
+        // The radius covers 3 standard deviations of the largest axis
+        let right = gs.camera.control.view().row(0).xyz();
+        let extent = 3.0 * (gaussian.scale * transform.scale).max_element();
+        let radius = gs
+            .camera
+            .control
+            .project(pos + right * extent, viewport_size)
+            .map(|edge| edge.distance(center))
+            .unwrap_or(0.0)
+            .max(6.0);
+
+        let painter = ui.painter_at(*rect);
+        let center = rect.min + egui::vec2(center.x, center.y);
+        painter.circle_stroke(
+            center,
+            radius,
+            egui::Stroke::new(2.0, egui::Color32::YELLOW),
+        );
+        painter.circle_filled(center, 2.0, egui::Color32::YELLOW);
+    }
+
+    /// The gizmo to drag the selected mask shape of the selected model in the viewport.
+    ///
+    /// The arrows move, the squares scale and the ring knobs rotate the shape along its local
+    /// axes.
+    fn mask_shape_gizmo(ui: &mut egui::Ui, gs: &mut app::GaussianSplatting, rect: &egui::Rect) {
+        const LENGTH: f32 = 80.0;
+        const HANDLE_SIZE: f32 = 14.0;
+        const RING_SEGMENTS: usize = 48;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        enum Handle {
+            Move,
+            Scale,
+            Rotate,
+        }
+
+        let Some(model) = gs
+            .models
+            .get_mut(&gs.selected_model_key)
+            .filter(|model| model.visible && model.mask.gizmos_visible)
+        else {
+            return;
+        };
+
+        let Some(shape) = model
+            .mask
+            .selected
+            .and_then(|index| model.mask.shapes.get_mut(index))
+            .filter(|shape| shape.visible)
+        else {
+            return;
+        };
+
+        use gs::CameraTrait;
+
+        let control = &gs.camera.control;
+        let viewport_size = Vec2::from_array(rect.size().into());
+        let project = |pos: Vec3| {
+            control
+                .project(pos, viewport_size)
+                .map(|screen| rect.min + egui::vec2(screen.x, screen.y))
+        };
+
+        let pos = shape.shape.pos;
+        let Some(center) = project(pos) else {
+            return;
+        };
+
+        // The world length of the handles for a constant screen length
+        let right = control.view().row(0).xyz();
+        let Some(length) = project(pos + right)
+            .map(|edge| edge.distance(center))
+            .filter(|unit| *unit > f32::EPSILON)
+            .map(|unit| LENGTH / unit)
+        else {
+            return;
+        };
+
+        let axes = [Vec3::X, Vec3::Y, Vec3::Z].map(|axis| shape.shape.rotation * axis);
+        let axis_colors = app::AxisColors::get(ui.ctx());
+        let colors = [0, 1, 2].map(|i| {
+            axis_colors
+                .color(i, true)
+                .or_else(|| app::AxisColors::Standard.color(i, true))
+                .expect("axis color")
+        });
+
+        let painter = ui.painter_at(*rect);
+        let mut changed = false;
+
+        for (i, axis) in axes.into_iter().enumerate() {
+            let [u, v] = [axes[(i + 1) % 3], axes[(i + 2) % 3]];
+            let radius = length * 0.8;
+
+            let ring = (0..RING_SEGMENTS)
+                .map(|segment| {
+                    let angle = segment as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                    project(pos + (u * angle.cos() + v * angle.sin()) * radius)
+                })
+                .collect::<Option<Vec<_>>>();
+            if let Some(ring) = ring {
+                painter.add(egui::Shape::closed_line(
+                    ring,
+                    egui::Stroke::new(1.5, colors[i].gamma_multiply(0.6)),
+                ));
+            }
+
+            let Some(tip) = project(pos + axis * length) else {
+                continue;
+            };
+            painter.line_segment([center, tip], egui::Stroke::new(2.0, colors[i]));
+
+            // The screen direction of the axis, scaled to the world length
+            let screen_axis = tip - center;
+
+            for (handle, handle_pos) in [
+                (Handle::Move, pos + axis * length),
+                (Handle::Scale, pos + axis * length * 0.55),
+                (Handle::Rotate, pos + (u + v).normalize() * radius),
+            ] {
+                let Some(handle_center) = project(handle_pos) else {
+                    continue;
+                };
+
+                let response = ui
+                    .interact(
+                        egui::Rect::from_center_size(handle_center, egui::Vec2::splat(HANDLE_SIZE)),
+                        ui.id().with(("mask_shape_gizmo", i, handle)),
+                        egui::Sense::drag(),
+                    )
+                    .on_hover_cursor(egui::CursorIcon::Grab);
+
+                let color = match response.hovered() || response.dragged() {
+                    true => egui::Color32::WHITE,
+                    false => colors[i],
+                };
+                let half_size = HANDLE_SIZE * 0.4;
+                painter.add(match handle {
+                    Handle::Move => egui::Shape::circle_filled(handle_center, half_size, color),
+                    Handle::Scale => egui::Shape::rect_filled(
+                        egui::Rect::from_center_size(
+                            handle_center,
+                            egui::Vec2::splat(half_size * 1.6),
+                        ),
+                        0.0,
+                        color,
+                    ),
+                    Handle::Rotate => egui::Shape::circle_stroke(
+                        handle_center,
+                        half_size * 0.8,
+                        egui::Stroke::new(2.5, color),
+                    ),
+                });
+
+                if !response.dragged() {
+                    continue;
+                }
+
+                let delta = response.drag_delta();
+                if delta == egui::Vec2::ZERO || screen_axis.length_sq() < 1.0 {
+                    continue;
+                }
+
+                let distance = delta.dot(screen_axis) / screen_axis.length_sq() * length;
+                match handle {
+                    Handle::Move => {
+                        shape.shape.pos += axis * distance;
+                    }
+                    Handle::Scale => {
+                        shape.shape.scale[i] = (shape.shape.scale[i] + distance).max(1e-3);
+                    }
+                    Handle::Rotate => {
+                        let Some(pointer) = response.interact_pointer_pos() else {
+                            continue;
+                        };
+
+                        // The screen is Y down, so a positive angle is clockwise
+                        let angle = (pointer - center).angle() - (pointer - delta - center).angle();
+                        let angle = (angle + std::f32::consts::PI)
+                            .rem_euclid(std::f32::consts::TAU)
+                            - std::f32::consts::PI;
+                        let angle = match axis.dot(control.pos() - pos) > 0.0 {
+                            true => -angle,
+                            false => angle,
+                        };
+
+                        shape.shape.rotation =
+                            (Quat::from_axis_angle(axis, angle) * shape.shape.rotation).normalize();
+
+                        let (z, x, y) = shape.shape.rotation.to_euler(EulerRot::ZYX);
+                        shape.rot = Vec3::new(x, y, z).map(f32::to_degrees);
+                    }
+                }
+
+                changed = true;
+            }
+        }
+
+        if changed {
+            model.mask.update_pods();
+
+            let op = model.mask.op();
+            gs.scene_tx
+                .send(app::SceneCommand::EvaluateMask(op))
+                .expect("send op");
+        }
+    }
+
+    /// Evaluate the pending mask once the running evaluation finishes on the GPU.
+    ///
+    /// The gizmo is updated when the evaluation finishes, so the shapes and the mask stay in
+    /// sync while the op code is changing.
+    fn mask_evaluation(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+    ) {
+        if let Some((key, rx)) = &self.mask_evaluation_rx {
+            match rx.try_recv() {
+                Ok(()) | Err(oneshot::TryRecvError::Disconnected) => {
+                    if let Some(model) = gs.models.get_mut(key) {
+                        apply_to_scene_resource!(frame, gs.compressions, |res| {
+                            res.update_mask_gizmo(
+                                frame.wgpu_render_state().expect("render state"),
+                                key,
+                                model,
+                            );
+                        });
+
+                        model.mask.evaluating = self
+                            .mask_evaluation_pending
+                            .as_ref()
+                            .is_some_and(|(pending, _)| pending == key);
+                    }
+                    self.mask_evaluation_rx = None;
+                }
+                Err(oneshot::TryRecvError::Empty) => {
+                    ui.ctx().request_repaint();
+                    return;
+                }
+            }
+        }
+
+        let Some((key, op)) = self.mask_evaluation_pending.take() else {
+            return;
+        };
+        let Some(model) = gs.models.get(&key) else {
+            return;
+        };
+
+        let render_state = frame.wgpu_render_state().expect("render state");
+        apply_to_scene_resource!(frame, gs.compressions, |res| {
+            res.dispatch_mask_evaluation(render_state, op.as_ref(), &key, model);
+        });
+
+        let (tx, rx) = oneshot::channel();
+        let ctx = ui.ctx().clone();
+        render_state.queue.on_submitted_work_done(move || {
+            // The scene may have been closed
+            tx.send(()).ok();
+            ctx.request_repaint();
+        });
+
+        self.mask_evaluation_rx = Some((key.clone(), rx));
+        self.mask_coverage_pending = Some((key, ui.input(|input| input.time)));
+    }
+
+    /// Download the mask coverage once the mask evaluation settles.
+    fn mask_coverage(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+    ) {
+        /// The time in seconds without evaluation before downloading the coverage.
+        const SETTLE_TIME: f64 = 0.3;
+
+        if let Some((key, rx)) = &self.mask_coverage_rx {
+            match rx.try_recv() {
+                Ok(kept) => {
+                    if let Some(model) = gs.models.get_mut(key) {
+                        model.mask.coverage = Some(kept);
+                    }
+                    self.mask_coverage_rx = None;
+                }
+                Err(oneshot::TryRecvError::Disconnected) => {
+                    self.mask_coverage_rx = None;
+                }
+                Err(oneshot::TryRecvError::Empty) => {}
+            }
+        }
+
+        let Some((key, time)) = &self.mask_coverage_pending else {
+            return;
+        };
+
+        let now = ui.input(|input| input.time);
+        if now - time < SETTLE_TIME || self.mask_coverage_rx.is_some() {
+            ui.ctx()
+                .request_repaint_after_secs((SETTLE_TIME - (now - time)).max(0.0) as f32);
+            return;
+        }
+
+        let key = key.clone();
+        self.mask_coverage_pending = None;
+
+        let Some(model) = gs.models.get(&key) else {
+            return;
+        };
+        let count = model.gaussians.gaussians.capacity();
+
+        let mask_buffer;
+        apply_to_scene_resource!(frame, gs.compressions, |res| {
+            mask_buffer = res
+                .viewer
+                .lock()
+                .expect("viewer")
+                .models
+                .get(&key)
+                .expect("model")
+                .gaussian_buffers
+                .mask_buffer
+                .clone();
+        });
+
+        let (tx, rx) = oneshot::channel();
+        self.mask_coverage_rx = Some((key, rx));
+
+        let render_state = frame.wgpu_render_state().expect("render state");
+        let device = render_state.device.clone();
+        let queue = render_state.queue.clone();
+        let ctx = ui.ctx().clone();
+        util::exec_task(async move {
+            match mask_buffer.download(&device, &queue).await {
+                Ok(mask) => {
+                    if tx
+                        .send(app::GaussianSplattingMask::count_kept(&mask, count))
+                        .is_err()
+                    {
+                        log::error!("Error sending mask coverage");
+                    }
+                    ctx.request_repaint();
+                }
+                Err(e) => log::error!("Download mask buffer: {e}"),
+            }
+        });
+    }
+
+    /// Flip the handedness of a model and upload its Gaussians again.
+    fn flip_handedness(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+        key: &str,
+    ) {
+        let Some(model) = gs.models.get_mut(key) else {
+            return;
+        };
+
+        model.flip_handedness();
+        log::info!("Flipped handedness of {key}: {}", model.flipped);
+
+        let op = model.mask.op();
+        apply_to_scene_resource!(frame, gs.compressions, |res| {
+            let render_state = frame.wgpu_render_state().expect("render state");
+            res.load_model(render_state, key, 0, &model.gaussians.gaussians);
+            res.evaluate_mask(render_state, op.as_ref(), key, model);
+        });
+
+        self.mask_coverage_pending = Some((key.to_string(), ui.input(|input| input.time)));
+    }
+
+    /// Compute the bounding box of the loaded models on the GPU.
+    ///
+    /// The models are computed one at a time once they are fully loaded, and the result sets the
+    /// center and size of the model.
+    fn model_bounds(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+    ) {
+        if let Some((key, rx)) = &self.model_bounds_rx {
+            match rx.try_recv() {
+                Ok((min, max)) => {
+                    if let Some(model) = gs.models.get_mut(key) {
+                        model.center = (min + max) / 2.0;
+                        model.size = Some(max - min);
+                        log::info!("Model bounds of {key} computed: {min} to {max}");
+                    }
+                    self.model_bounds_rx = None;
+                }
+                Err(oneshot::TryRecvError::Disconnected) => {
+                    self.model_bounds_rx = None;
+                }
+                Err(oneshot::TryRecvError::Empty) => return,
+            }
+        }
+
+        let loading = gs.model_loader.as_ref().map(|(key, _)| key);
+        let Some(key) = gs
+            .models
+            .iter()
+            .find(|(key, model)| {
+                model.size.is_none()
+                    && Some(*key) != loading
+                    && !model.gaussians.gaussians.is_empty()
+                    && model.gaussians.gaussians.len() == model.gaussians.gaussians.capacity()
+            })
+            .map(|(key, _)| key.clone())
+        else {
+            return;
+        };
+
+        let render_state = frame.wgpu_render_state().expect("render state");
+
+        let download;
+        apply_to_scene_resource!(frame, gs.compressions, |res| {
+            download = res.prepare_model_bounds(render_state, &key);
+        });
+
+        let (tx, rx) = oneshot::channel();
+        self.model_bounds_rx = Some((key, rx));
+
+        let device = render_state.device.clone();
+        let ctx = ui.ctx().clone();
+        util::exec_task(async move {
+            match renderer::ModelBounds::map(&device, &download).await {
+                Ok(bounds) => {
+                    if tx.send(bounds).is_err() {
+                        log::error!("Error sending model bounds");
+                    }
+                    ctx.request_repaint();
+                }
+                Err(e) => log::error!("Download model bounds buffer: {e}"),
+            }
+        });
+    }
+
+    /// Start downloading the selection of a model for a selection task.
+    ///
+    /// The edits of the model are also downloaded for [`SelectionTask::Delete`].
+    fn download_selection(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &app::GaussianSplatting,
+        key: String,
+        task: SelectionTask,
+    ) {
+        if self.selection_task_rx.is_some() {
+            log::warn!("Selection task is already running");
+            return;
+        }
+
+        if !gs.models.contains_key(&key) {
+            return;
+        }
+
+        let render_state = frame.wgpu_render_state().expect("render state");
+
+        let download;
+        let edit_buffer;
+        apply_to_scene_resource!(frame, gs.compressions, |res| {
+            download = res.prepare_selection_download(render_state, &key);
+            edit_buffer = res
+                .viewer
+                .lock()
+                .expect("viewer")
+                .models
+                .get(&key)
+                .expect("model")
+                .gaussian_buffers
+                .gaussians_edit_buffer
+                .clone();
+        });
+
+        let (tx, rx) = oneshot::channel();
+        self.selection_task_rx = Some((key, task, rx));
+
+        let device = render_state.device.clone();
+        let queue = render_state.queue.clone();
+        let ctx = ui.ctx().clone();
+        util::exec_task(async move {
+            let selection = match renderer::SelectionDownload::map(&device, &download).await {
+                Ok(selection) => selection,
+                Err(e) => {
+                    log::error!("Download selection buffer: {e}");
+                    return;
+                }
+            };
+
+            let edits = match task {
+                SelectionTask::Extract | SelectionTask::SaveLayer | SelectionTask::LookAt => {
+                    Vec::new()
+                }
+                SelectionTask::Delete => match edit_buffer.download(&device, &queue).await {
+                    Ok(edits) => edits,
+                    Err(e) => {
+                        log::error!("Download edit buffer: {e}");
+                        return;
+                    }
+                },
+            };
+
+            if tx.send((selection, edits)).is_err() {
+                log::error!("Error sending selection");
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    /// Run the selection task when the selection is downloaded.
+    fn selection_task(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+    ) {
+        let Some((key, task, rx)) = &self.selection_task_rx else {
+            return;
+        };
+
+        let (selection, edits) = match rx.try_recv() {
+            Ok(downloaded) => downloaded,
+            Err(oneshot::TryRecvError::Empty) => return,
+            Err(oneshot::TryRecvError::Disconnected) => {
+                self.selection_task_rx = None;
+                return;
+            }
+        };
+
+        let key = key.clone();
+        let task = *task;
+        self.selection_task_rx = None;
+
+        if !gs.models.contains_key(&key) {
+            return;
+        }
+
+        let is_selected = |i: usize| {
+            selection
+                .get(i / 32)
+                .is_some_and(|word| word & (1 << (i % 32)) != 0)
+        };
+
+        match task {
+            SelectionTask::Extract => Self::extract_selection(frame, gs, key, is_selected),
+            SelectionTask::Delete => self.delete_selection(ui, frame, gs, key, &edits, is_selected),
+            SelectionTask::SaveLayer => Self::save_selection_layer(gs, key, is_selected),
+            SelectionTask::LookAt => Self::look_at_selection(gs, key, is_selected),
+        }
+    }
+
+    /// Turn the camera to the center of the selected Gaussians.
+    fn look_at_selection(
+        gs: &mut app::GaussianSplatting,
+        key: String,
+        is_selected: impl Fn(usize) -> bool,
+    ) {
+        let model = &gs.models[&key];
+        let (sum, count) = model
+            .gaussians
+            .gaussians
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| is_selected(*i))
+            .fold((Vec3::ZERO, 0usize), |(sum, count), (_, gaussian)| {
+                (sum + model.transform.apply(gaussian.pos), count + 1)
+            });
+
+        if count == 0 {
+            log::warn!("No Gaussians selected in {key} to look at");
+            return;
+        }
+
+        gs.camera.look_at(sum / count as f32);
+    }
+
+    /// Save the selected Gaussians with the current edit as a new layer.
+    ///
+    /// The current edit is already written to the selected Gaussians by the preprocessor.
+    fn save_selection_layer(
+        gs: &mut app::GaussianSplatting,
+        key: String,
+        is_selected: impl Fn(usize) -> bool,
+    ) {
+        let indices = (0..gs.models[&key].gaussians.gaussians.len())
+            .filter(|i| is_selected(*i))
+            .map(|i| i as u32)
+            .collect::<Vec<_>>();
+
+        if indices.is_empty() {
+            log::warn!("No Gaussians selected in {key} to save as a layer");
+            return;
+        }
+
+        let name = format!("Layer {}", gs.selection.layers.len() + 1);
+        log::info!(
+            "Saved {} selected Gaussians of {key} as {name}",
+            indices.len()
+        );
+
+        gs.selection.layers.push(app::SelectionLayer {
+            name,
+            key,
+            indices,
+            edit: gs.selection.edit.clone().unwrap_or_default(),
+            linked: false,
+        });
+    }
+
+    /// Extract the selected Gaussians to a new model.
+    ///
+    /// The new model has the same transform as the source model so it stays in place.
+    fn extract_selection(
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+        key: String,
+        is_selected: impl Fn(usize) -> bool,
+    ) {
+        let source = gs.models.get(&key).expect("source model");
+
+        let gaussians = gs::Gaussians {
+            gaussians: source
+                .gaussians
+                .gaussians
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| is_selected(*i))
+                .map(|(_, gaussian)| *gaussian)
+                .collect(),
+        };
+
+        if gaussians.gaussians.is_empty() {
+            log::warn!("No Gaussians selected in {key} to extract");
+            return;
+        }
+
+        let mut ply = Vec::new();
+        if let Err(e) = gaussians.write_ply(
+            &mut ply,
+            None::<Vec<&gs::GaussianEditPod>>,
+            None::<Vec<u32>>,
+        ) {
+            log::error!("Error writing extracted selection: {e}");
+            return;
+        }
+
+        let transform = source.transform.clone();
+        let file_name = format!("{key} (selection)");
+
+        match Self::add_model(frame, gs, file_name, None, Box::new(Cursor::new(ply))) {
+            Ok(new_key) => {
+                log::info!(
+                    "Extracted {} selected Gaussians from {key} to {new_key}",
+                    gaussians.gaussians.len()
+                );
+
+                apply_to_scene_resource!(frame, gs.compressions, |res| {
+                    res.update_model_transform(
+                        &frame.wgpu_render_state().expect("render state").queue,
+                        &new_key,
+                        &transform,
+                    )
+                });
+
+                if let Some(model) = gs.models.get_mut(&new_key) {
+                    model.transform = transform;
+                }
+            }
+            Err(e) => log::error!("Error extracting selection: {e}"),
+        }
+    }
+
+    /// Delete the selected Gaussians from the model.
+    ///
+    /// The previous Gaussians and edits are kept in [`app::Selection::deleted`] for undo.
+    fn delete_selection(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+        key: String,
+        edits: &[gs::GaussianEditPod],
+        is_selected: impl Fn(usize) -> bool,
+    ) {
+        let model = gs.models.get(&key).expect("model");
+
+        let kept = (0..model.gaussians.gaussians.len())
+            .filter(|i| !is_selected(*i))
+            .collect::<Vec<_>>();
+        let count = model.gaussians.gaussians.len() - kept.len();
+
+        if count == 0 {
+            log::warn!("No Gaussians selected in {key} to delete");
+            return;
+        }
+
+        if kept.is_empty() {
+            log::warn!("Cannot delete all Gaussians of {key}");
+            return;
+        }
+
+        let mut gaussians = Vec::with_capacity(kept.len());
+        gaussians.extend(kept.iter().map(|i| model.gaussians.gaussians[*i]));
+
+        let kept_edits = renderer::EditRestore::sparse(kept.iter().filter_map(|i| edits.get(*i)));
+
+        let prev = self.replace_gaussians(ui, frame, gs, &key, gaussians, &kept_edits);
+
+        // The model no longer matches its file, so it cannot be reopened from a project
+        let path = gs.models.get_mut(&key).expect("model").path.take();
+
+        gs.selection.deleted = Some(app::DeletedSelection {
+            key: key.clone(),
+            gaussians: prev,
+            edits: renderer::EditRestore::sparse(edits),
+            path,
+            count,
+        });
+
+        log::info!("Deleted {count} selected Gaussians from {key}");
+    }
+
+    /// Undo the last deletion of selected Gaussians.
+    fn undo_delete_selection(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+    ) {
+        let Some(deleted) = gs.selection.deleted.take() else {
+            return;
+        };
+
+        if !gs.models.contains_key(&deleted.key) {
+            return;
+        }
+
+        self.replace_gaussians(
+            ui,
+            frame,
+            gs,
+            &deleted.key,
+            deleted.gaussians,
+            &deleted.edits,
+        );
+
+        gs.models.get_mut(&deleted.key).expect("model").path = deleted.path;
+
+        log::info!(
+            "Restored {} deleted Gaussians to {}",
+            deleted.count,
+            deleted.key
+        );
+    }
+
+    /// Replace the Gaussians of a model.
+    ///
+    /// The buffers of the model are reallocated, the edits are restored and the mask is evaluated
+    /// again on the new Gaussians. The selection of the model is cleared.
+    ///
+    /// Returns the previous Gaussians.
+    fn replace_gaussians(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+        key: &String,
+        gaussians: Vec<gs::Gaussian>,
+        edits: &[[u32; 3]],
+    ) -> Vec<gs::Gaussian> {
+        let model = gs.models.get_mut(key).expect("model");
+        let prev = std::mem::replace(&mut model.gaussians.gaussians, gaussians);
+        model.mask.coverage = None;
+        model.size = None;
+
+        // The indices of the layers no longer match the Gaussians
+        gs.selection.layers.retain(|layer| layer.key != *key);
+
+        if gs
+            .picked_gaussian
+            .as_ref()
+            .is_some_and(|picked| picked.key == *key)
+        {
+            gs.picked_gaussian = None;
+        }
+
+        let model = gs.models.get(key).expect("model");
+        let op = model.mask.op();
+
+        apply_to_scene_resource!(frame, gs.compressions, |res| {
+            let render_state = frame.wgpu_render_state().expect("render state");
+            res.reallocate_model(render_state, key, &model.gaussians.gaussians);
+            res.update_model_transform(&render_state.queue, key, &model.transform);
+            res.restore_edits(render_state, key, edits);
+            res.evaluate_mask(render_state, op.as_ref(), key, model);
+        });
+
+        self.mask_coverage_pending = Some((key.clone(), ui.input(|input| input.time)));
+
+        prev
+    }
+
+    /// Run the next command of the script when it is not waiting.
+    ///
+    /// The script is stopped on the first error.
+    fn run_script(&mut self, ui: &mut egui::Ui, frame: &mut eframe::Frame, state: &mut app::State) {
+        let Some(mut script) = state.script.take() else {
+            return;
+        };
+
+        ui.ctx().request_repaint();
+
+        let time = ui.input(|input| input.time);
+        let waiting = match (&script.wait, &state.gs) {
+            (None, _) => false,
+            (Some(script::ScriptWait::Open), app::Loadable::Unloaded(unloaded))
+                if unloaded.err.is_some() =>
+            {
+                log::error!(
+                    "Script stopped: {}",
+                    unloaded.err.as_deref().unwrap_or_default()
+                );
+                return;
+            }
+            (Some(script::ScriptWait::Open), app::Loadable::Loaded(gs)) => {
+                !self.initialized || gs.model_loader.is_some()
+            }
+            (Some(script::ScriptWait::Open), app::Loadable::Unloaded(_)) => true,
+            (Some(script::ScriptWait::Add), app::Loadable::Loaded(gs)) => gs.model_loader.is_some(),
+            (Some(script::ScriptWait::Time(until)), _) => time < *until,
+            (Some(script::ScriptWait::Screenshot(path)), _) => {
+                let image = ui.input(|input| {
+                    input.raw.events.iter().find_map(|event| match event {
+                        egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                        _ => None,
+                    })
+                });
+
+                match image {
+                    Some(image) => {
+                        match script::save_png(path, &image) {
+                            Ok(()) => log::info!("Screenshot saved to {}", path.display()),
+                            Err(e) => log::error!("Error saving screenshot: {e}"),
+                        }
+                        false
+                    }
+                    None => true,
+                }
+            }
+            // 模型已关闭，由下一个命令报告错误
+            (Some(script::ScriptWait::Add), app::Loadable::Unloaded(_)) => false,
+        };
+
+        if waiting {
+            state.script = Some(script);
+            return;
+        }
+        script.wait = None;
+
+        let Some((line, command)) = script.commands.pop_front() else {
+            log::info!("Script finished");
+            return;
+        };
+
+        log::debug!("Script line {line}: {command:?}");
+
+        match self.run_script_command(ui, frame, state, command) {
+            Ok(wait) => {
+                script.wait = wait;
+                state.script = Some(script);
+            }
+            Err(e) => log::error!("Script line {line}: {e}"),
+        }
+    }
+
+    /// Run a command of the script.
+    ///
+    /// Returns what the script should wait for before running the next command.
+    fn run_script_command(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        state: &mut app::State,
+        command: script::ScriptCommand,
+    ) -> Result<Option<script::ScriptWait>, String> {
+        /// Open a file for reading by path.
+        fn open(
+            path: &std::path::Path,
+        ) -> Result<(String, std::io::BufReader<std::fs::File>), String> {
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Unnamed".to_string());
+            let file = std::fs::File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+
+            Ok((file_name, std::io::BufReader::new(file)))
+        }
+
+        if let script::ScriptCommand::Open(path) = &command {
+            let (file_name, reader) = open(path)?;
+            let gs = app::GaussianSplatting::new(
+                file_name,
+                Some(path.clone()),
+                reader,
+                state.default_compressions.clone(),
+            )
+            .map_err(|e| e.to_string())?;
+
+            state.gs = app::Loadable::unloaded();
+            if let app::Loadable::Unloaded(unloaded) = &state.gs {
+                unloaded.tx.send(Ok(gs)).expect("send gs");
+            }
+
+            return Ok(Some(script::ScriptWait::Open));
+        }
+
+        let app::Loadable::Loaded(gs) = &mut state.gs else {
+            return Err("No model is opened".to_string());
+        };
+
+        match command {
+            script::ScriptCommand::Open(..) => unreachable!(),
+            script::ScriptCommand::Add(path) => {
+                let (file_name, reader) = open(&path)?;
+                let key = Self::add_model(frame, gs, file_name, Some(path), Box::new(reader))?;
+                gs.selected_model_key = key;
+
+                return Ok(Some(script::ScriptWait::Add));
+            }
+            script::ScriptCommand::Select(key) => {
+                if !gs.models.contains_key(&key) {
+                    return Err(format!("No model named \"{key}\""));
+                }
+                gs.selected_model_key = key;
+            }
+            script::ScriptCommand::Position(pos) => {
+                gs.selected_model_mut().transform.pos = pos;
+            }
+            script::ScriptCommand::Rotation(rot) => {
+                gs.selected_model_mut().transform.rot = rot;
+            }
+            script::ScriptCommand::Scale(scale) => {
+                gs.selected_model_mut().transform.scale = scale;
+            }
+            script::ScriptCommand::Mask { kind, pos, scale } => {
+                let mask = &mut gs.selected_model_mut().mask;
+
+                let mut shape = app::GaussianSplattingMaskShape::new();
+                shape.shape.kind = kind;
+                shape.shape.pos = pos;
+                shape.shape.scale = scale;
+                mask.shapes.push(shape);
+                mask.update_pods();
+
+                let op = mask.op();
+                gs.scene_tx
+                    .send(app::SceneCommand::EvaluateMask(op))
+                    .expect("send op");
+            }
+            script::ScriptCommand::MaskOp(op_code) => {
+                let mask = &mut gs.selected_model_mut().mask;
+
+                let op =
+                    app::GaussianSplattingMaskOp::parse(&op_code).map_err(|e| e.to_string())?;
+                if let Some(op) = &op {
+                    op.validate_shapes(mask.shapes.len())
+                        .map_err(|e| format!("Invalid shape index: {e}"))?;
+                }
+
+                mask.op_code = op_code;
+                gs.scene_tx
+                    .send(app::SceneCommand::EvaluateMask(op))
+                    .expect("send op");
+            }
+            script::ScriptCommand::Camera { pos, target } => {
+                let mut orbit = gs.camera.control.to_orbit(1.0);
+                orbit.pos = pos;
+                orbit.target = target;
+                gs.camera.control = app::CameraControl::Orbit(orbit);
+            }
+            script::ScriptCommand::Wait(seconds) => {
+                return Ok(Some(script::ScriptWait::Time(
+                    ui.input(|input| input.time) + seconds,
+                )));
+            }
+            script::ScriptCommand::Screenshot(path) => {
+                ui.ctx()
+                    .send_viewport_cmd(egui::ViewportCommand::Screenshot(Default::default()));
+
+                return Ok(Some(script::ScriptWait::Screenshot(path)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Add a model and start loading it.
+    ///
+    /// Returns the key of the model.
+    fn add_model(
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+        file_name: String,
+        path: Option<PathBuf>,
+        reader: Box<dyn BufRead + Send>,
+    ) -> Result<String, String> {
+        let mut i = 0;
+        let mut new_file_name = file_name.clone();
+        while gs.models.contains_key(&new_file_name) {
+            i += 1;
+            new_file_name = format!("{} ({})", file_name, i);
+        }
+
+        let file_name = new_file_name;
+
+        if let Some((other, ..)) = &gs.model_loader {
+            return Err(format!("Model loader is already running for {other}"));
+        }
+
+        let (count, color_space, gaussian_rx) =
+            app::GaussianSplattingModel::init_load(reader).map_err(|e| e.to_string())?;
+        let mut model = app::GaussianSplattingModel::new(file_name.clone(), count);
+        model.set_path(path);
+        model.color_space = color_space;
+
+        gs.model_loader = Some((file_name.clone(), gaussian_rx));
+
+        log::debug!("Additional model loaded: {file_name}");
+
+        apply_to_scene_resource!(frame, gs.compressions, |res| {
+            res.add_model(
+                frame.wgpu_render_state().expect("render state"),
+                file_name.clone(),
+                count,
+            )
+        });
+
+        gs.models.insert(file_name.clone(), model);
+
+        Ok(file_name)
+    }
+
+    /// Rebuild the GPU resources released by [`Scene::idle`] on any input, or right away when
+    /// the export needs them.
+    ///
+    /// This runs every frame even when the scene is not shown, e.g. when another tab in the same
+    /// dock node is active, which would otherwise never resume.
+    pub fn wake(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame, state: &mut app::State) {
+        let app::Loadable::Loaded(gs) = &mut state.gs else {
+            return;
+        };
+
+        let (now, input) = ctx.input(|input| {
+            (
+                input.time,
+                !input.events.is_empty() || input.pointer.any_down(),
+            )
+        });
+        if input {
+            self.input_time = now;
+        }
+
+        let export = gs.export_modal.is_some();
+
+        match std::mem::replace(&mut self.idle, SceneIdle::Active) {
+            // Cancelled by the input or the export
+            SceneIdle::Releasing(..) if input || export => {}
+            SceneIdle::Released(release) if export => {
+                Self::resume_resource(frame, gs, &release);
+                log::info!("GPU resources rebuilt for exporting");
+            }
+            SceneIdle::Released(release) if input => {
+                // Show the indicator for a frame before rebuilding
+                self.idle = SceneIdle::Resuming(release);
+                ctx.request_repaint();
+            }
+            SceneIdle::Resuming(release) => {
+                Self::resume_resource(frame, gs, &release);
+                log::info!("GPU resources rebuilt");
+            }
+            idle => self.idle = idle,
+        }
+    }
+
+    /// Release the GPU resources after being idle, [`Scene::wake`] rebuilds them.
+    ///
+    /// The edits and selections are downloaded before releasing, the masks, transforms and
+    /// measurements are rebuilt from the state.
+    ///
+    /// Returns whether the resources are released, the scene must not be shown.
+    fn idle(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+        performance: &app::Performance,
+    ) -> bool {
+        let now = ui.input(|input| input.time);
+
+        match std::mem::replace(&mut self.idle, SceneIdle::Active) {
+            SceneIdle::Active => {
+                let Some(minutes) = performance.idle_release else {
+                    return false;
+                };
+
+                let remaining = self.input_time + minutes as f64 * 60.0 - now;
+                if remaining > 0.0 {
+                    // Wake up to release even when nothing repaints
+                    ui.ctx().request_repaint_after_secs(remaining as f32);
+                    return false;
+                }
+
+                if gs.model_loader.is_some()
+                    || gs.project_restore.is_some()
+                    || gs.export_modal.is_some()
+                    || self.selection_task_rx.is_some()
+                {
+                    return false;
+                }
+
+                let (mut project, edit_buffers) = Self::project(frame, gs);
+
+                let render_state = frame.wgpu_render_state().expect("render state");
+
+                let selection_downloads: Vec<_>;
+                apply_to_scene_resource!(frame, gs.compressions, |res| {
+                    selection_downloads = gs
+                        .models
+                        .keys()
+                        .map(|key| {
+                            (
+                                key.clone(),
+                                res.prepare_selection_download(render_state, key),
+                            )
+                        })
+                        .collect();
+                });
+
+                let (tx, rx) = oneshot::channel();
+                self.idle = SceneIdle::Releasing(rx);
+
+                let device = render_state.device.clone();
+                let queue = render_state.queue.clone();
+                let ctx = ui.ctx().clone();
+                util::exec_task(async move {
+                    project.download_edits(edit_buffers, &device, &queue).await;
+
+                    let mut selections = HashMap::new();
+                    for (key, download) in selection_downloads {
+                        match renderer::SelectionDownload::map(&device, &download).await {
+                            Ok(selection) if selection.iter().any(|word| *word != 0) => {
+                                selections.insert(key, selection);
+                            }
+                            Ok(..) => {}
+                            Err(e) => log::error!("Error downloading selection of {key}: {e}"),
+                        }
+                    }
+
+                    tx.send(SceneRelease {
+                        project,
+                        selections,
+                    })
+                    .ok();
+                    ctx.request_repaint();
+                });
+
+                false
+            }
+            SceneIdle::Releasing(rx) => match rx.try_recv() {
+                Ok(release) => {
+                    Self::release_resource(frame, &gs.compressions);
+                    log::info!("GPU resources released after being idle");
+
+                    self.idle = SceneIdle::Released(release);
+                    self.idle(ui, frame, gs, performance)
+                }
+                Err(oneshot::TryRecvError::Empty) => {
+                    self.idle = SceneIdle::Releasing(rx);
+                    false
+                }
+                Err(oneshot::TryRecvError::Disconnected) => false,
+            },
+            SceneIdle::Released(release) => {
+                self.idle = SceneIdle::Released(release);
+
+                ui.centered_and_justified(|ui| {
+                    ui.label(
+                        egui::RichText::new(
+                            "💤 GPU resources are released while idle, interact to resume",
+                        )
+                        .weak(),
+                    );
+                });
+
+                true
+            }
+            SceneIdle::Resuming(release) => {
+                self.idle = SceneIdle::Resuming(release);
+
+                ui.centered_and_justified(|ui| {
+                    ui.label(egui::RichText::new("⏳ Resuming...").weak());
+                });
+
+                true
+            }
+        }
+    }
+
+    /// Remove the scene resource, which releases all GPU buffers of the viewer.
+    fn release_resource(frame: &mut eframe::Frame, compressions: &app::Compressions) {
+        macro_rules! case {
+            ($sh:ident, $cov3d:ident) => {
+                app::Compressions {
+                    sh: app::ShCompression::$sh,
+                    cov3d: app::Cov3dCompression::$cov3d,
+                }
+            };
+        }
+
+        macro_rules! remove {
+            ($sh:ident, $cov3d:ident) => {
+                paste::paste! {
+                    frame
+                        .wgpu_render_state()
+                        .expect("render state")
+                        .renderer
+                        .write()
+                        .callback_resources
+                        .remove::<SceneResource<
+                            gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
+                        >>()
+                }
+            };
+        }
+
+        match compressions {
+            case!(Single, Single) => {
+                remove!(Single, Single);
+            }
+            case!(Single, Half) => {
+                remove!(Single, Half);
+            }
+            case!(Half, Single) => {
+                remove!(Half, Single);
+            }
+            case!(Half, Half) => {
+                remove!(Half, Half);
+            }
+            case!(Norm8, Single) => {
+                remove!(Norm8, Single);
+            }
+            case!(Norm8, Half) => {
+                remove!(Norm8, Half);
+            }
+            case!(Remove, Single) => {
+                remove!(None, Single);
+            }
+            case!(Remove, Half) => {
+                remove!(None, Half);
+            }
+        }
+    }
+
+    /// Rebuild the scene resource released by [`Scene::release_resource`].
+    ///
+    /// `release` has the edits and selections downloaded before releasing.
+    fn resume_resource(
+        frame: &mut eframe::Frame,
+        gs: &app::GaussianSplatting,
+        release: &SceneRelease,
+    ) {
+        let compressions = gs.compressions.clone();
+        Self::create_resource(frame, gs, &compressions);
+
+        apply_to_scene_resource!(frame, compressions, |res| {
+            let render_state = frame.wgpu_render_state().expect("render state");
+
+            for (key, model) in gs.models.iter() {
+                if *key != gs.selected_model_key {
+                    res.add_model(
+                        render_state,
+                        key.clone(),
+                        model.gaussians.gaussians.capacity(),
+                    );
+                    res.load_model(render_state, key, 0, &model.gaussians.gaussians);
+                }
+
+                res.update_model_transform(&render_state.queue, key, &model.transform);
+
+                let op = app::GaussianSplattingMaskOp::parse(&model.mask.op_code)
+                    .ok()
+                    .flatten()
+                    .filter(|op| op.validate_shapes(model.mask.shapes.len()).is_ok());
+                res.evaluate_mask(render_state, op.as_ref(), key, model);
+
+                if let Some(project_model) = release.project.models.iter().find(|m| m.key == *key) {
+                    res.restore_edits(render_state, key, &project_model.edits);
+                }
+
+                if let Some(selection) = release.selections.get(key) {
+                    res.restore_selection(render_state, key, selection);
+                }
+            }
+
+            res.update_measurement(&gs.measurement);
+        });
+    }
+
+    /// Restore the models of the opened project one by one.
+    ///
+    /// The mask and edits of a model are applied after it is loaded.
+    fn project_restore(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &mut app::GaussianSplatting,
+    ) {
+        let Some(mut restore) = gs.project_restore.take() else {
+            return;
+        };
+
+        if let Some((key, project_model)) = &restore.loading {
+            if gs.model_loader.is_some() {
+                gs.project_restore = Some(restore);
+                return;
+            }
+
+            if let Some(model) = gs.models.get_mut(key) {
+                project_model.apply_mask(&mut model.mask);
+
+                let op = match app::GaussianSplattingMaskOp::parse(&model.mask.op_code) {
+                    Ok(op) => op.filter(|op| op.validate_shapes(model.mask.shapes.len()).is_ok()),
+                    Err(e) => {
+                        log::warn!("Invalid mask operation in project: {e}");
+                        None
+                    }
+                };
+
+                apply_to_scene_resource!(frame, gs.compressions, |res| {
+                    let render_state = frame.wgpu_render_state().expect("render state");
+                    res.evaluate_mask(render_state, op.as_ref(), key, model);
+                    res.restore_edits(render_state, key, &project_model.edits);
+                });
+
+                gs.selection
+                    .layers
+                    .extend(project_model.selection_layers(key));
+
+                self.mask_coverage_pending = Some((key.clone(), ui.input(|input| input.time)));
+            }
+
+            restore.loading = None;
+        }
+
+        while restore.loading.is_none() {
+            let Some(project_model) = restore.pending.pop_front() else {
+                break;
+            };
+
+            match project_model.open().and_then(|reader| {
+                Self::add_model(
+                    frame,
+                    gs,
+                    project_model.file_name.clone(),
+                    project_model.path.clone(),
+                    reader,
+                )
+            }) {
+                Ok(key) => {
+                    let model = gs.models.get_mut(&key).expect("model");
+                    project_model.apply(model);
+
+                    // Only the selected model transform is updated every frame
+                    apply_to_scene_resource!(frame, gs.compressions, |res| {
+                        res.update_model_transform(
+                            &frame.wgpu_render_state().expect("render state").queue,
+                            &key,
+                            &model.transform,
+                        )
+                    });
+
+                    restore.keys.insert(project_model.key.clone(), key.clone());
+                    restore.loading = Some((key, project_model));
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Error opening model {} in project: {e}",
+                        project_model.file_name
+                    );
+                    restore
+                        .missing
+                        .push(format!("{}: {e}", project_model.file_name));
+                }
+            }
+        }
+
+        if !restore.is_done() {
+            gs.project_restore = Some(restore);
+            return;
+        }
+
+        // Only select once when all models are restored
+        if let Some(key) = restore
+            .keys
+            .get(&std::mem::take(&mut restore.selected_model_key))
+        {
+            gs.selected_model_key = key.clone();
+        }
+
+        if restore.missing.is_empty() {
+            return;
+        }
+
+        let mut dismissed = false;
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new("⚠ Some models in the project could not be opened")
+                    .color(ui.visuals().warn_fg_color),
+            )
+            .on_hover_text(restore.missing.join("\n"));
+
+            dismissed = ui.button("Dismiss").clicked();
+        });
+        ui.separator();
+
+        if !dismissed {
+            gs.project_restore = Some(restore);
+        }
+    }
+
+    /// Create a project of the session.
+    ///
+    /// Returns the project and the edit buffers of its models, which are to be downloaded by
+    /// [`project::Project::download_edits`].
+    fn project(
+        frame: &mut eframe::Frame,
+        gs: &app::GaussianSplatting,
+    ) -> (project::Project, Vec<gs::GaussiansEditBuffer>) {
+        let project = project::Project::new(gs);
+
+        let edit_buffers;
+        apply_to_scene_resource!(frame, gs.compressions, |res| {
+            let viewer = res.viewer.lock().expect("viewer");
+            edit_buffers = project
+                .models
+                .iter()
+                .map(|model| {
+                    viewer
+                        .models
+                        .get(&model.key)
+                        .expect("model")
+                        .gaussian_buffers
+                        .gaussians_edit_buffer
+                        .clone()
+                })
+                .collect::<Vec<_>>();
+        });
+
+        (project, edit_buffers)
+    }
+
+    /// Periodically save the session to the recovery project.
+    ///
+    /// The recovery project is only written when the session has changed.
+    fn autosave(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &mut eframe::Frame,
+        gs: &app::GaussianSplatting,
+    ) {
+        /// The interval in seconds between auto-saves.
+        const AUTOSAVE_INTERVAL: f64 = 60.0;
+
+        if let Some(rx) = &self.autosave_rx {
+            match rx.try_recv() {
+                Ok(hash) => {
+                    self.autosave_hash = hash;
+                    self.autosave_rx = None;
+                }
+                Err(oneshot::TryRecvError::Disconnected) => {
+                    self.autosave_rx = None;
+                }
+                Err(oneshot::TryRecvError::Empty) => return,
+            }
+        }
+
+        let now = ui.input(|input| input.time);
+        if now - self.autosave_time < AUTOSAVE_INTERVAL
+            || gs.model_loader.is_some()
+            || gs.project_restore.is_some()
+        {
+            return;
+        }
+
+        self.autosave_time = now;
+
+        let (mut project, edit_buffers) = Self::project(frame, gs);
+
+        let (tx, rx) = oneshot::channel();
+        self.autosave_rx = Some(rx);
+
+        let render_state = frame.wgpu_render_state().expect("render state");
+        let device = render_state.device.clone();
+        let queue = render_state.queue.clone();
+        let prev_hash = self.autosave_hash;
+        util::exec_task(async move {
+            project.download_edits(edit_buffers, &device, &queue).await;
+
+            let json = project.to_json();
+            let hash = {
+                use std::hash::{Hash, Hasher};
+
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                json.hash(&mut hasher);
+                hasher.finish()
+            };
+
+            if hash != prev_hash {
+                match project::Project::save_recovery(&json) {
+                    Ok(()) => log::debug!("Session auto-saved"),
+                    Err(e) => log::error!("Auto-save: {e}"),
+                }
+            }
+
+            tx.send(hash).ok();
+        });
+    }
+
+    /// Save the session to a project file.
+    ///
+    /// The edits are downloaded in the background while the save dialog is open.
+    fn save_project(frame: &mut eframe::Frame, gs: &app::GaussianSplatting) {
+        let (mut project, edit_buffers) = Self::project(frame, gs);
+        let scene_tx = gs.scene_tx.clone();
+
+        let task = rfd::AsyncFileDialog::new()
+            .set_title("Save the project")
+            .add_filter("Project", &[project::Project::EXTENSION])
+            .set_file_name(format!("scene.{}", project::Project::EXTENSION))
+            .save_file();
+
+        let render_state = frame.wgpu_render_state().expect("render state");
+        let device = render_state.device.clone();
+        let queue = render_state.queue.clone();
+        util::exec_task(async move {
+            project.download_edits(edit_buffers, &device, &queue).await;
+
+            let Some(file) = task.await else {
+                return;
+            };
+
+            match file.write(project.to_json().as_bytes()).await {
+                Ok(()) => {
+                    scene_tx.send(app::SceneCommand::Saved).ok();
+                }
+                Err(e) => log::error!("Save project: {e}"),
+            }
+        });
+    }
+
+    /// 执行后处理
+    ///
+    /// 由于 eframe 不允许在渲染通道之后进行任何计算通道，
+    /// 因此在此运行预处理通道之前计算上一帧的结果。
+    fn loaded_postprocess<G: gs::GaussianPod>(
+        &mut self,
+        frame: &mut eframe::Frame,
+        rect: &egui::Rect,
+        gs: &mut app::GaussianSplatting,
+    ) {
+        // 解构渲染状态
+        let egui_wgpu::RenderState {
+            device,
+            queue,
+            renderer,
+            ..
+        } = frame.wgpu_render_state().expect("render state");
+
+        let mut renderer = renderer.write(); // 锁定渲染器
+        // 获取场景资源
+        let SceneResource::<G> {
+            viewer,
+            selection_mask,
+            selection_depth,
+            gpu_timer,
+            gpu_timing,
+            ..
+        } = renderer
+            .callback_resources
+            .get_mut()
+            .expect("scene resource");
+        let viewer = viewer.lock().expect("viewer"); // 锁定查看器
+        let mut gpu_timer = gpu_timer.as_mut().filter(|_| *gpu_timing);
+
+        // 后处理，因为 eframe 无法在渲染通道后执行任何计算通道
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Postprocess Encoder"), // 设置编码器标签
+        });
+
+        // 读取上一帧的时间戳，需在写入本帧的时间戳之前
+        if let Some(gpu_timer) = &mut gpu_timer {
+            gpu_timer.resolve(&mut encoder);
+            gpu_timer.write(&mut encoder, app::GpuPass::Postprocess, false);
+        }
+
+        // 对每个可见模型执行后处理
+        for (key, gs_model) in gs.models.iter().filter(|(_, m)| m.visible) {
+            let model = &viewer.models.get(key).expect("model"); // 获取模型
+
+            // 将选择限制在最近高斯之后的深度范围内，需在应用查询结果之前
+            if gs.selection.limit_to_depth && matches!(self.query, Query::Selection { .. }) {
+                selection_depth.limit(
+                    device,
+                    &mut encoder,
+                    &model.gaussian_buffers,
+                    &viewer.world_buffers.camera_buffer,
+                    gs.selection.depth_tolerance,
+                    gs_model.gaussians.gaussians.len() as u32,
+                );
+            }
+
+            // 执行后处理
+            viewer.postprocessor.postprocess(
+                &mut encoder,
+                &model.bind_groups.postprocessor.0, // 第一个后处理器绑定组
+                &model.bind_groups.postprocessor.1, // 第二个后处理器绑定组
+                gs_model.gaussians.gaussians.len() as u32, // 已加载的高斯数量
+                &model.gaussian_buffers.postprocess_indirect_args_buffer, // 后处理间接参数缓冲区
+            );
+
+            // 将选择限制在遮罩内
+            if gs.selection.limit_to_mask && matches!(self.query, Query::Selection { .. }) {
+                selection_mask.intersect(
+                    device,
+                    &mut encoder,
+                    &model.gaussian_buffers.selection_buffer,
+                    &model.gaussian_buffers.mask_buffer,
+                    gs_model.gaussians.gaussians.len() as u32,
+                );
+            }
+        }
+
+        if let Some(gpu_timer) = &gpu_timer {
+            gpu_timer.write(&mut encoder, app::GpuPass::Postprocess, true);
+        }
+
+        queue.submit(Some(encoder.finish())); // 提交命令
+
+        // 映射时间戳，在之后的帧中读取
+        if let Some(gpu_timer) = &gpu_timer {
+            gpu_timer.map();
+        }
+
+        device.poll(wgpu::Maintain::Wait); // 等待设备完成操作
+
+        // 接收查询结果
+        match &mut self.query_result {
+            // 如果是测量定位命中查询结果
+            Some(QueryResult::MeasurementLocateHit) => {
+                if let Query::MeasurementLocateHit {
+                    pod,
+                    hit_method,
+                    alpha_range,
+                    center,
+                    model_key,
+                    tx,
+                } = &self.query
+                {
+                    let (query_result_tx, rx) = oneshot::channel(); // 创建单次通道
+                    self.query_result = Some(QueryResult::Downloading(rx)); // 设置为下载状态
+
+                    let device = device.clone(); // 克隆设备
+                    let queue = queue.clone(); // 克隆队列
+                    let pod = *pod; // 克隆查询POD
+                    let hit_method = *hit_method; // 克隆命中方法
+                    let alpha_range = *alpha_range; // 克隆透明度范围
+                    let center = *center; // 克隆是否定位到高斯中心
+                    let model_key = model_key.clone(); // 克隆模型键
+                    let tx = tx.clone(); // 克隆发送端
+                    let camera = gs.camera.control.clone(); // 克隆相机控制
+                    let viewer_size = self.viewer_size(rect); // 获取视图尺寸
+
+                    // 获取计数缓冲区
+                    let count_buffer = viewer
+                        .models
+                        .get(&model_key)
+                        .expect("model")
+                        .gaussian_buffers
+                        .query_result_count_buffer
+                        .clone();
+                    // 获取结果缓冲区
+                    let results_buffer = viewer
+                        .models
+                        .get(&model_key)
+                        .expect("model")
+                        .gaussian_buffers
+                        .query_results_buffer
+                        .clone();
+
+                    // 执行阻塞任务
+                    util::exec_blocking_task(async move {
+                        // 下载查询结果
+                        let mut results =
+                            gs::query::download(&device, &queue, &count_buffer, &results_buffer)
+                                .await
+                                .expect("download")
+                                .into_iter()
+                                .map(gs::QueryHitResultPod::from) // 转换为查询命中结果POD
+                                .collect::<Vec<_>>();
+
+                        // 根据命中方法确定命中结果的索引和位置
+                        let hit = match hit_method {
+                            // 最大透明度方法
+                            app::MeasurementHitMethod::MostAlpha => {
+                                // 按alpha范围查找命中位置
+                                gs::query::hit_pos_by_alpha_range(
+                                    &pod,
+                                    &mut results,
+                                    &camera,
+                                    viewer_size,
+                                    alpha_range, // alpha范围
+                                )
+                                .map(|(i, _, pos)| (i, pos)) // 提取索引和位置
+                            }
+                            // 最近方法
+                            app::MeasurementHitMethod::Closest => {
+                                // 按最近距离查找命中位置
+                                gs::query::hit_pos_by_closest(&pod, &results, &camera, viewer_size)
+                            }
+                        };
+
+                        // 定位到高斯中心时，高斯位置在主线程中查找
+                        if let (true, Some((i, _))) = (center, hit) {
+                            query_result_tx
+                                .send(Some(QueryResult::MeasurementLocateCenter {
+                                    model_key,
+                                    index: results[i].index() as usize,
+                                    tx,
+                                }))
+                                .expect("send");
+                            return;
+                        }
+
+                        // 如果发送位置失败，记录错误，未命中时为零向量
+                        let pos = hit.map(|(_, pos)| pos).unwrap_or(Vec3::ZERO);
+                        if let Err(e) = tx.send(pos) {
+                            log::error!("Error sending locate hit query result: {e}");
+                        }
+
+                        query_result_tx.send(None).expect("send"); // 发送查询结果
+                    });
+                } else {
+                    self.query_result = None; // 重置查询结果
+                }
+            }
+            Some(QueryResult::PickGaussian) => {
+                if let Query::PickGaussian { pod, model_key, tx } = &self.query {
+                    let (query_result_tx, rx) = oneshot::channel();
+                    self.query_result = Some(QueryResult::Downloading(rx));
+
+                    let device = device.clone();
+                    let queue = queue.clone();
+                    let pod = *pod;
+                    let model_key = model_key.clone();
+                    let tx = tx.clone();
+                    let camera = gs.camera.control.clone();
+                    let viewer_size = self.viewer_size(rect);
+
+                    let gaussian_buffers = &viewer
+                        .models
+                        .get(&model_key)
+                        .expect("model")
+                        .gaussian_buffers;
+                    let count_buffer = gaussian_buffers.query_result_count_buffer.clone();
+                    let results_buffer = gaussian_buffers.query_results_buffer.clone();
+
+                    util::exec_blocking_task(async move {
+                        let results =
+                            gs::query::download(&device, &queue, &count_buffer, &results_buffer)
+                                .await
+                                .expect("download")
+                                .into_iter()
+                                .map(gs::QueryHitResultPod::from)
+                                .collect::<Vec<_>>();
+
+                        // Pick the nearest hit
+                        let picked =
+                            gs::query::hit_pos_by_closest(&pod, &results, &camera, viewer_size)
+                                .map(|(i, _)| app::PickedGaussian {
+                                    key: model_key,
+                                    index: results[i].index() as usize,
+                                });
+
+                        if let Err(e) = tx.send(picked) {
+                            log::error!("Error sending pick Gaussian query result: {e}");
+                        }
+
+                        query_result_tx.send(None).expect("send");
+                    });
+                } else {
+                    self.query_result = None;
+                }
+            }
+            // 其他情况无需处理
+            None
+            | Some(QueryResult::Downloading(..))
+            | Some(QueryResult::MeasurementLocateCenter { .. }) => {}
+        }
+
+        // 如果正在下载查询结果
+        if let Some(QueryResult::Downloading(rx)) = &self.query_result {
+            if let Ok(query_result) = rx.try_recv() {
+                // 尝试接收结果
+                self.query_result = query_result; // 更新查询结果
+            }
+        }
+
+        // 定位到命中高斯的中心
+        if let Some(QueryResult::MeasurementLocateCenter {
+            model_key,
+            index,
+            tx,
+        }) = &self.query_result
+        {
+            // 查找命中高斯在世界空间中的中心
+            let pos = gs
+                .models
+                .get(model_key)
+                .and_then(|model| {
+                    model
+                        .gaussians
+                        .gaussians
+                        .get(*index)
+                        .map(|gaussian| model.transform.apply(gaussian.pos))
+                })
+                .unwrap_or(Vec3::ZERO);
+
+            if let Err(e) = tx.send(pos) {
+                log::error!("Error sending locate hit query result: {e}");
+            }
+
+            self.query_result = None;
+        }
+    }
+
+    /// The tone mapping and gamma correction menu.
+    fn tone_mapping_menu(&mut self, ui: &mut egui::Ui, frame: &eframe::Frame) {
+        ui.menu_button("🌗 Color", |ui| {
+            ui.label(egui::RichText::new("Tone Mapping").strong());
+            ui.checkbox(&mut self.tone_mapping.enabled, "Enabled");
+
+            ui.add_enabled_ui(self.tone_mapping.enabled, |ui| {
+                egui::Grid::new("tone_mapping_grid").show(ui, |ui| {
+                    ui.label("Operator");
+                    ui.horizontal(|ui| {
+                        for operator in app::ToneMappingOperator::iter() {
+                            ui.selectable_value(
+                                &mut self.tone_mapping.operator,
+                                operator,
+                                operator.to_string(),
+                            );
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Exposure");
+                    ui.add(
+                        egui::Slider::new(&mut self.tone_mapping.exposure, -5.0..=5.0)
+                            .fixed_decimals(1)
+                            .suffix(" EV"),
+                    );
+                    ui.end_row();
+                });
+
+                if ui.button("Reset").clicked() {
+                    self.tone_mapping = app::ToneMapping {
+                        enabled: true,
+                        ..app::ToneMapping::new()
+                    };
+                }
+            });
+
+            ui.separator();
+
+            let target_format = frame
+                .wgpu_render_state()
+                .expect("render state")
+                .target_format;
+
+            ui.label(egui::RichText::new("Gamma Correction").strong())
+                .on_hover_text(format!(
+                    "Convert the final color from linear to sRGB, the target format is {}",
+                    util::texture_format_text(target_format),
+                ));
+            ui.horizontal(|ui| {
+                for gamma_correction in app::GammaCorrection::iter() {
+                    ui.selectable_value(
+                        &mut self.gamma_correction,
+                        gamma_correction,
+                        gamma_correction.to_string(),
+                    );
+                }
+            });
+        });
+    }
+
+    /// The menu of the maximum scale clamping of the Gaussians.
+    fn max_scale_menu(&mut self, ui: &mut egui::Ui, gs: &mut app::GaussianSplatting) {
+        let transform = &mut gs.gaussian_transform;
+
+        ui.menu_button("💥 Clamp", |ui| {
+            ui.label(egui::RichText::new("Max Scale").strong())
+                .on_hover_text(
+                    "Clamp the scales of oversized Gaussians when rendering, e.g. the giant \
+                Gaussians of broken scans, the models are not modified",
+                );
+
+            let mut enabled = transform.max_scale.is_some();
+            if ui.checkbox(&mut enabled, "Enabled").changed() {
+                self.max_scale_drag = None;
+                transform.max_scale =
+                    enabled.then_some(app::GaussianSplattingGaussianTransform::DEFAULT_MAX_SCALE);
+            }
+
+            let Some(max_scale) = transform.max_scale else {
+                return;
+            };
+
+            let mut value = self.max_scale_drag.unwrap_or(max_scale);
+            let response = ui.add(
+                egui::Slider::new(&mut value, 0.001..=10.0)
+                    .logarithmic(true)
+                    .max_decimals(3),
+            );
+            if response.dragged() {
+                self.max_scale_drag = Some(value);
+            } else if response.changed() || response.drag_stopped() {
+                self.max_scale_drag = None;
+                transform.max_scale = Some(value);
+            }
+
+            self.max_scale_sorted
+                .retain(|key, _| gs.models.contains_key(key));
+
+            let mut count = 0;
+            let mut clamped = 0;
+            for (key, model) in gs.models.iter() {
+                let gaussians = &model.gaussians.gaussians;
+                let sorted = self.max_scale_sorted.entry(key.clone()).or_default();
+                if sorted.len() != gaussians.len() {
+                    *sorted = gaussians
+                        .iter()
+                        .map(|gaussian| gaussian.scale.max_element())
+                        .collect();
+                    sorted.sort_unstable_by(f32::total_cmp);
+                }
+
+                count += sorted.len();
+                clamped += sorted.len() - sorted.partition_point(|scale| *scale <= value);
+            }
+
+            ui.label(format!(
+                "{} of {} Gaussians clamped",
+                clamped.to_formatted_string(&num_format::Locale::en),
+                count.to_formatted_string(&num_format::Locale::en),
+            ));
+        });
+    }
+
+    /// Update the effective render scale of the frame.
+    ///
+    /// The full resolution is used when the camera has been idle for a while if refinement is
+    /// enabled.
+    fn update_render_scale(
+        &mut self,
+        ui: &egui::Ui,
+        gs: &app::GaussianSplatting,
+        performance: &app::Performance,
+    ) {
+        const REFINE_DELAY: f64 = 0.3;
+
+        use gs::CameraTrait;
+
+        let time = ui.ctx().input(|input| input.time);
+        let view = gs.camera.control.view();
+        if view != self.camera_view {
+            self.camera_view = view;
+            self.camera_moved_time = time;
+        }
+
+        self.render_scale = match performance.render_scale_refine {
+            true if time - self.camera_moved_time >= REFINE_DELAY => 1.0,
+            true => {
+                ui.ctx().request_repaint_after_secs(
+                    (self.camera_moved_time + REFINE_DELAY - time) as f32,
+                );
+                performance.render_scale
+            }
+            false => performance.render_scale,
+        };
+    }
+
+    /// Update the temporal accumulation frame and the camera jitter of the frame.
+    ///
+    /// The accumulation restarts when the camera moves.
+    fn update_accumulation(&mut self, ui: &egui::Ui, performance: &app::Performance) {
+        /// The Halton sequence.
+        fn halton(mut index: u32, base: u32) -> f32 {
+            let mut result = 0.0;
+            let mut fraction = 1.0;
+            while index > 0 {
+                fraction /= base as f32;
+                result += fraction * (index % base) as f32;
+                index /= base;
+            }
+            result
+        }
+
+        if !performance.temporal_accumulation {
+            self.accumulation_frame = None;
+            self.jitter = Vec2::ZERO;
+            return;
+        }
+
+        let time = ui.ctx().input(|input| input.time);
+        let frame = match self.accumulation_frame {
+            Some(frame) if self.camera_moved_time != time => frame + 1,
+            _ => 0,
+        };
+        self.accumulation_frame = Some(frame);
+
+        // The first frame is not jittered, so moving the camera stays sharp
+        self.jitter = match frame {
+            0 => Vec2::ZERO,
+            _ => {
+                let index = (frame - 1) % Self::ACCUMULATION_FRAMES + 1;
+                (vec2(halton(index, 2), halton(index, 3)) - 0.5) / self.render_scale
+            }
+        };
+
+        if frame < Self::ACCUMULATION_FRAMES {
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Track the pixels per point, which changes with the browser zoom or when the window is
+    /// moved to a display with a different scale.
+    ///
+    /// The query texture follows the new viewer size, but an ongoing query was made in the old
+    /// pixels, so it is cancelled.
+    fn update_pixels_per_point(&mut self, ui: &egui::Ui) {
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        if pixels_per_point == self.pixels_per_point {
+            return;
+        }
+
+        log::debug!(
+            "Pixels per point changed from {} to {pixels_per_point}",
+            self.pixels_per_point
+        );
+
+        self.pixels_per_point = pixels_per_point;
+        self.query = Query::none();
+        ui.ctx().request_repaint();
+    }
+
+    /// The size of the viewer in physical pixels.
+    ///
+    /// The query texture and the query positions are in physical pixels, so the selection is
+    /// aligned with the rendered image on scaled displays.
+    fn viewer_size(&self, rect: &egui::Rect) -> UVec2 {
+        (Vec2::from_array(rect.size().into()) * self.pixels_per_point)
+            .round()
+            .as_uvec2()
+            .max(UVec2::ONE)
+    }
+
+    /// The offscreen target of the viewport.
+    ///
+    /// `write_mask` is the color channels written to the viewport, which is not all channels for
+    /// anaglyph stereo.
+    ///
+    /// Returns [`None`] if neither the tone mapping, the explicit gamma correction, the render
+    /// scale, the temporal accumulation nor the write mask changes the rendered image.
+    fn offscreen_target(
+        &self,
+        ui: &egui::Ui,
+        frame: &eframe::Frame,
+        rect: &egui::Rect,
+        performance: &app::Performance,
+        write_mask: wgpu::ColorWrites,
+    ) -> Option<SceneOffscreen> {
+        let target_format = frame
+            .wgpu_render_state()
+            .expect("render state")
+            .target_format;
+        if !self.tone_mapping.enabled
+            && self.gamma_correction.is_enabled(target_format)
+            && self.render_scale >= 1.0
+            && self.accumulation_frame.is_none()
+            && !performance.fxaa
+            && write_mask == wgpu::ColorWrites::ALL
+        {
+            return None;
+        }
+
+        let size = Vec2::from_array((rect.size() * ui.ctx().pixels_per_point()).into())
+            * self.render_scale.min(1.0);
+        let size = size.round().as_uvec2().max(UVec2::ONE);
+
+        let filter = match performance.upscale_filter {
+            app::UpscaleFilter::Nearest => wgpu::FilterMode::Nearest,
+            app::UpscaleFilter::Linear => wgpu::FilterMode::Linear,
+        };
+
+        Some(SceneOffscreen {
+            size,
+            background: ui.visuals().window_fill(),
+            filter,
+            accumulation_frame: self.accumulation_frame,
+            write_mask,
+        })
+    }
+
+    /// 执行预处理
+    fn loaded_preprocess<G: gs::GaussianPod>(
+        &mut self,
+        frame: &mut eframe::Frame,
+        rect: &egui::Rect,
+        gs: &mut app::GaussianSplatting,
+        performance: &app::Performance,
+    ) {
+        // 在VR模式下，左眼也需要偏移（向左）
+        self.loaded_preprocess_with_camera_offset::<G>(
+            frame,
+            rect,
+            gs,
+            performance,
+            false,
+            self.stereo_mode != app::StereoMode::Off,
+        );
+    }
+
+    /// 执行预处理（支持VR相机偏移）
+    fn loaded_preprocess_with_camera_offset<G: gs::GaussianPod>(
+        &mut self,
+        frame: &mut eframe::Frame,
+        rect: &egui::Rect,
+        gs: &mut app::GaussianSplatting,
+        performance: &app::Performance,
+        is_vr_right_eye: bool,
+        apply_vr_offset: bool,
+    ) {
+        if is_vr_right_eye {
+            // VR右眼模式：使用独立的VR viewer
+            log::info!("🎯 [VR DEBUG] *** STARTING VR RIGHT EYE PREPROCESS ***");
+
+            let egui_wgpu::RenderState {
+                device,
+                queue,
+                renderer,
+                ..
+            } = frame.wgpu_render_state().expect("render state");
+
+            log::debug!("📊 [VR DEBUG] Got render state for VR preprocess");
 
             let mut renderer = renderer.write();
             let scene_resource = renderer
@@ -1057,7 +4099,7 @@ impl Scene {
             if let Some(vr_viewer) = &scene_resource.vr_right_eye_viewer {
                 log::debug!("📊 [VR DEBUG] VR viewer exists, proceeding with preprocess");
                 let mut vr_viewer = vr_viewer.lock().expect("vr viewer");
-                let viewer_size = Vec2::from_array(rect.size().into()).as_uvec2();
+                let viewer_size = self.viewer_size(rect);
 
                 log::debug!(
                     "📊 [VR DEBUG] VR viewer has {} models, selected_key: '{}'",
@@ -1096,23 +4138,21 @@ impl Scene {
 
                 // 不再修改相机位置，保持原始相机
                 // 更新VR右窗口viewer的相机（使用原始相机）
-                vr_viewer.update_camera(queue, &gs.camera.control, viewer_size);
+                vr_viewer.update_camera(
+                    queue,
+                    &SceneJitteredCamera::new(&gs.camera.control, self.jitter, viewer_size),
+                    viewer_size,
+                );
                 log::debug!("📷 [VR DEBUG] Updated VR camera (no offset)");
 
-                // 基础IPD（眼间距）
-                const BASE_IPD: f32 = 0.065;
-
-                // 使用视差强度参数（从UI滑块获取）
-                let effective_ipd = BASE_IPD * self.vr_parallax_strength;
-
-                // 右窗口：模型向右偏移（修正）
+                // 右窗口：模型向右偏移（交换双眼时向左）
                 let mut modified_model_pos = gs.selected_model().transform.pos;
-                modified_model_pos.x += effective_ipd / 2.0;
+                modified_model_pos.x -= self.vr_left_eye_offset();
 
                 log::info!(
-                    "👁️ [VR DEBUG] RIGHT window - Parallax strength: {:.1}×, Model X offset: +{:.3}",
+                    "👁️ [VR DEBUG] RIGHT window - Parallax strength: {:.1}×, Model X offset: {:+.3}",
                     self.vr_parallax_strength,
-                    effective_ipd / 2.0
+                    -self.vr_left_eye_offset()
                 );
 
                 // 更新VR右窗口viewer的模型变换（使用偏移后的模型位置）
@@ -1127,7 +4167,14 @@ impl Scene {
 
                 vr_viewer.update_gaussian_transform(
                     queue,
-                    gs.gaussian_transform.size,
+                    gs.gaussian_transform.shader_size(
+                        gs.gaussian_transform.display_mode,
+                        gs.camera
+                            .control
+                            .pos()
+                            .distance(gs.selected_model().world_center()),
+                        viewer_size.y,
+                    ),
                     gs.gaussian_transform.display_mode,
                     gs.gaussian_transform.sh_deg,
                     gs.gaussian_transform.no_sh0,
@@ -1140,7 +4187,7 @@ impl Scene {
                 });
 
                 // 对所有可见模型执行预处理和排序
-                for key in gs.models.iter().filter(|(_, m)| m.visible).map(|(k, _)| k) {
+                for (key, gs_model) in gs.models.iter().filter(|(_, m)| m.visible) {
                     if let Some(vr_model) = vr_viewer.models.get(key) {
                         log::debug!("🎨 [VR DEBUG] Preprocessing and sorting model '{}'", key);
 
@@ -1148,7 +4195,7 @@ impl Scene {
                         vr_viewer.preprocessor.preprocess(
                             &mut encoder,
                             &vr_model.bind_groups.preprocessor,
-                            vr_model.gaussian_buffers.gaussians_buffer.len() as u32,
+                            gs_model.gaussians.gaussians.len() as u32,
                         );
 
                         // 执行基数排序
@@ -1158,6 +4205,15 @@ impl Scene {
                             &vr_model.gaussian_buffers.radix_sort_indirect_args_buffer,
                         );
 
+                        // 限制渲染数量
+                        scene_resource.render_limit.limit(
+                            device,
+                            queue,
+                            &mut encoder,
+                            &vr_model.gaussian_buffers.indirect_args_buffer,
+                            performance.render_limit,
+                        );
+
                         log::debug!("✅ [VR DEBUG] Preprocessed and sorted model '{}'", key);
                     }
                 }
@@ -1191,17 +4247,29 @@ impl Scene {
             viewer,
             measurement_renderer,
             measurement_visible_hit_pairs,
+            capture_cameras_renderer,
             query_toolset,
             query_texture_overlay,
             query_cursor,
             unedited_models,
             show_unedited_model,
+            tone_mapping,
+            render_limit,
+            gpu_timer,
+            gpu_timing,
             ..
         } = renderer
             .callback_resources
             .get_mut()
             .expect("scene resource");
 
+        tone_mapping.update(
+            queue,
+            &self.tone_mapping,
+            self.gamma_correction,
+            performance.fxaa,
+        );
+
         let mut viewer = viewer.lock().expect("viewer"); // 锁定查看器
         // 创建命令编码器
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -1215,7 +4283,7 @@ impl Scene {
                 viewer.world_buffers.query_texture.texture().size();
             let texture_size = uvec2(width, height); // 纹理尺寸
 
-            let viewer_size = Vec2::from_array(rect.size().into()).as_uvec2(); // 视图尺寸
+            let viewer_size = self.viewer_size(rect); // 视图尺寸（物理像素）
             // 如果纹理尺寸与视图尺寸不同
             if texture_size != viewer_size {
                 viewer.update_query_texture_size(device, viewer_size); // 更新查询纹理尺寸
@@ -1236,12 +4304,15 @@ impl Scene {
             // 处理新查询
             if let Query::MeasurementLocateHit { .. } = self.query {
                 self.query_result = Some(QueryResult::MeasurementLocateHit); // 设置为测量定位命中结果
+            } else if let Query::PickGaussian { .. } = self.query {
+                self.query_result = Some(QueryResult::PickGaussian);
             }
 
             // 根据查询类型获取查询POD
             let query_pod = match &self.query {
                 Query::None { pod } => pod.as_query(), // 无查询
                 Query::MeasurementLocateHit { pod, .. } => pod.as_query(), // 测量定位查询
+                Query::PickGaussian { pod, .. } => pod.as_query(),
                 Query::Selection {
                     // 选择查询
                     action,
@@ -1262,7 +4333,18 @@ impl Scene {
                         None => query_toolset.update_pos(*pos),                 // 更新位置
                     };
 
-                    query_cursor.update_query_toolset(queue, query_toolset, *pos); // 更新查询光标
+                    // 更新查询光标，画笔选择在开始前预览画笔半径，矩形选择在开始前不显示
+                    query_cursor.update_query(
+                        queue,
+                        query_toolset.query(),
+                        Some(gs::QueryNoneSupplement {
+                            position: *pos,
+                            radius: match gs.selection.method {
+                                app::SelectionMethod::Brush => *brush_radius,
+                                app::SelectionMethod::Rect => 0,
+                            },
+                        }),
+                    );
 
                     query_toolset.query() // 获取查询
                 }
@@ -1280,25 +4362,23 @@ impl Scene {
             }
 
             // 更新查看器相机（不再修改相机位置）
-            viewer.update_camera(queue, &gs.camera.control, viewer_size);
+            viewer.update_camera(
+                queue,
+                &SceneJitteredCamera::new(&gs.camera.control, self.jitter, viewer_size),
+                viewer_size,
+            );
 
             // 在VR模式下，通过偏移模型位置来创建立体效果
             let model_pos = if apply_vr_offset {
                 let mut modified_pos = gs.selected_model().transform.pos;
 
-                // 基础IPD（眼间距）
-                const BASE_IPD: f32 = 0.065;
-
-                // 使用视差强度参数（从UI滑块获取）
-                let effective_ipd = BASE_IPD * self.vr_parallax_strength;
-
-                // 左窗口：模型向左偏移（修正）
-                modified_pos.x -= effective_ipd / 2.0;
+                // 左窗口：模型向左偏移（交换双眼时向右）
+                modified_pos.x += self.vr_left_eye_offset();
 
                 log::debug!(
-                    "👁️ [VR DEBUG] LEFT window - Parallax strength: {:.1}×, Model X offset: -{:.3}",
+                    "👁️ [VR DEBUG] LEFT window - Parallax strength: {:.1}×, Model X offset: {:+.3}",
                     self.vr_parallax_strength,
-                    effective_ipd / 2.0
+                    self.vr_left_eye_offset()
                 );
 
                 modified_pos
@@ -1317,12 +4397,54 @@ impl Scene {
             viewer.update_gaussian_transform(
                 // 更新高斯变换
                 queue,
-                gs.gaussian_transform.size,         // 尺寸
+                // 尺寸，点显示模式下按选中模型中心的距离换算像素大小
+                gs.gaussian_transform.shader_size(
+                    gs.gaussian_transform.display_mode,
+                    gs.camera
+                        .control
+                        .pos()
+                        .distance(gs.selected_model().world_center()),
+                    viewer_size.y,
+                ),
                 gs.gaussian_transform.display_mode, // 显示模式
                 gs.gaussian_transform.sh_deg,       // 球谐度数
                 gs.gaussian_transform.no_sh0,       // 是否无SH0
             );
 
+            // 更新每个模型的高斯变换，未覆盖显示模式的模型使用全局显示模式
+            for (key, unedited_model) in unedited_models.iter() {
+                let model = gs.models.get(key);
+
+                // 按相机到模型边界的距离降低球谐度数
+                let sh_deg = match (model, performance.sh_lod) {
+                    (Some(model), Some(..)) => performance.sh_lod_degree(
+                        gs.gaussian_transform.sh_deg,
+                        (gs.camera.control.pos().distance(model.world_center())
+                            - model.world_radius())
+                        .max(0.0),
+                    ),
+                    _ => gs.gaussian_transform.sh_deg,
+                };
+
+                let display_mode = model
+                    .and_then(|model| model.display_mode)
+                    .unwrap_or(gs.gaussian_transform.display_mode);
+
+                unedited_model.gaussian_transform_buffer.update(
+                    queue,
+                    gs.gaussian_transform.shader_size(
+                        display_mode,
+                        model.map_or(1.0, |model| {
+                            gs.camera.control.pos().distance(model.world_center())
+                        }),
+                        viewer_size.y,
+                    ),
+                    display_mode,
+                    sh_deg,
+                    gs.gaussian_transform.no_sh0,
+                );
+            }
+
             // 处理选择
             match gs.action {
                 Some(app::Action::Selection) => match &gs.selection.edit {
@@ -1365,14 +4487,27 @@ impl Scene {
             }
         }
 
+        // 更新拍摄相机标记
+        capture_cameras_renderer.update_markers(
+            device,
+            &gs.capture_cameras.markers(&gs.models),
+            &viewer.world_buffers.camera_buffer,
+        );
+
         *show_unedited_model = gs.selection.show_unedited; // 更新显示未编辑模型标志
         if *show_unedited_model {
             // 如果显示未编辑模型，使用默认编辑更新选择编辑
             viewer.update_selection_edit_with_pod(queue, &gs::GaussianEditPod::default());
         }
 
-        // 预处理
-        for key in gs.models.iter().filter(|(_, m)| m.visible).map(|(k, _)| k) {
+        let gpu_timer = gpu_timer.as_ref().filter(|_| *gpu_timing);
+
+        // 预处理，先处理所有模型再排序，以便分别测量耗时
+        if let Some(gpu_timer) = gpu_timer {
+            gpu_timer.write(&mut encoder, app::GpuPass::Preprocess, false);
+        }
+
+        for (key, gs_model) in gs.models.iter().filter(|(_, m)| m.visible) {
             let model = &viewer.models.get(key).expect("model"); // 获取模型
             let unedited_model = unedited_models.get(key).expect("unedited model"); // 获取未编辑模型
 
@@ -1384,8 +4519,19 @@ impl Scene {
                     true => &unedited_model.preprocessor_bind_group, // 未编辑模型绑定组
                     false => &model.bind_groups.preprocessor,        // 模型预处理器绑定组
                 },
-                model.gaussian_buffers.gaussians_buffer.len() as u32, // 高斯缓冲区长度
+                // 只处理已加载的高斯，以便在加载过程中逐步预览
+                gs_model.gaussians.gaussians.len() as u32,
             );
+        }
+
+        if let Some(gpu_timer) = gpu_timer {
+            gpu_timer.write(&mut encoder, app::GpuPass::Preprocess, true);
+            gpu_timer.write(&mut encoder, app::GpuPass::Sort, false);
+        }
+
+        // 排序
+        for (key, _) in gs.models.iter().filter(|(_, m)| m.visible) {
+            let model = &viewer.models.get(key).expect("model"); // 获取模型
 
             // 执行基数排序
             viewer.radix_sorter.sort(
@@ -1393,6 +4539,19 @@ impl Scene {
                 &model.bind_groups.radix_sorter, // 基数排序绑定组
                 &model.gaussian_buffers.radix_sort_indirect_args_buffer, // 间接参数缓冲区
             );
+
+            // 限制渲染数量
+            render_limit.limit(
+                device,
+                queue,
+                &mut encoder,
+                &model.gaussian_buffers.indirect_args_buffer,
+                performance.render_limit,
+            );
+        }
+
+        if let Some(gpu_timer) = gpu_timer {
+            gpu_timer.write(&mut encoder, app::GpuPass::Sort, true);
         }
 
         queue.submit(Some(encoder.finish())); // 提交命令
@@ -1404,13 +4563,41 @@ impl Scene {
     /// 高斯模型已加载，当前正在为查看器选择压缩设置
     ///
     /// 返回 true 表示确认，false 表示取消，[`None`] 表示尚未确认
+    #[allow(clippy::too_many_arguments)]
     fn initialize(
         &mut self,
         ui: &mut egui::Ui,
         frame: &mut eframe::Frame,
         gs: &mut app::GaussianSplatting,
         compressions: &mut app::Compressions,
+        performance: &app::Performance,
+        coordinate_system: &mut app::CoordinateSystem,
+        flip_handedness: &mut bool,
+        confirm: &mut bool,
     ) -> Result<Option<bool>, String> {
+        // 不需要确认时直接使用当前压缩设置，除非显存可能不足
+        if !*confirm
+            && performance
+                .memory_warnings(
+                    &frame
+                        .wgpu_render_state()
+                        .expect("render state")
+                        .device
+                        .limits(),
+                    compressions,
+                    gs.selected_model().gaussians.gaussians.capacity(),
+                )
+                .is_empty()
+        {
+            log::info!(
+                "Initializing scene with {} SH and {} covariance 3D without confirmation",
+                compressions.sh,
+                compressions.cov3d
+            );
+            Self::create_resource(frame, gs, compressions);
+            return Ok(Some(true));
+        }
+
         // 显示初始化场景模态窗口
         egui::Modal::new(egui::Id::new("initialize_scene_modal"))
             .show(ui.ctx(), |ui| {
@@ -1521,6 +4708,50 @@ impl Scene {
 
                 ui.label(""); // 空标签
 
+                // 压缩质量预览
+                self.compression_preview_ui(ui, gs, compressions, performance);
+
+                ui.label(""); // 空标签
+
+                // 选择导入的坐标系，项目使用自己的变换
+                if gs.project_restore.is_none() {
+                    ui.horizontal(|ui| {
+                        ui.label("Coordinate System")
+                            .on_hover_text("The up axis and handedness of the model");
+                        egui::ComboBox::from_id_salt("initialize_scene_coordinate_system")
+                            .width(150.0) // 宽度
+                            .selected_text(coordinate_system.to_string()) // 已选文本
+                            .show_ui(ui, |ui| {
+                                for system in app::CoordinateSystem::iter() {
+                                    if ui
+                                        .selectable_value(
+                                            coordinate_system,
+                                            system,
+                                            system.to_string(),
+                                        )
+                                        .clicked()
+                                    {
+                                        gs.selected_model_mut().transform = system.transform();
+                                    }
+                                }
+                            });
+
+                        // 已接收的高斯也会被翻转
+                        if ui
+                            .checkbox(flip_handedness, "Flip Handedness")
+                            .on_hover_text(
+                                "Mirror the model for left-handed data that renders mirrored, \
+                                it can also be flipped later in the transform tab",
+                            )
+                            .changed()
+                        {
+                            gs.selected_model_mut().flip_handedness();
+                        }
+                    });
+
+                    ui.label(""); // 空标签
+                }
+
                 // 显示高斯数量
                 ui.label(format!(
                     "Gaussian Count: {}",
@@ -1531,101 +4762,356 @@ impl Scene {
                         .to_formatted_string(&num_format::Locale::en) // 格式化数字
                 ));
 
-                // 显示原始尺寸
-                ui.label(format!(
-                    "Original Size: {}",
-                    util::human_readable_size(
-                        gs.selected_model().gaussians.gaussians.capacity()  // 容量
-                            * std::mem::size_of::<gs::PlyGaussianPod>() // PlyGaussianPod占用的内存大小
-                    )
-                ));
-                // 显示压缩后尺寸
-                ui.label(format!(
-                    "Compressed Size: {}",
-                    util::human_readable_size(
-                        compressions
-                            .compressed_size(gs.selected_model().gaussians.gaussians.capacity()) // 压缩后的尺寸
-                    )
-                ));
-                ui.label(""); // 空标签
+                // 显示原始尺寸
+                ui.label(format!(
+                    "Original Size: {}",
+                    util::human_readable_size(
+                        gs.selected_model().gaussians.gaussians.capacity()  // 容量
+                            * std::mem::size_of::<gs::PlyGaussianPod>() // PlyGaussianPod占用的内存大小
+                    )
+                ));
+                // 显示压缩后尺寸
+                ui.label(format!(
+                    "Compressed Size: {}",
+                    util::human_readable_size(
+                        compressions
+                            .compressed_size(gs.selected_model().gaussians.gaussians.capacity()) // 压缩后的尺寸
+                    )
+                ));
+                // 显示估计的显存占用
+                ui.label(format!(
+                    "Estimated GPU Memory: {}",
+                    util::human_readable_size(
+                        compressions.gpu_memory_estimate(
+                            gs.selected_model().gaussians.gaussians.capacity()
+                        )
+                    )
+                ));
+                ui.label(""); // 空标签
+
+                // 显存不足警告
+                let warnings = performance.memory_warnings(
+                    &frame
+                        .wgpu_render_state()
+                        .expect("render state")
+                        .device
+                        .limits(),
+                    compressions,
+                    gs.selected_model().gaussians.gaussians.capacity(),
+                );
+                if !warnings.is_empty() {
+                    for warning in warnings {
+                        ui.label(
+                            egui::RichText::new(format!("⚠ {warning}"))
+                                .color(ui.visuals().warn_fg_color),
+                        );
+                    }
+
+                    // 提供切换到更高压缩的选项
+                    if let Some(higher) = compressions.higher() {
+                        if ui
+                            .button("Use Higher Compression")
+                            .on_hover_text(format!(
+                                "Switch to {} SH and {} covariance 3D",
+                                higher.sh, higher.cov3d
+                            ))
+                            .clicked()
+                        {
+                            *compressions = higher;
+                            gs.compressions = compressions.clone(); // 更新全局设置
+                        }
+                    }
+
+                    ui.label(""); // 空标签
+                }
+
+                // 不再询问，之后加载时直接使用当前压缩设置
+                let mut skip = !*confirm;
+                ui.checkbox(&mut skip, "Don't ask again, use current compression settings")
+                    .on_hover_text(
+                        "Can be changed again in the compression settings of the menu or the settings tab",
+                    );
+                *confirm = !skip;
+                ui.label(""); // 空标签
+
+                // 水平布局按钮
+                ui.horizontal(|ui| {
+                    // 如果点击了确认按钮
+                    if ui.button("Confirm").clicked() {
+                        Self::create_resource(frame, gs, compressions);
+
+                        return Ok(Some(true)); // 返回确认
+                    }
+
+                    // 如果点击了取消按钮
+                    if ui.button("Cancel").clicked() {
+                        return Ok(Some(false)); // 返回取消
+                    }
+
+                    Ok(None) // 返回未确认
+                })
+                .inner
+            })
+            .inner
+    }
+
+    /// Receive the Gaussians of the loading model within the loading budget.
+    ///
+    /// Returns the key of the model and the index of the first received Gaussian, or [`None`] if
+    /// no model is loading.
+    fn receive_gaussians(
+        ui: &egui::Ui,
+        gs: &mut app::GaussianSplatting,
+        performance: &app::Performance,
+    ) -> Option<(String, usize)> {
+        let (loading, rx) = gs.model_loader.as_ref()?;
+        let loading = loading.clone();
+        let timer = chrono::Local::now();
+
+        let model = gs.models.get_mut(&loading).expect("model");
+        let mut new_count = 0;
+
+        for gaussian in rx.try_iter() {
+            match gaussian {
+                Ok(gaussian) => {
+                    model.gaussians.gaussians.push(gaussian);
+                    new_count += 1;
+                }
+                Err(e) => {
+                    log::error!("Error loading gaussian: {e}");
+                }
+            }
+
+            const BATCH_SIZE: usize = 1000;
+            if new_count % BATCH_SIZE == 0
+                && (chrono::Local::now() - timer).num_milliseconds() as f32
+                    > performance.loading_budget
+            {
+                break;
+            }
+        }
+
+        let start = model.gaussians.gaussians.len() - new_count;
+        model.flip_loaded(start);
+
+        if model.gaussians.gaussians.len() == model.gaussians.gaussians.capacity() {
+            gs.model_loader = None;
+
+            // Fit the orbit distance limits and the movement scale to the models, a restored
+            // project keeps its own
+            if gs.project_restore.is_none() {
+                let radius = gs
+                    .models
+                    .values()
+                    .map(|model| model.world_radius())
+                    .fold(0.0, f32::max);
+
+                gs.camera.distance_limits = app::CameraDistanceLimits::fit(radius);
+                if gs.camera.auto_scale {
+                    gs.camera.scale = app::Camera::fit_scale(radius);
+                }
+            }
+        } else {
+            ui.ctx().request_repaint();
+        }
+
+        Some((loading, start))
+    }
+
+    /// Show the side-by-side preview of the uncompressed and compressed Gaussians.
+    ///
+    /// The preview is rendered on CPU from a sample of the selected model once it is fully
+    /// received, and only re-rendered when the compression settings change.
+    fn compression_preview_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        gs: &mut app::GaussianSplatting,
+        compressions: &app::Compressions,
+        performance: &app::Performance,
+    ) {
+        if self.compression_preview.is_none() {
+            Self::receive_gaussians(ui, gs, performance);
+        }
+
+        let gaussians = &gs.selected_model().gaussians.gaussians;
+        if gaussians.len() < gaussians.capacity() {
+            egui::CollapsingHeader::new("Quality Preview")
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!(
+                            "Reading Gaussians {:.0}%",
+                            gaussians.len() as f32 / gaussians.capacity() as f32 * 100.0
+                        ));
+                    });
+                });
+            return;
+        }
+
+        let preview = self.compression_preview.get_or_insert_with(|| {
+            let preview = CompressionPreview::new(gs.selected_model());
+            let image = preview.render(None);
+            let texture = ui.ctx().load_texture(
+                "compression_preview_uncompressed",
+                image.clone(),
+                egui::TextureOptions::LINEAR,
+            );
+
+            SceneCompressionPreview {
+                preview,
+                uncompressed: (image, texture),
+                compressed: None,
+            }
+        });
+
+        if preview
+            .compressed
+            .as_ref()
+            .is_none_or(|(c, ..)| c != compressions)
+        {
+            let image = preview.preview.render(Some(compressions));
+            let psnr = CompressionPreview::psnr(&preview.uncompressed.0, &image);
+            let texture = ui.ctx().load_texture(
+                "compression_preview_compressed",
+                image,
+                egui::TextureOptions::LINEAR,
+            );
+
+            preview.compressed = Some((compressions.clone(), texture, psnr));
+        }
+
+        let (_, compressed, psnr) = preview.compressed.as_ref().expect("compressed preview");
+        let size = egui::Vec2::splat(CompressionPreview::SIZE as f32);
 
-                // 水平布局按钮
+        egui::CollapsingHeader::new("Quality Preview")
+            .default_open(true)
+            .show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    // 如果点击了确认按钮
-                    if ui.button("Confirm").clicked() {
-                        // 定义压缩类型组合宏
-                        macro_rules! case {
-                            ($sh:ident, $cov3d:ident) => {
-                                app::Compressions {
-                                    sh: app::ShCompression::$sh,
-                                    cov3d: app::Cov3dCompression::$cov3d,
-                                }
-                            };
-                        }
+                    ui.vertical(|ui| {
+                        ui.label("Uncompressed");
+                        ui.image((preview.uncompressed.1.id(), size));
+                    });
+                    ui.vertical(|ui| {
+                        ui.label("Compressed");
+                        ui.image((compressed.id(), size));
+                    });
+                });
 
-                        // 定义新建资源宏
-                        macro_rules! new {
-                            ($sh:ident, $cov3d:ident, $frame:expr, $gs:expr) => {
-                                paste::paste! {
-                                    // 插入场景资源
-                                    frame
-                                        .wgpu_render_state()
-                                        .expect("render state")
-                                        .renderer
-                                        .write()
-                                        .callback_resources
-                                        .insert(SceneResource::<
-                                            gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
-                                        >::new(  // 创建新的场景资源
-                                            $frame.wgpu_render_state().expect("render state"),  // 渲染状态
-                                            $gs.selected_model().file_name.clone(),            // 文件名
-                                            $gs.selected_model().gaussians.gaussians.capacity(), // 容量
-                                        ))
-                                }
-                            };
-                        }
+                ui.label(match psnr.is_finite() {
+                    true => format!("PSNR: {psnr:.1} dB"),
+                    false => "PSNR: Identical".to_string(),
+                })
+                .on_hover_text(format!(
+                    "Peak signal-to-noise ratio of a rough preview of up to {} sampled Gaussians, \
+                    higher is better",
+                    CompressionPreview::SAMPLE_COUNT.to_formatted_string(&num_format::Locale::en)
+                ));
+            });
+    }
 
-                        // 根据压缩设置创建相应的资源
-                        match compressions {
-                            case!(Single, Single) => {
-                                new!(Single, Single, frame, gs);
-                            }
-                            case!(Single, Half) => {
-                                new!(Single, Half, frame, gs);
-                            }
-                            case!(Half, Single) => {
-                                new!(Half, Single, frame, gs);
-                            }
-                            case!(Half, Half) => {
-                                new!(Half, Half, frame, gs);
-                            }
-                            case!(Norm8, Single) => {
-                                new!(Norm8, Single, frame, gs);
-                            }
-                            case!(Norm8, Half) => {
-                                new!(Norm8, Half, frame, gs);
-                            }
-                            case!(Remove, Single) => {
-                                new!(None, Single, frame, gs);
-                            }
-                            case!(Remove, Half) => {
-                                new!(None, Half, frame, gs);
-                            }
-                        }
+    /// 根据压缩设置创建场景资源
+    fn create_resource(
+        frame: &mut eframe::Frame,
+        gs: &app::GaussianSplatting,
+        compressions: &app::Compressions,
+    ) {
+        // 定义压缩类型组合宏
+        macro_rules! case {
+            ($sh:ident, $cov3d:ident) => {
+                app::Compressions {
+                    sh: app::ShCompression::$sh,
+                    cov3d: app::Cov3dCompression::$cov3d,
+                }
+            };
+        }
 
-                        return Ok(Some(true)); // 返回确认
-                    }
+        // 定义新建资源宏
+        macro_rules! new {
+            ($sh:ident, $cov3d:ident, $frame:expr, $gs:expr) => {
+                paste::paste! {
+                    // 插入场景资源
+                    frame
+                        .wgpu_render_state()
+                        .expect("render state")
+                        .renderer
+                        .write()
+                        .callback_resources
+                        .insert(SceneResource::<
+                            gs::[< GaussianPodWithSh $sh Cov3d $cov3d Configs >]
+                        >::new(  // 创建新的场景资源
+                            $frame.wgpu_render_state().expect("render state"),  // 渲染状态
+                            $gs.selected_model().file_name.clone(),            // 文件名
+                            $gs.selected_model().gaussians.gaussians.capacity(), // 容量
+                        ))
+                }
+            };
+        }
 
-                    // 如果点击了取消按钮
-                    if ui.button("Cancel").clicked() {
-                        return Ok(Some(false)); // 返回取消
-                    }
+        // 根据压缩设置创建相应的资源
+        match compressions {
+            case!(Single, Single) => {
+                new!(Single, Single, frame, gs);
+            }
+            case!(Single, Half) => {
+                new!(Single, Half, frame, gs);
+            }
+            case!(Half, Single) => {
+                new!(Half, Single, frame, gs);
+            }
+            case!(Half, Half) => {
+                new!(Half, Half, frame, gs);
+            }
+            case!(Norm8, Single) => {
+                new!(Norm8, Single, frame, gs);
+            }
+            case!(Norm8, Half) => {
+                new!(Norm8, Half, frame, gs);
+            }
+            case!(Remove, Single) => {
+                new!(None, Single, frame, gs);
+            }
+            case!(Remove, Half) => {
+                new!(None, Half, frame, gs);
+            }
+        }
 
-                    Ok(None) // 返回未确认
-                })
-                .inner
-            })
-            .inner
+        // 上传已经读取的高斯点
+        let gaussians = &gs.selected_model().gaussians.gaussians;
+        if !gaussians.is_empty() {
+            apply_to_scene_resource!(frame, compressions, |res| {
+                res.load_model(
+                    frame.wgpu_render_state().expect("render state"),
+                    &gs.selected_model_key,
+                    0,
+                    gaussians,
+                )
+            });
+        }
+    }
+}
+
+/// The compression preview of [`Scene::compression_preview_ui`].
+struct SceneCompressionPreview {
+    /// The preview renderer.
+    preview: CompressionPreview,
+
+    /// The uncompressed image and texture.
+    uncompressed: (egui::ColorImage, egui::TextureHandle),
+
+    /// The compression settings, texture and PSNR of the compressed image.
+    compressed: Option<(app::Compressions, egui::TextureHandle, f32)>,
+}
+
+impl std::fmt::Debug for SceneCompressionPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SceneCompressionPreview")
+            .field("preview", &self.preview)
+            .field(
+                "compressed",
+                &self.compressed.as_ref().map(|(c, _, psnr)| (c, psnr)),
+            )
+            .finish_non_exhaustive()
     }
 }
 
@@ -1640,6 +5126,19 @@ struct SceneInput {
     /// Currently this is for selection operation only.
     prev_modifiers: egui::Modifiers,
 
+    /// The position of the context menu relative to the viewport in physical pixels, and the
+    /// located world point under it.
+    context_menu_pos: Option<(egui::Pos2, Option<Vec3>)>,
+
+    /// The sender of the located world point under the context menu.
+    context_menu_tx: mpsc::Sender<Vec3>,
+
+    /// The receiver of the located world point under the context menu.
+    context_menu_rx: mpsc::Receiver<Vec3>,
+
+    /// The first person rotation not applied yet by the mouse-look smoothing.
+    look_pending: Vec2,
+
     /// The web event listener.
     ///
     /// This is only available on the web.
@@ -1650,11 +5149,19 @@ struct SceneInput {
 impl SceneInput {
     /// Create a new scene input state.
     fn new() -> Self {
+        let (context_menu_tx, context_menu_rx) = mpsc::channel();
+
         Self {
             focused: false,
 
             prev_modifiers: egui::Modifiers::default(),
 
+            context_menu_pos: None,
+            context_menu_tx,
+            context_menu_rx,
+
+            look_pending: Vec2::ZERO,
+
             #[cfg(target_arch = "wasm32")]
             web_event_listener: SceneInputWebEventListener::new(),
         }
@@ -1668,6 +5175,7 @@ impl SceneInput {
         query: &mut Query,
         rect: &egui::Rect,
         response: &egui::Response,
+        settings: &app::Settings,
     ) {
         #[cfg(target_arch = "wasm32")]
         let web_result = self.web_event_listener.update();
@@ -1680,6 +5188,7 @@ impl SceneInput {
                 gs,
                 rect,
                 response,
+                settings,
                 #[cfg(target_arch = "wasm32")]
                 &web_result,
             );
@@ -1688,6 +5197,166 @@ impl SceneInput {
         self.prev_modifiers = ui.ctx().input(|input| input.modifiers);
     }
 
+    /// Handle the context menu.
+    ///
+    /// The menu opens on a right click, which egui tells apart from the right drag for panning.
+    /// The world point under the cursor is located by a hit query when the menu opens, so this
+    /// only starts queries when `can_query` is true, i.e. no query result is pending.
+    fn context_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        gs: &mut app::GaussianSplatting,
+        query: &mut Query,
+        rect: &egui::Rect,
+        response: &egui::Response,
+        can_query: bool,
+    ) {
+        if gs.action.is_some() {
+            return;
+        }
+
+        // The located point query is done
+        if can_query && matches!(query, Query::MeasurementLocateHit { .. }) {
+            *query = Query::none();
+        }
+
+        if let Ok(point) = self.context_menu_rx.try_recv() {
+            if let Some((_, located)) = &mut self.context_menu_pos {
+                *located = Some(point);
+            }
+        }
+
+        if can_query && response.secondary_clicked() {
+            if let Some(interact_pos) = response.interact_pointer_pos() {
+                let pos = ((interact_pos - rect.min) * ui.ctx().pixels_per_point()).to_pos2();
+                self.context_menu_pos = Some((pos, None));
+                *query = Query::measurement_locate_hit(
+                    pos,
+                    gs.measurement.hit_method,
+                    gs.measurement.alpha_range,
+                    gs.measurement.locate_center,
+                    gs.selected_model_key.clone(),
+                    self.context_menu_tx.clone(),
+                );
+            }
+        }
+
+        let Some((pos, point)) = self.context_menu_pos else {
+            return;
+        };
+
+        response.context_menu(|ui| {
+            match point {
+                Some(point) => {
+                    ui.label(format!("📍 {:.3}, {:.3}, {:.3}", point.x, point.y, point.z))
+                        .on_hover_text("The point under the cursor on the selected model");
+                }
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Locating point...");
+                    });
+                }
+            }
+
+            ui.separator();
+
+            if ui
+                .add_enabled(point.is_some(), egui::Button::new("🎯 Look at point"))
+                .clicked()
+            {
+                let mut orbit = gs.camera.control.to_orbit(1.0);
+                orbit.target = point.expect("point");
+                gs.camera.control.set_orbit(orbit);
+                ui.close_menu();
+            }
+
+            if ui.button("📦 Frame selected model").clicked() {
+                let model = gs.selected_model();
+                let center = model.world_center();
+                let distance =
+                    model.world_radius() / (gs.camera.control.vertical_fov() / 2.0).tan();
+
+                let mut orbit = gs.camera.control.to_orbit(1.0);
+                let direction = (orbit.pos - orbit.target).normalize_or(Vec3::Z);
+                orbit.target = center;
+                orbit.pos = center + direction * distance;
+                gs.camera.control.set_orbit(orbit);
+                ui.close_menu();
+            }
+
+            if ui.button("🔄 Reset camera").clicked() {
+                gs.camera.control = app::Camera::new().control;
+                ui.close_menu();
+            }
+
+            ui.separator();
+
+            if ui
+                .add_enabled(can_query, egui::Button::new("🔍 Pick Gaussian here"))
+                .clicked()
+            {
+                let (tx, rx) = mpsc::channel();
+                *query = Query::pick_gaussian(pos, gs.selected_model_key.clone(), tx.clone());
+                gs.action = Some(app::Action::PickGaussian { tx, rx });
+                ui.close_menu();
+            }
+
+            if ui
+                .add_enabled(point.is_some(), egui::Button::new("📏 Measure from point"))
+                .on_hover_text("Add a measurement starting at this point, then click on the end")
+                .clicked()
+            {
+                let point = point.expect("point");
+                gs.measurement.add_hit_pair();
+
+                let hit_pair_index = gs.measurement.hit_pairs.len() - 1;
+                for hit in gs.measurement.hit_pairs[hit_pair_index].hits.iter_mut() {
+                    hit.pos = point;
+                }
+                gs.scene_tx
+                    .send(app::SceneCommand::UpdateMeasurementHit)
+                    .expect("send update measurement hit");
+
+                let (tx, rx) = mpsc::channel();
+                gs.action = Some(app::Action::MeasurementLocateHit {
+                    hit_pair_index,
+                    hit_index: 1,
+                    tx,
+                    rx,
+                });
+                ui.close_menu();
+            }
+
+            for (kind, label) in [
+                (gs::MaskShapeKind::Box, "⬛ Add box mask at point"),
+                (
+                    gs::MaskShapeKind::Ellipsoid,
+                    "⚫ Add ellipsoid mask at point",
+                ),
+            ] {
+                if ui
+                    .add_enabled(point.is_some(), egui::Button::new(label))
+                    .clicked()
+                {
+                    let mask = &mut gs.selected_model_mut().mask;
+
+                    let mut shape = app::GaussianSplattingMaskShape::new();
+                    shape.shape.kind = kind;
+                    shape.shape.pos = point.expect("point");
+                    mask.shapes.push(shape);
+                    mask.update_pods();
+
+                    let op = mask.op();
+                    gs.scene_tx
+                        .send(app::SceneCommand::EvaluateMask(op))
+                        .expect("send op");
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+
     /// Handle action.
     fn action(
         &mut self,
@@ -1713,26 +5382,210 @@ impl SceneInput {
                         .expect("send gs");
                 }
             }
-            None | Some(app::Action::Selection) => {}
+            Some(app::Action::RegistrationLocatePoint {
+                pair_index,
+                point_index,
+                rx,
+                ..
+            }) => {
+                if let Ok(point) = rx.try_recv() {
+                    if let Some(pair) = gs.registration.pairs.get_mut(*pair_index) {
+                        pair[*point_index] = Some(point);
+                    }
+                    gs.action = None;
+                }
+            }
+            Some(app::Action::PickGaussian { rx, .. }) => {
+                if let Ok(picked) = rx.try_recv() {
+                    gs.picked_gaussian = picked;
+                    gs.action = None;
+                }
+            }
+            None | Some(app::Action::Selection) | Some(app::Action::MoveModel { .. }) => {}
+        }
+
+        // Locked models cannot be selected or moved in the viewport
+        if gs.selected_model().locked
+            && matches!(
+                gs.action,
+                Some(app::Action::Selection) | Some(app::Action::MoveModel { .. })
+            )
+        {
+            gs.action = None;
         }
 
+        // Queries are in physical pixels
+        let pixels_per_point = ui.ctx().pixels_per_point();
+
         // Do action
         match &mut gs.action {
-            Some(app::Action::MeasurementLocateHit { tx, .. }) => {
+            Some(app::Action::MeasurementLocateHit {
+                hit_pair_index,
+                hit_index,
+                tx,
+                ..
+            }) => {
+                // Snap
+                let snap = gs.measurement.snap != ui.ctx().input(|input| input.modifiers.alt);
+                let snap_target = response.hover_pos().filter(|_| snap).and_then(|hover_pos| {
+                    gs.measurement.snap_target(
+                        &gs.camera.control,
+                        Vec2::from_array(rect.size().into()),
+                        Vec2::from_array((hover_pos - rect.min).into()),
+                        (*hit_pair_index, *hit_index),
+                    )
+                });
+
+                if let Some((_, screen_pos)) = snap_target {
+                    let painter = ui
+                        .ctx()
+                        .layer_painter(egui::LayerId::new(
+                            egui::Order::Foreground,
+                            egui::Id::new("measurement_snap_indicator"),
+                        ))
+                        .with_clip_rect(*rect);
+                    painter.circle_stroke(
+                        rect.min + egui::vec2(screen_pos.x, screen_pos.y),
+                        gs.measurement.snap_radius,
+                        egui::Stroke::new(2.0, egui::Color32::YELLOW),
+                    );
+                }
+
+                if !response.clicked_by(egui::PointerButton::Primary) {
+                    *query = Query::none();
+                    return;
+                }
+
+                let interact_pos = response.interact_pointer_pos().expect("pointer pos");
+
+                if !rect.contains(interact_pos) {
+                    *query = Query::none();
+                    return;
+                }
+
+                if let Some((hit, _)) = snap_target {
+                    if let Err(e) = tx.send(hit) {
+                        log::error!("Error sending snapped hit: {e}");
+                    }
+                    *query = Query::none();
+                    return;
+                }
+
+                let pos = ((interact_pos - rect.min) * pixels_per_point).to_pos2();
+                *query = Query::measurement_locate_hit(
+                    pos,
+                    gs.measurement
+                        .hit_pairs
+                        .get(*hit_pair_index)
+                        .map_or(gs.measurement.hit_method, |hit_pair| hit_pair.hit_method),
+                    gs.measurement.alpha_range,
+                    gs.measurement.locate_center,
+                    gs.selected_model_key.clone(),
+                    tx.clone(),
+                );
+            }
+            Some(app::Action::RegistrationLocatePoint { model_key, tx, .. }) => {
                 if !response.clicked_by(egui::PointerButton::Primary) {
                     *query = Query::none();
                     return;
                 }
 
-                let interact_pos = response.interact_pointer_pos().expect("pointer pos");
+                let interact_pos = response.interact_pointer_pos().expect("pointer pos");
+
+                if !rect.contains(interact_pos) {
+                    *query = Query::none();
+                    return;
+                }
+
+                let pos = ((interact_pos - rect.min) * pixels_per_point).to_pos2();
+                *query = Query::measurement_locate_hit(
+                    pos,
+                    gs.measurement.hit_method,
+                    gs.measurement.alpha_range,
+                    gs.measurement.locate_center,
+                    model_key.clone(),
+                    tx.clone(),
+                );
+            }
+            Some(app::Action::PickGaussian { tx, .. }) => {
+                if !response.clicked_by(egui::PointerButton::Primary) {
+                    *query = Query::none();
+                    return;
+                }
+
+                let interact_pos = response.interact_pointer_pos().expect("pointer pos");
+
+                if !rect.contains(interact_pos) {
+                    *query = Query::none();
+                    return;
+                }
+
+                let pos = ((interact_pos - rect.min) * pixels_per_point).to_pos2();
+                *query = Query::pick_gaussian(pos, gs.selected_model_key.clone(), tx.clone());
+            }
+            Some(app::Action::MoveModel { drag }) => {
+                *query = Query::none();
+
+                if response.hovered() {
+                    ui.ctx()
+                        .output_mut(|out| out.cursor_icon = egui::CursorIcon::Move);
+                }
+
+                let model = gs
+                    .models
+                    .get_mut(&gs.selected_model_key)
+                    .expect("selected model");
+
+                if response.drag_started_by(egui::PointerButton::Primary) {
+                    *drag = Some((model.transform.pos, Vec3::ZERO));
+                }
+
+                if response.drag_stopped() {
+                    *drag = None;
+                }
+
+                let Some((start, movement)) = drag else {
+                    return;
+                };
 
-                if !rect.contains(interact_pos) {
-                    *query = Query::none();
+                if !response.dragged_by(egui::PointerButton::Primary) {
                     return;
                 }
 
-                let pos = (interact_pos - rect.min).to_pos2();
-                *query = Query::measurement_locate_hit(pos, gs.measurement.hit_method, tx.clone());
+                use gs::CameraTrait;
+
+                // Same as the orbit pan, scale the pixels to world units at the depth of the model
+                let camera = gs.camera.control.view().inverse();
+                let right = camera.x_axis.truncate();
+                let up = camera.y_axis.truncate();
+                let forward = -camera.z_axis.truncate();
+                let eye = camera.w_axis.truncate();
+
+                let depth = (model.transform.apply(model.center) - eye)
+                    .dot(forward)
+                    .max(1e-3);
+                let projection = gs.camera.control.projection(rect.width() / rect.height());
+                let scale = 2.0 * depth / (projection.y_axis.y * rect.height());
+
+                let delta = response.drag_delta();
+                *movement += (right * delta.x - up * delta.y) * scale;
+
+                // Constrain to the axis with the most movement
+                let movement = match ui.ctx().input(|input| input.modifiers.shift) {
+                    true => {
+                        let abs = movement.abs();
+                        if abs.x >= abs.y && abs.x >= abs.z {
+                            Vec3::X * movement.x
+                        } else if abs.y >= abs.z {
+                            Vec3::Y * movement.y
+                        } else {
+                            Vec3::Z * movement.z
+                        }
+                    }
+                    false => *movement,
+                };
+
+                model.transform.pos = *start + movement;
             }
             Some(app::Action::Selection) => {
                 let app::Selection {
@@ -1754,7 +5607,7 @@ impl SceneInput {
                     return;
                 }
 
-                let pos = Vec2::from_array((hover_pos - rect.min).to_pos2().into());
+                let pos = Vec2::from_array(((hover_pos - rect.min) * pixels_per_point).into());
 
                 // Brush radius
                 if *method == app::SelectionMethod::Brush {
@@ -1764,6 +5617,7 @@ impl SceneInput {
 
                     *brush_radius = (*brush_radius as i32 + scroll_delta).clamp(1, 200) as u32;
                 }
+                let brush_radius = (*brush_radius as f32 * pixels_per_point).round() as u32;
 
                 // Operation
                 let (shift, ctrl) = ui
@@ -1778,6 +5632,31 @@ impl SceneInput {
                     *operation = gs::QuerySelectionOp::Set;
                 }
 
+                // Switching the immediate mode while dragging ends the drag in the old mode, so
+                // the query texture overlay of the old mode does not linger, the drag restarts in
+                // the new mode
+                if let Query::Selection {
+                    action: Some(QuerySelectionAction::Start(..)) | None,
+                    immediate: running_immediate,
+                    ..
+                } = query
+                {
+                    if *running_immediate != *immediate
+                        && ui
+                            .ctx()
+                            .input(|input| input.pointer.button_down(egui::PointerButton::Primary))
+                    {
+                        *query = Query::selection(
+                            Some(QuerySelectionAction::End),
+                            *operation,
+                            *running_immediate,
+                            brush_radius,
+                            pos,
+                        );
+                        return;
+                    }
+                }
+
                 // End
                 if ui
                     .ctx()
@@ -1787,7 +5666,7 @@ impl SceneInput {
                         Some(QuerySelectionAction::End),
                         *operation,
                         *immediate,
-                        *brush_radius,
+                        brush_radius,
                         pos,
                     );
                     return;
@@ -1798,7 +5677,21 @@ impl SceneInput {
                     .ctx()
                     .input(|input| input.pointer.button_down(egui::PointerButton::Primary))
                 {
-                    *query = Query::selection(None, *operation, *immediate, *brush_radius, pos);
+                    *query = Query::selection(None, *operation, *immediate, brush_radius, pos);
+                    return;
+                }
+
+                // A rect selection only starts once the pointer moves beyond the drag
+                // threshold, so a click does not select
+                let (could_be_click, press_origin) = ui.ctx().input(|input| {
+                    (
+                        input.pointer.could_any_button_be_click(),
+                        input.pointer.press_origin(),
+                    )
+                });
+
+                if *method == app::SelectionMethod::Rect && could_be_click {
+                    *query = Query::selection(None, *operation, *immediate, brush_radius, pos);
                     return;
                 }
 
@@ -1817,7 +5710,16 @@ impl SceneInput {
                     _ => None,
                 };
 
-                *query = Query::selection(action, *operation, *immediate, *brush_radius, pos);
+                // The rect starts where the pointer was pressed
+                let pos = match (action, press_origin) {
+                    (
+                        Some(QuerySelectionAction::Start(gs::QueryToolsetTool::Rect)),
+                        Some(press_origin),
+                    ) => Vec2::from_array(((press_origin - rect.min) * pixels_per_point).into()),
+                    _ => pos,
+                };
+
+                *query = Query::selection(action, *operation, *immediate, brush_radius, pos);
             }
             None => {
                 *query = Query::none();
@@ -1882,12 +5784,32 @@ impl SceneInput {
         gs: &mut app::GaussianSplatting,
         rect: &egui::Rect,
         response: &egui::Response,
+        settings: &app::Settings,
         #[cfg(target_arch = "wasm32")] web_result: &SceneInputWebEventResult,
     ) {
         if !response.contains_pointer() {
             return;
         }
 
+        // Click on a capture camera marker to move to its pose
+        if response.clicked_by(egui::PointerButton::Primary) {
+            if let Some(interact_pos) = response.interact_pointer_pos() {
+                let markers = gs.capture_cameras.markers(&gs.models);
+                if let Some(marker) = gs.capture_cameras.pick(
+                    &markers,
+                    &gs.camera.control,
+                    Vec2::from_array(rect.size().into()),
+                    Vec2::from_array((interact_pos - rect.min).into()),
+                ) {
+                    if let Some(Ok(metadata)) = &gs.models[&marker.key].capture {
+                        metadata.cameras[marker.index]
+                            .apply(&mut gs.camera, &gs.models[&marker.key]);
+                    }
+                    gs.capture_cameras.selected = Some((marker.key.clone(), marker.index));
+                }
+            }
+        }
+
         match gs.camera.control {
             app::CameraControl::FirstPerson(_) => {
                 self.control_by_first_person(
@@ -1899,7 +5821,7 @@ impl SceneInput {
                 );
             }
             app::CameraControl::Orbit(_) => {
-                self.control_by_orbit(ui, gs, rect, response);
+                self.control_by_orbit(ui, gs, rect, response, settings);
             }
         }
     }
@@ -1920,6 +5842,7 @@ impl SceneInput {
         );
 
         if !self.focused {
+            self.look_pending = Vec2::ZERO;
             return;
         }
 
@@ -1954,7 +5877,7 @@ impl SceneInput {
 
         movement += control.get_right().with_y(0.0).normalize_or_zero() * right;
 
-        movement = movement.normalize_or_zero() * gs.camera.speed;
+        movement = movement.normalize_or_zero() * gs.camera.speed * gs.camera.scale;
 
         let mut up = 0.0;
         if ui.ctx().input(|input| input.key_down(egui::Key::Space)) {
@@ -1964,7 +5887,7 @@ impl SceneInput {
             up -= 1.0;
         }
 
-        movement.y += up * gs.camera.speed;
+        movement.y += up * gs.camera.speed * gs.camera.scale;
 
         control.pos += movement * dt;
 
@@ -1985,23 +5908,43 @@ impl SceneInput {
         #[cfg(target_arch = "wasm32")]
         let mouse_delta = web_result.mouse_move;
 
-        let mut rotation = -mouse_delta * 0.01;
+        /// The rotation in radians per second of the keys at a sensitivity of 1.
+        const KEY_ROTATION_SPEED: f32 = std::f32::consts::TAU;
+
+        // The mouse delta of the frame is a fixed angle per pixel, while the keys turn at a rate
+        let mut key_rotation = Vec2::ZERO;
 
         if ui.ctx().input(|input| input.key_down(egui::Key::I)) {
-            rotation.y += 1.0;
+            key_rotation.y += 1.0;
         }
         if ui.ctx().input(|input| input.key_down(egui::Key::K)) {
-            rotation.y -= 1.0;
+            key_rotation.y -= 1.0;
         }
 
         if ui.ctx().input(|input| input.key_down(egui::Key::J)) {
-            rotation.x += 1.0;
+            key_rotation.x += 1.0;
         }
         if ui.ctx().input(|input| input.key_down(egui::Key::L)) {
-            rotation.x -= 1.0;
+            key_rotation.x -= 1.0;
         }
 
-        rotation *= gs.camera.sensitivity;
+        let rotation = -mouse_delta * 0.01 + key_rotation * KEY_ROTATION_SPEED * dt;
+        self.look_pending += rotation * gs.camera.sensitivity;
+
+        // Exponential smoothing of the pending rotation, which is independent of the frame rate
+        // and applies all the rotation eventually
+        let smoothing_time = gs.camera.smoothing * app::Camera::MAX_SMOOTHING_TIME;
+        let rotation = match smoothing_time > 0.0 {
+            true => self.look_pending * (1.0 - (-dt / smoothing_time).exp()),
+            false => self.look_pending,
+        };
+        self.look_pending -= rotation;
+
+        if self.look_pending.length_squared() < 1e-10 {
+            self.look_pending = Vec2::ZERO;
+        } else {
+            ui.ctx().request_repaint();
+        }
 
         control.yaw_by(rotation.x);
         control.pitch_by(rotation.y);
@@ -2014,7 +5957,10 @@ impl SceneInput {
         gs: &mut app::GaussianSplatting,
         rect: &egui::Rect,
         response: &egui::Response,
+        settings: &app::Settings,
     ) {
+        let zoom = &settings.zoom;
+        let limits = gs.camera.distance_limits;
         let control = match &mut gs.camera.control {
             app::CameraControl::Orbit(orbit) => orbit,
             _ => {
@@ -2056,6 +6002,43 @@ impl SceneInput {
             direction * diff.length()
         }
 
+        /// Move the pos and target by the screen space delta.
+        fn pan(control: &mut app::CameraOrbitControl, rect: &egui::Rect, delta: Vec2, speed: f32) {
+            let right = (control.pos - control.target).cross(Vec3::Y).normalize();
+            let up = (control.target - control.pos).cross(right).normalize();
+
+            let target_distance = (control.pos - control.target).length();
+
+            let view_height = 2.0 * target_distance * f32::tan(control.vertical_fov * 0.5);
+            let aspect_ratio = rect.width() / rect.height();
+            let view_width = view_height * aspect_ratio;
+
+            let scale_x = view_width / rect.width();
+            let scale_y = view_height / rect.height();
+
+            let world_delta = Vec2::new(delta.x * scale_x, delta.y * scale_y);
+
+            let movement = (right * world_delta.x + up * world_delta.y) * speed;
+
+            control.pos += movement;
+            control.target += movement;
+        }
+
+        // Touch, one finger drag is already reported as primary drag by egui
+        if let Some(touch) = ui.ctx().multi_touch() {
+            // Two finger drag to pan
+            let delta = Vec2::from_array(touch.translation_delta.into());
+            pan(control, rect, delta, gs.camera.speed);
+
+            // Pinch to zoom
+            let diff = control.target - control.pos;
+            let distance = limits.clamp(diff.length() / touch.zoom_delta);
+            control.pos = control.target - diff.normalize() * distance;
+
+            // The emulated pointer drag is not an orbit while multiple fingers are down
+            return;
+        }
+
         // Orbit
         if response.dragged_by(egui::PointerButton::Primary) {
             let delta = Vec2::from_array(response.drag_delta().into());
@@ -2073,41 +6056,67 @@ impl SceneInput {
         // Pan
         if response.dragged_by(egui::PointerButton::Secondary) {
             let delta = Vec2::from_array(response.drag_delta().into());
+            pan(control, rect, delta, gs.camera.speed);
+        }
 
-            let right = (control.pos - control.target).cross(Vec3::Y).normalize();
-            let up = (control.target - control.pos).cross(right).normalize();
-
-            let target_distance = (control.pos - control.target).length();
-
-            let view_height = 2.0 * target_distance * f32::tan(control.vertical_fov * 0.5);
-            let aspect_ratio = rect.width() / rect.height();
-            let view_width = view_height * aspect_ratio;
-
-            let scale_x = view_width / rect.width();
-            let scale_y = view_height / rect.height();
-
-            let world_delta = Vec2::new(delta.x * scale_x, delta.y * scale_y);
-
-            let movement = (right * world_delta.x + up * world_delta.y) * gs.camera.speed;
+        // Zoom
+        let delta = ui.ctx().input(|input| input.smooth_scroll_delta.y);
+        if delta != 0.0 {
+            let diff = control.target - control.pos;
+            let distance = zoom.zoom(diff.length(), delta, &limits);
+
+            // Zoom to cursor, fall back to the target if there is no point under the cursor
+            let anchor = response
+                .hover_pos()
+                .filter(|_| zoom.to_cursor)
+                .and_then(|hover_pos| {
+                    control.target_plane_point(
+                        Vec2::from_array((hover_pos - rect.min).into()),
+                        Vec2::from_array(rect.size().into()),
+                    )
+                });
 
-            control.pos += movement;
-            control.target += movement;
+            match anchor {
+                Some(anchor) => {
+                    let scale = distance / diff.length();
+                    control.pos = anchor + (control.pos - anchor) * scale;
+                    control.target = anchor + (control.target - anchor) * scale;
+                }
+                None => control.pos = control.target - diff.normalize() * distance,
+            }
         }
 
-        // Zoom
-        const MAX_ZOOM: f32 = 0.1;
+        // Orbit and zoom by keys toward the target, unless typing in a text field
+        if ui.ctx().wants_keyboard_input() {
+            return;
+        }
 
-        let delta = ui.ctx().input(|input| input.smooth_scroll_delta.y);
-        let diff = control.target - control.pos;
-        let diff_length = diff.length();
+        // Orbit by a step on each press of the arrow keys in the same direction as dragging, like
+        // looking around by IJKL in the first person control
+        let steps = ui.ctx().input(|input| {
+            let count = |key| input.num_presses(key) as f32;
+            vec2(
+                count(egui::Key::ArrowRight) - count(egui::Key::ArrowLeft),
+                count(egui::Key::ArrowDown) - count(egui::Key::ArrowUp),
+            )
+        });
+        if steps != Vec2::ZERO {
+            let rotation =
+                steps * settings.orbit_key_step.to_radians() * gs.camera.sensitivity * 2.0;
+            control.pos = control.target - orbit(control.pos, control.target, rotation);
+        }
 
-        let distance_scale = diff_length * 0.001;
-        let zoom_amount = delta * gs.camera.speed * distance_scale;
+        let (direction, dt) = ui.ctx().input(|input| {
+            let zoom_in = input.key_down(egui::Key::Plus) || input.key_down(egui::Key::Equals);
+            let zoom_out = input.key_down(egui::Key::Minus);
+            (zoom_in as i32 - zoom_out as i32, input.unstable_dt)
+        });
+        if direction != 0 {
+            let diff = control.target - control.pos;
+            let distance = zoom.zoom_by_key(diff.length(), direction as f32, dt, &limits);
+            control.pos = control.target - diff.normalize() * distance;
 
-        if delta > 0.0 && diff_length <= zoom_amount + MAX_ZOOM {
-            control.pos = control.target - diff.normalize() * MAX_ZOOM;
-        } else {
-            control.pos += diff.normalize() * zoom_amount;
+            ui.ctx().request_repaint();
         }
     }
 }
@@ -2128,6 +6137,48 @@ pub enum QuerySelectionAction {
     End,
 }
 
+/// The release of the GPU resources when idle, see [`Scene::idle`].
+#[derive(Debug)]
+enum SceneIdle {
+    /// The resources are in use.
+    Active,
+
+    /// The edits and selections are downloading before releasing.
+    Releasing(oneshot::Receiver<SceneRelease>),
+
+    /// The resources are released.
+    Released(SceneRelease),
+
+    /// The resources are to be rebuilt in the next frame by [`Scene::wake`].
+    Resuming(SceneRelease),
+}
+
+/// The state downloaded before releasing the GPU resources, see [`Scene::idle`].
+#[derive(Debug)]
+struct SceneRelease {
+    /// The project with the downloaded edits.
+    project: project::Project,
+
+    /// The downloaded selection of each model with selected Gaussians.
+    selections: HashMap<String, Vec<u32>>,
+}
+
+/// The task to run on the downloaded selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelectionTask {
+    /// Extract the selected Gaussians to a new model.
+    Extract,
+
+    /// Delete the selected Gaussians from the model.
+    Delete,
+
+    /// Save the selected Gaussians as a layer.
+    SaveLayer,
+
+    /// Turn the camera to the center of the selected Gaussians.
+    LookAt,
+}
+
 /// The query callback resources.
 #[derive(Debug, Clone)]
 enum Query {
@@ -2142,10 +6193,31 @@ enum Query {
         /// The query method.
         hit_method: app::MeasurementHitMethod,
 
+        /// The depth range of [`app::MeasurementHitMethod::MostAlpha`].
+        alpha_range: f32,
+
+        /// Whether to locate at the center of the hit Gaussian.
+        center: bool,
+
+        /// The key of the model to locate on.
+        model_key: String,
+
         /// The query result sender.
         tx: mpsc::Sender<Vec3>,
     },
 
+    /// The pick Gaussian query.
+    PickGaussian {
+        /// The query POD.
+        pod: gs::QueryHitPod,
+
+        /// The key of the model to pick on.
+        model_key: String,
+
+        /// The query result sender.
+        tx: mpsc::Sender<Option<app::PickedGaussian>>,
+    },
+
     /// The selection query.
     Selection {
         /// The action.
@@ -2177,11 +6249,30 @@ impl Query {
     fn measurement_locate_hit(
         coords: egui::Pos2,
         hit_method: app::MeasurementHitMethod,
+        alpha_range: f32,
+        center: bool,
+        model_key: String,
         tx: mpsc::Sender<Vec3>,
     ) -> Self {
         Self::MeasurementLocateHit {
             pod: gs::QueryHitPod::new(Vec2::from_array(coords.into())),
             hit_method,
+            alpha_range,
+            center,
+            model_key,
+            tx,
+        }
+    }
+
+    /// Create a [`Query::PickGaussian`] query.
+    fn pick_gaussian(
+        coords: egui::Pos2,
+        model_key: String,
+        tx: mpsc::Sender<Option<app::PickedGaussian>>,
+    ) -> Self {
+        Self::PickGaussian {
+            pod: gs::QueryHitPod::new(Vec2::from_array(coords.into())),
+            model_key,
             tx,
         }
     }
@@ -2212,6 +6303,21 @@ pub enum QueryResult {
 
     /// The measurement locate hit result.
     MeasurementLocateHit,
+
+    /// The hit Gaussian of the measurement locate hit, to be located at its center.
+    MeasurementLocateCenter {
+        /// The key of the model.
+        model_key: String,
+
+        /// The index of the Gaussian.
+        index: usize,
+
+        /// The query result sender.
+        tx: mpsc::Sender<Vec3>,
+    },
+
+    /// The pick Gaussian result.
+    PickGaussian,
 }
 
 /// The web event listener for [`SceneInput`].
@@ -2357,12 +6463,16 @@ enum SceneInputWebEvent {
 
 /// The buffer and bind groups for showing unedited model.
 ///
-/// Not really a model, but data for showing unedited version of the actual model.
+/// Not really a model, but data for showing unedited version of the actual model. This also
+/// holds the Gaussian transform of the model, which is used by both renderer bind groups.
 #[derive(Debug)]
 pub struct UneditedModel {
     /// The unedited Gaussian edit buffer.
     pub gaussians_edit_buffer: gs::GaussiansEditBuffer,
 
+    /// The Gaussian transform buffer of the model, for the per model display mode.
+    pub gaussian_transform_buffer: gs::GaussianTransformBuffer,
+
     /// The preprocessor bind group.
     pub preprocessor_bind_group: wgpu::BindGroup,
 
@@ -2376,6 +6486,7 @@ impl UneditedModel {
         viewer: &gs::MultiModelViewer<G>,
         render_state: &egui_wgpu::RenderState,
         gaussian_buffers: &gs::MultiModelViewerGaussianBuffers<G>,
+        gaussian_transform_buffer: gs::GaussianTransformBuffer,
     ) -> Self {
         let gaussians_edit_buffer = gs::GaussiansEditBuffer::new(
             &render_state.device,
@@ -2405,7 +6516,7 @@ impl UneditedModel {
             &render_state.device,
             &viewer.world_buffers.camera_buffer,
             &gaussian_buffers.model_transform_buffer,
-            &viewer.world_buffers.gaussian_transform_buffer,
+            &gaussian_transform_buffer,
             &gaussian_buffers.gaussians_buffer,
             &gaussian_buffers.indirect_indices_buffer,
             &viewer.world_buffers.query_buffer,
@@ -2418,6 +6529,7 @@ impl UneditedModel {
 
         Self {
             gaussians_edit_buffer,
+            gaussian_transform_buffer,
             preprocessor_bind_group,
             renderer_bind_group,
         }
@@ -2485,6 +6597,12 @@ pub struct SceneResource<G: gs::GaussianPod> {
     /// The visible measurement hit pair.
     pub measurement_visible_hit_pairs: Vec<app::MeasurementHitPair>,
 
+    /// Whether the measurements are drawn on top of the Gaussians.
+    pub measurement_always_on_top: bool,
+
+    /// The capture camera markers renderer.
+    pub capture_cameras_renderer: renderer::CaptureCameras,
+
     /// The query toolset.
     pub query_toolset: gs::QueryToolset,
 
@@ -2506,14 +6624,59 @@ pub struct SceneResource<G: gs::GaussianPod> {
     /// The mask evaluator.
     pub mask_evaluator: gs::MaskEvaluator,
 
+    /// The selection mask compute pass.
+    pub selection_mask: renderer::SelectionMask,
+
+    /// The selection depth compute pass.
+    pub selection_depth: renderer::SelectionDepth,
+
+    /// The edit restore compute pass.
+    pub edit_restore: renderer::EditRestore,
+
+    /// The selection download compute pass.
+    pub selection_download: renderer::SelectionDownload,
+
+    /// The model bounds compute pass.
+    pub model_bounds: renderer::ModelBounds,
+
+    /// The render limit compute pass.
+    pub render_limit: renderer::RenderLimit,
+
+    /// The tone mapping renderer.
+    pub tone_mapping: renderer::ToneMapping,
+
     /// The mask gizmos.
     pub mask_gizmos: HashMap<String, MaskGizmosResource>,
 
+    /// The maximum scale of the uploaded Gaussians, see
+    /// [`app::GaussianSplattingGaussianTransform::max_scale`].
+    pub max_scale: Option<f32>,
+
+    /// The renderer of the billboard projection, [`None`] for the perspective projection, see
+    /// [`app::GaussianSplattingGaussianTransform::projection`].
+    pub billboard_renderer: Option<renderer::BillboardRenderer>,
+
+    /// The color spaces of the uploaded models, see [`app::GaussianSplattingModel::color_space`].
+    pub color_spaces: HashMap<String, app::ModelColorSpace>,
+
     /// VR右眼viewer（用于VR模式的立体视觉）
     pub vr_right_eye_viewer: Option<Arc<Mutex<gs::MultiModelViewer<G>>>>,
 
     /// VR viewer数据是否已同步
     pub vr_data_synced: bool,
+
+    /// Whether the Gaussians write depth, see [`app::Performance::depth_write`].
+    pub depth_write: bool,
+
+    /// The opacity multiplier of the uploaded Gaussians, see
+    /// [`app::GaussianSplattingGaussianTransform::opacity`].
+    pub opacity: f32,
+
+    /// The GPU timer, [`None`] if timestamp queries are not supported.
+    pub gpu_timer: Option<renderer::GpuTimer>,
+
+    /// Whether the passes are timed, see [`app::Performance::gpu_timing`].
+    pub gpu_timing: bool,
 }
 
 impl<G: gs::GaussianPod> SceneResource<G> {
@@ -2525,13 +6688,7 @@ impl<G: gs::GaussianPod> SceneResource<G> {
         let viewer = Arc::new(Mutex::new(gs::MultiModelViewer::new_with(
             &render_state.device,
             render_state.target_format,
-            Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: false,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
+            Some(Self::gaussian_depth_stencil(false)),
             uvec2(1, 1),
         )));
 
@@ -2546,6 +6703,13 @@ impl<G: gs::GaussianPod> SceneResource<G> {
 
         let measurement_visible_hit_pairs = Vec::new();
 
+        log::debug!("Creating capture cameras renderer");
+        let capture_cameras_renderer = renderer::CaptureCameras::new(
+            &render_state.device,
+            render_state.target_format,
+            &locked_viewer.world_buffers.camera_buffer,
+        );
+
         log::debug!("Creating query toolset");
         let query_toolset = {
             gs::QueryToolset::new(
@@ -2592,6 +6756,31 @@ impl<G: gs::GaussianPod> SceneResource<G> {
         log::debug!("Creating mask gizmos");
         let mut mask_gizmos = HashMap::new();
 
+        log::debug!("Creating selection mask");
+        let selection_mask = renderer::SelectionMask::new(&render_state.device);
+
+        log::debug!("Creating selection depth");
+        let selection_depth = renderer::SelectionDepth::new(&render_state.device);
+
+        log::debug!("Creating edit restore");
+        let edit_restore = renderer::EditRestore::new(&render_state.device);
+
+        log::debug!("Creating selection download");
+        let selection_download = renderer::SelectionDownload::new(&render_state.device);
+
+        log::debug!("Creating model bounds");
+        let model_bounds = renderer::ModelBounds::new(&render_state.device);
+
+        log::debug!("Creating render limit");
+        let render_limit = renderer::RenderLimit::new(&render_state.device);
+
+        log::debug!("Creating tone mapping");
+        let tone_mapping =
+            renderer::ToneMapping::new(&render_state.device, render_state.target_format);
+
+        log::debug!("Creating GPU timer");
+        let gpu_timer = renderer::GpuTimer::new(&render_state.device, &render_state.queue);
+
         log::debug!("Initializing first model");
         Self::add_model_with_viewer(
             &mut locked_viewer,
@@ -2601,30 +6790,223 @@ impl<G: gs::GaussianPod> SceneResource<G> {
             &mask_evaluator,
             key.clone(),
             count,
+            gs::GaussianTransformBuffer::new(&render_state.device),
         );
 
-        std::mem::drop(locked_viewer);
+        std::mem::drop(locked_viewer);
+
+        log::info!("Scene loaded");
+
+        // VR右眼viewer将在需要时延迟创建，避免资源浪费
+        let vr_right_eye_viewer = None;
+        let vr_data_synced = false;
+
+        Self {
+            viewer,
+            measurement_renderer,
+            measurement_visible_hit_pairs,
+            measurement_always_on_top: false,
+            capture_cameras_renderer,
+            query_toolset,
+            query_texture_overlay,
+            query_cursor,
+            unedited_models,
+            show_unedited_model: false,
+            mask_evaluator,
+            mask_gizmos,
+            selection_mask,
+            selection_depth,
+            edit_restore,
+            selection_download,
+            model_bounds,
+            render_limit,
+            tone_mapping,
+            vr_right_eye_viewer,
+            vr_data_synced,
+            depth_write: false,
+            opacity: 1.0,
+            max_scale: None,
+            billboard_renderer: None,
+            color_spaces: HashMap::new(),
+            gpu_timer,
+            gpu_timing: false,
+        }
+    }
+
+    /// Get the depth stencil state of the Gaussian render pipeline.
+    ///
+    /// The Gaussians are alpha blended, so they only write depth when `depth_write` is enabled,
+    /// and the depth is at the center of each Gaussian.
+    fn gaussian_depth_stencil(depth_write: bool) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: depth_write,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+
+    /// Set whether the Gaussians write depth.
+    ///
+    /// The Gaussian render pipelines are recreated when this changes, the bind groups are kept
+    /// since the layout is the same.
+    fn set_depth_write(&mut self, render_state: &egui_wgpu::RenderState, depth_write: bool) {
+        if self.depth_write == depth_write {
+            return;
+        }
+
+        log::info!("Setting Gaussian depth write to {depth_write}");
+        self.depth_write = depth_write;
+
+        let viewers = std::iter::once(&self.viewer).chain(self.vr_right_eye_viewer.as_ref());
+        for viewer in viewers {
+            viewer.lock().expect("viewer").renderer = gs::Renderer::new_without_bind_group::<G>(
+                &render_state.device,
+                render_state.target_format,
+                Some(Self::gaussian_depth_stencil(depth_write)),
+            );
+        }
+
+        if self.billboard_renderer.is_some() {
+            self.billboard_renderer = Some(Self::billboard_renderer(render_state, depth_write));
+        }
+    }
+
+    /// Set the opacity multiplier of the Gaussians.
+    ///
+    /// The Gaussian transform of [`gs`] has no opacity, so all models are uploaded again with
+    /// their alpha scaled when this changes.
+    fn set_opacity(
+        &mut self,
+        render_state: &egui_wgpu::RenderState,
+        opacity: f32,
+        models: &HashMap<String, app::GaussianSplattingModel>,
+    ) {
+        if self.opacity == opacity {
+            return;
+        }
+
+        log::info!("Setting Gaussian opacity to {opacity}");
+        self.opacity = opacity;
+
+        for (key, model) in models.iter() {
+            self.load_model(render_state, key, 0, &model.gaussians.gaussians);
+        }
+    }
+
+    /// Set the maximum scale of the Gaussians.
+    ///
+    /// The preprocess of [`gs`] cannot clamp the covariance, so all models are uploaded again
+    /// with their scales clamped, the Gaussians of the models are unchanged.
+    fn set_max_scale(
+        &mut self,
+        render_state: &egui_wgpu::RenderState,
+        max_scale: Option<f32>,
+        models: &HashMap<String, app::GaussianSplattingModel>,
+    ) {
+        if self.max_scale == max_scale {
+            return;
+        }
+
+        log::info!("Setting Gaussian max scale to {max_scale:?}");
+        self.max_scale = max_scale;
+
+        for (key, model) in models.iter() {
+            self.load_model(render_state, key, 0, &model.gaussians.gaussians);
+        }
+    }
+
+    /// Set the projection of the Gaussians.
+    ///
+    /// The billboard renderer is created for [`app::SplatProjection::Billboard`], it draws the
+    /// models with their renderer bind groups, so nothing is uploaded again.
+    fn set_projection(
+        &mut self,
+        render_state: &egui_wgpu::RenderState,
+        projection: app::SplatProjection,
+    ) {
+        let billboard = projection == app::SplatProjection::Billboard;
+        if self.billboard_renderer.is_some() == billboard {
+            return;
+        }
+
+        log::info!("Setting Gaussian projection to {projection}");
+        self.billboard_renderer =
+            billboard.then(|| Self::billboard_renderer(render_state, self.depth_write));
+    }
+
+    /// Create the billboard renderer.
+    fn billboard_renderer(
+        render_state: &egui_wgpu::RenderState,
+        depth_write: bool,
+    ) -> renderer::BillboardRenderer {
+        renderer::BillboardRenderer::new::<G>(
+            &render_state.device,
+            render_state.target_format,
+            Some(Self::gaussian_depth_stencil(depth_write)),
+        )
+    }
+
+    /// Set the color spaces of the models.
+    ///
+    /// Only the models whose color space changed are uploaded again, with their colors corrected
+    /// to sRGB.
+    fn set_color_spaces(
+        &mut self,
+        render_state: &egui_wgpu::RenderState,
+        models: &HashMap<String, app::GaussianSplattingModel>,
+    ) {
+        for (key, model) in models.iter() {
+            let color_space = self.color_spaces.get(key).copied().unwrap_or_default();
+            if color_space == model.color_space {
+                continue;
+            }
+
+            log::info!(
+                "Setting color space of model {key} to {}",
+                model.color_space
+            );
+            self.color_spaces.insert(key.clone(), model.color_space);
+
+            self.load_model(render_state, key, 0, &model.gaussians.gaussians);
+        }
+    }
 
-        log::info!("Scene loaded");
+    /// Get the Gaussians of a model with their alpha scaled by the opacity multiplier, their
+    /// scales clamped to the maximum scale, and their colors corrected from the color space of
+    /// the model.
+    fn adjusted_gaussians<'a>(
+        &self,
+        key: &str,
+        gaussians: &'a [gs::Gaussian],
+    ) -> Cow<'a, [gs::Gaussian]> {
+        let color_space = self.color_spaces.get(key).copied().unwrap_or_default();
+        if self.opacity == 1.0
+            && self.max_scale.is_none()
+            && color_space == app::ModelColorSpace::Srgb
+        {
+            return Cow::Borrowed(gaussians);
+        }
 
-        // VR右眼viewer将在需要时延迟创建，避免资源浪费
-        let vr_right_eye_viewer = None;
-        let vr_data_synced = false;
+        let max_scale = Vec3::splat(self.max_scale.unwrap_or(f32::INFINITY));
+        Cow::Owned(
+            gaussians
+                .iter()
+                .map(|gaussian| {
+                    let mut gaussian = *gaussian;
+                    gaussian.color.w = (gaussian.color.w as f32 * self.opacity).round() as u8;
+                    gaussian.scale = gaussian.scale.min(max_scale);
+                    color_space.correct(&mut gaussian);
+                    gaussian
+                })
+                .collect(),
+        )
+    }
 
-        Self {
-            viewer,
-            measurement_renderer,
-            measurement_visible_hit_pairs,
-            query_toolset,
-            query_texture_overlay,
-            query_cursor,
-            unedited_models,
-            show_unedited_model: false,
-            mask_evaluator,
-            mask_gizmos,
-            vr_right_eye_viewer,
-            vr_data_synced,
-        }
+    /// Get the GPU timer if the passes are timed.
+    fn active_gpu_timer(&self) -> Option<&renderer::GpuTimer> {
+        self.gpu_timer.as_ref().filter(|_| self.gpu_timing)
     }
 
     /// 确保VR右眼viewer存在（延迟创建）
@@ -2636,13 +7018,7 @@ impl<G: gs::GaussianPod> SceneResource<G> {
             let vr_viewer = Arc::new(Mutex::new(gs::MultiModelViewer::new_with(
                 &render_state.device,
                 render_state.target_format,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: false,
-                    depth_compare: wgpu::CompareFunction::Less,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
+                Some(Self::gaussian_depth_stencil(self.depth_write)),
                 uvec2(1, 1),
             )));
 
@@ -2671,6 +7047,12 @@ impl<G: gs::GaussianPod> SceneResource<G> {
                     &self.mask_evaluator,
                     key.clone(),
                     gaussian_count,
+                    // 与主viewer共用高斯变换
+                    self.unedited_models
+                        .get(key)
+                        .expect("unedited model")
+                        .gaussian_transform_buffer
+                        .clone(),
                 );
 
                 log::info!(
@@ -2719,7 +7101,7 @@ impl<G: gs::GaussianPod> SceneResource<G> {
                     vr_model.gaussian_buffers.gaussians_buffer.update_range(
                         &render_state.queue,
                         0,
-                        &gs_model.gaussians.gaussians,
+                        &self.adjusted_gaussians(key, &gs_model.gaussians.gaussians),
                     );
 
                     log::info!(
@@ -2754,6 +7136,8 @@ impl<G: gs::GaussianPod> SceneResource<G> {
             gaussians.len()
         );
 
+        let gaussians = self.adjusted_gaussians(key, gaussians);
+
         // 更新主viewer
         self.viewer
             .lock()
@@ -2763,7 +7147,7 @@ impl<G: gs::GaussianPod> SceneResource<G> {
             .expect("model")
             .gaussian_buffers
             .gaussians_buffer
-            .update_range(&render_state.queue, start, gaussians);
+            .update_range(&render_state.queue, start, &gaussians);
 
         // 更新VR右眼viewer（如果存在且有该模型）
         if let Some(vr_viewer) = &self.vr_right_eye_viewer {
@@ -2777,7 +7161,7 @@ impl<G: gs::GaussianPod> SceneResource<G> {
                 vr_model.gaussian_buffers.gaussians_buffer.update_range(
                     &render_state.queue,
                     start,
-                    gaussians,
+                    &gaussians,
                 );
             } else {
                 log::warn!(
@@ -2803,6 +7187,7 @@ impl<G: gs::GaussianPod> SceneResource<G> {
             &self.mask_evaluator,
             key.clone(),
             count,
+            gs::GaussianTransformBuffer::new(&render_state.device),
         );
         drop(viewer); // 释放锁
 
@@ -2824,6 +7209,12 @@ impl<G: gs::GaussianPod> SceneResource<G> {
                 &self.mask_evaluator,
                 key.clone(),
                 count,
+                // 与主viewer共用高斯变换
+                self.unedited_models
+                    .get(&key)
+                    .expect("unedited model")
+                    .gaussian_transform_buffer
+                    .clone(),
             );
             log::info!(
                 "✅ [VR DEBUG] VR viewer now has {} models after adding '{}'",
@@ -2838,6 +7229,7 @@ impl<G: gs::GaussianPod> SceneResource<G> {
     }
 
     /// Add a new model with a viewer.
+    #[allow(clippy::too_many_arguments)]
     fn add_model_with_viewer(
         viewer: &mut gs::MultiModelViewer<G>,
         unedited_models: &mut HashMap<String, UneditedModel>,
@@ -2846,10 +7238,11 @@ impl<G: gs::GaussianPod> SceneResource<G> {
         mask_evaluator: &gs::MaskEvaluator,
         key: String,
         count: usize,
+        gaussian_transform_buffer: gs::GaussianTransformBuffer,
     ) {
         let gaussian_buffers =
             gs::MultiModelViewerGaussianBuffers::new_empty(&render_state.device, count);
-        let bind_groups = gs::MultiModelViewerBindGroups::new(
+        let mut bind_groups = gs::MultiModelViewerBindGroups::new(
             &render_state.device,
             &viewer.preprocessor,
             &viewer.radix_sorter,
@@ -2858,7 +7251,29 @@ impl<G: gs::GaussianPod> SceneResource<G> {
             &gaussian_buffers,
             &viewer.world_buffers,
         );
-        let unedited_model = UneditedModel::new(viewer, render_state, &gaussian_buffers);
+
+        // 渲染时使用模型自己的高斯变换，以便按模型覆盖显示模式
+        bind_groups.renderer = viewer.renderer.create_bind_group(
+            &render_state.device,
+            &viewer.world_buffers.camera_buffer,
+            &gaussian_buffers.model_transform_buffer,
+            &gaussian_transform_buffer,
+            &gaussian_buffers.gaussians_buffer,
+            &gaussian_buffers.indirect_indices_buffer,
+            &viewer.world_buffers.query_buffer,
+            &gaussian_buffers.query_result_count_buffer,
+            &gaussian_buffers.query_results_buffer,
+            &viewer.world_buffers.selection_highlight_buffer,
+            &gaussian_buffers.selection_buffer,
+            &gaussian_buffers.gaussians_edit_buffer,
+        );
+
+        let unedited_model = UneditedModel::new(
+            viewer,
+            render_state,
+            &gaussian_buffers,
+            gaussian_transform_buffer,
+        );
 
         mask_evaluator.evaluate(
             &render_state.device,
@@ -2912,6 +7327,8 @@ impl<G: gs::GaussianPod> SceneResource<G> {
             return;
         }
 
+        self.remove_render_limit(key);
+
         // 从主viewer移除
         self.viewer.lock().expect("viewer").remove_model(key);
 
@@ -2921,14 +7338,208 @@ impl<G: gs::GaussianPod> SceneResource<G> {
         }
     }
 
-    /// Update the measurement visible hit pair.
-    fn update_measurement_visible_hit_pairs(&mut self, hit_pairs: &[app::MeasurementHitPair]) {
+    /// Remove the render limits of a model in both viewers before its buffers are removed.
+    fn remove_render_limit(&mut self, key: &String) {
+        let viewers = std::iter::once(&self.viewer).chain(self.vr_right_eye_viewer.as_ref());
+        for viewer in viewers {
+            if let Some(model) = viewer.lock().expect("viewer").models.get(key) {
+                self.render_limit
+                    .remove(&model.gaussian_buffers.indirect_args_buffer);
+            }
+        }
+    }
+
+    /// Reallocate the buffers of a model for the new Gaussians.
+    fn reallocate_model(
+        &mut self,
+        render_state: &egui_wgpu::RenderState,
+        key: &String,
+        gaussians: &[gs::Gaussian],
+    ) {
+        self.remove_render_limit(key);
+
+        self.viewer.lock().expect("viewer").remove_model(key);
+
+        if let Some(vr_viewer) = &self.vr_right_eye_viewer {
+            vr_viewer.lock().expect("vr viewer").remove_model(key);
+        }
+
+        self.add_model(render_state, key.clone(), gaussians.len());
+        self.load_model(render_state, key, 0, gaussians);
+    }
+
+    /// Update the transform of a model that may not be the selected model.
+    fn update_model_transform(
+        &self,
+        queue: &wgpu::Queue,
+        key: &String,
+        transform: &app::GaussianSplattingModelTransform,
+    ) {
+        self.viewer.lock().expect("viewer").update_model_transform(
+            queue,
+            key,
+            transform.pos,
+            transform.quat(),
+            transform.scale,
+        );
+
+        if let Some(vr_viewer) = &self.vr_right_eye_viewer {
+            vr_viewer.lock().expect("vr viewer").update_model_transform(
+                queue,
+                key,
+                transform.pos,
+                transform.quat(),
+                transform.scale,
+            );
+        }
+    }
+
+    /// Copy the selection of a model to a mappable buffer.
+    fn prepare_selection_download(
+        &self,
+        render_state: &egui_wgpu::RenderState,
+        key: &str,
+    ) -> wgpu::Buffer {
+        let viewer = self.viewer.lock().expect("viewer");
+
+        self.selection_download.prepare(
+            &render_state.device,
+            &render_state.queue,
+            &viewer
+                .models
+                .get(key)
+                .expect("model")
+                .gaussian_buffers
+                .selection_buffer,
+        )
+    }
+
+    /// Restore the selection of a model downloaded by
+    /// [`SceneResource::prepare_selection_download`].
+    fn restore_selection(&self, render_state: &egui_wgpu::RenderState, key: &str, words: &[u32]) {
+        let viewer = self.viewer.lock().expect("viewer");
+
+        let mut encoder =
+            render_state
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Restore Selection Encoder"),
+                });
+
+        self.selection_download.upload(
+            &render_state.device,
+            &mut encoder,
+            &viewer
+                .models
+                .get(key)
+                .expect("model")
+                .gaussian_buffers
+                .selection_buffer,
+            words,
+        );
+
+        render_state.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Compute the bounds of a model to a mappable buffer.
+    fn prepare_model_bounds(
+        &self,
+        render_state: &egui_wgpu::RenderState,
+        key: &str,
+    ) -> wgpu::Buffer {
+        let viewer = self.viewer.lock().expect("viewer");
+
+        self.model_bounds.prepare(
+            &render_state.device,
+            &render_state.queue,
+            &viewer
+                .models
+                .get(key)
+                .expect("model")
+                .gaussian_buffers
+                .gaussians_buffer,
+        )
+    }
+
+    /// Select or deselect all Gaussians of a model.
+    ///
+    /// If `limit_to_mask`, only the Gaussians kept by the mask are selected.
+    fn fill_selection(
+        &self,
+        render_state: &egui_wgpu::RenderState,
+        key: &str,
+        count: usize,
+        selected: bool,
+        limit_to_mask: bool,
+    ) {
+        if count == 0 {
+            return;
+        }
+
+        let viewer = self.viewer.lock().expect("viewer");
+        let gaussian_buffers = &viewer.models.get(key).expect("model").gaussian_buffers;
+
+        let mut words = vec![if selected { u32::MAX } else { 0 }; count.div_ceil(32)];
+
+        // The bits after the last Gaussian are left unselected
+        if selected && count % 32 != 0 {
+            *words.last_mut().expect("last word") = (1 << (count % 32)) - 1;
+        }
+
+        let mut encoder =
+            render_state
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Fill Selection Encoder"),
+                });
+
+        // The selection buffer cannot be written to directly
+        self.selection_download.upload(
+            &render_state.device,
+            &mut encoder,
+            &gaussian_buffers.selection_buffer,
+            &words,
+        );
+
+        if selected && limit_to_mask {
+            self.selection_mask.intersect(
+                &render_state.device,
+                &mut encoder,
+                &gaussian_buffers.selection_buffer,
+                &gaussian_buffers.mask_buffer,
+                count as u32,
+            );
+        }
+
+        render_state.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Restore the edits of a model.
+    fn restore_edits(&self, render_state: &egui_wgpu::RenderState, key: &str, edits: &[[u32; 3]]) {
+        let viewer = self.viewer.lock().expect("viewer");
+
+        self.edit_restore.restore(
+            &render_state.device,
+            &render_state.queue,
+            &viewer
+                .models
+                .get(key)
+                .expect("model")
+                .gaussian_buffers
+                .gaussians_edit_buffer,
+            edits,
+        );
+    }
+
+    /// Update the measurement visible hit pair and options.
+    fn update_measurement(&mut self, measurement: &app::Measurement) {
+        self.measurement_always_on_top = measurement.always_on_top;
+
         self.measurement_visible_hit_pairs.clear();
         self.measurement_visible_hit_pairs.extend(
-            hit_pairs
-                .iter()
-                .filter(|hit_pair| hit_pair.visible)
-                .cloned(),
+            measurement
+                .visible_hit_pairs()
+                .map(|(_, hit_pair)| hit_pair.clone()),
         );
     }
 
@@ -2939,6 +7550,18 @@ impl<G: gs::GaussianPod> SceneResource<G> {
         op: Option<&app::GaussianSplattingMaskOp>,
         key: &str,
         model: &app::GaussianSplattingModel,
+    ) {
+        self.dispatch_mask_evaluation(render_state, op, key, model);
+        self.update_mask_gizmo(render_state, key, model);
+    }
+
+    /// Dispatch the mask evaluation given the op code without updating the gizmo.
+    fn dispatch_mask_evaluation(
+        &self,
+        render_state: &egui_wgpu::RenderState,
+        op: Option<&app::GaussianSplattingMaskOp>,
+        key: &str,
+        model: &app::GaussianSplattingModel,
     ) {
         let viewer = self.viewer.lock().expect("viewer");
         let gaussian_buffers = &viewer.models.get(key).expect("model").gaussian_buffers;
@@ -2952,24 +7575,40 @@ impl<G: gs::GaussianPod> SceneResource<G> {
             &gaussian_buffers.model_transform_buffer,
             &gaussian_buffers.gaussians_buffer,
         );
+    }
+
+    /// Update the mask gizmo to the visible shapes of the mask, highlighting the selected one.
+    fn update_mask_gizmo(
+        &mut self,
+        render_state: &egui_wgpu::RenderState,
+        key: &str,
+        model: &app::GaussianSplattingModel,
+    ) {
+        const SELECTED_COLOR: Vec4 = Vec4::new(1.0, 0.85, 0.0, 1.0);
 
+        let viewer = self.viewer.lock().expect("viewer");
         let gizmo = self.mask_gizmos.get_mut(key).expect("gizmo");
 
-        gizmo.box_gizmos = model
-            .mask
-            .shapes
-            .iter()
-            .filter(|shape| shape.shape.kind == gs::MaskShapeKind::Box && shape.visible)
-            .map(|shape| shape.shape.to_mask_gizmo_pod())
-            .collect();
+        let gizmo_pods = |kind: gs::MaskShapeKind| {
+            model
+                .mask
+                .shapes
+                .iter()
+                .enumerate()
+                .filter(|(_, shape)| shape.shape.kind == kind && shape.visible)
+                .map(|(index, shape)| match model.mask.selected == Some(index) {
+                    true => gs::MaskShape {
+                        color: SELECTED_COLOR,
+                        ..shape.shape.clone()
+                    }
+                    .to_mask_gizmo_pod(),
+                    false => shape.shape.to_mask_gizmo_pod(),
+                })
+                .collect()
+        };
 
-        gizmo.ellipsoid_gizmos = model
-            .mask
-            .shapes
-            .iter()
-            .filter(|shape| shape.shape.kind == gs::MaskShapeKind::Ellipsoid && shape.visible)
-            .map(|shape| shape.shape.to_mask_gizmo_pod())
-            .collect();
+        gizmo.box_gizmos = gizmo_pods(gs::MaskShapeKind::Box);
+        gizmo.ellipsoid_gizmos = gizmo_pods(gs::MaskShapeKind::Ellipsoid);
 
         if !gizmo.box_gizmos.is_empty() {
             gizmo.gizmo.update(
@@ -2993,41 +7632,126 @@ impl<G: gs::GaussianPod> SceneResource<G> {
     }
 }
 
+/// The offscreen target of [`SceneCallback`].
+#[derive(Debug, Clone, Copy)]
+struct SceneOffscreen {
+    /// The physical size.
+    size: UVec2,
+
+    /// The background color.
+    background: egui::Color32,
+
+    /// The filter for upscaling to the viewport.
+    filter: wgpu::FilterMode,
+
+    /// The temporal accumulation frame index, 0 when restarted.
+    accumulation_frame: Option<u32>,
+
+    /// The color channels written to the viewport.
+    write_mask: wgpu::ColorWrites,
+}
+
+/// The camera with the projection offset by a subpixel jitter.
+struct SceneJitteredCamera<'a> {
+    /// The camera.
+    camera: &'a app::CameraControl,
+
+    /// The jitter in normalized device coordinates.
+    jitter: Vec2,
+}
+
+impl<'a> SceneJitteredCamera<'a> {
+    /// Create a new jittered camera from the jitter in points.
+    fn new(camera: &'a app::CameraControl, jitter: Vec2, viewer_size: UVec2) -> Self {
+        Self {
+            camera,
+            jitter: jitter * 2.0 / viewer_size.max(UVec2::ONE).as_vec2() * vec2(1.0, -1.0),
+        }
+    }
+}
+
+impl gs::CameraTrait for SceneJitteredCamera<'_> {
+    fn view(&self) -> Mat4 {
+        self.camera.view()
+    }
+
+    fn projection(&self, aspect_ratio: f32) -> Mat4 {
+        Mat4::from_translation(self.jitter.extend(0.0)) * self.camera.projection(aspect_ratio)
+    }
+}
+
 /// The scene callback.
 struct SceneCallback<G: gs::GaussianPod + Send + Sync> {
     /// The model render keys.
     model_render_keys: Vec<String>,
 
+    /// The keys of the models with visible mask gizmos.
+    mask_gizmo_keys: Vec<String>,
+
     /// The query.
     query: Query,
 
     /// Whether this is the right eye in VR mode.
     is_vr_right_eye: bool,
 
+    /// The offscreen target if tone mapping, gamma correction, render scale or temporal
+    /// accumulation is applied.
+    offscreen: Option<SceneOffscreen>,
+
     /// The phantom data.
     phantom: PhantomData<G>,
 }
 
-impl<G: gs::GaussianPod + Send + Sync> egui_wgpu::CallbackTrait for SceneCallback<G> {
-    fn paint(
+impl<G: gs::GaussianPod + Send + Sync> SceneCallback<G> {
+    /// The index of the offscreen target.
+    fn offscreen_index(&self) -> usize {
+        self.is_vr_right_eye as usize
+    }
+
+    /// Render the depth tested overlays, i.e. the mask gizmos, the measurements that are not
+    /// always on top and the capture cameras.
+    fn render_overlays(
         &self,
-        _info: egui::PaintCallbackInfo,
         render_pass: &mut wgpu::RenderPass<'static>,
-        callback_resources: &egui_wgpu::CallbackResources,
+        resource: &SceneResource<G>,
     ) {
+        for key in self.mask_gizmo_keys.iter() {
+            let gizmo = resource.mask_gizmos.get(key).expect("gizmo");
+
+            if !gizmo.box_gizmos.is_empty() {
+                gizmo.gizmo.render_box_with_pass(render_pass);
+            }
+
+            if !gizmo.ellipsoid_gizmos.is_empty() {
+                gizmo.gizmo.render_ellipsoid_with_pass(render_pass);
+            }
+        }
+
+        if !resource.measurement_visible_hit_pairs.is_empty() && !resource.measurement_always_on_top
+        {
+            resource.measurement_renderer.render(render_pass, false);
+        }
+
+        resource.capture_cameras_renderer.render(render_pass);
+    }
+
+    /// Render the scene.
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'static>, resource: &SceneResource<G>) {
         let SceneResource::<G> {
             viewer,
             measurement_renderer,
             measurement_visible_hit_pairs,
+            measurement_always_on_top,
             query_toolset,
             query_texture_overlay,
             query_cursor,
             unedited_models,
             show_unedited_model,
-            mask_gizmos,
             vr_right_eye_viewer,
+            depth_write,
+            billboard_renderer,
             ..
-        } = callback_resources.get().expect("scene resource");
+        } = resource;
 
         // 选择使用哪个viewer进行渲染
         let active_viewer = if self.is_vr_right_eye {
@@ -3044,20 +7768,18 @@ impl<G: gs::GaussianPod + Send + Sync> egui_wgpu::CallbackTrait for SceneCallbac
             viewer
         };
 
-        for key in self.model_render_keys.iter() {
-            let gizmo = mask_gizmos.get(key).expect("gizmo");
-
-            if !gizmo.box_gizmos.is_empty() {
-                gizmo.gizmo.render_box_with_pass(render_pass);
-            }
-
-            if !gizmo.ellipsoid_gizmos.is_empty() {
-                gizmo.gizmo.render_ellipsoid_with_pass(render_pass);
-            }
+        // 高斯写入深度时，叠加层在高斯之后渲染，才能被前方的高斯遮挡；
+        // 否则叠加层先写入深度，遮挡其后方的高斯，前方的高斯混合在其上
+        if !*depth_write {
+            self.render_overlays(render_pass, resource);
         }
 
-        if !measurement_visible_hit_pairs.is_empty() {
-            measurement_renderer.render(render_pass, measurement_visible_hit_pairs.len() as u32);
+        // 只测量主viewer的渲染耗时
+        let gpu_timer = resource
+            .active_gpu_timer()
+            .filter(|_| !self.is_vr_right_eye);
+        if let Some(gpu_timer) = gpu_timer {
+            gpu_timer.write_in_pass(render_pass, app::GpuPass::Render, false);
         }
 
         {
@@ -3090,14 +7812,24 @@ impl<G: gs::GaussianPod + Send + Sync> egui_wgpu::CallbackTrait for SceneCallbac
                         self.is_vr_right_eye
                     );
 
-                    active_viewer_locked.renderer.render_with_pass(
-                        render_pass,
-                        match show_unedited_model {
-                            true => &unedited_model.renderer_bind_group,
-                            false => &model.bind_groups.renderer,
-                        },
-                        &model.gaussian_buffers.indirect_args_buffer,
-                    );
+                    let bind_group = match show_unedited_model {
+                        true => &unedited_model.renderer_bind_group,
+                        false => &model.bind_groups.renderer,
+                    };
+                    let indirect_args = &model.gaussian_buffers.indirect_args_buffer;
+
+                    match billboard_renderer {
+                        Some(billboard_renderer) => billboard_renderer.render_with_pass(
+                            render_pass,
+                            bind_group,
+                            indirect_args,
+                        ),
+                        None => active_viewer_locked.renderer.render_with_pass(
+                            render_pass,
+                            bind_group,
+                            indirect_args,
+                        ),
+                    }
                 } else if self.is_vr_right_eye {
                     // 如果是VR右眼且模型不存在，记录警告但继续渲染其他模型
                     log::error!(
@@ -3111,6 +7843,19 @@ impl<G: gs::GaussianPod + Send + Sync> egui_wgpu::CallbackTrait for SceneCallbac
             }
         }
 
+        if let Some(gpu_timer) = gpu_timer {
+            gpu_timer.write_in_pass(render_pass, app::GpuPass::Render, true);
+        }
+
+        if *depth_write {
+            self.render_overlays(render_pass, resource);
+        }
+
+        // 置顶的测量在高斯之后渲染，才不会被高斯覆盖
+        if !measurement_visible_hit_pairs.is_empty() && *measurement_always_on_top {
+            measurement_renderer.render(render_pass, true);
+        }
+
         if let Query::Selection { .. } = self.query {
             if let Some((gs::QueryToolsetUsedTool::QueryTextureTool { .. }, ..)) =
                 query_toolset.state()
@@ -3122,3 +7867,70 @@ impl<G: gs::GaussianPod + Send + Sync> egui_wgpu::CallbackTrait for SceneCallbac
         }
     }
 }
+
+impl<G: gs::GaussianPod + Send + Sync> egui_wgpu::CallbackTrait for SceneCallback<G> {
+    fn prepare(
+        &self,
+        device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+        _screen_descriptor: &egui_wgpu::ScreenDescriptor,
+        egui_encoder: &mut wgpu::CommandEncoder,
+        callback_resources: &mut egui_wgpu::CallbackResources,
+    ) -> Vec<wgpu::CommandBuffer> {
+        let Some(offscreen) = self.offscreen else {
+            return Vec::new();
+        };
+
+        // Render to the offscreen target, which is tone mapped to the viewport in paint
+        let resource = callback_resources
+            .get_mut::<SceneResource<G>>()
+            .expect("scene resource");
+        resource.tone_mapping.prepare_target(
+            device,
+            self.offscreen_index(),
+            offscreen.size,
+            offscreen.filter,
+        );
+
+        {
+            let mut render_pass = resource.tone_mapping.begin_render_pass(
+                egui_encoder,
+                self.offscreen_index(),
+                renderer::ToneMapping::clear_color(offscreen.background),
+            );
+            self.render(&mut render_pass, resource);
+        }
+
+        if let Some(frame) = offscreen.accumulation_frame {
+            resource.tone_mapping.accumulate(
+                egui_encoder,
+                self.offscreen_index(),
+                frame == 0,
+                Scene::ACCUMULATION_FRAMES,
+            );
+        }
+
+        Vec::new()
+    }
+
+    fn paint(
+        &self,
+        _info: egui::PaintCallbackInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+        callback_resources: &egui_wgpu::CallbackResources,
+    ) {
+        let resource = callback_resources
+            .get::<SceneResource<G>>()
+            .expect("scene resource");
+
+        match self.offscreen {
+            Some(offscreen) => resource.tone_mapping.render_with_pass(
+                render_pass,
+                self.offscreen_index(),
+                offscreen.accumulation_frame.is_some(),
+                offscreen.write_mask,
+            ),
+            None => self.render(render_pass, resource),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::{ops::RangeInclusive, sync::mpsc};
+
 use glam::*;
 use wgpu_3dgs_viewer as gs;
 
@@ -7,14 +9,19 @@ use super::Tab;
 
 /// The selection tab.
 #[derive(Debug)]
-pub struct Selection;
+pub struct Selection {
+    /// The key of the model to delete the selection from, waiting for confirmation.
+    delete_confirm: Option<String>,
+}
 
 impl Tab for Selection {
     fn create(_state: &mut app::State) -> Self
     where
         Self: Sized,
     {
-        Self
+        Self {
+            delete_confirm: None,
+        }
     }
 
     fn title(&mut self, _frame: &mut eframe::Frame, _state: &mut app::State) -> egui::WidgetText {
@@ -22,13 +29,24 @@ impl Tab for Selection {
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame, state: &mut app::State) {
-        let (selection, action, ui_builder) = match &mut state.gs {
-            app::Loadable::Loaded(gs) => {
-                (&mut gs.selection, &mut gs.action, egui::UiBuilder::new())
-            }
+        let (selection, action, scene, ui_builder) = match &mut state.gs {
+            app::Loadable::Loaded(gs) => (
+                &mut gs.selection,
+                &mut gs.action,
+                Some((
+                    &gs.scene_tx,
+                    &gs.selected_model_key,
+                    gs.model_loader.is_none(),
+                )),
+                match gs.models[&gs.selected_model_key].locked {
+                    true => egui::UiBuilder::new().disabled(),
+                    false => egui::UiBuilder::new(),
+                },
+            ),
             app::Loadable::Unloaded { .. } => (
                 &mut app::Selection::new(),
                 &mut None,
+                None,
                 egui::UiBuilder::new().disabled(),
             ),
         };
@@ -74,9 +92,14 @@ impl Tab for Selection {
                 });
                 ui.end_row();
 
-                ui.label("Immediate Mode")
-                    .on_hover_text("The selection is immediately applied while dragging");
-                ui.add(util::toggle(&mut selection.immediate));
+                let label = ui.label("Immediate Mode").on_hover_text(
+                    "On: the selection is applied to the Gaussians continuously while dragging\n\
+                    Off: the dragged area is drawn as an overlay and applied once on release\n\
+                    Switching while dragging applies the current drag and continues in the new \
+                    mode",
+                );
+                ui.add(util::toggle(&mut selection.immediate))
+                    .labelled_by(label.id);
                 ui.end_row();
 
                 ui.label("Highlight Color");
@@ -89,9 +112,36 @@ impl Tab for Selection {
                     ui.end_row();
                 }
 
-                ui.label("Show Unedited")
+                let label = ui
+                    .label("Limit to Mask")
+                    .on_hover_text("Only select Gaussians kept by the mask of the model");
+                ui.add(util::toggle(&mut selection.limit_to_mask))
+                    .labelled_by(label.id);
+                ui.end_row();
+
+                let label = ui.label("Limit to Depth").on_hover_text(
+                    "Only select Gaussians within the tolerance behind the nearest selected \
+                    Gaussian, to select a foreground object without the background behind it",
+                );
+                ui.horizontal(|ui| {
+                    ui.add(util::toggle(&mut selection.limit_to_depth))
+                        .labelled_by(label.id);
+                    ui.add_enabled(
+                        selection.limit_to_depth,
+                        egui::DragValue::new(&mut selection.depth_tolerance)
+                            .range(0.0..=f32::MAX)
+                            .speed(0.01)
+                            .fixed_decimals(2),
+                    )
+                    .on_hover_text("The depth tolerance in world units");
+                });
+                ui.end_row();
+
+                let label = ui
+                    .label("Show Unedited")
                     .on_hover_text("Show the model without any edits");
-                ui.add(util::toggle(&mut selection.show_unedited));
+                ui.add(util::toggle(&mut selection.show_unedited))
+                    .labelled_by(label.id);
                 if selection.show_unedited {
                     selection.edit = None;
                 }
@@ -127,6 +177,79 @@ impl Tab for Selection {
                 }
             });
 
+            if let Some((scene_tx, selected_model_key, idle)) = scene {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(idle, egui::Button::new("Select All"))
+                        .on_hover_text(
+                            "Select all Gaussians of the visible models, limited to the mask if \
+                            enabled, e.g. to edit the whole model",
+                        )
+                        .on_disabled_hover_text("Wait for the model loading to finish")
+                        .clicked()
+                    {
+                        scene_tx
+                            .send(app::SceneCommand::SelectAll)
+                            .expect("send select all");
+                    }
+
+                    if ui
+                        .button("Clear Selection")
+                        .on_hover_text("Deselect all Gaussians and stop editing")
+                        .clicked()
+                    {
+                        selection.edit = None;
+                        scene_tx
+                            .send(app::SceneCommand::ClearSelection)
+                            .expect("send clear selection");
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(idle, egui::Button::new("Extract to New Model"))
+                        .on_hover_text(
+                            "Copy the selected Gaussians of the selected model to a new model",
+                        )
+                        .on_disabled_hover_text("Wait for the model loading to finish")
+                        .clicked()
+                    {
+                        scene_tx
+                            .send(app::SceneCommand::ExtractSelection(
+                                selected_model_key.clone(),
+                            ))
+                            .expect("send extract selection");
+                    }
+
+                    if ui
+                        .add_enabled(idle, egui::Button::new("Delete Selection"))
+                        .on_hover_text("Permanently remove the selected Gaussians from the model")
+                        .on_disabled_hover_text("Wait for the model loading to finish")
+                        .clicked()
+                    {
+                        self.delete_confirm = Some(selected_model_key.clone());
+                    }
+
+                    if let Some(deleted) = &selection.deleted {
+                        if ui
+                            .add_enabled(idle, egui::Button::new("Undo Delete"))
+                            .on_hover_text(format!(
+                                "Restore the {} Gaussians deleted from {}",
+                                deleted.count, deleted.key
+                            ))
+                            .on_disabled_hover_text("Wait for the model loading to finish")
+                            .clicked()
+                        {
+                            scene_tx
+                                .send(app::SceneCommand::UndoDeleteSelection)
+                                .expect("send undo delete selection");
+                        }
+                    }
+                });
+
+                self.delete_confirm(ui, scene_tx, selection.deleted.is_some());
+            }
+
             if let Some(edit) = &mut selection.edit {
                 selection.show_unedited = false;
 
@@ -134,76 +257,253 @@ impl Tab for Selection {
                     *edit = app::SelectionEdit::default();
                 }
 
-                egui::Grid::new("selection_edit_grid").show(ui, |ui| {
-                    ui.label("Hidden");
-                    ui.checkbox(&mut edit.hidden, "");
-                    ui.end_row();
+                Self::edit_ui(ui, "selection_edit_grid", edit);
+            }
 
-                    ui.label("Color");
-                    ui.horizontal(|ui| {
-                        macro_rules! value {
-                            ($ui:expr, $value:expr, $label:ident, $display:expr, $val:expr) => {
-                                if $ui
-                                    .selectable_label(
-                                        matches!($value, app::SelectionColorEdit::$label(..)),
-                                        $display,
-                                    )
-                                    .clicked()
-                                {
-                                    $value = app::SelectionColorEdit::$label($val);
-                                }
-                            };
-                        }
+            if let Some((scene_tx, selected_model_key, idle)) = scene {
+                Self::layers(ui, selection, scene_tx, selected_model_key, idle);
+            }
+        });
+    }
+}
+
+impl Selection {
+    /// Show the saved selection layers.
+    fn layers(
+        ui: &mut egui::Ui,
+        selection: &mut app::Selection,
+        scene_tx: &mpsc::Sender<app::SceneCommand>,
+        selected_model_key: &str,
+        idle: bool,
+    ) {
+        ui.separator();
+        ui.heading("Layers");
+
+        if ui
+            .add_enabled(idle, egui::Button::new("Save Selection as Layer"))
+            .on_hover_text(
+                "Save the selected Gaussians of the selected model with the current edit, \
+                to grade them again after selecting other regions",
+            )
+            .on_disabled_hover_text("Wait for the model loading to finish")
+            .clicked()
+        {
+            scene_tx
+                .send(app::SceneCommand::SaveSelectionLayer(
+                    selected_model_key.to_string(),
+                ))
+                .expect("send save selection layer");
+        }
+
+        let mut changed = Vec::new();
+        for (i, layer) in selection.layers.iter_mut().enumerate() {
+            egui::CollapsingHeader::new(&layer.name)
+                .id_salt(("selection_layer", i))
+                .show(ui, |ui| {
+                    egui::Grid::new(("selection_layer_grid", i)).show(ui, |ui| {
+                        ui.label("Name");
+                        ui.text_edit_singleline(&mut layer.name);
+                        ui.end_row();
 
-                        value!(ui, edit.color, Hsv, "HSV", vec3(0.0, 1.0, 1.0));
-                        value!(
-                            ui,
-                            edit.color,
-                            OverrideColor,
-                            "Override Color",
-                            vec3(1.0, 1.0, 1.0)
+                        ui.label("Model");
+                        ui.label(format!("{} ({} Gaussians)", layer.key, layer.indices.len()));
+                        ui.end_row();
+
+                        let label = ui.label("Linked").on_hover_text(
+                            "Keep the exposure and contrast the same as the other linked layers",
                         );
+                        if ui
+                            .add(util::toggle(&mut layer.linked))
+                            .labelled_by(label.id)
+                            .changed()
+                            && layer.linked
+                        {
+                            changed.push(i);
+                        }
+                        ui.end_row();
                     });
-                    ui.end_row();
 
-                    match &mut edit.color {
-                        app::SelectionColorEdit::Hsv(hsv) => {
-                            ui.label("Hue");
-                            ui.add(egui::Slider::new(&mut hsv.x, 0.0..=1.0).fixed_decimals(2));
-                            ui.end_row();
+                    if Self::edit_ui(ui, ("selection_layer_edit_grid", i), &mut layer.edit) {
+                        changed.push(i);
+                    }
 
-                            ui.label("Saturation");
-                            ui.add(egui::Slider::new(&mut hsv.y, 0.0..=2.0).fixed_decimals(2));
-                            ui.end_row();
+                    if ui.button("🗑 Remove Layer").clicked() {
+                        scene_tx
+                            .send(app::SceneCommand::RemoveSelectionLayer(i))
+                            .expect("send remove selection layer");
+                    }
+                });
+        }
 
-                            ui.label("Brightness");
-                            ui.add(egui::Slider::new(&mut hsv.z, 0.0..=2.0).fixed_decimals(2));
-                            ui.end_row();
-                        }
-                        app::SelectionColorEdit::OverrideColor(rgb) => {
-                            ui.label("RGB Color");
-                            ui.color_edit_button_rgb(bytemuck::cast_mut(rgb));
-                            ui.end_row();
+        if let Some(i) = changed.first().copied() {
+            changed.extend(selection.sync_linked_layers(i));
+            scene_tx
+                .send(app::SceneCommand::ApplySelectionLayers(changed))
+                .expect("send apply selection layers");
+        }
+    }
+
+    /// Show the parameters of a selection edit.
+    ///
+    /// Returns whether the edit is changed.
+    fn edit_ui(
+        ui: &mut egui::Ui,
+        id_salt: impl std::hash::Hash,
+        edit: &mut app::SelectionEdit,
+    ) -> bool {
+        let prev = bytemuck::cast::<_, [u32; 2]>(edit.to_pod());
+
+        egui::Grid::new(id_salt).show(ui, |ui| {
+            ui.label("Hidden");
+            ui.checkbox(&mut edit.hidden, "");
+            ui.end_row();
+
+            ui.label("Color");
+            ui.horizontal(|ui| {
+                macro_rules! value {
+                    ($ui:expr, $value:expr, $label:ident, $display:expr, $val:expr) => {
+                        if $ui
+                            .selectable_label(
+                                matches!($value, app::SelectionColorEdit::$label(..)),
+                                $display,
+                            )
+                            .clicked()
+                        {
+                            $value = app::SelectionColorEdit::$label($val);
                         }
-                    }
+                    };
+                }
+
+                value!(ui, edit.color, Hsv, "HSV", vec3(0.0, 1.0, 1.0));
+                value!(
+                    ui,
+                    edit.color,
+                    OverrideColor,
+                    "Override Color",
+                    vec3(1.0, 1.0, 1.0)
+                );
+            });
+            ui.end_row();
 
-                    ui.label("Opacity");
-                    ui.add(egui::Slider::new(&mut edit.alpha, 0.0..=2.0).fixed_decimals(2));
+            match &mut edit.color {
+                app::SelectionColorEdit::Hsv(hsv) => {
+                    ui.label("Hue");
+                    Self::slider_value(ui, &mut hsv.x, 0.0..=1.0);
                     ui.end_row();
 
-                    ui.label("Contrast");
-                    ui.add(egui::Slider::new(&mut edit.contrast, -1.0..=1.0).fixed_decimals(2));
+                    ui.label("Saturation");
+                    Self::slider_value(ui, &mut hsv.y, 0.0..=2.0);
                     ui.end_row();
 
-                    ui.label("Exposure");
-                    ui.add(egui::Slider::new(&mut edit.exposure, -5.0..=5.0).fixed_decimals(2));
+                    ui.label("Brightness");
+                    Self::slider_value(ui, &mut hsv.z, 0.0..=2.0);
                     ui.end_row();
+                }
+                app::SelectionColorEdit::OverrideColor(rgb) => {
+                    ui.label("RGB Color");
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_rgb(bytemuck::cast_mut(rgb));
 
-                    ui.label("Gamma");
-                    ui.add(egui::Slider::new(&mut edit.gamma, 0.0..=5.0).fixed_decimals(2));
+                        // The color is in linear space, the hex code is in sRGB
+                        let [r, g, b, _] =
+                            egui::Color32::from(egui::Rgba::from_rgb(rgb.x, rgb.y, rgb.z))
+                                .to_array();
+                        ui.label(
+                            egui::RichText::new(format!("#{r:02x}{g:02x}{b:02x}")).monospace(),
+                        )
+                        .on_hover_text("The hex code of the color in sRGB");
+                    });
                     ui.end_row();
-                });
+
+                    for (label, value) in ["Red", "Green", "Blue"].into_iter().zip(rgb.as_mut()) {
+                        ui.label(label);
+                        Self::slider_value(ui, value, 0.0..=1.0);
+                        ui.end_row();
+                    }
+                }
+            }
+
+            ui.label("Opacity");
+            ui.add(egui::Slider::new(&mut edit.alpha, 0.0..=2.0).fixed_decimals(2));
+            ui.end_row();
+
+            ui.label("Contrast");
+            ui.add(egui::Slider::new(&mut edit.contrast, -1.0..=1.0).fixed_decimals(2));
+            ui.end_row();
+
+            ui.label("Exposure");
+            ui.add(egui::Slider::new(&mut edit.exposure, -5.0..=5.0).fixed_decimals(2));
+            ui.end_row();
+
+            ui.label("Gamma");
+            ui.add(egui::Slider::new(&mut edit.gamma, 0.0..=5.0).fixed_decimals(2));
+            ui.end_row();
+        });
+
+        bytemuck::cast::<_, [u32; 2]>(edit.to_pod()) != prev
+    }
+
+    /// Show a slider for coarse adjustment with a drag value for typing an exact value.
+    fn slider_value(ui: &mut egui::Ui, value: &mut f32, range: RangeInclusive<f32>) {
+        ui.horizontal(|ui| {
+            ui.add(egui::Slider::new(value, range.clone()).show_value(false));
+            ui.add(
+                egui::DragValue::new(value)
+                    .range(range)
+                    .speed(0.01)
+                    .fixed_decimals(2),
+            );
+        });
+    }
+
+    /// Show the modal to confirm deleting the selection.
+    fn delete_confirm(
+        &mut self,
+        ui: &mut egui::Ui,
+        scene_tx: &mpsc::Sender<app::SceneCommand>,
+        has_undo: bool,
+    ) {
+        let Some(key) = &self.delete_confirm else {
+            return;
+        };
+
+        let mut confirmed = None;
+
+        egui::Modal::new(egui::Id::new("delete_selection_modal")).show(ui.ctx(), |ui| {
+            ui.add(egui::Label::new(
+                egui::RichText::new("Delete selection").heading(),
+            ));
+            ui.separator();
+
+            ui.label(format!(
+                "The selected Gaussians will be removed from {key}."
+            ));
+            ui.label("Only the last deletion can be undone.");
+            if has_undo {
+                ui.label("The previous deletion will become permanent.");
             }
+            ui.label("");
+
+            ui.horizontal(|ui| {
+                if ui.button("Delete").clicked() {
+                    confirmed = Some(true);
+                }
+
+                if ui.button("Cancel").clicked() {
+                    confirmed = Some(false);
+                }
+            });
         });
+
+        match confirmed {
+            Some(true) => {
+                scene_tx
+                    .send(app::SceneCommand::DeleteSelection(key.clone()))
+                    .expect("send delete selection");
+                self.delete_confirm = None;
+            }
+            Some(false) => self.delete_confirm = None,
+            None => {}
+        }
     }
 }
@@ -0,0 +1,251 @@
+use strum::IntoEnumIterator;
+
+use crate::{app, util};
+
+use super::Tab;
+
+/// The settings tab.
+#[derive(Debug)]
+pub struct Settings;
+
+impl Tab for Settings {
+    fn create(_state: &mut app::State) -> Self
+    where
+        Self: Sized,
+    {
+        Self
+    }
+
+    fn title(&mut self, _frame: &mut eframe::Frame, _state: &mut app::State) -> egui::WidgetText {
+        "Settings".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame, state: &mut app::State) {
+        ui.spacing_mut().item_spacing = egui::vec2(ui.spacing().item_spacing.x, 12.0);
+
+        ui.label(egui::RichText::new("Interface").strong());
+        self.interface(ui, &mut state.settings);
+
+        ui.separator();
+
+        ui.label(egui::RichText::new("Default Compression").strong());
+        self.compressions(ui, &mut state.default_compressions, &mut state.settings);
+
+        ui.separator();
+
+        ui.label(egui::RichText::new("Key Bindings").strong());
+        self.key_bindings(ui);
+    }
+}
+
+impl Settings {
+    /// Create the interface settings.
+    fn interface(&mut self, ui: &mut egui::Ui, settings: &mut app::Settings) {
+        egui::Grid::new("settings_interface_grid").show(ui, |ui| {
+            ui.label("UI Scale");
+            ui.add(
+                egui::Slider::new(&mut settings.ui_scale, 0.5..=3.0)
+                    .fixed_decimals(2)
+                    .step_by(0.05),
+            );
+            ui.end_row();
+
+            ui.label("Theme");
+            settings.theme.radio_buttons(ui);
+            ui.end_row();
+
+            ui.label("Length Unit")
+                .on_hover_text("The unit for displaying distances, the scene unit is meter");
+            egui::ComboBox::from_id_salt("settings_length_unit")
+                .selected_text(settings.length_unit.to_string())
+                .show_ui(ui, |ui| {
+                    for unit in app::LengthUnit::iter() {
+                        ui.selectable_value(&mut settings.length_unit, unit, unit.to_string());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Axis Colors")
+                .on_hover_text("The colors of the X, Y and Z labels of the numeric fields");
+            ui.horizontal(|ui| {
+                for colors in app::AxisColors::iter() {
+                    ui.selectable_value(&mut settings.axis_colors, colors, colors.to_string());
+                }
+            });
+            ui.end_row();
+
+            ui.label("Coordinate System").on_hover_text(
+                "The up axis and handedness of imported models, which sets their initial transform, \
+                the default Y-Down rotates models by 180°, choose Y-Up to keep them as they are",
+            );
+            egui::ComboBox::from_id_salt("settings_coordinate_system")
+                .selected_text(settings.coordinate_system.to_string())
+                .show_ui(ui, |ui| {
+                    for system in app::CoordinateSystem::iter() {
+                        ui.selectable_value(
+                            &mut settings.coordinate_system,
+                            system,
+                            system.to_string(),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            let label = ui.label("Flip Handedness").on_hover_text(
+                "Mirror imported models along the X axis, including the rotations and spherical \
+                harmonics of the Gaussians, for left-handed data that renders mirrored",
+            );
+            ui.add(util::toggle(&mut settings.flip_handedness))
+                .labelled_by(label.id);
+            ui.end_row();
+
+            if !cfg!(target_arch = "wasm32") {
+                let label = ui.label("Auto-save").on_hover_text(
+                    "Periodically save the session, so it can be restored after a crash",
+                );
+                ui.add(util::toggle(&mut settings.autosave))
+                    .labelled_by(label.id);
+                ui.end_row();
+            }
+
+            ui.label("Drag Threshold").on_hover_text(
+                "How far the pointer has to move while pressed to start a drag, a press and \
+                release within this distance is a click",
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.drag_threshold, 1.0..=32.0)
+                    .fixed_decimals(0)
+                    .suffix(" pt"),
+            );
+            ui.end_row();
+
+            ui.label("Double-click Delay").on_hover_text(
+                "The longest time between the two clicks of a double click, increase this if \
+                double clicks are not recognized",
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.double_click_delay, 0.1..=1.5)
+                    .fixed_decimals(2)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            let label = ui.label("Auto-hide Interface").on_hover_text(
+                "Hide the menu bar and the other tabs when the pointer is inactive, move the \
+                pointer near the window edges to show them again",
+            );
+            ui.add(util::toggle(&mut settings.auto_hide))
+                .labelled_by(label.id);
+            ui.end_row();
+
+            ui.label("Auto-hide Delay")
+                .on_hover_text("The seconds of inactivity before hiding the interface");
+            ui.add_enabled(
+                settings.auto_hide,
+                egui::Slider::new(&mut settings.auto_hide_delay, 1.0..=30.0)
+                    .fixed_decimals(0)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            let label = ui.label("Load Sample on Start").on_hover_text(
+                "Load the sample model when the app starts, this is turned off once another \
+                model is opened",
+            );
+            ui.add(util::toggle(&mut settings.load_sample))
+                .labelled_by(label.id);
+            ui.end_row();
+        });
+    }
+
+    /// Create the default compression settings.
+    fn compressions(
+        &mut self,
+        ui: &mut egui::Ui,
+        compressions: &mut app::Compressions,
+        settings: &mut app::Settings,
+    ) {
+        egui::Grid::new("settings_compressions_grid").show(ui, |ui| {
+            ui.label("Spherical Harmonics");
+            egui::ComboBox::from_id_salt("settings_sh_compression")
+                .width(150.0)
+                .selected_text(compressions.sh.to_string())
+                .show_ui(ui, |ui| {
+                    for sh in app::ShCompression::iter() {
+                        ui.selectable_value(&mut compressions.sh, sh, sh.to_string());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Covariance 3D");
+            egui::ComboBox::from_id_salt("settings_cov3d_compression")
+                .width(150.0)
+                .selected_text(compressions.cov3d.to_string())
+                .show_ui(ui, |ui| {
+                    for cov3d in app::Cov3dCompression::iter() {
+                        ui.selectable_value(&mut compressions.cov3d, cov3d, cov3d.to_string());
+                    }
+                });
+            ui.end_row();
+
+            let label = ui
+                .label("Confirm When Loading")
+                .on_hover_text("Ask to confirm the compression settings for every loaded model");
+            ui.add(util::toggle(&mut settings.confirm_initialize))
+                .labelled_by(label.id);
+            ui.end_row();
+        });
+
+        if ui
+            .add_enabled(
+                *compressions != app::Compressions::default(),
+                egui::Button::new("Reset to App Defaults"),
+            )
+            .on_hover_text(format!(
+                "Use {} SH and {} covariance 3D",
+                app::Compressions::default().sh,
+                app::Compressions::default().cov3d
+            ))
+            .clicked()
+        {
+            *compressions = app::Compressions::default();
+        }
+
+        ui.label(
+            egui::RichText::new("Compression only applies to the next opened model")
+                .weak()
+                .italics(),
+        );
+    }
+
+    /// Create the key bindings reference.
+    fn key_bindings(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("settings_key_bindings_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for (action, binding) in [
+                    ("Orbit", "Primary Drag"),
+                    ("Look", "Middle Drag"),
+                    ("Pan", "Secondary Drag"),
+                    ("Zoom", "Scroll / + / -"),
+                    ("Move", "W / A / S / D"),
+                    ("Move Up / Down", "Space / Shift"),
+                    ("Rotate", "I / J / K / L"),
+                    ("Release Cursor", "Escape"),
+                    ("Add to Selection", "Shift"),
+                    ("Remove from Selection", "Ctrl"),
+                    ("Presentation Mode", "F11"),
+                ] {
+                    ui.label(action);
+                    ui.label(egui::RichText::new(binding).monospace());
+                    ui.end_row();
+                }
+            });
+
+        ui.label(
+            egui::RichText::new("Key bindings cannot be changed yet")
+                .weak()
+                .italics(),
+        );
+    }
+}
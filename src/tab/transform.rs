@@ -1,14 +1,38 @@
 // 引入外部依赖和模块
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+};
+
+use glam::*;
+use itertools::Itertools;
+use strum::IntoEnumIterator;
 use wgpu_3dgs_viewer as gs; // 将 wgpu_3dgs_viewer 库作为 gs 别名使用
 
-use crate::{app, util}; // 导入当前 crate 的 app 和 util 模块
+use crate::{app, registration, util}; // 导入当前 crate 的 app、registration 和 util 模块
 
 use super::Tab; // 从父模块导入 Tab trait
 
 /// 变换编辑器标签页
 /// 这个结构体负责处理模型的变换操作（位置、旋转、缩放等）
 #[derive(Debug)]
-pub struct Transform;
+pub struct Transform {
+    /// 模型对齐对话框，打开时为 Some
+    alignment: Option<Alignment>,
+
+    /// 批量变换对话框，打开时为 Some
+    batch: Option<BatchTransform>,
+
+    /// 粘贴变换的部分
+    paste_part: app::TransformPart,
+
+    /// 正在拖动的不透明度，拖动结束时才应用，避免每帧重新上传高斯
+    opacity: Option<f32>,
+}
 
 // 为 Transform 结构体实现 Tab trait
 impl Tab for Transform {
@@ -17,7 +41,12 @@ impl Tab for Transform {
     where
         Self: Sized,
     {
-        Self
+        Self {
+            alignment: None,
+            batch: None,
+            paste_part: app::TransformPart::All,
+            opacity: None,
+        }
     }
 
     // 返回标签页标题
@@ -28,38 +57,139 @@ impl Tab for Transform {
     // 定义用户界面的主要逻辑
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame, state: &mut app::State) {
         // 匹配状态中的 gs 字段，获取模型和高斯变换数据
-        let (model, gaussian, ui_builder) = match &mut state.gs {
-            // 如果数据已加载，则获取当前选中模型的变换信息和全局高斯变换
-            app::Loadable::Loaded(gs) => (
-                &mut gs
-                    .models
-                    .get_mut(&gs.selected_model_key) // 获取当前选中的模型
-                    .expect("selected model") // 确保模型存在
-                    .transform, // 获取模型的变换信息
-                &mut gs.gaussian_transform, // 获取全局高斯变换
-                egui::UiBuilder::new(),     // 创建可用的 UI 构建器
-            ),
-            // 如果数据未加载，则创建默认变换值，并禁用 UI
-            app::Loadable::Unloaded { .. } => (
-                &mut app::GaussianSplattingModelTransform::new(), // 创建默认模型变换
-                &mut app::GaussianSplattingGaussianTransform::new(), // 创建默认高斯变换
-                egui::UiBuilder::new().disabled(),                // 创建禁用的 UI 构建器
-            ),
-        };
+        let (model, locked, mut flipped, color_space, gaussian, action, ui_builder) =
+            match &mut state.gs {
+                // 如果数据已加载，则获取当前选中模型的变换信息和全局高斯变换
+                app::Loadable::Loaded(gs) => {
+                    let model = gs
+                        .models
+                        .get_mut(&gs.selected_model_key) // 获取当前选中的模型
+                        .expect("selected model"); // 确保模型存在
+
+                    (
+                        &mut model.transform,       // 获取模型的变换信息
+                        model.locked,               // 模型是否已锁定
+                        model.flipped,              // 模型是否已翻转手性
+                        &mut model.color_space,     // 模型的颜色空间
+                        &mut gs.gaussian_transform, // 获取全局高斯变换
+                        &mut gs.action,             // 获取当前操作
+                        egui::UiBuilder::new(),     // 创建可用的 UI 构建器
+                    )
+                }
+                // 如果数据未加载，则创建默认变换值，并禁用 UI
+                app::Loadable::Unloaded { .. } => (
+                    &mut app::GaussianSplattingModelTransform::new(), // 创建默认模型变换
+                    false,                                            // 未锁定
+                    false,                                            // 未翻转
+                    &mut app::ModelColorSpace::Srgb,                  // 默认 sRGB
+                    &mut app::GaussianSplattingGaussianTransform::new(), // 创建默认高斯变换
+                    &mut None,                                        // 没有操作
+                    egui::UiBuilder::new().disabled(),                // 创建禁用的 UI 构建器
+                ),
+            };
 
         // 调整 UI 元素之间的间距
         ui.spacing_mut().item_spacing = egui::vec2(ui.spacing().item_spacing.x, 12.0);
 
+        // 是否将复制的变换粘贴到所有模型
+        let mut paste_all = false;
+
+        // 是否翻转选中模型的手性
+        let mut flip = false;
+
         // 使用 UI 构建器范围创建界面
         ui.scope_builder(ui_builder, |ui| {
             ui.label(egui::RichText::new("Model").strong()); // 添加加粗的 "Model" 标签
-            self.model(ui, model); // 渲染模型变换 UI
+
+            // 锁定的模型不能编辑变换
+            if locked {
+                ui.label("🔒 The selected model is locked in the models tab");
+            }
+
+            ui.add_enabled_ui(!locked, |ui| {
+                self.model(ui, model); // 渲染模型变换 UI
+                self.move_model(ui, action); // 渲染视口拖动模式切换
+
+                // 翻转手性需要重新上传高斯，由场景处理
+                flip = ui
+                    .checkbox(&mut flipped, "Flip Handedness")
+                    .on_hover_text(
+                        "Mirror the Gaussians along the X axis, including their rotations and \
+                        spherical harmonics, for models from left-handed pipelines that render \
+                        mirrored, e.g. with mirrored text",
+                    )
+                    .changed();
+
+                // 颜色空间的校正在上传高斯时由场景处理
+                ui.horizontal(|ui| {
+                    ui.label("Color Space").on_hover_text(
+                        "The color space of the colors in the file, linear colors are \
+                            converted to sRGB, which the renderer expects, detected from the \
+                            comments of the PLY header on import",
+                    );
+                    for value in app::ModelColorSpace::iter() {
+                        ui.selectable_value(color_space, value, value.to_string());
+                    }
+                });
+            });
+            paste_all = self.clipboard(ui, model, locked, &mut state.transform_clipboard); // 渲染复制粘贴变换
 
             ui.separator(); // 添加分隔线
 
             ui.label(egui::RichText::new("Gaussian").strong()); // 添加加粗的 "Gaussian" 标签
             self.gaussian(ui, gaussian); // 渲染高斯变换 UI
+
+            ui.separator(); // 添加分隔线
+
+            // 打开模型对齐对话框
+            if ui
+                .button("🧲 Align Models...")
+                .on_hover_text("Align the selected model to another model")
+                .clicked()
+            {
+                self.alignment.get_or_insert_with(Alignment::new);
+            }
+
+            // 打开批量变换对话框
+            if ui
+                .button("🔁 Apply to All Models...")
+                .on_hover_text(
+                    "Apply a transform to every unlocked model at once, or only the selected \
+                    models if more than one is selected in the models tab",
+                )
+                .clicked()
+            {
+                self.batch.get_or_insert_with(BatchTransform::new);
+            }
         });
+
+        // 显示模型对齐和批量变换对话框
+        if let app::Loadable::Loaded(gs) = &mut state.gs {
+            if flip {
+                gs.scene_tx
+                    .send(app::SceneCommand::FlipHandedness(
+                        gs.selected_model_key.clone(),
+                    ))
+                    .expect("send flip handedness");
+            }
+
+            // 将复制的变换粘贴到所有未锁定的模型
+            if let (true, Some(clipboard)) = (paste_all, &state.transform_clipboard) {
+                for model in gs.models.values_mut().filter(|model| !model.locked) {
+                    model.transform.paste(clipboard, self.paste_part);
+                }
+
+                gs.scene_tx
+                    .send(app::SceneCommand::UpdateModelTransforms)
+                    .expect("send update model transforms");
+            }
+
+            self.alignment(ui.ctx(), gs);
+            self.batch(ui.ctx(), gs);
+        } else {
+            self.alignment = None;
+            self.batch = None;
+        }
     }
 }
 
@@ -71,19 +201,20 @@ impl Transform {
         egui::Grid::new("model_transform_grid").show(ui, |ui| {
             // 定义一个宏用于快速创建坐标轴值输入控件
             macro_rules! value {
-                ($ui:expr, $axis:expr, $value:expr) => {
+                ($ui:expr, $axis:expr, $value:expr, $default:expr) => {
                     $ui.horizontal(|ui| {
                         // 减小水平间距
                         ui.spacing_mut().item_spacing.x /= 2.0;
 
                         // 显示坐标轴标签
-                        ui.label($axis);
-                        // 添加可拖拽的数值输入框
-                        ui.add(
+                        util::axis_label(ui, $axis);
+                        // 添加可拖拽的数值输入框，右键可重置为默认值
+                        let response = ui.add(
                             egui::DragValue::new(&mut $value)
                                 .speed(0.01) // 设置拖动速度
                                 .fixed_decimals(4), // 设置固定小数位数
                         );
+                        util::reset_menu(response, &mut $value, $default);
                     });
                 };
             }
@@ -92,9 +223,9 @@ impl Transform {
             ui.label("Position");
             // 水平排列 X、Y、Z 坐标输入
             ui.horizontal(|ui| {
-                value!(ui, "X", transform.pos.x); // X 轴位置
-                value!(ui, "Y", transform.pos.y); // Y 轴位置
-                value!(ui, "Z", transform.pos.z); // Z 轴位置
+                value!(ui, "X", transform.pos.x, 0.0); // X 轴位置
+                value!(ui, "Y", transform.pos.y, 0.0); // Y 轴位置
+                value!(ui, "Z", transform.pos.z, 0.0); // Z 轴位置
             });
             ui.end_row(); // 结束当前网格行
 
@@ -102,24 +233,531 @@ impl Transform {
             ui.label("Rotation");
             // 水平排列 X、Y、Z 轴旋转值
             ui.horizontal(|ui| {
-                value!(ui, "X", transform.rot.x); // X 轴旋转
-                value!(ui, "Y", transform.rot.y); // Y 轴旋转
-                value!(ui, "Z", transform.rot.z); // Z 轴旋转
+                value!(ui, "X", transform.rot.x, 0.0); // X 轴旋转
+                value!(ui, "Y", transform.rot.y, 0.0); // Y 轴旋转
+                value!(ui, "Z", transform.rot.z, 0.0); // Z 轴旋转
             });
             ui.end_row(); // 结束当前网格行
 
+            // 移除默认的 180° 旋转，用于已正确朝向的模型
+            ui.label("");
+            if ui
+                .button("Remove Default Rotation")
+                .on_hover_text(
+                    "Undo the 180° Z rotation applied to models in the default Y-Down (3DGS) \
+                    coordinate system, choose Y-Up in the settings to import without it",
+                )
+                .clicked()
+            {
+                transform.remove_default_rotation();
+            }
+            ui.end_row(); // 结束当前网格行
+
             // 缩放行标签
             ui.label("Scale");
             // 水平排列 X、Y、Z 轴缩放值
             ui.horizontal(|ui| {
-                value!(ui, "X", transform.scale.x); // X 轴缩放
-                value!(ui, "Y", transform.scale.y); // Y 轴缩放
-                value!(ui, "Z", transform.scale.z); // Z 轴缩放
+                value!(ui, "X", transform.scale.x, 1.0); // X 轴缩放
+                value!(ui, "Y", transform.scale.y, 1.0); // Y 轴缩放
+                value!(ui, "Z", transform.scale.z, 1.0); // Z 轴缩放
             });
             ui.end_row(); // 结束当前网格行
         });
     }
 
+    /// 创建复制和粘贴模型变换的按钮
+    ///
+    /// 粘贴到选中模型时直接修改变换，返回是否需要粘贴到所有模型
+    fn clipboard(
+        &mut self,
+        ui: &mut egui::Ui,
+        transform: &mut app::GaussianSplattingModelTransform,
+        locked: bool,
+        clipboard: &mut Option<app::GaussianSplattingModelTransform>,
+    ) -> bool {
+        let mut paste_all = false;
+
+        ui.horizontal(|ui| {
+            if ui
+                .button("📋 Copy Transform")
+                .on_hover_text("Copy the transform of the selected model")
+                .clicked()
+            {
+                *clipboard = Some(transform.clone()); // 复制选中模型的变换
+            }
+
+            ui.add_enabled_ui(clipboard.is_some(), |ui| {
+                if ui
+                    .add_enabled(!locked, egui::Button::new("Paste"))
+                    .on_hover_text("Paste the copied transform onto the selected model")
+                    .clicked()
+                {
+                    if let Some(clipboard) = clipboard {
+                        transform.paste(clipboard, self.paste_part); // 粘贴到选中模型
+                    }
+                }
+
+                if ui
+                    .button("Paste to All")
+                    .on_hover_text("Paste the copied transform onto every unlocked model")
+                    .clicked()
+                {
+                    paste_all = true;
+                }
+
+                // 选择粘贴的部分
+                egui::ComboBox::from_id_salt("transform_paste_part")
+                    .selected_text(self.paste_part.to_string())
+                    .show_ui(ui, |ui| {
+                        for part in app::TransformPart::iter() {
+                            ui.selectable_value(&mut self.paste_part, part, part.to_string());
+                        }
+                    });
+            });
+        });
+
+        paste_all
+    }
+
+    /// 创建在视口中拖动模型的模式切换按钮
+    fn move_model(&mut self, ui: &mut egui::Ui, action: &mut Option<app::Action>) {
+        match action {
+            Some(app::Action::MoveModel { .. }) => {
+                if ui.button("Moving...").clicked() {
+                    *action = None; // 退出移动模式
+                }
+            }
+            _ => {
+                if ui
+                    .button("✋ Move in Viewport")
+                    .on_hover_text(
+                        "Drag in the viewport to move the model in the screen plane, \
+                        hold Shift to move along the axis with the most movement",
+                    )
+                    .clicked()
+                {
+                    *action = Some(app::Action::MoveModel { drag: None }); // 进入移动模式
+                }
+            }
+        }
+    }
+
+    /// 创建模型对齐对话框
+    fn alignment(&mut self, ctx: &egui::Context, gs: &mut app::GaussianSplatting) {
+        let Some(alignment) = &mut self.alignment else {
+            return;
+        };
+
+        // 接收对齐进度
+        if let Some(task) = &mut alignment.task {
+            for progress in task.rx.try_iter() {
+                match progress {
+                    registration::IcpProgress::Iteration { iteration, error } => {
+                        task.iteration = iteration + 1;
+                        task.error = error;
+                    }
+                    registration::IcpProgress::Done(rigid) => {
+                        if let Some(model) = gs.models.get_mut(&task.source) {
+                            model.transform.apply_rigid(&rigid);
+                        }
+                        alignment.status = Some(format!(
+                            "Aligned in {} iterations, RMS error: {:.6}",
+                            task.iteration, task.error
+                        ));
+                        task.done = true;
+                    }
+                    registration::IcpProgress::Failed(e) => {
+                        alignment.status = Some(format!("Alignment failed: {e}"));
+                        task.done = true;
+                    }
+                }
+            }
+
+            match task.done {
+                true => alignment.task = None,
+                false => ctx.request_repaint(),
+            }
+        }
+
+        let source_key = gs.selected_model_key.clone();
+        let reference_keys = gs
+            .models
+            .keys()
+            .filter(|key| **key != source_key)
+            .sorted()
+            .cloned()
+            .collect::<Vec<_>>();
+        if !reference_keys.contains(&alignment.reference) {
+            alignment.reference = reference_keys.first().cloned().unwrap_or_default();
+        }
+
+        let mut open = true;
+        egui::Window::new("Align Models")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.spacing_mut().item_spacing = egui::vec2(ui.spacing().item_spacing.x, 12.0);
+
+                ui.label(
+                    "Align the source model to the reference model by ICP (iterative closest \
+                    point) on the Gaussian centers. The models should be roughly aligned first.",
+                );
+
+                let running = alignment.task.is_some();
+                let locked = gs.selected_model().locked;
+
+                if locked {
+                    ui.label("🔒 The source model is locked in the models tab");
+                }
+
+                ui.add_enabled_ui(!running && !locked && !reference_keys.is_empty(), |ui| {
+                    egui::Grid::new("alignment_grid").show(ui, |ui| {
+                        ui.label("Source");
+                        ui.label(&gs.selected_model().file_name);
+                        ui.end_row();
+
+                        ui.label("Reference");
+                        egui::ComboBox::from_id_salt("alignment_reference")
+                            .width(150.0)
+                            .selected_text(&alignment.reference)
+                            .show_ui(ui, |ui| {
+                                for key in reference_keys.iter() {
+                                    ui.selectable_value(&mut alignment.reference, key.clone(), key);
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Max Iterations");
+                        ui.add(
+                            egui::DragValue::new(&mut alignment.settings.max_iterations)
+                                .range(1..=1000),
+                        );
+                        ui.end_row();
+
+                        ui.label("Threshold").on_hover_text(
+                            "Stop when the change in RMS error between iterations is less than this",
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut alignment.settings.threshold)
+                                .speed(0.00001)
+                                .range(0.0..=1.0)
+                                .max_decimals(6),
+                        );
+                        ui.end_row();
+
+                        ui.label("Samples")
+                            .on_hover_text("The number of Gaussians sampled from the source model");
+                        ui.add(
+                            egui::DragValue::new(&mut alignment.settings.sample_count)
+                                .speed(100)
+                                .range(100..=100_000),
+                        );
+                        ui.end_row();
+                    });
+
+                    ui.horizontal(|ui| {
+                        // 粗对齐：对齐两个模型的中心
+                        if ui
+                            .button("Match Centers")
+                            .on_hover_text(
+                                "Coarse alignment by moving the source model center to the \
+                                reference model center, the transform can also be adjusted manually",
+                            )
+                            .clicked()
+                        {
+                            let reference_center =
+                                gs.models[&alignment.reference].world_center();
+                            let source = gs.models.get_mut(&source_key).expect("source model");
+                            source.transform.pos += reference_center - source.world_center();
+                            alignment.status = None;
+                        }
+
+                        if ui.button("Run ICP").clicked() {
+                            alignment.run(gs, &source_key);
+                        }
+                    });
+                });
+
+                if reference_keys.is_empty() {
+                    ui.label(
+                        egui::RichText::new("Add another model as the reference to align")
+                            .weak()
+                            .italics(),
+                    );
+                }
+
+                if let Some(task) = &alignment.task {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Spinner::new());
+                        ui.add(
+                            egui::ProgressBar::new(
+                                task.iteration as f32 / alignment.settings.max_iterations as f32,
+                            )
+                            .desired_width(200.0)
+                            .text(format!(
+                                "Iteration {}, RMS error: {:.6}",
+                                task.iteration, task.error
+                            )),
+                        );
+
+                        if ui.button("Cancel").clicked() {
+                            task.cancel.store(true, Ordering::Relaxed);
+                        }
+                    });
+                }
+
+                if let Some(status) = &alignment.status {
+                    ui.label(status);
+                }
+
+                ui.separator();
+
+                ui.label(egui::RichText::new("Point Pairs").strong());
+                ui.label(
+                    "Alternatively, pick at least 3 corresponding points on the source and \
+                    reference models in the scene, then apply the transform that best maps them.",
+                );
+
+                ui.add_enabled_ui(!running && !locked && !reference_keys.is_empty(), |ui| {
+                    Self::point_pairs(ui, gs, &source_key, &alignment.reference);
+                });
+            });
+
+        if !open {
+            if let Some(task) = &alignment.task {
+                task.cancel.store(true, Ordering::Relaxed);
+            }
+            self.alignment = None;
+        }
+    }
+
+    /// 创建点对配准的 UI
+    fn point_pairs(
+        ui: &mut egui::Ui,
+        gs: &mut app::GaussianSplatting,
+        source_key: &str,
+        reference_key: &str,
+    ) {
+        let mut removed = None;
+
+        egui::Grid::new("point_pairs_grid").show(ui, |ui| {
+            ui.label("");
+            ui.strong("Source");
+            ui.strong("Reference");
+            ui.end_row();
+
+            for pair_index in 0..gs.registration.pairs.len() {
+                ui.label(format!("#{}", pair_index + 1));
+
+                for (point_index, model_key) in [source_key, reference_key].into_iter().enumerate()
+                {
+                    // 当前是否正在拾取这个点
+                    let picking = matches!(
+                        &gs.action,
+                        Some(app::Action::RegistrationLocatePoint {
+                            pair_index: i,
+                            point_index: j,
+                            ..
+                        }) if *i == pair_index && *j == point_index
+                    );
+
+                    let text = match (picking, gs.registration.pairs[pair_index][point_index]) {
+                        (true, _) => "Picking...".to_string(),
+                        (false, Some(point)) => {
+                            format!("{:.3}, {:.3}, {:.3}", point.x, point.y, point.z)
+                        }
+                        (false, None) => "Pick".to_string(),
+                    };
+
+                    if ui
+                        .selectable_label(picking, text)
+                        .on_hover_text("Click on the model in the scene to pick the point")
+                        .clicked()
+                    {
+                        gs.action = match picking {
+                            true => None,
+                            false => {
+                                let (tx, rx) = mpsc::channel();
+                                Some(app::Action::RegistrationLocatePoint {
+                                    pair_index,
+                                    point_index,
+                                    model_key: model_key.to_string(),
+                                    tx,
+                                    rx,
+                                })
+                            }
+                        };
+                    }
+                }
+
+                if ui.button("🗑").clicked() {
+                    removed = Some(pair_index);
+                }
+                ui.end_row();
+            }
+        });
+
+        if let Some(index) = removed {
+            gs.registration.pairs.remove(index);
+
+            // 移除点对后索引失效，取消正在进行的拾取
+            if matches!(gs.action, Some(app::Action::RegistrationLocatePoint { .. })) {
+                gs.action = None;
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("➕ Add Pair").clicked() {
+                gs.registration.pairs.push([None; 2]);
+            }
+
+            let label = ui
+                .label("Solve Scale")
+                .on_hover_text("Also solve for a uniform scale between the models");
+            ui.add(util::toggle(&mut gs.registration.with_scale))
+                .labelled_by(label.id);
+        });
+
+        let pairs = gs.registration.complete_pairs();
+        let solved = gs.registration.solve();
+
+        match &solved {
+            Some(similarity) => {
+                ui.label(format!(
+                    "RMS error: {:.6}, scale: {:.4}",
+                    similarity.rms_error(&pairs),
+                    similarity.scale,
+                ));
+            }
+            None => {
+                ui.label(
+                    egui::RichText::new(format!("{} / 3 complete pairs", pairs.len()))
+                        .weak()
+                        .italics(),
+                );
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(solved.is_some(), egui::Button::new("Apply"))
+                .clicked()
+            {
+                let similarity = solved.expect("solved");
+                let source = gs.models.get_mut(source_key).expect("source model");
+                source.transform.apply_similarity(&similarity);
+
+                // 源模型已移动，同步更新源点的位置
+                for pair in gs.registration.pairs.iter_mut() {
+                    if let Some(point) = &mut pair[0] {
+                        *point = similarity.apply(*point);
+                    }
+                }
+            }
+
+            if ui.button("Clear").clicked() {
+                gs.registration = app::Registration::new();
+                if matches!(gs.action, Some(app::Action::RegistrationLocatePoint { .. })) {
+                    gs.action = None;
+                }
+            }
+        });
+    }
+
+    /// 创建批量变换对话框
+    fn batch(&mut self, ctx: &egui::Context, gs: &mut app::GaussianSplatting) {
+        let Some(mut batch) = self.batch.take() else {
+            return;
+        };
+
+        // 撤销记录中的模型已全部移除时，不再可以撤销
+        if let Some(undo) = &batch.undo {
+            if !undo.keys().any(|key| gs.models.contains_key(key)) {
+                batch.undo = None;
+            }
+        }
+
+        let mut open = true;
+        egui::Window::new("Apply to All Models")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.spacing_mut().item_spacing = egui::vec2(ui.spacing().item_spacing.x, 12.0);
+
+                // 多选时只应用到选中的模型
+                ui.label(match gs.is_multi_selected() {
+                    true => format!(
+                        "Apply the transform to the {} selected models.",
+                        gs.batch_model_keys().len()
+                    ),
+                    false => format!(
+                        "Apply the transform to all {} loaded models.",
+                        gs.models.len()
+                    ),
+                });
+
+                // 切换模式时重置变换，两种模式下变换的含义不同
+                ui.horizontal(|ui| {
+                    let relative = batch.relative;
+                    ui.selectable_value(&mut batch.relative, true, "Relative")
+                        .on_hover_text(
+                            "Add the position and rotation, and multiply the scale of each model",
+                        );
+                    ui.selectable_value(&mut batch.relative, false, "Absolute")
+                        .on_hover_text("Set the same transform to each model");
+                    if batch.relative != relative {
+                        batch.reset();
+                    }
+                });
+
+                self.model(ui, &mut batch.transform);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        batch.apply(gs);
+                        gs.scene_tx
+                            .send(app::SceneCommand::UpdateModelTransforms)
+                            .expect("send update model transforms");
+                    }
+
+                    if ui
+                        .add_enabled(!batch.relative, egui::Button::new("Use Selected"))
+                        .on_hover_text("Copy the transform of the selected model")
+                        .clicked()
+                    {
+                        batch.transform = gs.selected_model().transform.clone();
+                    }
+
+                    if ui.button("Reset").clicked() {
+                        batch.reset();
+                    }
+                });
+
+                ui.separator();
+
+                if ui
+                    .add_enabled(batch.undo.is_some(), egui::Button::new("Undo"))
+                    .on_hover_text("Only the last apply can be undone")
+                    .clicked()
+                {
+                    for (key, transform) in batch.undo.take().into_iter().flatten() {
+                        if let Some(model) = gs.models.get_mut(&key) {
+                            model.transform = transform;
+                        }
+                    }
+                    gs.scene_tx
+                        .send(app::SceneCommand::UpdateModelTransforms)
+                        .expect("send update model transforms");
+                }
+            });
+
+        if open {
+            self.batch = Some(batch);
+        }
+    }
+
     /// 创建高斯变换的 UI
     fn gaussian(
         &mut self,
@@ -132,10 +770,36 @@ impl Transform {
             ui.spacing_mut().slider_width = 100.0;
 
             // 大小控制
-            ui.label("Size");
+            ui.label("Size").on_hover_text(
+                "The size multiplier of the Gaussians, except in the Point display mode",
+            );
             ui.add(egui::Slider::new(&mut transform.size, 0.0..=2.0).fixed_decimals(2)); // 添加大小滑块，范围 0.0 到 2.0
             ui.end_row();
 
+            // 点大小控制，仅用于点显示模式
+            ui.label("Point Size").on_hover_text(
+                "The size of the points in pixels at the model center in the Point display mode",
+            );
+            ui.add(
+                egui::Slider::new(&mut transform.point_size, 1.0..=32.0)
+                    .suffix(" px")
+                    .fixed_decimals(1),
+            ); // 添加点大小滑块，范围 1 到 32 像素
+            ui.end_row();
+
+            // 不透明度控制，拖动时不应用
+            ui.label("Opacity")
+                .on_hover_text("The opacity multiplier of the Gaussians");
+            let mut opacity = self.opacity.unwrap_or(transform.opacity);
+            let response = ui.add(egui::Slider::new(&mut opacity, 0.0..=1.0).fixed_decimals(2)); // 添加不透明度滑块，范围 0.0 到 1.0
+            if response.dragged() {
+                self.opacity = Some(opacity);
+            } else {
+                self.opacity = None;
+                transform.opacity = opacity;
+            }
+            ui.end_row();
+
             // 显示模式选择
             ui.label("Display Mode");
             ui.horizontal(|ui| {
@@ -162,6 +826,23 @@ impl Transform {
             });
             ui.end_row();
 
+            // 投影方式选择
+            ui.label("Projection").on_hover_text(
+                "Perspective projects the ellipsoid of each Gaussian, Billboard draws each \
+                Gaussian as a round splat facing the camera with the volume of its ellipsoid, \
+                which ignores the shape of the Gaussians but is cheaper to project",
+            );
+            ui.horizontal(|ui| {
+                for projection in app::SplatProjection::iter() {
+                    ui.selectable_value(
+                        &mut transform.projection,
+                        projection,
+                        projection.to_string(),
+                    );
+                }
+            });
+            ui.end_row();
+
             // 球谐函数度数控制
             ui.label("SH Degree") // 球谐(Spherical Harmonics)度数标签
                 .on_hover_text("Degree of spherical harmonics"); // 鼠标悬停提示文本
@@ -171,10 +852,169 @@ impl Transform {
             ui.end_row();
 
             // 不使用 SH0 控制
-            ui.label("No SH0") // "不使用0次球谐函数"标签
+            let label = ui
+                .label("No SH0") // "不使用0次球谐函数"标签
                 .on_hover_text("Exclude the 0th degree of spherical harmonics"); // 鼠标悬停提示
-            ui.add(util::toggle(&mut transform.no_sh0)); // 添加开关控件控制是否排除0次球谐
+            ui.add(util::toggle(&mut transform.no_sh0))
+                .labelled_by(label.id); // 添加开关控件控制是否排除0次球谐
             ui.end_row();
         });
     }
 }
+
+/// 模型对齐对话框的状态
+#[derive(Debug)]
+struct Alignment {
+    /// 参考模型的键
+    reference: String,
+
+    /// ICP 设置
+    settings: registration::IcpSettings,
+
+    /// 正在运行的对齐任务
+    task: Option<AlignmentTask>,
+
+    /// 上一次对齐的结果
+    status: Option<String>,
+}
+
+impl Alignment {
+    /// 创建新的模型对齐对话框状态
+    fn new() -> Self {
+        Self {
+            reference: String::new(),
+            settings: registration::IcpSettings::new(),
+            task: None,
+            status: None,
+        }
+    }
+
+    /// 在后台任务中运行 ICP
+    fn run(&mut self, gs: &app::GaussianSplatting, source_key: &str) {
+        /// 参考模型采样数量相对于源模型采样数量的倍数
+        const REFERENCE_SAMPLE_FACTOR: usize = 10;
+
+        let sample = |model: &app::GaussianSplattingModel, count: usize| {
+            let gaussians = &model.gaussians.gaussians;
+            gaussians
+                .iter()
+                .step_by(gaussians.len().div_ceil(count.max(1)).max(1))
+                .map(|gaussian| model.transform.apply(gaussian.pos))
+                .collect::<Vec<_>>()
+        };
+
+        let source = sample(&gs.models[source_key], self.settings.sample_count);
+        let reference = sample(
+            &gs.models[&self.reference],
+            self.settings.sample_count * REFERENCE_SAMPLE_FACTOR,
+        );
+
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let settings = self.settings.clone();
+
+        self.status = None;
+        self.task = Some(AlignmentTask {
+            source: source_key.to_string(),
+            rx,
+            cancel: cancel.clone(),
+            iteration: 0,
+            error: 0.0,
+            done: false,
+        });
+
+        util::exec_task(async move {
+            registration::icp(&source, reference, &settings, cancel, tx).await;
+        });
+    }
+}
+
+/// 批量变换对话框的状态
+#[derive(Debug)]
+struct BatchTransform {
+    /// 是否为相对变换
+    relative: bool,
+
+    /// 要应用的变换，相对模式下为增量
+    transform: app::GaussianSplattingModelTransform,
+
+    /// 上一次应用前各模型的变换，用于撤销
+    undo: Option<HashMap<String, app::GaussianSplattingModelTransform>>,
+}
+
+impl BatchTransform {
+    /// 相对模式下不改变模型的变换
+    const IDENTITY: app::GaussianSplattingModelTransform = app::GaussianSplattingModelTransform {
+        pos: Vec3::ZERO,
+        rot: Vec3::ZERO,
+        scale: Vec3::ONE,
+    };
+
+    /// 创建新的批量变换对话框状态
+    fn new() -> Self {
+        Self {
+            relative: true,
+            transform: Self::IDENTITY,
+            undo: None,
+        }
+    }
+
+    /// 重置变换
+    fn reset(&mut self) {
+        self.transform = match self.relative {
+            true => Self::IDENTITY,
+            false => app::GaussianSplattingModelTransform::new(),
+        };
+    }
+
+    /// 将变换应用到批量操作的模型，并记录原变换用于撤销
+    fn apply(&mut self, gs: &mut app::GaussianSplatting) {
+        let keys = gs.batch_model_keys();
+
+        self.undo = Some(
+            gs.models
+                .iter()
+                .filter(|(key, _)| keys.contains(*key))
+                .map(|(key, model)| (key.clone(), model.transform.clone()))
+                .collect(),
+        );
+
+        for model in gs
+            .models
+            .iter_mut()
+            .filter(|(key, model)| keys.contains(*key) && !model.locked)
+            .map(|(_, model)| model)
+        {
+            match self.relative {
+                true => {
+                    model.transform.pos += self.transform.pos;
+                    model.transform.rot += self.transform.rot;
+                    model.transform.scale *= self.transform.scale;
+                }
+                false => model.transform = self.transform.clone(),
+            }
+        }
+    }
+}
+
+/// 正在运行的对齐任务
+#[derive(Debug)]
+struct AlignmentTask {
+    /// 源模型的键
+    source: String,
+
+    /// 对齐进度的接收端
+    rx: mpsc::Receiver<registration::IcpProgress>,
+
+    /// 取消标志
+    cancel: Arc<AtomicBool>,
+
+    /// 已完成的迭代次数
+    iteration: usize,
+
+    /// 当前的 RMS 误差
+    error: f32,
+
+    /// 是否已结束
+    done: bool,
+}
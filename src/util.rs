@@ -1,4 +1,10 @@
-use std::future::Future;
+use std::{
+    future::Future,
+    io::{BufRead, BufReader, Cursor, Read, Seek},
+    path::PathBuf,
+};
+
+use crate::app;
 
 #[cfg(not(target_arch = "wasm32"))]
 /// Execute a task on a background thread.
@@ -27,9 +33,122 @@ pub fn exec_blocking_task(f: impl Future<Output = ()> + 'static) {
     wasm_bindgen_futures::spawn_local(f);
 }
 
+/// Get the path of a file handle.
+///
+/// This is only available on native, [`None`] is returned on the web.
+pub fn file_handle_path(file: &rfd::FileHandle) -> Option<PathBuf> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return Some(file.path().to_path_buf());
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = file;
+        None
+    }
+}
+
+/// Reveal a file in the file manager of the platform.
+///
+/// The file is selected where the file manager supports it, otherwise its folder is opened.
+/// This is only available on native.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn reveal_in_file_manager(path: &std::path::Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("{} no longer exists", path.display()));
+    }
+
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("explorer");
+        command.arg("/select,").arg(path);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = std::process::Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    } else {
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(path.parent().unwrap_or(path));
+        command
+    };
+
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Error opening the file manager: {e}"))
+}
+
+/// A reader that can also seek, e.g. for reading zip archives.
+pub trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// Open a dropped file for reading.
+///
+/// The bytes are used on the web, and the file is opened by path on native.
+pub fn dropped_file_reader(file: &egui::DroppedFile) -> std::io::Result<Box<dyn ReadSeek>> {
+    match (&file.bytes, &file.path) {
+        (Some(bytes), _) => Ok(Box::new(Cursor::new(bytes.clone()))),
+        (None, Some(path)) => Ok(Box::new(std::io::BufReader::new(std::fs::File::open(
+            path,
+        )?))),
+        (None, None) => Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Dropped file has no bytes or path",
+        )),
+    }
+}
+
+/// The file extensions of the PLY models for the file dialogs, `gz` is for gzipped PLY.
+pub const PLY_EXTENSIONS: [&str; 2] = ["ply", "gz"];
+
+/// Whether the file name is of a PLY model, including the gzipped `.ply.gz`.
+pub fn is_ply(file_name: &str) -> bool {
+    let file_name = file_name.to_lowercase();
+    file_name.ends_with(".ply") || file_name.ends_with(".ply.gz")
+}
+
+/// Get the file name of an uncompressed PLY model, e.g. for exporting.
+///
+/// The `.gz` extension is removed and `.ply` is appended if missing.
+pub fn ply_file_name(file_name: &str) -> String {
+    let file_name = match file_name.to_lowercase().ends_with(".ply.gz") {
+        true => &file_name[..file_name.len() - ".gz".len()],
+        false => file_name,
+    };
+
+    match is_ply(file_name) {
+        true => file_name.to_string(),
+        false => format!("{file_name}.ply"),
+    }
+}
+
+/// Wrap the reader in a gzip decoder if it starts with the gzip magic bytes.
+///
+/// The magic bytes are only peeked, so the reader is returned as is otherwise.
+pub fn gunzip_reader(
+    mut reader: impl BufRead + Send + 'static,
+) -> std::io::Result<Box<dyn BufRead + Send>> {
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+    match reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        true => Ok(Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(reader),
+        ))),
+        false => Ok(Box::new(reader)),
+    }
+}
+
+/// Whether the file name is of a zip archive.
+pub fn is_zip(file_name: &str) -> bool {
+    file_name.to_lowercase().ends_with(".zip")
+}
+
 /// A wrapper that allows the more idiomatic usage pattern: `ui.add(toggle(&mut my_bool))`
 /// iOS-style toggle switch.
 ///
+/// The toggle can be focused with Tab and switched with Space or Enter. Use
+/// [`egui::Response::labelled_by`] to name it for screen readers.
+///
 /// ## Example:
 /// ``` ignore
 /// ui.add(toggle(&mut my_bool));
@@ -62,12 +181,62 @@ pub fn toggle(on: &mut bool) -> impl egui::Widget + '_ {
             let center = egui::pos2(circle_x, rect.center().y);
             ui.painter()
                 .circle(center, 0.75 * radius, visuals.bg_fill, visuals.fg_stroke);
+
+            if response.has_focus() {
+                ui.painter().rect_stroke(
+                    rect.expand(2.0),
+                    radius + 2.0,
+                    ui.visuals().selection.stroke,
+                    egui::StrokeKind::Outside,
+                );
+            }
         }
 
         response
     }
 }
 
+/// Add a context menu to a numeric field to reset it to `default`.
+///
+/// The response is marked as changed when the value is reset.
+///
+/// ## Example:
+/// ``` ignore
+/// let response = ui.add(egui::DragValue::new(&mut value));
+/// reset_menu(response, &mut value, 0.0);
+/// ```
+pub fn reset_menu(mut response: egui::Response, value: &mut f32, default: f32) -> egui::Response {
+    let mut reset = false;
+    response.context_menu(|ui| {
+        if ui.button(format!("Reset to {default}")).clicked() {
+            reset = true;
+            ui.close_menu();
+        }
+    });
+
+    if reset {
+        *value = default;
+        response.mark_changed();
+    }
+
+    response
+}
+
+/// Create the label of an axis, colored by [`app::AxisColors`] in the settings.
+///
+/// `axis` is "X", "Y" or "Z", other labels are not colored.
+pub fn axis_label(ui: &mut egui::Ui, axis: &str) -> egui::Response {
+    let color = ["X", "Y", "Z"]
+        .iter()
+        .position(|a| *a == axis)
+        .and_then(|i| app::AxisColors::get(ui.ctx()).color(i, ui.visuals().dark_mode));
+
+    match color {
+        Some(color) => ui.label(egui::RichText::new(axis).color(color).strong()),
+        None => ui.label(axis),
+    }
+}
+
 /// Convert a `usize` to a human-readable string representing storage size.
 pub fn human_readable_size(size: usize) -> String {
     const KB: f64 = 1024.0;
@@ -92,3 +261,11 @@ pub fn human_readable_size(size: usize) -> String {
 
     format!("{:.2} {}", size, unit)
 }
+
+/// Describe a texture format and whether it is sRGB or linear.
+pub fn texture_format_text(format: eframe::wgpu::TextureFormat) -> String {
+    format!(
+        "{format:?} ({})",
+        if format.is_srgb() { "sRGB" } else { "Linear" }
+    )
+}
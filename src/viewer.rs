@@ -0,0 +1,261 @@
+use itertools::Itertools;
+use wgpu_3dgs_viewer as gs;
+
+use crate::{
+    app, project,
+    tab::{Tab, scene::Scene},
+};
+
+/// The viewer to embed in another eframe app.
+///
+/// This is the scene of [`crate::App`] without the dock, the menu bar and the other tabs, which
+/// the app shows in its scene tab. It owns the state of the viewer and renders it with the same
+/// scene. The eframe app must use the wgpu renderer with a 32-bit depth buffer, and the device
+/// should be created with the limits of the adapter for large models.
+#[derive(Debug)]
+pub struct ViewerWidget {
+    /// The scene.
+    scene: Scene,
+
+    /// The state of the viewer.
+    pub(crate) state: app::State,
+
+    /// The auto-saved project found by [`ViewerWidget::load_recovery`], waiting for the user to
+    /// restore or discard.
+    recovery: Option<project::Project>,
+
+    /// The cumulative pass number of the last [`ViewerWidget::update`].
+    updated_pass: Option<u64>,
+}
+
+impl ViewerWidget {
+    /// Create a new viewer with the default settings and no model.
+    pub fn new() -> Self {
+        Self::with_state(app::State::default())
+    }
+
+    /// Create a new viewer with the state.
+    fn with_state(mut state: app::State) -> Self {
+        let scene = Scene::create(&mut state);
+
+        Self {
+            scene,
+            state,
+            recovery: None,
+            updated_pass: None,
+        }
+    }
+
+    /// Whether a model is loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.state.gs.is_loaded()
+    }
+
+    /// Open a PLY model from its bytes, replacing the current session.
+    ///
+    /// The model is parsed right away, the Gaussians are streamed into the scene after.
+    pub fn open(&mut self, file_name: String, data: Vec<u8>) {
+        self.state.open(file_name, data);
+    }
+
+    /// Load the sample model, replacing the current session.
+    pub fn load_sample(&mut self) {
+        self.state.load_sample();
+    }
+
+    /// Close the models.
+    ///
+    /// The auto-saved session is removed, so a later crash does not offer to restore the closed
+    /// models.
+    pub fn close(&mut self) {
+        self.state.gs = app::Loadable::unloaded();
+        project::Project::clear_recovery();
+    }
+
+    /// Look for the auto-saved session of a previous crash.
+    ///
+    /// If there is one, the next update asks the user to restore or discard it.
+    pub fn load_recovery(&mut self) {
+        self.recovery = project::Project::load_recovery();
+    }
+
+    /// Whether an auto-saved session is waiting for the user to restore or discard.
+    pub fn has_recovery(&self) -> bool {
+        self.recovery.is_some()
+    }
+
+    /// Get the title of the scene.
+    pub(crate) fn title(&mut self, frame: &mut eframe::Frame) -> egui::WidgetText {
+        self.scene.title(frame, &mut self.state)
+    }
+
+    /// Update the viewer apart from the scene.
+    ///
+    /// This applies the settings, handles the shortcuts, resumes the released GPU resources,
+    /// shows the modals and schedules the repaints of the render mode. It runs once per pass,
+    /// [`ViewerWidget::show`] calls it, so it only has to be called when the viewer is not shown
+    /// in every frame.
+    pub fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let pass = ctx.cumulative_pass_nr();
+        if self.updated_pass == Some(pass) {
+            return;
+        }
+        self.updated_pass = Some(pass);
+
+        self.state.settings.apply(ctx);
+        self.state.shortcuts(ctx);
+
+        // The export needs the GPU resources, which may be released while the scene is hidden
+        self.scene.wake(ctx, frame, &mut self.state);
+
+        self.recovery(ctx);
+        self.export_modal(ctx, frame);
+
+        match self.state.performance.render_mode {
+            app::RenderMode::Always => match self.state.performance.max_fps {
+                // The browser schedules the frames, so skip frames down to the target rate
+                #[cfg(target_arch = "wasm32")]
+                Some(max_fps) => ctx.request_repaint_after_secs(1.0 / max_fps.max(1) as f32),
+                _ => ctx.request_repaint(),
+            },
+            app::RenderMode::OnInteraction => {}
+            app::RenderMode::PowerSaver => {
+                ctx.request_repaint_after_secs(1.0 / app::RenderMode::POWER_SAVER_FPS as f32)
+            }
+        }
+    }
+
+    /// Show the viewer in the available space of `ui`.
+    ///
+    /// Without a model, this shows the prompt to open a file like the scene tab.
+    pub fn show(&mut self, ui: &mut egui::Ui, frame: &mut eframe::Frame) {
+        self.update(ui.ctx(), frame);
+        self.scene.ui(ui, frame, &mut self.state);
+    }
+
+    /// Show the modal for restoring the auto-saved session.
+    ///
+    /// The referenced models are only reloaded after the user chooses to restore.
+    fn recovery(&mut self, ctx: &egui::Context) {
+        let Some(recovery) = &self.recovery else {
+            return;
+        };
+
+        let mut restore = None;
+
+        egui::Modal::new(egui::Id::new("recovery_modal")).show(ctx, |ui| {
+            ui.add(egui::Label::new(
+                egui::RichText::new("Restore session").heading(),
+            ));
+            ui.separator();
+
+            ui.label("The app was not closed properly, an auto-saved session is found.");
+            ui.label(format!(
+                "Models: {}",
+                recovery
+                    .models
+                    .iter()
+                    .map(|model| model.file_name.as_str())
+                    .join(", ")
+            ));
+            ui.label("");
+
+            ui.horizontal(|ui| {
+                if ui.button("Restore").clicked() {
+                    restore = Some(true);
+                }
+
+                if ui.button("Discard").clicked() {
+                    restore = Some(false);
+                }
+            });
+        });
+
+        match restore {
+            Some(true) => {
+                let recovery = self.recovery.take().expect("recovery");
+
+                self.state.gs = app::Loadable::unloaded();
+                let app::Loadable::Unloaded(unloaded) = &mut self.state.gs else {
+                    unreachable!()
+                };
+
+                unloaded
+                    .tx
+                    .send(recovery.open(self.state.compressions.clone()))
+                    .expect("send gs");
+            }
+            Some(false) => {
+                self.recovery = None;
+                project::Project::clear_recovery();
+            }
+            None => {}
+        }
+    }
+
+    /// Show the modal for exporting the models.
+    fn export_modal(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let app::Loadable::Loaded(gs) = &mut self.state.gs else {
+            return;
+        };
+        let Some(export_modal) = &mut gs.export_modal else {
+            return;
+        };
+
+        macro_rules! case {
+            ($sh:ident, $cov3d:ident) => {
+                app::Compressions {
+                    sh: app::ShCompression::$sh,
+                    cov3d: app::Cov3dCompression::$cov3d,
+                }
+            };
+        }
+
+        macro_rules! ui {
+            ($sh:ident, $cov3d:ident) => {
+                paste::paste! {
+                    if !export_modal.ui::<
+                        gs::[<GaussianPodWithSh $sh Cov3d $cov3d Configs>]
+                    >(
+                        ctx,
+                        frame,
+                        &gs.models,
+                        gs.model_loader.as_ref().map(|(key, _)| key),
+                    ) {
+                        gs.unsaved &= !export_modal.exported;
+                        gs.export_modal = None;
+                    }
+                }
+            };
+        }
+
+        match &gs.compressions {
+            case!(Single, Single) => ui!(Single, Single),
+            case!(Single, Half) => ui!(Single, Half),
+            case!(Half, Single) => ui!(Half, Single),
+            case!(Half, Half) => ui!(Half, Half),
+            case!(Norm8, Single) => ui!(Norm8, Single),
+            case!(Norm8, Half) => ui!(Norm8, Half),
+            case!(Remove, Single) => ui!(None, Single),
+            case!(Remove, Half) => ui!(None, Half),
+        }
+    }
+}
+
+impl Default for ViewerWidget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl serde::Serialize for ViewerWidget {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.state.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ViewerWidget {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        app::State::deserialize(deserializer).map(Self::with_state)
+    }
+}